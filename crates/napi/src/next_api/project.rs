@@ -33,6 +33,9 @@ use turbopack_core::{
     SOURCE_MAP_PREFIX,
 };
 use turbopack_ecmascript_hmr_protocol::{ClientUpdateInstruction, ResourceIdentifier};
+use turbopack_node::{
+    configure_node_js_pools, node_js_pools_stats, NodeJsPoolOptions, NodeJsPoolsStats,
+};
 use turbopack_trace_utils::{
     exit::{ExitHandler, ExitReceiver},
     raw_trace::RawTraceLayer,
@@ -192,6 +195,31 @@ pub struct NapiTurboEngineOptions {
     pub persistent_caching: Option<bool>,
     /// An upper bound of memory that turbopack will attempt to stay under.
     pub memory_limit: Option<f64>,
+    /// Limits of the Node.js processes that run webpack loaders and PostCSS.
+    pub node_pool: Option<NapiNodePoolOptions>,
+}
+
+#[napi(object)]
+pub struct NapiNodePoolOptions {
+    /// Share the Node.js processes and their limit between all loaders.
+    pub shared: Option<bool>,
+    /// The number of Node.js processes of the shared pool.
+    pub max_workers: Option<u32>,
+    /// Processes using more memory, in MB, are restarted.
+    pub max_worker_rss_mb: Option<u32>,
+    /// Processes that are idle for longer, in milliseconds, are stopped.
+    pub worker_idle_timeout_ms: Option<u32>,
+}
+
+impl From<NapiNodePoolOptions> for NodeJsPoolOptions {
+    fn from(val: NapiNodePoolOptions) -> Self {
+        NodeJsPoolOptions {
+            shared: val.shared,
+            max_workers: val.max_workers.map(|v| v as usize),
+            max_worker_rss_mb: val.max_worker_rss_mb.map(u64::from),
+            worker_idle_timeout_ms: val.worker_idle_timeout_ms.map(u64::from),
+        }
+    }
 }
 
 impl From<NapiWatchOptions> for WatchOptions {
@@ -341,6 +369,9 @@ pub async fn project_new(
         .map(|m| m as usize)
         .unwrap_or(usize::MAX);
     let persistent_caching = turbo_engine_options.persistent_caching.unwrap_or_default();
+    if let Some(node_pool) = turbo_engine_options.node_pool {
+        configure_node_js_pools(node_pool.into());
+    }
     let turbo_tasks = create_turbo_tasks(
         PathBuf::from(&options.dist_dir),
         persistent_caching,
@@ -890,7 +921,7 @@ pub fn project_hmr_identifiers_subscribe(
 
 enum UpdateMessage {
     Start,
-    End(UpdateInfo),
+    End(UpdateInfo, NodeJsPoolsStats),
 }
 
 #[napi(object)]
@@ -906,9 +937,13 @@ impl From<UpdateMessage> for NapiUpdateMessage {
                 update_type: "start".to_string(),
                 value: None,
             },
-            UpdateMessage::End(info) => NapiUpdateMessage {
+            UpdateMessage::End(info, node_js_pools) => NapiUpdateMessage {
                 update_type: "end".to_string(),
-                value: Some(info.into()),
+                value: Some(NapiUpdateInfo {
+                    duration: info.duration.as_millis() as u32,
+                    tasks: info.tasks as u32,
+                    node_js_pools: node_js_pools.into(),
+                }),
             },
         }
     }
@@ -918,13 +953,32 @@ impl From<UpdateMessage> for NapiUpdateMessage {
 struct NapiUpdateInfo {
     pub duration: u32,
     pub tasks: u32,
+    pub node_js_pools: NapiNodeJsPoolsStats,
+}
+
+#[napi(object)]
+struct NapiNodeJsPoolsStats {
+    pub pools: u32,
+    pub workers: u32,
+    pub booting_workers: u32,
+    pub idle_workers: u32,
+    pub spare_workers: u32,
+    pub queued_tasks: u32,
+    pub recycled_workers: u32,
+    pub idle_timed_out_workers: u32,
 }
 
-impl From<UpdateInfo> for NapiUpdateInfo {
-    fn from(update_info: UpdateInfo) -> Self {
+impl From<NodeJsPoolsStats> for NapiNodeJsPoolsStats {
+    fn from(stats: NodeJsPoolsStats) -> Self {
         Self {
-            duration: update_info.duration.as_millis() as u32,
-            tasks: update_info.tasks as u32,
+            pools: stats.pools,
+            workers: stats.workers,
+            booting_workers: stats.booting_workers,
+            idle_workers: stats.idle_workers,
+            spare_workers: stats.spare_workers,
+            queued_tasks: stats.queued_tasks,
+            recycled_workers: stats.recycled_workers,
+            idle_timed_out_workers: stats.idle_timed_out_workers,
         }
     }
 }
@@ -937,7 +991,8 @@ impl From<UpdateInfo> for NapiUpdateInfo {
 /// information about the computations that happened since the
 /// [UpdateMessage::Start] event. It contains the duration of the computation
 /// (excluding the idle time that was spend waiting for `aggregation_ms`), and
-/// the number of tasks that were executed.
+/// the number of tasks that were executed. It also contains the current
+/// statistics of the Node.js pools used for webpack loaders and PostCSS.
 ///
 /// The signature of the `func` is `(update_message: UpdateMessage) => void`.
 #[napi]
@@ -974,7 +1029,7 @@ pub fn project_update_info_subscribe(
             };
 
             let status = func.call(
-                Ok(UpdateMessage::End(update_info)),
+                Ok(UpdateMessage::End(update_info, node_js_pools_stats())),
                 ThreadsafeFunctionCallMode::NonBlocking,
             );
 
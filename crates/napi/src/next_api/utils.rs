@@ -12,6 +12,7 @@ use serde::Serialize;
 use turbo_tasks::{
     trace::TraceRawVcs, ReadRef, TaskId, TryJoinIterExt, TurboTasks, UpdateInfo, Vc,
 };
+use turbo_tasks_backend::BackingStorageKind;
 use turbo_tasks_fs::FileContent;
 use turbopack_core::{
     diagnostics::{Diagnostic, DiagnosticContextExt, PlainDiagnostic},
@@ -124,10 +125,15 @@ pub fn create_turbo_tasks(
     memory_limit: usize,
 ) -> Result<NextTurboTasks> {
     Ok(if persistent_caching {
+        let store = match std::env::var("NEXT_TURBOPACK_CACHE_STORE") {
+            Ok(store) => store
+                .parse()
+                .context("Invalid value for NEXT_TURBOPACK_CACHE_STORE")?,
+            Err(_) => BackingStorageKind::default(),
+        };
+        let backing_storage = store.open(&output_path.join("cache/turbopack"), &cache_version())?;
         NextTurboTasks::PersistentCaching(TurboTasks::new(
-            turbo_tasks_backend::TurboTasksBackend::new(Arc::new(
-                turbo_tasks_backend::LmdbBackingStorage::new(&output_path.join("cache/turbopack"))?,
-            )),
+            turbo_tasks_backend::TurboTasksBackend::new(backing_storage),
         ))
     } else {
         NextTurboTasks::Memory(TurboTasks::new(turbo_tasks_memory::MemoryBackend::new(
@@ -136,6 +142,18 @@ pub fn create_turbo_tasks(
     })
 }
 
+/// Identifies the build that writes the persistent cache. The serialized
+/// format of cached data can change with any code change, so a cache written
+/// by another build is discarded.
+fn cache_version() -> String {
+    format!(
+        "{}-{}-{}",
+        crate::build::PKG_VERSION,
+        crate::build::COMMIT_HASH,
+        crate::build::BUILD_TIME
+    )
+}
+
 /// A helper type to hold both a Vc operation and the TurboTasks root process.
 /// Without this, we'd need to pass both individually all over the place
 #[derive(Clone)]
@@ -1288,6 +1288,20 @@ impl AppEndpoint {
                 );
                 server_assets.extend(loadable_manifest_output.await?.iter().copied());
 
+                server_assets.extend(
+                    this.app_project
+                        .project()
+                        .analyze_report_assets(
+                            client_assets,
+                            Vc::cell(server_assets.iter().cloned().collect()),
+                            node_root
+                                .join(format!("server/app{}", &app_entry.original_name).into()),
+                        )
+                        .await?
+                        .iter()
+                        .copied(),
+                );
+
                 AppEndpointOutput::Edge {
                     files,
                     server_assets: Vc::cell(server_assets.iter().cloned().collect::<Vec<_>>()),
@@ -1443,6 +1457,20 @@ impl AppEndpoint {
                 );
                 server_assets.extend(loadable_manifest_output.await?.iter().copied());
 
+                server_assets.extend(
+                    this.app_project
+                        .project()
+                        .analyze_report_assets(
+                            client_assets,
+                            Vc::cell(server_assets.iter().cloned().collect()),
+                            node_root
+                                .join(format!("server/app{}", &app_entry.original_name).into()),
+                        )
+                        .await?
+                        .iter()
+                        .copied(),
+                );
+
                 AppEndpointOutput::NodeJs {
                     rsc_chunk,
                     server_assets: Vc::cell(server_assets.iter().cloned().collect::<Vec<_>>()),
@@ -1,8 +1,19 @@
 use anyhow::Result;
-use turbo_tasks::Vc;
-use turbopack_core::chunk::module_id_strategies::{GlobalModuleIdStrategy, ModuleIdStrategy};
+use indexmap::IndexMap;
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    chunk::{
+        module_id_strategies::{
+            GlobalModuleIdStrategy, ModuleIdCollisionIssue, ModuleIdRecords, ModuleIdStrategy,
+        },
+        ModuleId,
+    },
+    issue::IssueExt,
+};
 use turbopack_ecmascript::global_module_id_strategy::{
-    children_modules_idents, merge_preprocessed_module_ids, PreprocessedChildrenIdents,
+    children_modules_idents, merge_preprocessed_module_ids,
+    merge_preprocessed_module_ids_with_records, PreprocessedChildrenIdents,
 };
 
 use crate::{
@@ -20,65 +31,137 @@ pub struct GlobalModuleIdStrategyBuilder;
 impl GlobalModuleIdStrategyBuilder {
     #[turbo_tasks::function]
     pub async fn build(project: Vc<Project>) -> Result<Vc<Box<dyn ModuleIdStrategy>>> {
-        let mut preprocessed_module_ids = Vec::new();
+        let preprocessed_module_ids = preprocess_project_module_ids(project).await?;
+        let module_id_map = merge_preprocessed_module_ids(preprocessed_module_ids).await?;
 
-        preprocessed_module_ids.push(children_modules_idents(project.client_main_modules()));
+        Ok(Vc::upcast(
+            GlobalModuleIdStrategy::new(module_id_map).await?,
+        ))
+    }
 
-        let entrypoints = project.entrypoints().await?;
+    /// Like [GlobalModuleIdStrategyBuilder::build], but keeps the module ids
+    /// of the previous build from the records at `records_path`. The records
+    /// of this build are written by the emit step, see
+    /// [GlobalModuleIdStrategyBuilder::module_id_records].
+    #[turbo_tasks::function]
+    pub async fn build_with_records(
+        project: Vc<Project>,
+        records_path: Vc<FileSystemPath>,
+    ) -> Result<Vc<Box<dyn ModuleIdStrategy>>> {
+        let recorded = recorded_module_ids(project, records_path).await?;
 
-        preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_error_endpoint));
-        preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_app_endpoint));
-        preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_document_endpoint));
+        Ok(Vc::upcast(
+            GlobalModuleIdStrategy::new_with_reserved_ids(
+                recorded.module_id_map.clone(),
+                recorded.reserved_ids.iter().copied(),
+            )
+            .await?,
+        ))
+    }
 
-        if let Some(middleware) = &entrypoints.middleware {
-            preprocessed_module_ids.push(preprocess_module_ids(middleware.endpoint));
-        }
+    /// The module id records to persist for the next build, including the
+    /// ids of modules that were removed.
+    #[turbo_tasks::function]
+    pub async fn module_id_records(
+        project: Vc<Project>,
+        records_path: Vc<FileSystemPath>,
+    ) -> Result<Vc<ModuleIdRecords>> {
+        Ok(recorded_module_ids(project, records_path).await?.records)
+    }
+}
+
+#[turbo_tasks::value]
+struct RecordedModuleIds {
+    module_id_map: IndexMap<RcStr, ModuleId>,
+    reserved_ids: Vec<u64>,
+    records: Vc<ModuleIdRecords>,
+}
 
-        if let Some(instrumentation) = &entrypoints.instrumentation {
-            let node_js = instrumentation.node_js;
-            let edge = instrumentation.edge;
-            preprocessed_module_ids.push(preprocess_module_ids(node_js));
-            preprocessed_module_ids.push(preprocess_module_ids(edge));
+#[turbo_tasks::function]
+async fn recorded_module_ids(
+    project: Vc<Project>,
+    records_path: Vc<FileSystemPath>,
+) -> Result<Vc<RecordedModuleIds>> {
+    let preprocessed_module_ids = preprocess_project_module_ids(project).await?;
+    let records = ModuleIdRecords::read(records_path).await?;
+    let (module_id_map, reserved) =
+        merge_preprocessed_module_ids_with_records(preprocessed_module_ids, &records).await?;
+
+    for collision in reserved.collisions {
+        ModuleIdCollisionIssue {
+            path: records_path,
+            ident: collision.ident,
+            colliding_ident: collision.colliding_ident,
+            module_id: collision.module_id.to_string().into(),
+            reassigned: true,
         }
+        .cell()
+        .emit();
+    }
 
-        for (_, route) in entrypoints.routes.iter() {
-            match route {
-                Route::Page {
-                    html_endpoint,
-                    data_endpoint,
-                } => {
-                    preprocessed_module_ids.push(preprocess_module_ids(*html_endpoint));
-                    preprocessed_module_ids.push(preprocess_module_ids(*data_endpoint));
-                }
-                Route::PageApi { endpoint } => {
-                    preprocessed_module_ids.push(preprocess_module_ids(*endpoint));
-                }
-                Route::AppPage(page_routes) => {
-                    for page_route in page_routes {
-                        preprocessed_module_ids
-                            .push(preprocess_module_ids(page_route.html_endpoint));
-                        preprocessed_module_ids
-                            .push(preprocess_module_ids(page_route.rsc_endpoint));
-                    }
-                }
-                Route::AppRoute {
-                    original_name: _,
-                    endpoint,
-                } => {
-                    preprocessed_module_ids.push(preprocess_module_ids(*endpoint));
-                }
-                Route::Conflict => {
-                    tracing::info!("WARN: conflict");
+    Ok(RecordedModuleIds {
+        records: records.update(&module_id_map).cell(),
+        module_id_map,
+        reserved_ids: reserved.used_ids.into_iter().collect(),
+    }
+    .cell())
+}
+
+async fn preprocess_project_module_ids(
+    project: Vc<Project>,
+) -> Result<Vec<Vc<PreprocessedChildrenIdents>>> {
+    let mut preprocessed_module_ids = Vec::new();
+
+    preprocessed_module_ids.push(children_modules_idents(project.client_main_modules()));
+
+    let entrypoints = project.entrypoints().await?;
+
+    preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_error_endpoint));
+    preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_app_endpoint));
+    preprocessed_module_ids.push(preprocess_module_ids(entrypoints.pages_document_endpoint));
+
+    if let Some(middleware) = &entrypoints.middleware {
+        preprocessed_module_ids.push(preprocess_module_ids(middleware.endpoint));
+    }
+
+    if let Some(instrumentation) = &entrypoints.instrumentation {
+        let node_js = instrumentation.node_js;
+        let edge = instrumentation.edge;
+        preprocessed_module_ids.push(preprocess_module_ids(node_js));
+        preprocessed_module_ids.push(preprocess_module_ids(edge));
+    }
+
+    for (_, route) in entrypoints.routes.iter() {
+        match route {
+            Route::Page {
+                html_endpoint,
+                data_endpoint,
+            } => {
+                preprocessed_module_ids.push(preprocess_module_ids(*html_endpoint));
+                preprocessed_module_ids.push(preprocess_module_ids(*data_endpoint));
+            }
+            Route::PageApi { endpoint } => {
+                preprocessed_module_ids.push(preprocess_module_ids(*endpoint));
+            }
+            Route::AppPage(page_routes) => {
+                for page_route in page_routes {
+                    preprocessed_module_ids.push(preprocess_module_ids(page_route.html_endpoint));
+                    preprocessed_module_ids.push(preprocess_module_ids(page_route.rsc_endpoint));
                 }
             }
+            Route::AppRoute {
+                original_name: _,
+                endpoint,
+            } => {
+                preprocessed_module_ids.push(preprocess_module_ids(*endpoint));
+            }
+            Route::Conflict => {
+                tracing::info!("WARN: conflict");
+            }
         }
-
-        let module_id_map = merge_preprocessed_module_ids(preprocessed_module_ids).await?;
-
-        Ok(Vc::upcast(
-            GlobalModuleIdStrategy::new(module_id_map).await?,
-        ))
     }
+
+    Ok(preprocessed_module_ids)
 }
 
 // NOTE(LichuAcu) We can't move this function to `turbopack-core` because we need access to
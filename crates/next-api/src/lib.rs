@@ -26,5 +26,6 @@ shadow_rs::shadow!(build);
 pub fn register() {
     next_core::register();
     turbopack_nodejs::register();
+    turbopack_bundle_analyzer::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
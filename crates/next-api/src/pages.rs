@@ -1100,6 +1100,19 @@ impl PageEndpoint {
                 let loadable_manifest_output = self.react_loadable_manifest(dynamic_import_entries);
                 server_assets.extend(loadable_manifest_output.await?.iter().copied());
 
+                server_assets.extend(
+                    this.pages_project
+                        .project()
+                        .analyze_report_assets(
+                            client_assets,
+                            Vc::cell(server_assets.clone()),
+                            node_root.join(format!("server/pages{manifest_path_prefix}").into()),
+                        )
+                        .await?
+                        .iter()
+                        .copied(),
+                );
+
                 PageEndpointOutput::NodeJs {
                     entry_chunk: entry,
                     server_assets: Vc::cell(server_assets),
@@ -1186,6 +1199,19 @@ impl PageEndpoint {
                 let loadable_manifest_output = self.react_loadable_manifest(dynamic_import_entries);
                 server_assets.extend(loadable_manifest_output.await?.iter().copied());
 
+                server_assets.extend(
+                    this.pages_project
+                        .project()
+                        .analyze_report_assets(
+                            client_assets,
+                            Vc::cell(server_assets.clone()),
+                            node_root.join(format!("server/pages{manifest_path_prefix}").into()),
+                        )
+                        .await?
+                        .iter()
+                        .copied(),
+                );
+
                 PageEndpointOutput::Edge {
                     files,
                     server_assets: Vc::cell(server_assets),
@@ -1,7 +1,7 @@
 use std::{path::MAIN_SEPARATOR, time::Duration};
 
 use anyhow::{bail, Context, Result};
-use indexmap::{indexmap, map::Entry, IndexMap};
+use indexmap::{indexmap, map::Entry, IndexMap, IndexSet};
 use next_core::{
     all_assets_from_entries,
     app_structure::find_app_dir,
@@ -26,14 +26,15 @@ use turbo_tasks::{
     debug::ValueDebugFormat,
     graph::{AdjacencyMap, GraphTraversal},
     trace::TraceRawVcs,
-    Completion, Completions, IntoTraitRef, RcStr, ReadRef, State, TaskInput, TransientInstance,
-    TryFlatJoinIterExt, Value, Vc,
+    CollectiblesSource, Completion, Completions, IntoTraitRef, RcStr, ReadRef, State, TaskInput,
+    TransientInstance, TryFlatJoinIterExt, Value, Vc,
 };
 use turbo_tasks_env::{EnvMap, ProcessEnv};
 use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath, VirtualFileSystem};
 use turbopack::{
     evaluate_context::node_build_environment, transition::TransitionOptions, ModuleAssetContext,
 };
+use turbopack_bundle_analyzer::AnalyzeReport;
 use turbopack_core::{
     changed::content_changed,
     chunk::{
@@ -52,6 +53,7 @@ use turbopack_core::{
     version::{
         NotFoundVersion, OptionVersionedContent, Update, Version, VersionState, VersionedContent,
     },
+    virtual_output::VirtualOutputAsset,
     PROJECT_FILESYSTEM_NAME,
 };
 use turbopack_node::execution_context::ExecutionContext;
@@ -625,6 +627,35 @@ impl Project {
         ))
     }
 
+    #[turbo_tasks::function]
+    pub(super) async fn should_create_analyze_report(&self) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.env.read("TURBOPACK_ANALYZE".into()).await?.is_some(),
+        ))
+    }
+
+    /// Emits the bundle analysis of the client and server assets of an
+    /// endpoint, including the assets they reference, into `output_dir`.
+    /// Empty unless [Project::should_create_analyze_report].
+    #[turbo_tasks::function]
+    pub(super) async fn analyze_report_assets(
+        self: Vc<Self>,
+        client_assets: Vc<OutputAssets>,
+        server_assets: Vc<OutputAssets>,
+        output_dir: Vc<FileSystemPath>,
+    ) -> Result<Vc<OutputAssets>> {
+        if !*self.should_create_analyze_report().await? {
+            return Ok(OutputAssets::empty());
+        }
+        Ok(AnalyzeReport::new(
+            all_assets_from_entries(client_assets),
+            self.client_root(),
+            all_assets_from_entries(server_assets),
+            self.node_root(),
+        )
+        .output_assets(output_dir))
+    }
+
     #[turbo_tasks::function]
     pub(super) async fn execution_context(self: Vc<Self>) -> Result<Vc<ExecutionContext>> {
         let node_root = self.node_root();
@@ -643,11 +674,14 @@ impl Project {
             .build(),
         );
 
+        // Files emitted by webpack loaders are referenced by client code, like in
+        // the client compilation of webpack
         Ok(ExecutionContext::new(
             self.project_path(),
             node_execution_chunking_context,
             self.env(),
-        ))
+        )
+        .with_emit_root(self.client_relative_path()))
     }
 
     #[turbo_tasks::function]
@@ -694,6 +728,10 @@ impl Project {
             self.client_compile_time_info().environment(),
             self.next_mode(),
             self.module_id_strategy(),
+            self.next_config().chunking_config(),
+            self.next_config().inline_css_imports(),
+            self.next_config().inline_wasm_limit(),
+            self.next_config().client_source_maps_type(),
         )
     }
 
@@ -711,6 +749,9 @@ impl Project {
                 self.next_config().computed_asset_prefix(),
                 self.server_compile_time_info().environment(),
                 self.module_id_strategy(),
+                self.next_config().chunking_config(),
+                self.next_config().inline_css_imports(),
+                self.next_config().inline_wasm_limit(),
             )
         } else {
             get_server_chunking_context(
@@ -719,6 +760,9 @@ impl Project {
                 self.node_root(),
                 self.server_compile_time_info().environment(),
                 self.module_id_strategy(),
+                self.next_config().chunking_config(),
+                self.next_config().inline_css_imports(),
+                self.next_config().inline_wasm_limit(),
             )
         }
     }
@@ -737,6 +781,9 @@ impl Project {
                 self.next_config().computed_asset_prefix(),
                 self.edge_compile_time_info().environment(),
                 self.module_id_strategy(),
+                self.next_config().chunking_config(),
+                self.next_config().inline_css_imports(),
+                self.next_config().inline_wasm_limit(),
             )
         } else {
             get_edge_chunking_context(
@@ -745,6 +792,9 @@ impl Project {
                 self.node_root(),
                 self.edge_compile_time_info().environment(),
                 self.module_id_strategy(),
+                self.next_config().chunking_config(),
+                self.next_config().inline_css_imports(),
+                self.next_config().inline_wasm_limit(),
             )
         }
     }
@@ -1140,6 +1190,28 @@ impl Project {
             let client_relative_path = self.client_relative_path();
             let node_root = self.node_root();
 
+            // The module id records are written here instead of while computing the
+            // module ids, as they are read there. Only builds update them, the ids of
+            // development don't end up in long-term cached chunks.
+            if let (Some(ModuleIdStrategyConfig::Records), NextMode::Build) = (
+                &*self.next_config().module_id_strategy_config().await?,
+                *self.next_mode().await?,
+            ) {
+                let records_path = self.module_id_records_path();
+                let records = GlobalModuleIdStrategyBuilder::module_id_records(self, records_path);
+                let _ = emit_assets(
+                    Vc::cell(vec![Vc::upcast(VirtualOutputAsset::new(
+                        records_path,
+                        records.content(),
+                    ))]),
+                    node_root,
+                    client_relative_path,
+                    node_root,
+                )
+                .resolve()
+                .await?;
+            }
+
             if let Some(map) = self.await?.versioned_content_map {
                 let _ = map
                     .insert_output_assets(
@@ -1270,6 +1342,15 @@ impl Project {
         Ok(Vc::cell(modules))
     }
 
+    /// The path of the module id records, used by the `records` module id
+    /// strategy. It's not part of the persistent cache in `cache/turbopack`,
+    /// which is discarded when Turbopack is updated.
+    #[turbo_tasks::function]
+    fn module_id_records_path(self: Vc<Self>) -> Vc<FileSystemPath> {
+        self.node_root()
+            .join("cache/turbopack-module-ids.json".into())
+    }
+
     /// Gets the module id strategy for the project.
     #[turbo_tasks::function]
     pub async fn module_id_strategy(self: Vc<Self>) -> Result<Vc<Box<dyn ModuleIdStrategy>>> {
@@ -1279,6 +1360,12 @@ impl Project {
             Some(ModuleIdStrategyConfig::Deterministic) => {
                 Ok(Vc::upcast(GlobalModuleIdStrategyBuilder::build(self)))
             }
+            Some(ModuleIdStrategyConfig::Records) => Ok(Vc::upcast(
+                GlobalModuleIdStrategyBuilder::build_with_records(
+                    self,
+                    self.module_id_records_path(),
+                ),
+            )),
             None => match *self.next_mode().await? {
                 NextMode::Development => Ok(Vc::upcast(DevModuleIdStrategy::new())),
                 NextMode::Build => Ok(Vc::upcast(GlobalModuleIdStrategyBuilder::build(self))),
@@ -1330,7 +1417,17 @@ async fn all_assets_from_entries_operation_inner(
 ) -> Result<Vc<OutputAssets>> {
     let assets = *operation.await?;
     Vc::connect(assets);
-    Ok(all_assets_from_entries(assets))
+    let all_assets = all_assets_from_entries(assets);
+    // Files emitted by webpack loaders via `this.emitFile` are not referenced by
+    // any output asset
+    let mut emitted_files = assets.peek_collectibles::<Box<dyn OutputAsset>>();
+    emitted_files.extend(all_assets.peek_collectibles::<Box<dyn OutputAsset>>());
+    if emitted_files.is_empty() {
+        return Ok(all_assets);
+    }
+    let mut all_assets = all_assets.await?.iter().copied().collect::<IndexSet<_>>();
+    all_assets.extend(emitted_files);
+    Ok(Vc::cell(all_assets.into_iter().collect()))
 }
 
 fn all_assets_from_entries_operation(
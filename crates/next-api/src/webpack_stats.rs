@@ -4,7 +4,7 @@ use serde::Serialize;
 use turbo_tasks::{RcStr, Vc};
 use turbopack_browser::ecmascript::EcmascriptDevChunk;
 use turbopack_core::{
-    chunk::{Chunk, ChunkItem},
+    chunk::{Chunk, ChunkItem, ChunkOutputAsset},
     output::OutputAsset,
 };
 
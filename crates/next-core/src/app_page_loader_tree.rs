@@ -226,6 +226,7 @@ impl AppPageLoaderTreeBuilder {
         let module = Vc::upcast(StructuredImageModuleType::create_module(
             Vc::upcast(FileSource::new(path)),
             BlurPlaceholderMode::None,
+            None,
             self.base.module_asset_context,
         ));
         let module = self.base.process_module(module);
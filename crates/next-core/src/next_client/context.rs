@@ -14,7 +14,9 @@ use turbopack::{
 };
 use turbopack_browser::{react_refresh::assert_can_resolve_react_refresh, BrowserChunkingContext};
 use turbopack_core::{
-    chunk::{module_id_strategies::ModuleIdStrategy, ChunkingContext},
+    chunk::{
+        module_id_strategies::ModuleIdStrategy, ChunkingConfig, ChunkingContext, SourceMapsType,
+    },
     compile_time_info::{
         CompileTimeDefineValue, CompileTimeDefines, CompileTimeInfo, DefineableNameSegment,
         FreeVarReference, FreeVarReferences,
@@ -225,6 +227,7 @@ pub async fn get_client_module_options_context(
     let foreign_enable_webpack_loaders = webpack_loader_options(
         project_path,
         next_config,
+        mode,
         true,
         conditions
             .iter()
@@ -236,7 +239,7 @@ pub async fn get_client_module_options_context(
 
     // Now creates a webpack rules that applies to all codes.
     let enable_webpack_loaders =
-        webpack_loader_options(project_path, next_config, false, conditions).await?;
+        webpack_loader_options(project_path, next_config, mode, false, conditions).await?;
 
     let tree_shaking_mode_for_user_code = *next_config
         .tree_shaking_mode_for_user_code(next_mode.is_development())
@@ -360,6 +363,10 @@ pub async fn get_client_chunking_context(
     environment: Vc<Environment>,
     mode: Vc<NextMode>,
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: Vc<bool>,
+    inline_wasm_limit: Vc<Option<u64>>,
+    source_maps_type: Vc<SourceMapsType>,
 ) -> Result<Vc<Box<dyn ChunkingContext>>> {
     let next_mode = mode.await?;
     let mut builder = BrowserChunkingContext::builder(
@@ -374,7 +381,11 @@ pub async fn get_client_chunking_context(
     .chunk_base_path(asset_prefix)
     .minify_type(next_mode.minify_type())
     .asset_base_path(asset_prefix)
-    .module_id_strategy(module_id_strategy);
+    .module_id_strategy(module_id_strategy)
+    .chunking_config(chunking_config)
+    .inline_css_imports(*inline_css_imports.await?)
+    .inline_wasm_limit(*inline_wasm_limit.await?)
+    .source_maps_type(*source_maps_type.await?);
 
     if next_mode.is_development() {
         builder = builder.hot_module_replacement();
@@ -8,9 +8,11 @@ use turbo_tasks::{trace::TraceRawVcs, RcStr, TaskInput, Vc};
 use turbo_tasks_env::EnvMap;
 use turbo_tasks_fs::FileSystemPath;
 use turbopack::module_options::{
-    module_options_context::MdxTransformOptions, LoaderRuleItem, OptionWebpackRules,
+    module_options_context::MdxTransformOptions, LoaderRuleConditions, LoaderRuleItem,
+    OptionWebpackRules,
 };
 use turbopack_core::{
+    chunk::{ChunkingConfig, SourceMapsType},
     issue::{Issue, IssueSeverity, IssueStage, OptionStyledString, StyledString},
     resolve::ResolveAliasMap,
 };
@@ -22,7 +24,9 @@ use turbopack_ecmascript_plugins::transform::{
 use turbopack_node::transforms::webpack::{WebpackLoaderItem, WebpackLoaderItems};
 
 use crate::{
-    next_import_map::mdx_import_source_file, next_shared::transforms::ModularizeImportPackageConfig,
+    next_image::module::{OptionResponsiveImageOptions, ResponsiveImageOptions},
+    next_import_map::mdx_import_source_file,
+    next_shared::transforms::ModularizeImportPackageConfig,
 };
 
 #[turbo_tasks::value]
@@ -404,6 +408,41 @@ pub struct ExperimentalTurboConfig {
     pub use_swc_css: Option<bool>,
     pub tree_shaking: Option<bool>,
     pub module_id_strategy: Option<ModuleIdStrategy>,
+    /// Generates resized variants of statically imported images for the
+    /// `images.deviceSizes` at build time, and exports them as `srcSet`. Only
+    /// applies to `output: "export"`, which has no image optimization server.
+    pub static_image_variants: Option<bool>,
+    /// Persists the results of webpack loaders in
+    /// `<distDir>/cache/turbopack-loaders`, so they are reused across restarts.
+    /// Only valid when all configured loaders are deterministic.
+    pub loader_cache: Option<bool>,
+    /// Inlines `@import`ed stylesheets into the chunk of the importing
+    /// stylesheet, in the order bundling them would result in.
+    pub inline_css_imports: Option<bool>,
+    /// Chunks smaller than this many bytes are merged with other small chunks
+    /// of the same chunk group.
+    pub min_chunk_size: Option<usize>,
+    /// Chunks larger than this many bytes are split by package or folder.
+    pub max_chunk_size: Option<usize>,
+    /// The maximum number of chunks a chunk group is split into.
+    pub max_chunk_count_per_group: Option<usize>,
+    /// Puts every node_modules package into its own chunks.
+    pub split_by_package: Option<bool>,
+    /// How the source maps of browser chunks are emitted and referenced.
+    pub client_source_maps: Option<ClientSourceMaps>,
+    /// WebAssembly modules smaller than this many bytes are embedded into the
+    /// chunk instead of being loaded with a separate request.
+    pub inline_wasm_limit: Option<u64>,
+}
+
+/// See [SourceMapsType].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientSourceMaps {
+    Full,
+    Hidden,
+    NoSources,
+    Inline,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -412,6 +451,22 @@ pub struct RuleConfigItemOptions {
     pub loaders: Vec<LoaderItem>,
     #[serde(default, alias = "as")]
     pub rename_as: Option<RcStr>,
+    /// Only apply the loaders when the query string of the module equals this,
+    /// e. g. `?raw`.
+    #[serde(default)]
+    pub resource_query: Option<RcStr>,
+    /// Only apply the loaders to imports with these import attributes.
+    #[serde(default)]
+    pub with: Option<RuleConfigImportAttributes>,
+    /// Only apply the loaders when the importing module matches this glob.
+    #[serde(default)]
+    pub issuer: Option<RcStr>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct RuleConfigImportAttributes {
+    #[serde(rename = "type")]
+    pub ty: RcStr,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -419,6 +474,9 @@ pub struct RuleConfigItemOptions {
 pub enum RuleConfigItemOrShortcut {
     Loaders(Vec<LoaderItem>),
     Advanced(RuleConfigItem),
+    /// Multiple rules for the same glob, e. g. with different
+    /// `resourceQuery`s. All matching rules are applied.
+    Multiple(Vec<RuleConfigItem>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -429,6 +487,34 @@ pub enum RuleConfigItem {
     Boolean(bool),
 }
 
+#[test]
+fn test_rule_config_deserialization() {
+    let json = serde_json::json!({
+        "*.svg": [
+            { "loaders": ["raw-loader"], "as": "*.js", "resourceQuery": "?raw" },
+            { "loaders": ["@svgr/webpack"], "as": "*.js" },
+        ],
+        "*.txt": ["raw-loader"],
+    });
+    let rules: IndexMap<RcStr, RuleConfigItemOrShortcut> = serde_json::from_value(json).unwrap();
+    let RuleConfigItemOrShortcut::Multiple(svg_rules) = &rules["*.svg"] else {
+        panic!(
+            "expected multiple rules for *.svg, got {:?}",
+            rules["*.svg"]
+        );
+    };
+    assert_eq!(svg_rules.len(), 2);
+    assert!(matches!(
+        &svg_rules[0],
+        RuleConfigItem::Options(RuleConfigItemOptions { resource_query: Some(query), .. })
+            if query == "?raw"
+    ));
+    assert!(matches!(
+        &rules["*.txt"],
+        RuleConfigItemOrShortcut::Loaders(_)
+    ));
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(untagged)]
 pub enum LoaderItem {
@@ -442,6 +528,9 @@ pub enum LoaderItem {
 pub enum ModuleIdStrategy {
     Named,
     Deterministic,
+    /// Like `Deterministic`, but the ids are persisted and reused in the next
+    /// build, so they only change for new modules.
+    Records,
 }
 
 #[turbo_tasks::value(transparent)]
@@ -864,7 +953,7 @@ impl NextConfig {
             return Vc::cell(None);
         }
         let active_conditions = active_conditions.into_iter().collect::<HashSet<_>>();
-        let mut rules = IndexMap::new();
+        let mut rules = Vec::new();
         for (ext, rule) in turbo_rules.iter() {
             fn transform_loaders(loaders: &[LoaderItem]) -> Vc<WebpackLoaderItems> {
                 Vc::cell(
@@ -910,27 +999,45 @@ impl NextConfig {
                     RuleConfigItem::Boolean(_) => FindRuleResult::Break,
                 }
             }
+            fn to_loader_rule(rule: &RuleConfigItemOptions) -> LoaderRuleItem {
+                let RuleConfigItemOptions {
+                    loaders,
+                    rename_as,
+                    resource_query,
+                    with,
+                    issuer,
+                } = rule;
+                LoaderRuleItem {
+                    loaders: transform_loaders(loaders),
+                    rename_as: rename_as.clone(),
+                    conditions: LoaderRuleConditions {
+                        resource_query: resource_query.clone(),
+                        import_with_type: with.as_ref().map(|with| with.ty.clone()),
+                        issuer: issuer.clone(),
+                    },
+                }
+            }
             match rule {
                 RuleConfigItemOrShortcut::Loaders(loaders) => {
-                    rules.insert(
+                    rules.push((
                         ext.clone(),
                         LoaderRuleItem {
                             loaders: transform_loaders(loaders),
                             rename_as: None,
+                            conditions: Default::default(),
                         },
-                    );
+                    ));
                 }
                 RuleConfigItemOrShortcut::Advanced(rule) => {
-                    if let FindRuleResult::Found(RuleConfigItemOptions { loaders, rename_as }) =
-                        find_rule(rule, &active_conditions)
-                    {
-                        rules.insert(
-                            ext.clone(),
-                            LoaderRuleItem {
-                                loaders: transform_loaders(loaders),
-                                rename_as: rename_as.clone(),
-                            },
-                        );
+                    if let FindRuleResult::Found(rule) = find_rule(rule, &active_conditions) {
+                        rules.push((ext.clone(), to_loader_rule(rule)));
+                    }
+                }
+                RuleConfigItemOrShortcut::Multiple(items) => {
+                    for rule in items {
+                        if let FindRuleResult::Found(rule) = find_rule(rule, &active_conditions) {
+                            rules.push((ext.clone(), to_loader_rule(rule)));
+                        }
                     }
                 }
             }
@@ -1104,6 +1211,109 @@ impl NextConfig {
         )
     }
 
+    /// The directory to persist the results of webpack loaders in, relative to
+    /// the project directory, when enabled.
+    #[turbo_tasks::function]
+    pub fn webpack_loader_cache_dir(&self) -> Vc<Option<RcStr>> {
+        let enabled = self
+            .experimental
+            .turbo
+            .as_ref()
+            .and_then(|turbo| turbo.loader_cache)
+            .unwrap_or(false);
+        Vc::cell(enabled.then(|| {
+            format!(
+                "{}/cache/turbopack-loaders",
+                self.dist_dir.as_deref().unwrap_or(".next")
+            )
+            .into()
+        }))
+    }
+
+    #[turbo_tasks::function]
+    pub fn responsive_image_options(&self) -> Vc<OptionResponsiveImageOptions> {
+        let static_image_variants = self
+            .experimental
+            .turbo
+            .as_ref()
+            .and_then(|turbo| turbo.static_image_variants)
+            .unwrap_or(false);
+        if !static_image_variants || !matches!(self.output, Some(OutputType::Export)) {
+            return Vc::cell(None);
+        }
+        Vc::cell(Some(
+            ResponsiveImageOptions {
+                widths: self
+                    .images
+                    .device_sizes
+                    .iter()
+                    .map(|&size| size.into())
+                    .collect(),
+                // The default quality of `next/image`
+                quality: 75,
+            }
+            .cell(),
+        ))
+    }
+
+    /// How the chunking contexts split chunk groups into chunks.
+    #[turbo_tasks::function]
+    pub fn chunking_config(&self) -> Vc<ChunkingConfig> {
+        let Some(turbo) = self.experimental.turbo.as_ref() else {
+            return ChunkingConfig::default_config();
+        };
+        let default = ChunkingConfig::default();
+        ChunkingConfig {
+            min_chunk_size: turbo.min_chunk_size.unwrap_or(default.min_chunk_size),
+            max_chunk_size: turbo.max_chunk_size.unwrap_or(default.max_chunk_size),
+            max_chunk_count_per_group: turbo.max_chunk_count_per_group,
+            split_by_package: turbo.split_by_package.unwrap_or(default.split_by_package),
+        }
+        .cell()
+    }
+
+    /// Whether `@import`ed stylesheets are inlined into the chunk item of the
+    /// importing stylesheet.
+    #[turbo_tasks::function]
+    pub fn inline_css_imports(&self) -> Vc<bool> {
+        Vc::cell(
+            self.experimental
+                .turbo
+                .as_ref()
+                .and_then(|turbo| turbo.inline_css_imports)
+                .unwrap_or(false),
+        )
+    }
+
+    /// WebAssembly modules smaller than this many bytes are embedded into the
+    /// chunk.
+    #[turbo_tasks::function]
+    pub fn inline_wasm_limit(&self) -> Vc<Option<u64>> {
+        Vc::cell(
+            self.experimental
+                .turbo
+                .as_ref()
+                .and_then(|turbo| turbo.inline_wasm_limit),
+        )
+    }
+
+    /// How the source maps of browser chunks are emitted and referenced.
+    #[turbo_tasks::function]
+    pub fn client_source_maps_type(&self) -> Vc<SourceMapsType> {
+        let source_maps = self
+            .experimental
+            .turbo
+            .as_ref()
+            .and_then(|turbo| turbo.client_source_maps);
+        match source_maps {
+            None | Some(ClientSourceMaps::Full) => SourceMapsType::Full,
+            Some(ClientSourceMaps::Hidden) => SourceMapsType::Hidden,
+            Some(ClientSourceMaps::NoSources) => SourceMapsType::NoSources,
+            Some(ClientSourceMaps::Inline) => SourceMapsType::Inline,
+        }
+        .cell()
+    }
+
     #[turbo_tasks::function]
     pub fn optimize_package_imports(&self) -> Vc<Vec<RcStr>> {
         Vc::cell(
@@ -6,7 +6,7 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack::resolve_options_context::ResolveOptionsContext;
 use turbopack_browser::BrowserChunkingContext;
 use turbopack_core::{
-    chunk::{module_id_strategies::ModuleIdStrategy, ChunkingContext},
+    chunk::{module_id_strategies::ModuleIdStrategy, ChunkingConfig, ChunkingContext},
     compile_time_info::{
         CompileTimeDefineValue, CompileTimeDefines, CompileTimeInfo, DefineableNameSegment,
         FreeVarReference, FreeVarReferences,
@@ -183,6 +183,9 @@ pub async fn get_edge_chunking_context_with_client_assets(
     asset_prefix: Vc<Option<RcStr>>,
     environment: Vc<Environment>,
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: Vc<bool>,
+    inline_wasm_limit: Vc<Option<u64>>,
 ) -> Result<Vc<Box<dyn ChunkingContext>>> {
     let output_root = node_root.join("server/edge".into());
     let next_mode = mode.await?;
@@ -199,6 +202,9 @@ pub async fn get_edge_chunking_context_with_client_assets(
         .asset_base_path(asset_prefix)
         .minify_type(next_mode.minify_type())
         .module_id_strategy(module_id_strategy)
+        .chunking_config(chunking_config)
+        .inline_css_imports(*inline_css_imports.await?)
+        .inline_wasm_limit(*inline_wasm_limit.await?)
         .build(),
     ))
 }
@@ -210,6 +216,9 @@ pub async fn get_edge_chunking_context(
     node_root: Vc<FileSystemPath>,
     environment: Vc<Environment>,
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: Vc<bool>,
+    inline_wasm_limit: Vc<Option<u64>>,
 ) -> Result<Vc<Box<dyn ChunkingContext>>> {
     let output_root = node_root.join("server/edge".into());
     let next_mode = mode.await?;
@@ -230,6 +239,9 @@ pub async fn get_edge_chunking_context(
         .asset_base_path(Vc::cell(Some("blob:server/edge/".into())))
         .minify_type(next_mode.minify_type())
         .module_id_strategy(module_id_strategy)
+        .chunking_config(chunking_config)
+        .inline_css_imports(*inline_css_imports.await?)
+        .inline_wasm_limit(*inline_wasm_limit.await?)
         .build(),
     ))
 }
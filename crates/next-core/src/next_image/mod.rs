@@ -1,4 +1,5 @@
 pub(crate) mod module;
+pub(crate) mod resized_source;
 pub(crate) mod source_asset;
 
 pub use module::StructuredImageModuleType;
@@ -1,5 +1,6 @@
-use indexmap::indexmap;
-use turbo_tasks::{TaskInput, Value, Vc};
+use anyhow::Result;
+use indexmap::{indexmap, IndexMap};
+use turbo_tasks::{RcStr, TaskInput, Value, Vc};
 use turbopack::{module_options::CustomModuleType, ModuleAssetContext};
 use turbopack_core::{
     context::AssetContext, module::Module, reference_type::ReferenceType, resolve::ModulePart,
@@ -7,7 +8,7 @@ use turbopack_core::{
 };
 use turbopack_static::StaticModuleAsset;
 
-use super::source_asset::StructuredImageFileSource;
+use super::{resized_source::ResizedImageSource, source_asset::StructuredImageFileSource};
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, Hash, TaskInput)]
@@ -25,11 +26,25 @@ pub enum BlurPlaceholderMode {
     NextImageUrl,
 }
 
+/// Resized variants of images to generate at build time, for sites that are
+/// deployed without an image optimization server.
+#[turbo_tasks::value(shared)]
+pub struct ResponsiveImageOptions {
+    /// The widths of the variants. Only widths smaller than the image are
+    /// generated.
+    pub widths: Vec<u32>,
+    pub quality: u8,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionResponsiveImageOptions(Option<Vc<ResponsiveImageOptions>>);
+
 /// Module type that analyzes images and offers some meta information like
 /// width, height and blur placeholder as export from the module.
 #[turbo_tasks::value]
 pub struct StructuredImageModuleType {
     pub blur_placeholder_mode: BlurPlaceholderMode,
+    pub responsive_images: Option<Vc<ResponsiveImageOptions>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -38,29 +53,51 @@ impl StructuredImageModuleType {
     pub(crate) async fn create_module(
         source: Vc<Box<dyn Source>>,
         blur_placeholder_mode: BlurPlaceholderMode,
+        responsive_images: Option<Vc<ResponsiveImageOptions>>,
         module_asset_context: Vc<ModuleAssetContext>,
-    ) -> Vc<Box<dyn Module>> {
+    ) -> Result<Vc<Box<dyn Module>>> {
         let static_asset = StaticModuleAsset::new(source, Vc::upcast(module_asset_context));
-        module_asset_context
+        let mut inner_assets: IndexMap<RcStr, Vc<Box<dyn Module>>> = indexmap!(
+            "IMAGE".into() => Vc::upcast(static_asset)
+        );
+        // The variants are only referenced by the image module when they are
+        // smaller than the image
+        if let Some(responsive_images) = responsive_images {
+            let responsive_images = responsive_images.await?;
+            for &width in &responsive_images.widths {
+                let resized = ResizedImageSource::new(source, width, responsive_images.quality);
+                inner_assets.insert(
+                    format!("IMAGE_{width}").into(),
+                    Vc::upcast(StaticModuleAsset::new(
+                        Vc::upcast(resized),
+                        Vc::upcast(module_asset_context),
+                    )),
+                );
+            }
+        }
+        Ok(module_asset_context
             .process(
                 Vc::upcast(
                     StructuredImageFileSource {
                         image: source,
                         blur_placeholder_mode,
+                        responsive_images,
                     }
                     .cell(),
                 ),
-                Value::new(ReferenceType::Internal(Vc::cell(indexmap!(
-                    "IMAGE".into() => Vc::upcast(static_asset)
-                )))),
+                Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
             )
-            .module()
+            .module())
     }
 
     #[turbo_tasks::function]
-    pub fn new(blur_placeholder_mode: Value<BlurPlaceholderMode>) -> Vc<Self> {
+    pub fn new(
+        blur_placeholder_mode: Value<BlurPlaceholderMode>,
+        responsive_images: Option<Vc<ResponsiveImageOptions>>,
+    ) -> Vc<Self> {
         StructuredImageModuleType::cell(StructuredImageModuleType {
             blur_placeholder_mode: blur_placeholder_mode.into_value(),
+            responsive_images,
         })
     }
 }
@@ -77,6 +114,7 @@ impl CustomModuleType for StructuredImageModuleType {
         StructuredImageModuleType::create_module(
             source,
             self.blur_placeholder_mode,
+            self.responsive_images,
             module_asset_context,
         )
     }
@@ -0,0 +1,60 @@
+use anyhow::{bail, Result};
+use turbo_tasks::{RcStr, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    source::Source,
+};
+use turbopack_image::process::optimize;
+
+/// A source asset that resizes an image to a width, keeping its aspect ratio
+/// and format. Images that are narrower already are kept as they are.
+#[turbo_tasks::value(shared)]
+pub struct ResizedImageSource {
+    pub image: Vc<Box<dyn Source>>,
+    pub width: u32,
+    pub quality: u8,
+}
+
+#[turbo_tasks::value_impl]
+impl ResizedImageSource {
+    #[turbo_tasks::function]
+    pub fn new(image: Vc<Box<dyn Source>>, width: u32, quality: u8) -> Vc<Self> {
+        ResizedImageSource {
+            image,
+            width,
+            quality,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for ResizedImageSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.image
+            .ident()
+            .with_modifier(Vc::cell(RcStr::from(format!(
+                "resized to {}w q{}",
+                self.width, self.quality
+            ))))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ResizedImageSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let AssetContent::File(content) = *self.image.content().await? else {
+            bail!("Input source is not a file and can't be resized");
+        };
+        Ok(AssetContent::file(optimize(
+            self.image.ident(),
+            content,
+            self.width,
+            u32::MAX,
+            self.quality,
+        )))
+    }
+}
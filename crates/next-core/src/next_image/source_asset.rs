@@ -11,7 +11,7 @@ use turbopack_core::{
 use turbopack_ecmascript::utils::StringifyJs;
 use turbopack_image::process::{get_meta_data, BlurPlaceholderOptions};
 
-use super::module::BlurPlaceholderMode;
+use super::module::{BlurPlaceholderMode, ResponsiveImageOptions};
 
 fn modifier() -> Vc<RcStr> {
     Vc::cell("structured image object".into())
@@ -26,12 +26,26 @@ fn blur_options() -> Vc<BlurPlaceholderOptions> {
     .cell()
 }
 
+/// Image types that can be resized to the widths of [ResponsiveImageOptions].
+/// AVIF images can only be decoded, and with that resized, by the native
+/// decoder.
+const RESIZABLE_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    #[cfg(feature = "image-avif")]
+    "image/avif",
+];
+
 /// An source asset that transforms an image into javascript code which exports
 /// an object with meta information like width, height and a blur placeholder.
+/// With [ResponsiveImageOptions], a `srcSet` of resized variants is exported
+/// too.
 #[turbo_tasks::value(shared)]
 pub struct StructuredImageFileSource {
     pub image: Vc<Box<dyn Source>>,
     pub blur_placeholder_mode: BlurPlaceholderMode,
+    pub responsive_images: Option<Vc<ResponsiveImageOptions>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -55,6 +69,30 @@ impl Asset for StructuredImageFileSource {
         };
         let mut result = RopeBuilder::from("");
         writeln!(result, "import src from \"IMAGE\";",)?;
+        let src_set = if let Some(responsive_images) = self.responsive_images {
+            let info = get_meta_data(self.image.ident(), content, None).await?;
+            let widths: Vec<u32> = match &info.mime_type {
+                Some(mime_type) if RESIZABLE_MIME_TYPES.contains(&mime_type.essence_str()) => {
+                    responsive_images
+                        .await?
+                        .widths
+                        .iter()
+                        .copied()
+                        .filter(|&width| width < info.width)
+                        .collect()
+                }
+                _ => vec![],
+            };
+            let mut src_set = Vec::with_capacity(widths.len() + 1);
+            for width in widths {
+                writeln!(result, "import src{width} from \"IMAGE_{width}\";")?;
+                src_set.push(format!("src{width} + \" {width}w\""));
+            }
+            src_set.push(format!("src + \" {}w\"", info.width));
+            format!(", srcSet: [{}].join(\", \")", src_set.join(", "))
+        } else {
+            String::new()
+        };
         let blur_options = blur_options();
         match self.blur_placeholder_mode {
             BlurPlaceholderMode::NextImageUrl => {
@@ -77,7 +115,7 @@ impl Asset for StructuredImageFileSource {
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
                      `/_next/image?w={blur_width}&q={quality}&url=${{encodeURIComponent(src)}}`, \
-                     blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     blurWidth: {blur_width}, blurHeight: {blur_height}{src_set} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                     quality = StringifyJs(&blur_options.quality),
@@ -90,7 +128,8 @@ impl Asset for StructuredImageFileSource {
                 writeln!(
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
-                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: {blur_height}{src_set} \
+                     }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                     blur_data_url =
@@ -105,7 +144,7 @@ impl Asset for StructuredImageFileSource {
                 let info = get_meta_data(self.image.ident(), content, None).await?;
                 writeln!(
                     result,
-                    "export default {{ src, width: {width}, height: {height} }}",
+                    "export default {{ src, width: {width}, height: {height}{src_set} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                 )?;
@@ -14,7 +14,7 @@ use turbopack::{
     transition::Transition,
 };
 use turbopack_core::{
-    chunk::module_id_strategies::ModuleIdStrategy,
+    chunk::{module_id_strategies::ModuleIdStrategy, ChunkingConfig},
     compile_time_info::{
         CompileTimeDefineValue, CompileTimeDefines, CompileTimeInfo, DefineableNameSegment,
         FreeVarReferences,
@@ -429,6 +429,7 @@ pub async fn get_server_module_options_context(
     let foreign_enable_webpack_loaders = webpack_loader_options(
         project_path,
         next_config,
+        mode,
         true,
         conditions
             .iter()
@@ -440,7 +441,7 @@ pub async fn get_server_module_options_context(
 
     // Now creates a webpack rules that applies to all codes.
     let enable_webpack_loaders =
-        webpack_loader_options(project_path, next_config, false, conditions).await?;
+        webpack_loader_options(project_path, next_config, mode, false, conditions).await?;
 
     let tree_shaking_mode_for_user_code = *next_config
         .tree_shaking_mode_for_user_code(next_mode.is_development())
@@ -930,6 +931,9 @@ pub async fn get_server_chunking_context_with_client_assets(
     asset_prefix: Vc<Option<RcStr>>,
     environment: Vc<Environment>,
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: Vc<bool>,
+    inline_wasm_limit: Vc<Option<u64>>,
 ) -> Result<Vc<NodeJsChunkingContext>> {
     let next_mode = mode.await?;
     // TODO(alexkirsz) This should return a trait that can be implemented by the
@@ -947,6 +951,9 @@ pub async fn get_server_chunking_context_with_client_assets(
     .asset_prefix(asset_prefix)
     .minify_type(next_mode.minify_type())
     .module_id_strategy(module_id_strategy)
+    .chunking_config(chunking_config)
+    .inline_css_imports(*inline_css_imports.await?)
+    .inline_wasm_limit(*inline_wasm_limit.await?)
     .build())
 }
 
@@ -957,6 +964,9 @@ pub async fn get_server_chunking_context(
     node_root: Vc<FileSystemPath>,
     environment: Vc<Environment>,
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: Vc<bool>,
+    inline_wasm_limit: Vc<Option<u64>>,
 ) -> Result<Vc<NodeJsChunkingContext>> {
     let next_mode = mode.await?;
     // TODO(alexkirsz) This should return a trait that can be implemented by the
@@ -973,5 +983,8 @@ pub async fn get_server_chunking_context(
     )
     .minify_type(next_mode.minify_type())
     .module_id_strategy(module_id_strategy)
+    .chunking_config(chunking_config)
+    .inline_css_imports(*inline_css_imports.await?)
+    .inline_wasm_limit(*inline_wasm_limit.await?)
     .build())
 }
@@ -122,7 +122,9 @@ pub async fn get_next_server_transforms_rules(
         // rules.push(get_next_optimize_server_react_rule(enable_mdx_rs,
         // optimize_use_state))
 
-        rules.push(get_next_image_rule());
+        rules.push(get_next_image_rule(
+            *next_config.responsive_image_options().await?,
+        ));
     }
 
     if let NextRuntime::Edge = next_runtime {
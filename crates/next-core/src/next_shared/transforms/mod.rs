@@ -33,9 +33,12 @@ use turbopack::module_options::{ModuleRule, ModuleRuleEffect, ModuleType, RuleCo
 use turbopack_core::reference_type::{ReferenceType, UrlReferenceSubType};
 use turbopack_ecmascript::{CustomTransformer, EcmascriptInputTransform};
 
-use crate::next_image::{module::BlurPlaceholderMode, StructuredImageModuleType};
+use crate::next_image::{
+    module::{BlurPlaceholderMode, ResponsiveImageOptions},
+    StructuredImageModuleType,
+};
 
-pub fn get_next_image_rule() -> ModuleRule {
+pub fn get_next_image_rule(responsive_images: Option<Vc<ResponsiveImageOptions>>) -> ModuleRule {
     ModuleRule::new(
         RuleCondition::All(vec![
             // avoid urlAssetReference to be affected by this rule, since urlAssetReference
@@ -52,17 +55,18 @@ pub fn get_next_image_rule() -> ModuleRule {
                 RuleCondition::ResourcePathEndsWith(".svg".to_string()),
                 RuleCondition::ResourcePathEndsWith(".bmp".to_string()),
                 RuleCondition::ResourcePathEndsWith(".ico".to_string()),
-                // These images may not be encoded by turbopack depends on the feature availability
-                // As turbopack-image returns raw bytes if compile time codec support is not
-                // enabled: ref:https://github.com/vercel/turbo/pull/5967
+                // AVIF images are only decoded when turbopack-image is built with the native
+                // decoder, otherwise only their metadata is read and the raw bytes are emitted.
+                // ref:https://github.com/vercel/turbo/pull/5967
                 RuleCondition::ResourcePathEndsWith(".webp".to_string()),
                 RuleCondition::ResourcePathEndsWith(".avif".to_string()),
             ]),
         ]),
         vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
-            Vc::upcast(StructuredImageModuleType::new(Value::new(
-                BlurPlaceholderMode::DataUrl,
-            ))),
+            Vc::upcast(StructuredImageModuleType::new(
+                Value::new(BlurPlaceholderMode::DataUrl),
+                responsive_images,
+            )),
         ))],
     )
 }
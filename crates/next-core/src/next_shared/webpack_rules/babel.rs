@@ -50,7 +50,12 @@ pub async fn maybe_add_babel_loader(
         let mut has_emitted_babel_resolve_issue = false;
         let mut has_changed = false;
         for pattern in ["*.js", "*.jsx", "*.ts", "*.tsx", "*.cjs", "*.mjs"] {
-            let rule = rules.get_mut(pattern);
+            // Conditional rules for the pattern keep their loaders, the babel-loader is
+            // added to the rule that applies to all modules
+            let rule = rules
+                .iter_mut()
+                .find(|(glob, rule)| glob == pattern && rule.conditions == Default::default())
+                .map(|(_, rule)| rule);
             let has_babel_loader = if let Some(rule) = rule.as_ref() {
                 rule.loaders
                     .await?
@@ -91,13 +96,14 @@ pub async fn maybe_add_babel_loader(
                     loaders.push(loader);
                     rule.loaders = Vc::cell(loaders);
                 } else {
-                    rules.insert(
+                    rules.push((
                         pattern.into(),
                         LoaderRuleItem {
                             loaders: Vc::cell(vec![loader]),
                             rename_as: Some("*".into()),
+                            conditions: Default::default(),
                         },
-                    );
+                    ));
                 }
                 has_changed = true;
             }
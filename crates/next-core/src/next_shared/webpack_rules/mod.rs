@@ -3,9 +3,12 @@ use turbo_tasks::{RcStr, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack::module_options::WebpackLoadersOptions;
 use turbopack_core::resolve::options::ImportMapping;
+use turbopack_node::transforms::loader_cache::LoaderCacheOptions;
 
 use self::{babel::maybe_add_babel_loader, sass::maybe_add_sass_loader};
-use crate::{next_build::get_external_next_compiled_package_mapping, next_config::NextConfig};
+use crate::{
+    mode::NextMode, next_build::get_external_next_compiled_package_mapping, next_config::NextConfig,
+};
 
 pub(crate) mod babel;
 pub(crate) mod sass;
@@ -13,6 +16,7 @@ pub(crate) mod sass;
 pub async fn webpack_loader_options(
     project_path: Vc<FileSystemPath>,
     next_config: Vc<NextConfig>,
+    mode: Vc<NextMode>,
     foreign: bool,
     conditions: Vec<RcStr>,
 ) -> Result<Option<Vc<WebpackLoadersOptions>>> {
@@ -23,10 +27,21 @@ pub async fn webpack_loader_options(
     } else {
         *maybe_add_babel_loader(project_path, rules).await?
     };
+    let loader_cache = match &*next_config.webpack_loader_cache_dir().await? {
+        Some(dir) => Some(
+            LoaderCacheOptions {
+                dir: project_path.join(dir.clone()),
+                mode: mode.await?.condition().into(),
+            }
+            .cell(),
+        ),
+        None => None,
+    };
     Ok(rules.map(|rules| {
         WebpackLoadersOptions {
             rules,
             loader_runner_package: Some(loader_runner_package_mapping()),
+            loader_cache,
         }
         .cell()
     }))
@@ -31,7 +31,10 @@ pub async fn maybe_add_sass_loader(
         let additional_data = sass_options
             .get("prependData")
             .or(sass_options.get("additionalData"));
-        let rule = rules.get_mut(pattern);
+        let rule = rules
+            .iter_mut()
+            .find(|(glob, rule)| glob == pattern && rule.conditions == Default::default())
+            .map(|(_, rule)| rule);
         let sass_loader = WebpackLoaderItem {
             loader: "next/dist/compiled/sass-loader".into(),
             options: take(
@@ -73,13 +76,14 @@ pub async fn maybe_add_sass_loader(
             loaders.push(sass_loader);
             rule.loaders = Vc::cell(loaders);
         } else {
-            rules.insert(
+            rules.push((
                 pattern.into(),
                 LoaderRuleItem {
                     loaders: Vc::cell(vec![resolve_url_loader, sass_loader]),
                     rename_as: Some(format!("*{rename}").into()),
+                    conditions: Default::default(),
                 },
-            );
+            ));
         }
     }
 
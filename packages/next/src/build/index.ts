@@ -1411,6 +1411,7 @@ export default async function build(
           {
             persistentCaching: config.experimental.turbo?.persistentCaching,
             memoryLimit: config.experimental.turbo?.memoryLimit,
+            nodePool: config.experimental.turbo?.nodePool,
           }
         )
 
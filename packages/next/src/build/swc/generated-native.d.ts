@@ -177,6 +177,18 @@ export interface NapiTurboEngineOptions {
   persistentCaching?: boolean
   /** An upper bound of memory that turbopack will attempt to stay under. */
   memoryLimit?: number
+  /** Limits of the Node.js processes that run webpack loaders and PostCSS. */
+  nodePool?: NapiNodePoolOptions
+}
+export interface NapiNodePoolOptions {
+  /** Share the Node.js processes and their limit between all loaders. */
+  shared?: boolean
+  /** The number of Node.js processes of the shared pool. */
+  maxWorkers?: number
+  /** Processes using more memory, in MB, are restarted. */
+  maxWorkerRssMb?: number
+  /** Processes that are idle for longer, in milliseconds, are stopped. */
+  workerIdleTimeoutMs?: number
 }
 export function projectNew(
   options: NapiProjectOptions,
@@ -246,6 +258,17 @@ export interface NapiUpdateMessage {
 export interface NapiUpdateInfo {
   duration: number
   tasks: number
+  nodeJsPools: NapiNodeJsPoolsStats
+}
+export interface NapiNodeJsPoolsStats {
+  pools: number
+  workers: number
+  bootingWorkers: number
+  idleWorkers: number
+  spareWorkers: number
+  queuedTasks: number
+  recycledWorkers: number
+  idleTimedOutWorkers: number
 }
 /**
  * Subscribes to lifecycle events of the compilation.
@@ -255,7 +278,8 @@ export interface NapiUpdateInfo {
  * information about the computations that happened since the
  * [UpdateMessage::Start] event. It contains the duration of the computation
  * (excluding the idle time that was spend waiting for `aggregation_ms`), and
- * the number of tasks that were executed.
+ * the number of tasks that were executed. It also contains the current
+ * statistics of the Node.js pools used for webpack loaders and PostCSS.
  *
  * The signature of the `func` is `(update_message: UpdateMessage) => void`.
  */
@@ -121,6 +121,16 @@ export interface TurboEngineOptions {
    * An upper bound of memory that turbopack will attempt to stay under.
    */
   memoryLimit?: number
+
+  /**
+   * Limits of the Node.js processes that run webpack loaders and PostCSS.
+   */
+  nodePool?: {
+    shared?: boolean
+    maxWorkers?: number
+    maxWorkerRssMb?: number
+    workerIdleTimeoutMs?: number
+  }
 }
 
 export interface Middleware {
@@ -198,6 +208,18 @@ export type UpdateMessage =
 export interface UpdateInfo {
   duration: number
   tasks: number
+  nodeJsPools: NodeJsPoolsStats
+}
+
+export interface NodeJsPoolsStats {
+  pools: number
+  workers: number
+  bootingWorkers: number
+  idleWorkers: number
+  spareWorkers: number
+  queuedTasks: number
+  recycledWorkers: number
+  idleTimedOutWorkers: number
 }
 
 export interface Project {
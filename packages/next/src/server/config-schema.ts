@@ -111,6 +111,9 @@ const zTurboRuleConfigItemOptions: zod.ZodType<TurboRuleConfigItemOptions> =
   z.object({
     loaders: z.array(zTurboLoaderItem),
     as: z.string().optional(),
+    resourceQuery: z.string().optional(),
+    with: z.object({ type: z.string() }).optional(),
+    issuer: z.string().optional(),
   })
 
 const zTurboRuleConfigItem: zod.ZodType<TurboRuleConfigItem> = z.union([
@@ -123,7 +126,11 @@ const zTurboRuleConfigItem: zod.ZodType<TurboRuleConfigItem> = z.union([
 ])
 
 const zTurboRuleConfigItemOrShortcut: zod.ZodType<TurboRuleConfigItemOrShortcut> =
-  z.union([z.array(zTurboLoaderItem), zTurboRuleConfigItem])
+  z.union([
+    z.array(zTurboLoaderItem),
+    zTurboRuleConfigItem,
+    z.array(zTurboRuleConfigItem),
+  ])
 
 export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
   z.strictObject({
@@ -393,7 +400,28 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
             treeShaking: z.boolean().optional(),
             persistentCaching: z.boolean().optional(),
             memoryLimit: z.number().optional(),
-            moduleIdStrategy: z.enum(['named', 'deterministic']).optional(),
+            moduleIdStrategy: z
+              .enum(['named', 'deterministic', 'records'])
+              .optional(),
+            staticImageVariants: z.boolean().optional(),
+            loaderCache: z.boolean().optional(),
+            inlineCssImports: z.boolean().optional(),
+            minChunkSize: z.number().int().nonnegative().optional(),
+            maxChunkSize: z.number().int().nonnegative().optional(),
+            maxChunkCountPerGroup: z.number().int().positive().optional(),
+            splitByPackage: z.boolean().optional(),
+            clientSourceMaps: z
+              .enum(['full', 'hidden', 'nosources', 'inline'])
+              .optional(),
+            inlineWasmLimit: z.number().int().nonnegative().optional(),
+            nodePool: z
+              .object({
+                shared: z.boolean().optional(),
+                maxWorkers: z.number().int().positive().optional(),
+                maxWorkerRssMb: z.number().int().positive().optional(),
+                workerIdleTimeoutMs: z.number().int().positive().optional(),
+              })
+              .optional(),
           })
           .optional(),
         optimizePackageImports: z.array(z.string()).optional(),
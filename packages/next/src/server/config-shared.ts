@@ -101,10 +101,26 @@ export type TurboLoaderItem =
 export type TurboRuleConfigItemOrShortcut =
   | TurboLoaderItem[]
   | TurboRuleConfigItem
+  | TurboRuleConfigItem[]
 
 export type TurboRuleConfigItemOptions = {
   loaders: TurboLoaderItem[]
   as?: string
+  /**
+   * Only apply the loaders when the query string of the module equals this,
+   * e.g. `'?raw'`.
+   */
+  resourceQuery?: string
+  /**
+   * Only apply the loaders to imports with these import attributes, e.g.
+   * `{ type: 'text' }` for `import text from './file.txt' with { type: 'text' }`.
+   */
+  with?: { type: string }
+  /**
+   * Only apply the loaders when the importing module matches this glob,
+   * relative to the project directory.
+   */
+  issuer?: string
 }
 
 export type TurboRuleConfigItem =
@@ -168,8 +184,95 @@ export interface ExperimentalTurboOptions {
    * The module ID strategy to use for Turbopack.
    * If not set, the default is `'named'` for development and `'deterministic'`
    * for production.
+   * `'records'` works like `'deterministic'`, but production builds persist
+   * the ids in `<distDir>/cache/turbopack-module-ids.json` and reuse them in
+   * the next build, so ids only change for new modules.
+   */
+  moduleIdStrategy?: 'named' | 'deterministic' | 'records'
+
+  /**
+   * Generate resized variants of statically imported images for every size in
+   * `images.deviceSizes` at build time, and export them as `srcSet`. Only
+   * applies to `output: 'export'`.
+   */
+  staticImageVariants?: boolean
+
+  /**
+   * Persist the results of webpack loaders in
+   * `<distDir>/cache/turbopack-loaders`, so they are reused after a restart.
+   * Only enable this when all configured loaders are deterministic.
    */
-  moduleIdStrategy?: 'named' | 'deterministic'
+  loaderCache?: boolean
+
+  /**
+   * Inline `@import`ed stylesheets into the chunk of the importing stylesheet,
+   * in the order bundling them would result in.
+   */
+  inlineCssImports?: boolean
+
+  /**
+   * Chunks smaller than this many bytes are merged with other small chunks of
+   * the same chunk group. Defaults to 100000.
+   */
+  minChunkSize?: number
+
+  /**
+   * Chunks larger than this many bytes are split by package or folder.
+   * Defaults to 1000000.
+   */
+  maxChunkSize?: number
+
+  /**
+   * The maximum number of chunks a chunk group is split into, i.e. the number
+   * of requests needed to load it.
+   */
+  maxChunkCountPerGroup?: number
+
+  /**
+   * Put every node_modules package into its own chunks, so a package update
+   * only invalidates its own chunks.
+   */
+  splitByPackage?: boolean
+
+  /**
+   * How the source maps of browser chunks are emitted. `hidden` omits the
+   * `sourceMappingURL` comment, `nosources` omits the original code and
+   * `inline` embeds the source map into the chunk. Defaults to `full`.
+   */
+  clientSourceMaps?: 'full' | 'hidden' | 'nosources' | 'inline'
+
+  /**
+   * WebAssembly modules smaller than this many bytes are embedded into the
+   * chunk as base64 instead of being loaded with a separate request.
+   */
+  inlineWasmLimit?: number
+
+  /**
+   * Limits of the Node.js processes that run webpack loaders and PostCSS
+   * configs. Options that aren't set fall back to the
+   * `TURBOPACK_NODE_POOL*` and `TURBOPACK_NODE_WORKER*` environment variables.
+   */
+  nodePool?: {
+    /**
+     * Share warm Node.js processes and one concurrency limit between all
+     * loaders instead of spawning processes per loader.
+     */
+    shared?: boolean
+    /**
+     * The number of Node.js processes of the shared pool. Defaults to the
+     * available parallelism.
+     */
+    maxWorkers?: number
+    /**
+     * Processes with a larger resident set size, in MB, after an evaluation
+     * are restarted instead of being reused.
+     */
+    maxWorkerRssMb?: number
+    /**
+     * Processes that are idle for longer, in milliseconds, are stopped.
+     */
+    workerIdleTimeoutMs?: number
+  }
 
   /**
    * This is the repo root usually and only files above this
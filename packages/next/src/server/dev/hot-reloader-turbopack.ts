@@ -178,6 +178,7 @@ export async function createHotReloaderTurbopack(
     {
       persistentCaching: opts.nextConfig.experimental.turbo?.persistentCaching,
       memoryLimit: opts.nextConfig.experimental.turbo?.memoryLimit,
+      nodePool: opts.nextConfig.experimental.turbo?.nodePool,
     }
   )
   opts.onCleanup(() => project.onExit())
@@ -16,6 +16,12 @@ export interface StaticImageData {
   blurDataURL?: string
   blurWidth?: number
   blurHeight?: number
+  /**
+   * Resized variants of the image, generated by Turbopack with
+   * `experimental.turbo.staticImageVariants`. Used when the image is not
+   * optimized.
+   */
+  srcSet?: string
 }
 
 export interface StaticRequire {
@@ -187,6 +193,7 @@ type GenImgAttrsData = {
   width?: number
   quality?: number
   sizes?: string
+  staticSrcSet?: string
 }
 
 type GenImgAttrsResult = {
@@ -203,8 +210,16 @@ function generateImgAttrs({
   quality,
   sizes,
   loader,
+  staticSrcSet,
 }: GenImgAttrsData): GenImgAttrsResult {
   if (unoptimized) {
+    if (staticSrcSet) {
+      return {
+        sizes: sizes || (width ? `${width}px` : '100vw'),
+        srcSet: staticSrcSet,
+        src,
+      }
+    }
     return { src, srcSet: undefined, sizes: undefined }
   }
 
@@ -348,6 +363,7 @@ export function getImgProps(
   let heightInt = getInt(height)
   let blurWidth: number | undefined
   let blurHeight: number | undefined
+  let staticSrcSet: string | undefined
   if (isStaticImport(src)) {
     const staticImageData = isStaticRequire(src) ? src.default : src
 
@@ -370,6 +386,7 @@ export function getImgProps(
     blurHeight = staticImageData.blurHeight
     blurDataURL = blurDataURL || staticImageData.blurDataURL
     staticSrc = staticImageData.src
+    staticSrcSet = staticImageData.srcSet
 
     if (!fill) {
       if (!widthInt && !heightInt) {
@@ -665,6 +682,7 @@ export function getImgProps(
     quality: qualityInt,
     sizes,
     loader,
+    staticSrcSet,
   })
 
   if (process.env.NODE_ENV !== 'production') {
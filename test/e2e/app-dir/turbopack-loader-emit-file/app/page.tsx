@@ -0,0 +1,10 @@
+// @ts-expect-error -- ignore
+import url from './hello.txt'
+
+export default function Page() {
+  return (
+    <a id="emitted" href={url}>
+      emitted file
+    </a>
+  )
+}
@@ -0,0 +1,15 @@
+/** @type {import('next').NextConfig} */
+const nextConfig = {
+  experimental: {
+    turbo: {
+      rules: {
+        '*.txt': {
+          loaders: ['emit-file-loader.js'],
+          as: '*.js',
+        },
+      },
+    },
+  },
+}
+
+module.exports = nextConfig
@@ -0,0 +1,19 @@
+import { nextTestSetup } from 'e2e-utils'
+;(process.env.TURBOPACK ? describe : describe.skip)(
+  'turbopack-loader-emit-file',
+  () => {
+    const { next } = nextTestSetup({
+      files: __dirname,
+    })
+
+    it('should serve files emitted by loaders', async () => {
+      const $ = await next.render$('/')
+      const url = $('#emitted').attr('href')
+      expect(url).toBe('/_next/static/media/emitted.txt')
+
+      const res = await next.fetch(url)
+      expect(res.status).toBe(200)
+      expect(await res.text()).toBe('Hello from a loader\n')
+    })
+  }
+)
@@ -114,6 +114,27 @@ const runTests = (isDev) => {
     expect(img.attr('height')).toBe('233')
   })
 
+  it('should read the dimensions of a statically imported webp and avif', async () => {
+    for (const id of ['#blur-webp', '#blur-avif']) {
+      const img = $(id)
+      expect(img.attr('width')).toBe('400')
+      expect(img.attr('height')).toBe('400')
+    }
+  })
+
+  it('should add a blur placeholder to a statically imported webp and avif', async () => {
+    for (const id of ['#blur-webp', '#blur-avif']) {
+      const style = $(id).attr('style')
+      if (isDev && !process.env.TURBOPACK) {
+        expect(style).toContain(`background-image:url("/_next/image?url=`)
+      } else {
+        expect(style).toContain(
+          `color:transparent;background-size:cover;background-position:50% 50%;background-repeat:no-repeat;background-image:url("data:image/svg+xml`
+        )
+      }
+    }
+  })
+
   it('should add a data URL placeholder to an image', async () => {
     const style = $('#data-url-placeholder').attr('style')
     expect(style).toBe(
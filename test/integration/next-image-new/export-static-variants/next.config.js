@@ -0,0 +1,12 @@
+module.exports = {
+  output: 'export',
+  images: {
+    unoptimized: true,
+    deviceSizes: [100, 200, 800],
+  },
+  experimental: {
+    turbo: {
+      staticImageVariants: true,
+    },
+  },
+}
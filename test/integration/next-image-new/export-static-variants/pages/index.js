@@ -0,0 +1,16 @@
+import React from 'react'
+import Image from 'next/image'
+import testPNG from '../images/test.png'
+import testWEBP from '../images/test.webp'
+
+const Page = () => {
+  return (
+    <div>
+      <h1>Static Image Variants</h1>
+      <Image id="png" alt="png" src={testPNG} />
+      <Image id="webp" alt="webp" src={testWEBP} />
+    </div>
+  )
+}
+
+export default Page
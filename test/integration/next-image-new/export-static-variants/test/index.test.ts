@@ -0,0 +1,49 @@
+/* eslint-env jest */
+
+import cheerio from 'cheerio'
+import fs from 'fs-extra'
+import { join } from 'path'
+import { nextBuild } from 'next-test-utils'
+
+const appDir = join(__dirname, '../')
+const outDir = join(appDir, 'out')
+
+describe('next/image with static image variants', () => {
+  // The variants are generated by Turbopack only
+  ;(process.env.TURBOPACK_BUILD ? describe : describe.skip)(
+    'production mode',
+    () => {
+      let $
+
+      beforeAll(async () => {
+        await fs.remove(outDir)
+        await nextBuild(appDir)
+        $ = cheerio.load(await fs.readFile(join(outDir, 'index.html'), 'utf8'))
+      })
+
+      for (const ext of ['png', 'webp']) {
+        it(`should export a srcSet of resized variants of the ${ext}`, async () => {
+          const srcSet = $(`#${ext}`).attr('srcset').split(', ')
+          // The image is 400px wide, so 800w isn't generated
+          expect(srcSet.map((src) => src.split(' ')[1])).toEqual([
+            '100w',
+            '200w',
+            '400w',
+          ])
+          const src = $(`#${ext}`).attr('src')
+          expect(srcSet[2]).toBe(`${src} 400w`)
+          for (const [url] of srcSet.map((src) => src.split(' '))) {
+            expect(url).toMatch(
+              new RegExp(`^/_next/static/media/test\\.[^/]+\\.${ext}$`)
+            )
+            expect(await fs.pathExists(join(outDir, url))).toBe(true)
+          }
+          // Every variant is a separate file
+          expect(new Set(srcSet.map((src) => src.split(' ')[0])).size).toBe(3)
+          // The variants are picked for the rendered width of the image
+          expect($(`#${ext}`).attr('sizes')).toBe('400px')
+        })
+      }
+    }
+  )
+})
@@ -615,7 +615,11 @@ impl TurboTasksBackendInner {
         // Continue all uncompleted operations
         // They can't be interrupted by a snapshot since the snapshotting job has not been scheduled
         // yet.
-        let uncompleted_operations = self.backing_storage.uncompleted_operations();
+        let uncompleted_operations = self
+            .backing_storage
+            .uncompleted_operations()
+            .inspect_err(|err| println!("Reading uncompleted operations failed: {err:?}"))
+            .unwrap_or_default();
         if !uncompleted_operations.is_empty() {
             let mut ctx = self.execute_context(turbo_tasks);
             for op in uncompleted_operations {
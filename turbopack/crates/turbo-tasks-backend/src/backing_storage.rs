@@ -1,12 +1,22 @@
-use std::sync::Arc;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    io::ErrorKind,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
 
-use anyhow::Result;
-use turbo_tasks::{backend::CachedTaskType, TaskId};
+use anyhow::{anyhow, bail, Context, Result};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use tracing::Span;
+use turbo_tasks::{backend::CachedTaskType, registry, KeyValuePair, TaskId};
 
 use crate::{
     backend::{AnyOperation, TaskDataCategory},
-    data::{CachedDataItem, CachedDataUpdate},
+    data::{CachedDataItem, CachedDataItemKey, CachedDataItemValue, CachedDataUpdate},
     utils::chunked_vec::ChunkedVec,
+    LmdbBackingStorage, LogBackingStorage,
 };
 
 #[derive(Clone, Copy)]
@@ -14,7 +24,7 @@ pub struct ReadTransaction(pub *const ());
 
 pub trait BackingStorage {
     fn next_free_task_id(&self) -> TaskId;
-    fn uncompleted_operations(&self) -> Vec<AnyOperation>;
+    fn uncompleted_operations(&self) -> Result<Vec<AnyOperation>>;
     fn save_snapshot(
         &self,
         operations: Vec<Arc<AnyOperation>>,
@@ -52,4 +62,517 @@ pub trait BackingStorage {
         task_id: TaskId,
         category: TaskDataCategory,
     ) -> Vec<CachedDataItem>;
+    /// Reads the data of a task like [BackingStorage::lookup_data], but reports
+    /// errors instead of treating the data as missing. Used to inspect the
+    /// content of the store.
+    fn read_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Vec<CachedDataItem>>;
+    /// Calls `visitor` for every stored entry, without deserializing them.
+    /// Used to inspect the content of the store.
+    fn visit_entries(&self, visitor: &mut dyn FnMut(StoredEntry<'_>)) -> Result<()>;
+    /// Removes the task cache entries and all data of the tasks.
+    fn remove_tasks(&self, task_ids: &[TaskId]) -> Result<()>;
+}
+
+/// An entry of a [BackingStorage], as reported by
+/// [BackingStorage::visit_entries].
+#[derive(Debug)]
+pub enum StoredEntry<'a> {
+    /// A task cache entry with the serialized [CachedTaskType].
+    TaskCache {
+        task_id: TaskId,
+        task_type: &'a [u8],
+    },
+    /// The serialized data of a task.
+    TaskData {
+        task_id: TaskId,
+        category: TaskDataCategory,
+        size: usize,
+    },
+    /// The serialized uncompleted operations.
+    Operations { size: usize },
+}
+
+/// The persistent [BackingStorage] implementations that can be selected when
+/// creating a [TurboTasksBackend](crate::TurboTasksBackend).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackingStorageKind {
+    /// An LMDB database. Fastest option, but requires a filesystem that
+    /// supports memory mapped files well.
+    #[default]
+    Lmdb,
+    /// A single append-only log file, read with regular file IO. Useful for
+    /// filesystems where memory mapping is problematic, e.g. overlayfs in
+    /// containers or network home directories.
+    Log,
+}
+
+impl BackingStorageKind {
+    /// Opens the persistent store of this kind at `path`, creating it when
+    /// necessary.
+    ///
+    /// `version` identifies the build that reads and writes the store. A store
+    /// that was written with a different version is discarded, since its
+    /// content can't be deserialized safely.
+    pub fn open(self, path: &Path, version: &str) -> Result<Arc<dyn BackingStorage + Sync + Send>> {
+        Ok(match self {
+            BackingStorageKind::Lmdb => Arc::new(LmdbBackingStorage::new(path, version)?),
+            BackingStorageKind::Log => Arc::new(LogBackingStorage::new(path, version)?),
+        })
+    }
+
+    /// Opens an existing store at `path` with the version it was written with,
+    /// regardless of the current version. Used to inspect a cache.
+    pub fn open_existing(path: &Path) -> Result<ExistingStore> {
+        let version_path = path.join(VERSION_FILE);
+        let content = fs::read_to_string(&version_path)
+            .with_context(|| anyhow!("{} is not a persistent cache", path.display()))?;
+        let mut lines = content.lines();
+        let (Some(store), Some(version)) = (lines.next(), lines.next()) else {
+            bail!("Invalid version file {}", version_path.display());
+        };
+        let registry_fingerprint = match lines.next() {
+            Some(fingerprint) => Some(u64::from_str_radix(fingerprint, 16).with_context(|| {
+                anyhow!("Invalid registry fingerprint in {}", version_path.display())
+            })?),
+            None => None,
+        };
+        let Some(kind) = [BackingStorageKind::Lmdb, BackingStorageKind::Log]
+            .into_iter()
+            .find(|kind| kind.store_name() == store)
+        else {
+            bail!(
+                "{} was written by an unsupported store {store:?}",
+                path.display()
+            );
+        };
+        Ok(ExistingStore {
+            kind,
+            storage: kind.open(path, version)?,
+            registry_fingerprint,
+        })
+    }
+
+    fn store_name(self) -> &'static str {
+        match self {
+            BackingStorageKind::Lmdb => crate::lmdb_backing_storage::STORE_NAME,
+            BackingStorageKind::Log => crate::log_backing_storage::STORE_NAME,
+        }
+    }
+}
+
+/// A store opened with [BackingStorageKind::open_existing].
+pub struct ExistingStore {
+    pub kind: BackingStorageKind,
+    pub storage: Arc<dyn BackingStorage + Sync + Send>,
+    /// The [registry fingerprint](turbo_tasks::registry::fingerprint) of the
+    /// build that created the store. Missing for stores created before it was
+    /// recorded.
+    pub registry_fingerprint: Option<u64>,
+}
+
+impl FromStr for BackingStorageKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "lmdb" => BackingStorageKind::Lmdb,
+            "log" => BackingStorageKind::Log,
+            _ => bail!("Unknown backing storage {s:?}, expected \"lmdb\" or \"log\""),
+        })
+    }
+}
+
+const VERSION_FILE: &str = "version";
+
+/// Returns true when `content` is a version file written by
+/// [prepare_versioned_dir], i.e. it starts with the name of a known store,
+/// including other revisions of it.
+fn is_version_file(content: &str) -> bool {
+    let Some((store, _)) = content.split_once('\n') else {
+        return false;
+    };
+    let Some((name, revision)) = store.rsplit_once('-') else {
+        return false;
+    };
+    matches!(name, "lmdb" | "log")
+        && !revision.is_empty()
+        && revision.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Returns true when `path` only contains the files of an LMDB environment,
+/// i.e. it is a store written before stores were marked with a version file.
+fn is_unversioned_lmdb_store(path: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(path) else {
+        return false;
+    };
+    let mut has_data = false;
+    for entry in entries {
+        let Ok(entry) = entry else {
+            return false;
+        };
+        match entry.file_name().to_str() {
+            Some("data.mdb") => has_data = true,
+            Some("lock.mdb") => {}
+            _ => return false,
+        }
+    }
+    has_data
+}
+
+/// Makes sure that `path` is a directory holding a store of type `store` that
+/// was written with `version`.
+///
+/// A directory written by another store type or version is cleared, so the
+/// store starts from scratch instead of misreading incompatible data. Only
+/// directories marked with a version file by this function, and LMDB stores
+/// from before version files were written, are ever cleared. Any other
+/// non-empty directory is an error.
+///
+/// A new version file also records the registry fingerprint of the current
+/// build, which tells whether the tasks in the store can be checked against
+/// the current registry.
+pub(crate) fn prepare_versioned_dir(path: &Path, store: &str, version: &str) -> Result<()> {
+    let expected = format!("{store}\n{version}\n");
+    let version_path = path.join(VERSION_FILE);
+    match fs::read_to_string(&version_path) {
+        Ok(content) if content.starts_with(&expected) => return Ok(()),
+        Ok(content) if is_version_file(&content) => {
+            println!(
+                "Discarding persistent cache at {}, it was written by an incompatible version",
+                path.display()
+            );
+            fs::remove_dir_all(path)
+                .with_context(|| anyhow!("Unable to remove cache at {}", path.display()))?;
+        }
+        Ok(_) => {
+            bail!(
+                "{} has a version file that wasn't written by a persistent cache, refusing to use \
+                 it as cache directory",
+                path.display()
+            );
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound && is_unversioned_lmdb_store(path) => {
+            println!(
+                "Discarding persistent cache at {}, it was written by an older version",
+                path.display()
+            );
+            fs::remove_dir_all(path)
+                .with_context(|| anyhow!("Unable to remove cache at {}", path.display()))?;
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            if fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_some()) {
+                bail!(
+                    "{} is not empty and is not a persistent cache, refusing to use it as cache \
+                     directory. Remove it or choose another directory.",
+                    path.display()
+                );
+            }
+        }
+        Err(err) => {
+            return Err(err).with_context(|| anyhow!("Unable to read {}", version_path.display()));
+        }
+    }
+    fs::create_dir_all(path)?;
+    let fingerprint = registry::fingerprint();
+    fs::write(&version_path, format!("{expected}{fingerprint:016x}\n"))
+        .with_context(|| anyhow!("Unable to write {}", version_path.display()))?;
+    Ok(())
+}
+
+pub(crate) type OrganizedTaskData = HashMap<
+    TaskId,
+    HashMap<CachedDataItemKey, (Option<CachedDataItemValue>, Option<CachedDataItemValue>)>,
+>;
+pub(crate) type ShardedOrganizedTaskData = Vec<OrganizedTaskData>;
+
+/// Groups the updates of a snapshot by task, dropping updates that restore the
+/// previous value.
+pub(crate) fn organize_task_data(
+    updates: Vec<ChunkedVec<CachedDataUpdate>>,
+) -> ShardedOrganizedTaskData {
+    let span = Span::current();
+    updates
+        .into_par_iter()
+        .map(|updates| {
+            let _span = span.clone().entered();
+            let mut task_updates: OrganizedTaskData = HashMap::new();
+            for CachedDataUpdate {
+                task,
+                key,
+                value,
+                old_value,
+            } in updates.into_iter()
+            {
+                let data = task_updates.entry(task).or_default();
+                match data.entry(key) {
+                    Entry::Occupied(mut entry) => {
+                        entry.get_mut().1 = value;
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert((old_value, value));
+                    }
+                }
+            }
+            task_updates.retain(|_, data| {
+                data.retain(|_, (old_value, value)| *old_value != *value);
+                !data.is_empty()
+            });
+            task_updates
+        })
+        .collect()
+}
+
+/// Applies the organized updates to the stored data of each task. `old_data`
+/// returns the currently stored data of a task, if there is any.
+pub(crate) fn restore_task_data(
+    task_updates: ShardedOrganizedTaskData,
+    mut old_data: impl FnMut(TaskId) -> Result<Option<Vec<CachedDataItem>>>,
+) -> Result<Vec<(TaskId, Vec<CachedDataItem>)>> {
+    let mut result = Vec::with_capacity(task_updates.iter().map(|m| m.len()).sum());
+
+    for (task, updates) in task_updates.into_iter().flatten() {
+        let mut map: HashMap<_, _> = match old_data(task)? {
+            Some(old_data) => old_data
+                .into_iter()
+                .map(|item| item.into_key_and_value())
+                .collect(),
+            None => HashMap::new(),
+        };
+        for (key, (_, value)) in updates {
+            if let Some(value) = value {
+                map.insert(key, value);
+            } else {
+                map.remove(&key);
+            }
+        }
+        let vec = map
+            .into_iter()
+            .map(|(key, value)| CachedDataItem::from_key_and_value(key, value))
+            .collect();
+        result.push((task, vec));
+    }
+
+    Ok(result)
+}
+
+/// Deserializes the stored data of a task. Falls back to a slower deserializer
+/// that reports the path of the failing item on errors.
+pub(crate) fn deserialize_task_data(task: TaskId, bytes: &[u8]) -> Result<Vec<CachedDataItem>> {
+    match pot::from_slice(bytes) {
+        Ok(data) => Ok(data),
+        Err(_) => serde_path_to_error::deserialize(
+            &mut pot::de::SymbolList::new().deserializer_for_slice(bytes)?,
+        )
+        .with_context(|| anyhow!("Unable to deserialize old value of {task}: {bytes:?}")),
+    }
+}
+
+pub(crate) fn serialize_task_data(
+    tasks: Vec<(TaskId, Vec<CachedDataItem>)>,
+) -> Result<Vec<(TaskId, Vec<u8>)>> {
+    tasks
+        .into_iter()
+        .map(|(task_id, mut data)| {
+            let value = match pot::to_vec(&data) {
+                #[cfg(not(feature = "verify_serialization"))]
+                Ok(value) => value,
+                _ => {
+                    let mut error = Ok(());
+                    data.retain(|item| {
+                        let mut buf = Vec::<u8>::new();
+                        let mut symbol_map = pot::ser::SymbolMap::new();
+                        let mut serializer = symbol_map.serializer_for(&mut buf).unwrap();
+                        if let Err(err) = serde_path_to_error::serialize(item, &mut serializer) {
+                            if item.is_optional() {
+                                println!("Skipping non-serializable optional item: {item:?}");
+                            } else {
+                                error = Err(err).context({
+                                    anyhow!(
+                                        "Unable to serialize data item for {task_id}: {item:#?}"
+                                    )
+                                });
+                            }
+                            false
+                        } else {
+                            #[cfg(feature = "verify_serialization")]
+                            {
+                                let deserialize: Result<CachedDataItem, _> =
+                                    serde_path_to_error::deserialize(
+                                        &mut pot::de::SymbolList::new()
+                                            .deserializer_for_slice(&buf)
+                                            .unwrap(),
+                                    );
+                                if let Err(err) = deserialize {
+                                    println!(
+                                        "Data item would not be deserializable {task_id}: \
+                                         {err:?}\n{item:#?}"
+                                    );
+                                    return false;
+                                }
+                            }
+                            true
+                        }
+                    });
+                    error?;
+
+                    pot::to_vec(&data).with_context(|| {
+                        anyhow!("Unable to serialize data items for {task_id}: {data:#?}")
+                    })?
+                }
+            };
+            Ok((task_id, value))
+        })
+        .collect()
+}
+
+/// The serialized content of a snapshot, ready to be written by a
+/// [BackingStorage].
+pub(crate) struct SerializedSnapshot {
+    pub operations: Vec<u8>,
+    pub task_cache: Vec<(Vec<u8>, TaskId)>,
+    pub meta: Vec<(TaskId, Vec<u8>)>,
+    pub data: Vec<(TaskId, Vec<u8>)>,
+}
+
+/// Serializes the updates of a snapshot. The task data updates are merged with
+/// the currently stored data, which is provided by `old_data`.
+pub(crate) fn serialize_snapshot(
+    operations: Vec<Arc<AnyOperation>>,
+    task_cache_updates: Vec<ChunkedVec<(Arc<CachedTaskType>, TaskId)>>,
+    meta_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+    data_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+    old_data: impl Fn(TaskDataCategory, TaskId) -> Result<Option<Vec<CachedDataItem>>> + Sync,
+) -> Result<SerializedSnapshot> {
+    let prepare = |category: TaskDataCategory, updates: Vec<ChunkedVec<CachedDataUpdate>>| {
+        let task_updates = organize_task_data(updates);
+        let items = restore_task_data(task_updates, |task| old_data(category, task))?;
+        serialize_task_data(items)
+    };
+    let (meta, data) = rayon::join(
+        || prepare(TaskDataCategory::Meta, meta_updates),
+        || prepare(TaskDataCategory::Data, data_updates),
+    );
+
+    let task_cache = task_cache_updates
+        .into_iter()
+        .flatten()
+        .map(|(task_type, task_id)| {
+            let task_type_bytes = pot::to_vec(&*task_type)
+                .with_context(|| anyhow!("Unable to serialize task cache key {task_type:?}"))?;
+            Ok((task_type_bytes, task_id))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let operations =
+        pot::to_vec(&operations).with_context(|| anyhow!("Unable to serialize operations"))?;
+
+    Ok(SerializedSnapshot {
+        operations,
+        task_cache,
+        meta: meta?,
+        data: data?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use turbo_tasks::registry;
+
+    use super::{is_version_file, prepare_versioned_dir, VERSION_FILE};
+
+    #[test]
+    fn version_files() {
+        assert!(is_version_file("lmdb-1\nabc\n"));
+        assert!(is_version_file("log-2\nabc\n"));
+        assert!(!is_version_file("lmdb-1"));
+        assert!(!is_version_file("lmdb\nabc\n"));
+        assert!(!is_version_file("1.2.3\n"));
+        assert!(!is_version_file("foo-1\nabc\n"));
+    }
+
+    #[test]
+    fn version_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        prepare_versioned_dir(&path, "log-1", "a").unwrap();
+        fs::write(path.join("data"), "data").unwrap();
+
+        prepare_versioned_dir(&path, "log-1", "a").unwrap();
+        assert_eq!(fs::read_to_string(path.join("data")).unwrap(), "data");
+        assert_eq!(
+            fs::read_to_string(path.join(VERSION_FILE)).unwrap(),
+            format!("log-1\na\n{:016x}\n", registry::fingerprint())
+        );
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        prepare_versioned_dir(&path, "log-1", "a").unwrap();
+        fs::write(path.join("data"), "data").unwrap();
+
+        prepare_versioned_dir(&path, "log-1", "b").unwrap();
+        assert!(!path.join("data").exists());
+        assert!(fs::read_to_string(path.join(VERSION_FILE))
+            .unwrap()
+            .starts_with("log-1\nb\n"));
+
+        prepare_versioned_dir(&path, "lmdb-1", "b").unwrap();
+        assert!(fs::read_to_string(path.join(VERSION_FILE))
+            .unwrap()
+            .starts_with("lmdb-1\nb\n"));
+    }
+
+    #[test]
+    fn no_marker() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // An empty directory is taken over.
+        let empty = dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        prepare_versioned_dir(&empty, "log-1", "a").unwrap();
+        assert!(empty.join(VERSION_FILE).exists());
+
+        // A directory with unrelated content is left alone.
+        let project = dir.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("package.json"), "{}").unwrap();
+        assert!(prepare_versioned_dir(&project, "log-1", "a").is_err());
+        assert!(project.join("package.json").exists());
+        assert!(!project.join(VERSION_FILE).exists());
+
+        // So is one with a version file that isn't ours.
+        fs::write(project.join(VERSION_FILE), "1.0.0\n").unwrap();
+        assert!(prepare_versioned_dir(&project, "log-1", "a").is_err());
+        assert!(project.join("package.json").exists());
+    }
+
+    #[test]
+    fn unversioned_lmdb_store() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // A LMDB store from before version files is stale and cleared.
+        let cache = dir.path().join("cache");
+        fs::create_dir(&cache).unwrap();
+        fs::write(cache.join("data.mdb"), "data").unwrap();
+        fs::write(cache.join("lock.mdb"), "lock").unwrap();
+        prepare_versioned_dir(&cache, "lmdb-1", "a").unwrap();
+        assert!(!cache.join("data.mdb").exists());
+        assert!(cache.join(VERSION_FILE).exists());
+
+        // Unless it contains anything else.
+        let mixed = dir.path().join("mixed");
+        fs::create_dir(&mixed).unwrap();
+        fs::write(mixed.join("data.mdb"), "data").unwrap();
+        fs::write(mixed.join("notes.txt"), "notes").unwrap();
+        assert!(prepare_versioned_dir(&mixed, "lmdb-1", "a").is_err());
+        assert!(mixed.join("data.mdb").exists());
+    }
 }
@@ -300,6 +300,35 @@ impl CachedDataItem {
         matches!(self, CachedDataItem::CellData { .. })
     }
 
+    /// The other task this item is an edge to, if any.
+    pub fn referenced_task(&self) -> Option<TaskId> {
+        match self {
+            CachedDataItem::Output {
+                value: OutputValue::Output(task),
+            } => Some(*task),
+            CachedDataItem::Output {
+                value: OutputValue::Cell(cell),
+            } => Some(cell.task),
+            CachedDataItem::Collectible { collectible, .. }
+            | CachedDataItem::AggregatedCollectible { collectible, .. }
+            | CachedDataItem::OutdatedCollectible { collectible, .. } => Some(collectible.task),
+            CachedDataItem::Child { task, .. }
+            | CachedDataItem::OutdatedChild { task, .. }
+            | CachedDataItem::OutputDependent { task, .. }
+            | CachedDataItem::CellDependent { task, .. }
+            | CachedDataItem::CollectiblesDependent { task, .. }
+            | CachedDataItem::Follower { task, .. }
+            | CachedDataItem::Upper { task, .. }
+            | CachedDataItem::AggregatedDirtyContainer { task, .. } => Some(*task),
+            CachedDataItem::OutputDependency { target, .. }
+            | CachedDataItem::OutdatedOutputDependency { target, .. } => Some(*target),
+            CachedDataItem::CellDependency { target, .. }
+            | CachedDataItem::OutdatedCellDependency { target, .. } => Some(target.task),
+            CachedDataItem::CollectiblesDependency { target, .. } => Some(target.task),
+            _ => None,
+        }
+    }
+
     pub fn new_scheduled(description: impl Fn() -> String + Sync + Send + 'static) -> Self {
         CachedDataItem::InProgress {
             value: InProgressState::Scheduled {
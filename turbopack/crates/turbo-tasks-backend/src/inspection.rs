@@ -0,0 +1,512 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{
+    de::{self, IgnoredAny, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use turbo_tasks::{registry, KeyValuePair, TaskId};
+
+use crate::{
+    backend::TaskDataCategory,
+    backing_storage::{BackingStorage, StoredEntry},
+    data::{CachedDataItemKey, CachedDataItemValue, CachedDataUpdate},
+    utils::chunked_vec::ChunkedVec,
+};
+
+/// The function of a task, read from a serialized
+/// [CachedTaskType](turbo_tasks::backend::CachedTaskType) without
+/// deserializing its arguments.
+///
+/// Unlike the task type itself, it can be read for functions that are not
+/// registered in the current build.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskTypeName {
+    Native {
+        function: String,
+    },
+    ResolveNative {
+        function: String,
+    },
+    ResolveTrait {
+        trait_type: String,
+        method_name: String,
+    },
+}
+
+impl TaskTypeName {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        pot::from_slice(bytes).context("Unable to read the function of a task type")
+    }
+
+    /// Whether the function is registered, i. e. whether the current build is
+    /// able to execute the task.
+    pub fn is_registered(&self) -> bool {
+        match self {
+            TaskTypeName::Native { function } | TaskTypeName::ResolveNative { function } => {
+                registry::get_function_id_by_global_name(function).is_some()
+            }
+            TaskTypeName::ResolveTrait {
+                trait_type,
+                method_name,
+            } => registry::get_trait_type_id_by_global_name(trait_type)
+                .is_some_and(|id| registry::get_trait(id).has_method(method_name)),
+        }
+    }
+}
+
+/// Uses the same format as
+/// [CachedTaskType::get_name](turbo_tasks::backend::CachedTaskType::get_name),
+/// but with global names.
+impl Display for TaskTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskTypeName::Native { function } => write!(f, "{function}"),
+            TaskTypeName::ResolveNative { function } => write!(f, "*{function}"),
+            TaskTypeName::ResolveTrait {
+                trait_type,
+                method_name,
+            } => write!(f, "*{trait_type}::{method_name}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskTypeName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// The `(function, arg)` pair of native task types.
+        struct FunctionName(String);
+
+        impl<'de> Deserialize<'de> for FunctionName {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct FunctionNameVisitor;
+                impl<'de> Visitor<'de> for FunctionNameVisitor {
+                    type Value = FunctionName;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        write!(formatter, "a function and its argument")
+                    }
+
+                    fn visit_seq<A: SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<FunctionName, A::Error> {
+                        let function = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                        while seq.next_element::<IgnoredAny>()?.is_some() {}
+                        Ok(FunctionName(function))
+                    }
+                }
+                deserializer.deserialize_seq(FunctionNameVisitor)
+            }
+        }
+
+        struct TaskTypeNameVisitor;
+        impl<'de> Visitor<'de> for TaskTypeNameVisitor {
+            type Value = TaskTypeName;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a valid CachedTaskType")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TaskTypeName, A::Error> {
+                let kind = seq
+                    .next_element::<u8>()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let name = match kind {
+                    0 | 1 => {
+                        let FunctionName(function) = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                        if kind == 0 {
+                            TaskTypeName::Native { function }
+                        } else {
+                            TaskTypeName::ResolveNative { function }
+                        }
+                    }
+                    2 => {
+                        let trait_type = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                        let method_name = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                        TaskTypeName::ResolveTrait {
+                            trait_type,
+                            method_name,
+                        }
+                    }
+                    _ => return Err(de::Error::custom("Invalid variant")),
+                };
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(name)
+            }
+        }
+
+        deserializer.deserialize_tuple(5, TaskTypeNameVisitor)
+    }
+}
+
+/// Statistics of the tasks of one function.
+#[derive(Debug, Default, Clone)]
+pub struct FunctionReport {
+    pub registered: bool,
+    pub tasks: usize,
+    /// Bytes of the serialized task types, i. e. of the task cache.
+    pub task_type_bytes: usize,
+    pub meta_bytes: usize,
+    pub data_bytes: usize,
+}
+
+/// The content of a [BackingStorage], grouped by the function of the tasks.
+#[derive(Debug, Default)]
+pub struct CacheReport {
+    pub functions: BTreeMap<TaskTypeName, FunctionReport>,
+    /// Tasks that have a task type that can't be read.
+    pub unreadable_tasks: Vec<TaskId>,
+    /// Data of tasks without a task cache entry, e. g. root tasks.
+    pub orphaned_meta_bytes: usize,
+    pub orphaned_data_bytes: usize,
+    pub operations_bytes: usize,
+    /// The number of uncompleted operations. `None` when they can't be
+    /// deserialized, e. g. because they contain task types that are not
+    /// registered in the current build.
+    pub uncompleted_operations: Option<usize>,
+}
+
+impl CacheReport {
+    pub fn tasks(&self) -> usize {
+        self.functions.values().map(|f| f.tasks).sum()
+    }
+
+    pub fn bytes(&self, category: TaskDataCategory) -> usize {
+        let (functions, orphaned) = match category {
+            TaskDataCategory::Meta => (
+                self.functions.values().map(|f| f.meta_bytes).sum::<usize>(),
+                self.orphaned_meta_bytes,
+            ),
+            TaskDataCategory::Data => (
+                self.functions.values().map(|f| f.data_bytes).sum::<usize>(),
+                self.orphaned_data_bytes,
+            ),
+            TaskDataCategory::All => {
+                return self.bytes(TaskDataCategory::Meta) + self.bytes(TaskDataCategory::Data)
+            }
+        };
+        functions + orphaned
+    }
+
+    /// The functions that are not registered in the current build.
+    pub fn unregistered_functions(&self) -> impl Iterator<Item = (&TaskTypeName, &FunctionReport)> {
+        self.functions
+            .iter()
+            .filter(|(_, report)| !report.registered)
+    }
+}
+
+/// Collects statistics about the content of a [BackingStorage].
+pub fn inspect_cache(storage: &dyn BackingStorage) -> Result<CacheReport> {
+    let mut report = CacheReport::default();
+    let mut task_names = HashMap::new();
+    let mut task_data = Vec::new();
+    storage.visit_entries(&mut |entry| match entry {
+        StoredEntry::TaskCache { task_id, task_type } => {
+            match TaskTypeName::from_bytes(task_type) {
+                Ok(name) => {
+                    let function = report.functions.entry(name.clone()).or_default();
+                    function.tasks += 1;
+                    function.task_type_bytes += task_type.len();
+                    task_names.insert(task_id, name);
+                }
+                Err(_) => report.unreadable_tasks.push(task_id),
+            }
+        }
+        StoredEntry::TaskData {
+            task_id,
+            category,
+            size,
+        } => task_data.push((task_id, category, size)),
+        StoredEntry::Operations { size } => report.operations_bytes = size,
+    })?;
+
+    for (task_id, category, size) in task_data {
+        let function = task_names
+            .get(&task_id)
+            .and_then(|name| report.functions.get_mut(name));
+        match (function, category) {
+            (Some(function), TaskDataCategory::Meta) => function.meta_bytes += size,
+            (Some(function), _) => function.data_bytes += size,
+            (None, TaskDataCategory::Meta) => report.orphaned_meta_bytes += size,
+            (None, _) => report.orphaned_data_bytes += size,
+        }
+    }
+    for (name, function) in report.functions.iter_mut() {
+        function.registered = name.is_registered();
+    }
+    report.uncompleted_operations = storage
+        .uncompleted_operations()
+        .ok()
+        .map(|operations| operations.len());
+
+    Ok(report)
+}
+
+/// Removes all tasks of functions that are not registered in the current
+/// build, and of task types that can't be read.
+///
+/// These tasks can't be executed by the current build, so they can never be
+/// reused. Edges of the remaining tasks to removed tasks are removed as well,
+/// and these tasks are marked as dirty so they are recomputed when used.
+/// Returns the number of removed tasks.
+///
+/// Whether a function is registered is only meaningful for a store written by
+/// the current build, so `registry_fingerprint` must be the
+/// [fingerprint](registry::fingerprint) of the build that wrote the store.
+pub fn prune_unregistered_tasks(
+    storage: &dyn BackingStorage,
+    registry_fingerprint: Option<u64>,
+) -> Result<usize> {
+    if registry_fingerprint != Some(registry::fingerprint()) {
+        bail!(
+            "The cache was written by a build with other functions than this one. Only caches \
+             written by this build can be pruned."
+        );
+    }
+    if !storage.uncompleted_operations()?.is_empty() {
+        bail!("The cache has uncompleted operations that might refer to pruned tasks");
+    }
+
+    let mut removed = HashSet::new();
+    let mut stored = Vec::new();
+    storage.visit_entries(&mut |entry| match entry {
+        StoredEntry::TaskCache { task_id, task_type } => {
+            if !TaskTypeName::from_bytes(task_type).is_ok_and(|name| name.is_registered()) {
+                removed.insert(task_id);
+            }
+        }
+        StoredEntry::TaskData {
+            task_id, category, ..
+        } => stored.push((task_id, category)),
+        StoredEntry::Operations { .. } => {}
+    })?;
+    if removed.is_empty() {
+        return Ok(0);
+    }
+
+    let mut meta_updates = ChunkedVec::new();
+    let mut data_updates = ChunkedVec::new();
+    let mut invalidated = HashSet::new();
+    for (task_id, category) in stored {
+        if removed.contains(&task_id) {
+            continue;
+        }
+        for item in storage.read_task_data(task_id, category)? {
+            if !item
+                .referenced_task()
+                .is_some_and(|task| removed.contains(&task))
+            {
+                continue;
+            }
+            let (key, old_value) = item.into_key_and_value();
+            let update = CachedDataUpdate {
+                task: task_id,
+                key,
+                value: None,
+                old_value: Some(old_value),
+            };
+            match category {
+                TaskDataCategory::Meta => meta_updates.push(update),
+                _ => data_updates.push(update),
+            }
+            invalidated.insert(task_id);
+        }
+    }
+    for task in invalidated {
+        meta_updates.push(CachedDataUpdate {
+            task,
+            key: CachedDataItemKey::Dirty {},
+            value: Some(CachedDataItemValue::Dirty { value: () }),
+            old_value: None,
+        });
+    }
+    if !meta_updates.is_empty() || !data_updates.is_empty() {
+        storage.save_snapshot(
+            Vec::new(),
+            Vec::new(),
+            vec![meta_updates],
+            vec![data_updates],
+        )?;
+    }
+
+    let removed = removed.into_iter().collect::<Vec<_>>();
+    storage.remove_tasks(&removed)?;
+    Ok(removed.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use turbo_tasks::{registry, KeyValuePair, TaskId};
+
+    use super::{inspect_cache, prune_unregistered_tasks, TaskTypeName};
+    use crate::{
+        backend::TaskDataCategory,
+        backing_storage::BackingStorage,
+        data::{CachedDataItemKey, CachedDataItemValue, CachedDataUpdate, OutputValue},
+        utils::chunked_vec::ChunkedVec,
+        MemoryBackingStorage,
+    };
+
+    #[test]
+    fn read_task_type_names() {
+        // Same layout as the serialization of `CachedTaskType`
+        let native = pot::to_vec(&(0u8, ("crate::function", 42u32), None::<u32>, (), ())).unwrap();
+        assert_eq!(
+            TaskTypeName::from_bytes(&native).unwrap(),
+            TaskTypeName::Native {
+                function: "crate::function".to_string()
+            }
+        );
+
+        let resolve = pot::to_vec(&(1u8, ("crate::function", "arg"), Some(1u32), (), ())).unwrap();
+        assert_eq!(
+            TaskTypeName::from_bytes(&resolve).unwrap().to_string(),
+            "*crate::function"
+        );
+
+        let resolve_trait = pot::to_vec(&(2u8, "crate::Trait", "method", 1u32, ())).unwrap();
+        assert_eq!(
+            TaskTypeName::from_bytes(&resolve_trait).unwrap(),
+            TaskTypeName::ResolveTrait {
+                trait_type: "crate::Trait".to_string(),
+                method_name: "method".to_string()
+            }
+        );
+        assert!(!TaskTypeName::from_bytes(&resolve_trait)
+            .unwrap()
+            .is_registered());
+
+        assert!(TaskTypeName::from_bytes(&pot::to_vec(&(3u8, ())).unwrap()).is_err());
+    }
+
+    #[test]
+    fn prune_round_trip() {
+        fn insert(
+            updates: &mut ChunkedVec<CachedDataUpdate>,
+            task: u32,
+            key: CachedDataItemKey,
+            value: CachedDataItemValue,
+        ) {
+            updates.push(CachedDataUpdate {
+                task: TaskId::from(task),
+                key,
+                value: Some(value),
+                old_value: None,
+            });
+        }
+
+        fn keys(
+            storage: &MemoryBackingStorage,
+            task: u32,
+            category: TaskDataCategory,
+        ) -> HashSet<CachedDataItemKey> {
+            storage
+                .read_task_data(TaskId::from(task), category)
+                .unwrap()
+                .into_iter()
+                .map(|item| item.into_key_and_value().0)
+                .collect()
+        }
+
+        // Task 1 has no task type, like a root task, and task 4 is only
+        // referenced. Both are kept. Task 2 belongs to an unregistered function
+        // and task 3 has a task type that can't be read.
+        let storage = MemoryBackingStorage::new();
+        storage.insert_task_type(
+            TaskId::from(2),
+            pot::to_vec(&(0u8, ("crate::function", 42u32), None::<u32>, (), ())).unwrap(),
+        );
+        storage.insert_task_type(TaskId::from(3), vec![0xff, 0x00]);
+
+        let mut meta = ChunkedVec::new();
+        let mut data = ChunkedVec::new();
+        for task in [2, 4] {
+            insert(
+                &mut data,
+                1,
+                CachedDataItemKey::Child {
+                    task: TaskId::from(task),
+                },
+                CachedDataItemValue::Child { value: () },
+            );
+        }
+        insert(
+            &mut data,
+            1,
+            CachedDataItemKey::OutputDependency {
+                target: TaskId::from(3),
+            },
+            CachedDataItemValue::OutputDependency { value: () },
+        );
+        insert(
+            &mut data,
+            1,
+            CachedDataItemKey::Output {},
+            CachedDataItemValue::Output {
+                value: OutputValue::Output(TaskId::from(4)),
+            },
+        );
+        insert(
+            &mut meta,
+            1,
+            CachedDataItemKey::Follower {
+                task: TaskId::from(2),
+            },
+            CachedDataItemValue::Follower { value: 1 },
+        );
+        insert(
+            &mut meta,
+            2,
+            CachedDataItemKey::Upper {
+                task: TaskId::from(1),
+            },
+            CachedDataItemValue::Upper { value: 1 },
+        );
+        storage
+            .save_snapshot(Vec::new(), Vec::new(), vec![meta], vec![data])
+            .unwrap();
+
+        // Caches of other builds are never pruned.
+        assert!(prune_unregistered_tasks(&storage, None).is_err());
+        assert!(prune_unregistered_tasks(&storage, Some(registry::fingerprint() ^ 1)).is_err());
+        assert_eq!(inspect_cache(&storage).unwrap().tasks(), 1);
+
+        let fingerprint = Some(registry::fingerprint());
+        assert_eq!(prune_unregistered_tasks(&storage, fingerprint).unwrap(), 2);
+
+        let report = inspect_cache(&storage).unwrap();
+        assert!(report.functions.is_empty());
+        assert!(report.unreadable_tasks.is_empty());
+        assert_eq!(report.uncompleted_operations, Some(0));
+        assert_eq!(
+            keys(&storage, 1, TaskDataCategory::Data),
+            HashSet::from([
+                CachedDataItemKey::Child {
+                    task: TaskId::from(4)
+                },
+                CachedDataItemKey::Output {},
+            ])
+        );
+        assert_eq!(
+            keys(&storage, 1, TaskDataCategory::Meta),
+            HashSet::from([CachedDataItemKey::Dirty {}])
+        );
+        assert!(keys(&storage, 2, TaskDataCategory::Meta).is_empty());
+
+        assert_eq!(prune_unregistered_tasks(&storage, fingerprint).unwrap(), 0);
+    }
+}
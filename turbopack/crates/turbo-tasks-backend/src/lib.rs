@@ -5,7 +5,19 @@
 mod backend;
 mod backing_storage;
 mod data;
+mod inspection;
 mod lmdb_backing_storage;
+mod log_backing_storage;
+mod memory_backing_storage;
 mod utils;
 
-pub use self::{backend::TurboTasksBackend, lmdb_backing_storage::LmdbBackingStorage};
+pub use self::{
+    backend::{TaskDataCategory, TurboTasksBackend},
+    backing_storage::{BackingStorage, BackingStorageKind, ExistingStore, StoredEntry},
+    inspection::{
+        inspect_cache, prune_unregistered_tasks, CacheReport, FunctionReport, TaskTypeName,
+    },
+    lmdb_backing_storage::LmdbBackingStorage,
+    log_backing_storage::LogBackingStorage,
+    memory_backing_storage::MemoryBackingStorage,
+};
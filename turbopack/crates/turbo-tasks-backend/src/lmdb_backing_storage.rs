@@ -1,9 +1,7 @@
 mod extended_key;
 
 use std::{
-    collections::{hash_map::Entry, HashMap},
     error::Error,
-    fs::create_dir_all,
     mem::{transmute, ManuallyDrop},
     path::Path,
     sync::Arc,
@@ -13,19 +11,26 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 use lmdb::{
-    Database, DatabaseFlags, Environment, EnvironmentFlags, RoTransaction, Transaction, WriteFlags,
+    Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RoTransaction, Transaction,
+    WriteFlags,
 };
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use tracing::Span;
-use turbo_tasks::{backend::CachedTaskType, KeyValuePair, TaskId};
+use turbo_tasks::{backend::CachedTaskType, TaskId};
 
 use crate::{
     backend::{AnyOperation, TaskDataCategory},
-    backing_storage::{BackingStorage, ReadTransaction},
-    data::{CachedDataItem, CachedDataItemKey, CachedDataItemValue, CachedDataUpdate},
+    backing_storage::{
+        deserialize_task_data, organize_task_data, prepare_versioned_dir, restore_task_data,
+        serialize_task_data, BackingStorage, ReadTransaction, ShardedOrganizedTaskData,
+        StoredEntry,
+    },
+    data::{CachedDataItem, CachedDataUpdate},
     utils::chunked_vec::ChunkedVec,
 };
 
+/// Identifies the on-disk layout of this store. Bump it when the layout
+/// changes.
+pub(crate) const STORE_NAME: &str = "lmdb-1";
+
 const META_KEY_OPERATIONS: u32 = 0;
 const META_KEY_NEXT_FREE_TASK_ID: u32 = 1;
 
@@ -59,8 +64,10 @@ pub struct LmdbBackingStorage {
 }
 
 impl LmdbBackingStorage {
-    pub fn new(path: &Path) -> Result<Self> {
-        create_dir_all(path)?;
+    /// Opens the database at `path`. A database that was written with another
+    /// `version` is discarded.
+    pub fn new(path: &Path, version: &str) -> Result<Self> {
+        prepare_versioned_dir(path, STORE_NAME, version)?;
 
         #[cfg(target_arch = "x86")]
         const MAP_SIZE: usize = usize::MAX;
@@ -102,6 +109,18 @@ impl LmdbBackingStorage {
         }
     }
 
+    fn restore_task_data(
+        &self,
+        db: Database,
+        task_updates: ShardedOrganizedTaskData,
+    ) -> Result<Vec<(TaskId, Vec<CachedDataItem>)>> {
+        let tx = self.env.begin_ro_txn()?;
+        restore_task_data(task_updates, |task| match tx.get(db, &IntKey::new(*task)) {
+            Ok(old_data) => Ok(Some(deserialize_task_data(task, old_data)?)),
+            Err(_) => Ok(None),
+        })
+    }
+
     fn to_tx(&self, tx: ReadTransaction) -> ManuallyDrop<RoTransaction<'_>> {
         ManuallyDrop::new(unsafe { transmute::<*const (), RoTransaction<'_>>(tx.0) })
     }
@@ -110,6 +129,21 @@ impl LmdbBackingStorage {
         ReadTransaction(unsafe { transmute::<RoTransaction<'_>, *const ()>(tx) })
     }
 
+    fn lookup_task_data(
+        &self,
+        tx: &RoTransaction<'_>,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Vec<CachedDataItem>> {
+        let bytes = match tx.get(self.db(category), &IntKey::new(*task_id)) {
+            Ok(bytes) => bytes,
+            Err(lmdb::Error::NotFound) => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let result: Vec<CachedDataItem> = pot::from_slice(bytes)?;
+        Ok(result)
+    }
+
     fn with_tx<T>(
         &self,
         tx: Option<ReadTransaction>,
@@ -138,14 +172,14 @@ impl BackingStorage for LmdbBackingStorage {
         TaskId::from(get(self).unwrap_or(1))
     }
 
-    fn uncompleted_operations(&self) -> Vec<AnyOperation> {
-        fn get(this: &LmdbBackingStorage) -> Result<Vec<AnyOperation>> {
-            let tx = this.env.begin_ro_txn()?;
-            let operations = tx.get(this.infra_db, &IntKey::new(META_KEY_OPERATIONS))?;
-            let operations = pot::from_slice(operations)?;
-            Ok(operations)
-        }
-        get(self).unwrap_or_default()
+    fn uncompleted_operations(&self) -> Result<Vec<AnyOperation>> {
+        let tx = self.env.begin_ro_txn()?;
+        let operations = match tx.get(self.infra_db, &IntKey::new(META_KEY_OPERATIONS)) {
+            Ok(operations) => operations,
+            Err(lmdb::Error::NotFound) => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        pot::from_slice(operations).context("Unable to deserialize uncompleted operations")
     }
 
     #[tracing::instrument(level = "trace", skip_all, fields(operations = operations.len(), task_cache_updates = task_cache_updates.len(), data_updates = data_updates.len()))]
@@ -182,7 +216,7 @@ impl BackingStorage for LmdbBackingStorage {
                     let _span =
                         tracing::trace_span!("restore task meta", tasks = task_meta_updates.len())
                             .entered();
-                    restore_task_data(self, self.meta_db, task_meta_updates)
+                    self.restore_task_data(self.meta_db, task_meta_updates)
                 };
                 task_meta_items_result = items_result.and_then(|items| {
                     let _span = tracing::trace_span!("serialize task meta").entered();
@@ -200,7 +234,7 @@ impl BackingStorage for LmdbBackingStorage {
                     let _span =
                         tracing::trace_span!("restore task data", tasks = task_data_updates.len())
                             .entered();
-                    restore_task_data(self, self.data_db, task_data_updates)
+                    self.restore_task_data(self.data_db, task_data_updates)
                 };
                 task_data_items_result = items_result.and_then(|items| {
                     let _span = tracing::trace_span!("serialize task data").entered();
@@ -387,168 +421,78 @@ impl BackingStorage for LmdbBackingStorage {
         task_id: TaskId,
         category: TaskDataCategory,
     ) -> Vec<CachedDataItem> {
-        fn lookup(
-            this: &LmdbBackingStorage,
-            tx: &RoTransaction<'_>,
-            task_id: TaskId,
-            category: TaskDataCategory,
-        ) -> Result<Vec<CachedDataItem>> {
-            let bytes = match tx.get(this.db(category), &IntKey::new(*task_id)) {
-                Ok(bytes) => bytes,
-                Err(err) => {
-                    if err == lmdb::Error::NotFound {
-                        return Ok(Vec::new());
-                    } else {
-                        return Err(err.into());
-                    }
-                }
-            };
-            let result: Vec<CachedDataItem> = pot::from_slice(bytes)?;
-            Ok(result)
-        }
-        self.with_tx(tx, |tx| lookup(self, tx, task_id, category))
+        self.with_tx(tx, |tx| self.lookup_task_data(tx, task_id, category))
             .inspect_err(|err| println!("Looking up data for {task_id} failed: {err:?}"))
             .unwrap_or_default()
     }
-}
 
-type OrganizedTaskData = HashMap<
-    TaskId,
-    HashMap<CachedDataItemKey, (Option<CachedDataItemValue>, Option<CachedDataItemValue>)>,
->;
-type ShardedOrganizedTaskData = Vec<OrganizedTaskData>;
-
-fn organize_task_data(updates: Vec<ChunkedVec<CachedDataUpdate>>) -> ShardedOrganizedTaskData {
-    let span = Span::current();
-    updates
-        .into_par_iter()
-        .map(|updates| {
-            let _span = span.clone().entered();
-            let mut task_updates: OrganizedTaskData = HashMap::new();
-            for CachedDataUpdate {
-                task,
-                key,
-                value,
-                old_value,
-            } in updates.into_iter()
-            {
-                let data = task_updates.entry(task).or_default();
-                match data.entry(key) {
-                    Entry::Occupied(mut entry) => {
-                        entry.get_mut().1 = value;
-                    }
-                    Entry::Vacant(entry) => {
-                        entry.insert((old_value, value));
-                    }
-                }
-            }
-            task_updates.retain(|_, data| {
-                data.retain(|_, (old_value, value)| *old_value != *value);
-                !data.is_empty()
-            });
-            task_updates
-        })
-        .collect()
-}
+    fn read_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Vec<CachedDataItem>> {
+        self.with_tx(None, |tx| self.lookup_task_data(tx, task_id, category))
+    }
 
-fn restore_task_data(
-    this: &LmdbBackingStorage,
-    db: Database,
-    task_updates: ShardedOrganizedTaskData,
-) -> Result<Vec<(TaskId, Vec<CachedDataItem>)>> {
-    let mut result = Vec::with_capacity(task_updates.iter().map(|m| m.len()).sum());
-
-    let tx = this.env.begin_ro_txn()?;
-    for (task, updates) in task_updates.into_iter().flatten() {
-        let mut map;
-        if let Ok(old_data) = tx.get(db, &IntKey::new(*task)) {
-            let old_data: Vec<CachedDataItem> = match pot::from_slice(old_data) {
-                Ok(d) => d,
-                Err(_) => serde_path_to_error::deserialize(
-                    &mut pot::de::SymbolList::new().deserializer_for_slice(old_data)?,
-                )
-                .with_context(|| {
-                    anyhow!("Unable to deserialize old value of {task}: {old_data:?}")
-                })?,
-            };
-            map = old_data
-                .into_iter()
-                .map(|item| item.into_key_and_value())
-                .collect();
-        } else {
-            map = HashMap::new();
+    fn visit_entries(&self, visitor: &mut dyn FnMut(StoredEntry<'_>)) -> Result<()> {
+        let tx = self.env.begin_ro_txn()?;
+        for entry in tx.open_ro_cursor(self.reverse_task_cache_db)?.iter_start() {
+            let (key, value) = entry?;
+            visitor(StoredEntry::TaskCache {
+                task_id: TaskId::from(u32::from_be_bytes(key.try_into()?)),
+                task_type: value,
+            });
         }
-        for (key, (_, value)) in updates {
-            if let Some(value) = value {
-                map.insert(key, value);
-            } else {
-                map.remove(&key);
+        for category in [TaskDataCategory::Meta, TaskDataCategory::Data] {
+            for entry in tx.open_ro_cursor(self.db(category))?.iter_start() {
+                let (key, value) = entry?;
+                visitor(StoredEntry::TaskData {
+                    task_id: TaskId::from(u32::from_be_bytes(key.try_into()?)),
+                    category,
+                    size: value.len(),
+                });
             }
         }
-        let vec = map
-            .into_iter()
-            .map(|(key, value)| CachedDataItem::from_key_and_value(key, value))
-            .collect();
-        result.push((task, vec));
+        if let Ok(operations) = tx.get(self.infra_db, &IntKey::new(META_KEY_OPERATIONS)) {
+            visitor(StoredEntry::Operations {
+                size: operations.len(),
+            });
+        }
+        tx.commit()?;
+        Ok(())
     }
 
-    Ok(result)
-}
-
-fn serialize_task_data(
-    tasks: Vec<(TaskId, Vec<CachedDataItem>)>,
-) -> Result<Vec<(TaskId, Vec<u8>)>> {
-    tasks
-        .into_iter()
-        .map(|(task_id, mut data)| {
-            let value = match pot::to_vec(&data) {
-                #[cfg(not(feature = "verify_serialization"))]
-                Ok(value) => value,
-                _ => {
-                    let mut error = Ok(());
-                    data.retain(|item| {
-                        let mut buf = Vec::<u8>::new();
-                        let mut symbol_map = pot::ser::SymbolMap::new();
-                        let mut serializer = symbol_map.serializer_for(&mut buf).unwrap();
-                        if let Err(err) = serde_path_to_error::serialize(item, &mut serializer) {
-                            if item.is_optional() {
-                                println!("Skipping non-serializable optional item: {item:?}");
-                            } else {
-                                error = Err(err).context({
-                                    anyhow!(
-                                        "Unable to serialize data item for {task_id}: {item:#?}"
-                                    )
-                                });
-                            }
-                            false
-                        } else {
-                            #[cfg(feature = "verify_serialization")]
-                            {
-                                let deserialize: Result<CachedDataItem, _> =
-                                    serde_path_to_error::deserialize(
-                                        &mut pot::de::SymbolList::new()
-                                            .deserializer_for_slice(&buf)
-                                            .unwrap(),
-                                    );
-                                if let Err(err) = deserialize {
-                                    println!(
-                                        "Data item would not be deserializable {task_id}: \
-                                         {err:?}\n{item:#?}"
-                                    );
-                                    return false;
-                                }
-                            }
-                            true
-                        }
-                    });
-                    error?;
+    fn remove_tasks(&self, task_ids: &[TaskId]) -> Result<()> {
+        fn ignore_not_found(result: lmdb::Result<()>) -> lmdb::Result<()> {
+            match result {
+                Err(lmdb::Error::NotFound) => Ok(()),
+                result => result,
+            }
+        }
 
-                    pot::to_vec(&data).with_context(|| {
-                        anyhow!("Unable to serialize data items for {task_id}: {data:#?}")
-                    })?
-                }
+        let mut tx = self.env.begin_rw_txn()?;
+        for &task_id in task_ids {
+            let key = IntKey::new(*task_id);
+            let task_type = match tx.get(self.reverse_task_cache_db, &key) {
+                Ok(task_type) => Some(task_type.to_vec()),
+                Err(lmdb::Error::NotFound) => None,
+                Err(err) => return Err(err.into()),
             };
-            Ok((task_id, value))
-        })
-        .collect()
+            if let Some(task_type) = task_type {
+                ignore_not_found(extended_key::delete(
+                    &mut tx,
+                    self.forward_task_cache_db,
+                    &task_type,
+                ))
+                .with_context(|| anyhow!("Unable to remove task cache entry of {task_id}"))?;
+            }
+            for db in [self.reverse_task_cache_db, self.meta_db, self.data_db] {
+                ignore_not_found(tx.del(db, &key, None))
+                    .with_context(|| anyhow!("Unable to remove {task_id}"))?;
+            }
+        }
+        tx.commit()
+            .with_context(|| anyhow!("Unable to commit removal of tasks"))?;
+        Ok(())
+    }
 }
@@ -64,6 +64,31 @@ pub fn put(
     }
 }
 
+pub fn delete(tx: &mut RwTransaction<'_>, database: Database, key: &[u8]) -> lmdb::Result<()> {
+    if key.len() > MAX_KEY_SIZE - 1 {
+        let hashed_key = hashed_key(key);
+
+        let old = tx.get(database, &hashed_key)?;
+        let mut data = Vec::with_capacity(old.len());
+        for (k, v) in ExtendedValueIter::new(old) {
+            if k != &key[SHARED_KEY..] {
+                data.extend_from_slice(&(k.len() as u32).to_be_bytes());
+                data.extend_from_slice(&(v.len() as u32).to_be_bytes());
+                data.extend_from_slice(k);
+                data.extend_from_slice(v);
+            }
+        }
+
+        if data.is_empty() {
+            tx.del(database, &hashed_key, None)
+        } else {
+            tx.put(database, &hashed_key, &data, WriteFlags::empty())
+        }
+    } else {
+        tx.del(database, &key, None)
+    }
+}
+
 fn hashed_key(key: &[u8]) -> [u8; MAX_KEY_SIZE] {
     let mut result = [0; MAX_KEY_SIZE];
     let mut hash = FxHasher::default();
@@ -0,0 +1,820 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use anyhow::{anyhow, Context, Result};
+use parking_lot::{Mutex, RwLock};
+use turbo_tasks::{backend::CachedTaskType, TaskId};
+
+use crate::{
+    backend::{AnyOperation, TaskDataCategory},
+    backing_storage::{
+        deserialize_task_data, prepare_versioned_dir, serialize_snapshot, BackingStorage,
+        ReadTransaction, SerializedSnapshot, StoredEntry,
+    },
+    data::{CachedDataItem, CachedDataUpdate},
+    utils::chunked_vec::ChunkedVec,
+};
+
+/// Identifies the on-disk layout of this store. Bump it when the layout
+/// changes.
+pub(crate) const STORE_NAME: &str = "log-1";
+const LOG_FILE: &str = "cache.log";
+const COMPACT_FILE: &str = "cache.log.tmp";
+
+/// The log is only compacted when it's larger than this and more than half of
+/// it is outdated.
+const COMPACT_MIN_SIZE: u64 = 16 * 1024 * 1024;
+
+/// `[type: u8][key length: u32][value length: u32]`, both lengths big endian.
+const HEADER_SIZE: u64 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+enum RecordType {
+    /// key: serialized [CachedTaskType], value: task id
+    TaskCache = 0,
+    /// key: task id, value: serialized meta items
+    Meta = 1,
+    /// key: task id, value: serialized data items
+    Data = 2,
+    /// key: empty, value: serialized uncompleted operations
+    Operations = 3,
+    /// key: empty, value: next free task id
+    NextFreeTaskId = 4,
+    /// Marks the end of a snapshot. Records after the last commit are ignored.
+    Commit = 5,
+    /// key: task id, value: serialized [CachedTaskType] or empty
+    ///
+    /// Removes the task cache entry and all data of a task.
+    RemoveTask = 6,
+}
+
+impl RecordType {
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => RecordType::TaskCache,
+            1 => RecordType::Meta,
+            2 => RecordType::Data,
+            3 => RecordType::Operations,
+            4 => RecordType::NextFreeTaskId,
+            5 => RecordType::Commit,
+            6 => RecordType::RemoveTask,
+            _ => return None,
+        })
+    }
+}
+
+/// The position of a value in the log file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ValueLocation {
+    offset: u64,
+    len: u32,
+}
+
+/// The in-memory index of the committed content of the log.
+#[derive(Debug, Default)]
+struct LogIndex {
+    forward_task_cache: HashMap<Vec<u8>, TaskId>,
+    /// Points to the key of the task cache record, which is the serialized
+    /// task type.
+    reverse_task_cache: HashMap<TaskId, ValueLocation>,
+    meta: HashMap<TaskId, ValueLocation>,
+    data: HashMap<TaskId, ValueLocation>,
+    operations: Option<ValueLocation>,
+    next_free_task_id: u32,
+    /// The length of the log up to and including the last commit.
+    committed_len: u64,
+}
+
+impl LogIndex {
+    fn apply(&mut self, record: Record) {
+        match record.ty {
+            RecordType::TaskCache => {
+                let task_id = TaskId::from(record.value_u32);
+                self.forward_task_cache.insert(record.task_type, task_id);
+                self.reverse_task_cache.insert(task_id, record.key_location);
+            }
+            RecordType::Meta => {
+                self.meta
+                    .insert(TaskId::from(record.key_u32), record.value_location);
+            }
+            RecordType::Data => {
+                self.data
+                    .insert(TaskId::from(record.key_u32), record.value_location);
+            }
+            RecordType::Operations => {
+                self.operations = Some(record.value_location);
+            }
+            RecordType::NextFreeTaskId => {
+                self.next_free_task_id = self.next_free_task_id.max(record.value_u32);
+            }
+            RecordType::RemoveTask => {
+                let task_id = TaskId::from(record.key_u32);
+                self.forward_task_cache.remove(&record.task_type);
+                self.reverse_task_cache.remove(&task_id);
+                self.meta.remove(&task_id);
+                self.data.remove(&task_id);
+            }
+            RecordType::Commit => {}
+        }
+    }
+
+    fn category(&self, category: TaskDataCategory) -> &HashMap<TaskId, ValueLocation> {
+        match category {
+            TaskDataCategory::Meta => &self.meta,
+            TaskDataCategory::Data => &self.data,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The number of bytes a freshly compacted log would need.
+    fn live_len(&self) -> u64 {
+        let record = |key_len: u64, value_len: u64| HEADER_SIZE + key_len + value_len;
+        let task_cache: u64 = self
+            .reverse_task_cache
+            .values()
+            .map(|location| record(location.len as u64, 4))
+            .sum();
+        let data: u64 = self
+            .meta
+            .values()
+            .chain(self.data.values())
+            .map(|location| record(4, location.len as u64))
+            .sum();
+        let operations = self
+            .operations
+            .map_or(0, |location| record(0, location.len as u64));
+        task_cache + data + operations + record(0, 4) + record(0, 0)
+    }
+}
+
+/// A parsed record. Only the parts needed for the index are kept in memory.
+struct Record {
+    ty: RecordType,
+    /// The serialized task type, for [RecordType::TaskCache] and
+    /// [RecordType::RemoveTask].
+    task_type: Vec<u8>,
+    key_location: ValueLocation,
+    value_location: ValueLocation,
+    /// The key as task id, for [RecordType::Meta], [RecordType::Data] and
+    /// [RecordType::RemoveTask].
+    key_u32: u32,
+    /// The value as number, for [RecordType::TaskCache] and
+    /// [RecordType::NextFreeTaskId].
+    value_u32: u32,
+}
+
+/// Reads the next record at `offset`. Returns `None` at the end of the log
+/// and for incomplete or corrupted records, which are left behind by a write
+/// that was interrupted.
+fn read_record(reader: &mut impl Read, offset: u64) -> io::Result<Option<Record>> {
+    let mut header = [0u8; HEADER_SIZE as usize];
+    if !read_exact_or_eof(reader, &mut header)? {
+        return Ok(None);
+    }
+    let Some(ty) = RecordType::from_u8(header[0]) else {
+        return Ok(None);
+    };
+    let key_len = u32::from_be_bytes(header[1..5].try_into().unwrap());
+    let value_len = u32::from_be_bytes(header[5..9].try_into().unwrap());
+    let key_location = ValueLocation {
+        offset: offset + HEADER_SIZE,
+        len: key_len,
+    };
+    let value_location = ValueLocation {
+        offset: key_location.offset + key_len as u64,
+        len: value_len,
+    };
+
+    let as_u32 = |bytes: &[u8]| bytes.try_into().ok().map(u32::from_be_bytes);
+    let mut task_type = Vec::new();
+    let mut key_u32 = None;
+    let mut value_u32 = None;
+    let valid = match ty {
+        RecordType::TaskCache => {
+            read_vec_or_eof(reader, key_len, &mut task_type)? && {
+                let mut value = Vec::new();
+                read_vec_or_eof(reader, value_len, &mut value)? && {
+                    value_u32 = as_u32(&value);
+                    value_u32.is_some()
+                }
+            }
+        }
+        RecordType::Meta | RecordType::Data => {
+            let mut key_bytes = Vec::new();
+            read_vec_or_eof(reader, key_len, &mut key_bytes)?
+                && {
+                    key_u32 = as_u32(&key_bytes);
+                    key_u32.is_some()
+                }
+                && skip_or_eof(reader, value_len as u64)?
+        }
+        RecordType::RemoveTask => {
+            let mut key_bytes = Vec::new();
+            read_vec_or_eof(reader, key_len, &mut key_bytes)?
+                && {
+                    key_u32 = as_u32(&key_bytes);
+                    key_u32.is_some()
+                }
+                && read_vec_or_eof(reader, value_len, &mut task_type)?
+        }
+        RecordType::Operations | RecordType::Commit => {
+            key_len == 0 && skip_or_eof(reader, value_len as u64)?
+        }
+        RecordType::NextFreeTaskId => {
+            let mut value = Vec::new();
+            key_len == 0 && read_vec_or_eof(reader, value_len, &mut value)? && {
+                value_u32 = as_u32(&value);
+                value_u32.is_some()
+            }
+        }
+    };
+    if !valid {
+        return Ok(None);
+    }
+    Ok(Some(Record {
+        ty,
+        task_type,
+        key_location,
+        value_location,
+        key_u32: key_u32.unwrap_or_default(),
+        value_u32: value_u32.unwrap_or_default(),
+    }))
+}
+
+fn read_exact_or_eof(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads `len` bytes into `buf`. Doesn't allocate upfront, so a corrupted
+/// length only costs as much memory as there is data left.
+fn read_vec_or_eof(reader: &mut impl Read, len: u32, buf: &mut Vec<u8>) -> io::Result<bool> {
+    let read = reader.take(len as u64).read_to_end(buf)?;
+    Ok(read == len as usize)
+}
+
+fn skip_or_eof(reader: &mut impl Read, len: u64) -> io::Result<bool> {
+    let skipped = io::copy(&mut reader.take(len), &mut io::sink())?;
+    Ok(skipped == len)
+}
+
+/// Builds the index from the content of a log. Records that are not followed
+/// by a commit are ignored.
+fn scan_log(reader: impl Read) -> io::Result<LogIndex> {
+    let mut reader = BufReader::new(reader);
+    let mut index = LogIndex {
+        next_free_task_id: 1,
+        ..Default::default()
+    };
+    let mut pending = Vec::new();
+    let mut offset = 0;
+    while let Some(record) = read_record(&mut reader, offset)? {
+        offset = record.value_location.offset + record.value_location.len as u64;
+        if record.ty == RecordType::Commit {
+            for record in pending.drain(..) {
+                index.apply(record);
+            }
+            index.committed_len = offset;
+        } else {
+            pending.push(record);
+        }
+    }
+    Ok(index)
+}
+
+/// Writes the records of a snapshot and keeps track of the resulting index
+/// changes.
+struct RecordWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    records: Vec<Record>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    fn new(writer: W, offset: u64) -> Self {
+        Self {
+            writer,
+            offset,
+            records: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, ty: RecordType, key: &[u8], value: &[u8]) -> io::Result<()> {
+        let key_len = u32::try_from(key.len()).map_err(io::Error::other)?;
+        let value_len = u32::try_from(value.len()).map_err(io::Error::other)?;
+        self.writer.write_all(&[ty as u8])?;
+        self.writer.write_all(&key_len.to_be_bytes())?;
+        self.writer.write_all(&value_len.to_be_bytes())?;
+        self.writer.write_all(key)?;
+        self.writer.write_all(value)?;
+
+        let as_u32 = |bytes: &[u8]| bytes.try_into().map(u32::from_be_bytes).unwrap_or(0);
+        let key_location = ValueLocation {
+            offset: self.offset + HEADER_SIZE,
+            len: key_len,
+        };
+        let value_location = ValueLocation {
+            offset: key_location.offset + key_len as u64,
+            len: value_len,
+        };
+        self.offset = value_location.offset + value_len as u64;
+        self.records.push(Record {
+            ty,
+            task_type: match ty {
+                RecordType::TaskCache => key.to_vec(),
+                RecordType::RemoveTask => value.to_vec(),
+                _ => Vec::new(),
+            },
+            key_location,
+            value_location,
+            key_u32: as_u32(key),
+            value_u32: as_u32(value),
+        });
+        Ok(())
+    }
+
+    fn commit(mut self) -> io::Result<(W, Vec<Record>, u64)> {
+        self.write(RecordType::Commit, &[], &[])?;
+        self.writer.flush()?;
+        Ok((self.writer, self.records, self.offset))
+    }
+}
+
+/// A [BackingStorage] that appends all changes to a single log file and keeps
+/// an index of the log in memory.
+///
+/// Unlike [LmdbBackingStorage](crate::LmdbBackingStorage) it doesn't rely on
+/// memory mapped files, which makes it usable on filesystems that don't support
+/// them well. Values are read with positional reads on demand. Outdated
+/// records are removed by compacting the log when it's opened.
+pub struct LogBackingStorage {
+    path: PathBuf,
+    file: File,
+    /// Serializes snapshots, which append to the log.
+    write_lock: Mutex<()>,
+    index: RwLock<LogIndex>,
+}
+
+impl LogBackingStorage {
+    /// Opens the log in the directory `path`. A log that was written with
+    /// another `version` is discarded.
+    pub fn new(path: &Path, version: &str) -> Result<Self> {
+        prepare_versioned_dir(path, STORE_NAME, version)?;
+        let log_path = path.join(LOG_FILE);
+        let mut this = Self::open(&log_path)?;
+        let index = this.index.get_mut();
+        if index.committed_len > COMPACT_MIN_SIZE && index.committed_len > 2 * index.live_len() {
+            this.compact(&path.join(COMPACT_FILE))?;
+            this = Self::open(&log_path)?;
+        }
+        Ok(this)
+    }
+
+    fn open(log_path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(log_path)
+            .with_context(|| anyhow!("Unable to open {}", log_path.display()))?;
+        let index =
+            scan_log(&file).with_context(|| anyhow!("Unable to read {}", log_path.display()))?;
+        // Drop the records of an interrupted snapshot.
+        if file.metadata()?.len() != index.committed_len {
+            file.set_len(index.committed_len)?;
+        }
+        Ok(Self {
+            path: log_path.to_path_buf(),
+            file,
+            write_lock: Mutex::new(()),
+            index: RwLock::new(index),
+        })
+    }
+
+    /// Rewrites the log with only the live records, then replaces the log
+    /// with it. Consumes the storage, since the log must be closed before it
+    /// can be replaced on all platforms.
+    fn compact(self, compact_path: &Path) -> Result<()> {
+        let start = Instant::now();
+        let index = self.index.read();
+        let file = File::create(compact_path)
+            .with_context(|| anyhow!("Unable to create {}", compact_path.display()))?;
+        let mut writer = RecordWriter::new(BufWriter::new(file), 0);
+        for (key, task_id) in index.forward_task_cache.iter() {
+            writer.write(RecordType::TaskCache, key, &task_id.to_be_bytes())?;
+        }
+        for (ty, map) in [
+            (RecordType::Meta, &index.meta),
+            (RecordType::Data, &index.data),
+        ] {
+            for (task_id, location) in map.iter() {
+                let value = self.read_value(*location)?;
+                writer.write(ty, &task_id.to_be_bytes(), &value)?;
+            }
+        }
+        if let Some(location) = index.operations {
+            writer.write(RecordType::Operations, &[], &self.read_value(location)?)?;
+        }
+        writer.write(
+            RecordType::NextFreeTaskId,
+            &[],
+            &index.next_free_task_id.to_be_bytes(),
+        )?;
+        let (writer, _, len) = writer.commit()?;
+        writer.into_inner()?.sync_all()?;
+        let old_len = index.committed_len;
+        drop(index);
+
+        let Self { path, file, .. } = self;
+        drop(file);
+        fs::rename(compact_path, &path)
+            .with_context(|| anyhow!("Unable to replace {}", path.display()))?;
+        println!(
+            "Compacted cache log from {old_len} to {len} bytes after {:?}",
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    fn read_value(&self, location: ValueLocation) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; location.len as usize];
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileExt;
+            self.file.read_exact_at(&mut buf, location.offset)?;
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::FileExt;
+            let mut read = 0;
+            while read < buf.len() {
+                match self
+                    .file
+                    .seek_read(&mut buf[read..], location.offset + read as u64)?
+                {
+                    0 => return Err(ErrorKind::UnexpectedEof.into()),
+                    n => read += n,
+                }
+            }
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            use std::io::{Seek, SeekFrom};
+            // Without positional reads the shared file can't be used
+            // concurrently, so use a separate handle for every read.
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(location.offset))?;
+            file.read_exact(&mut buf)?;
+        }
+        Ok(buf)
+    }
+
+    fn lookup_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Option<Vec<CachedDataItem>>> {
+        let Some(location) = self.index.read().category(category).get(&task_id).copied() else {
+            return Ok(None);
+        };
+        let bytes = self.read_value(location)?;
+        Ok(Some(deserialize_task_data(task_id, &bytes)?))
+    }
+
+    /// Appends the records written by `write_records` as one commit, and
+    /// applies them to the index afterwards.
+    fn append(
+        &self,
+        write_records: impl FnOnce(&LogIndex, &mut RecordWriter<BufWriter<&File>>) -> io::Result<()>,
+    ) -> Result<usize> {
+        let _write_lock = self.write_lock.lock();
+        let index = self.index.read();
+        let offset = index.committed_len;
+
+        let write = || -> io::Result<_> {
+            let mut writer = RecordWriter::new(BufWriter::new(&self.file), offset);
+            write_records(&index, &mut writer)?;
+            let (writer, records, len) = writer.commit()?;
+            writer.into_inner()?.sync_data()?;
+            Ok((records, len))
+        };
+        let (records, len) = match write() {
+            Ok(result) => result,
+            Err(err) => {
+                // Don't leave a partial snapshot behind, later snapshots are
+                // appended at the committed length.
+                let _ = self.file.set_len(offset);
+                return Err(err).context("Unable to write to the cache log");
+            }
+        };
+        drop(index);
+
+        let count = records.len();
+        let mut index = self.index.write();
+        for record in records {
+            index.apply(record);
+        }
+        index.committed_len = len;
+        Ok(count)
+    }
+
+    fn append_snapshot(&self, snapshot: SerializedSnapshot) -> Result<usize> {
+        self.append(|index, writer| {
+            let next_free_task_id = snapshot
+                .task_cache
+                .iter()
+                .map(|(_, task_id)| **task_id + 1)
+                .fold(index.next_free_task_id, u32::max);
+            for (key, task_id) in snapshot.task_cache.iter() {
+                writer.write(RecordType::TaskCache, key, &task_id.to_be_bytes())?;
+            }
+            for (ty, items) in [
+                (RecordType::Meta, &snapshot.meta),
+                (RecordType::Data, &snapshot.data),
+            ] {
+                for (task_id, value) in items.iter() {
+                    writer.write(ty, &task_id.to_be_bytes(), value)?;
+                }
+            }
+            writer.write(RecordType::Operations, &[], &snapshot.operations)?;
+            writer.write(
+                RecordType::NextFreeTaskId,
+                &[],
+                &next_free_task_id.to_be_bytes(),
+            )?;
+            Ok(())
+        })
+    }
+}
+
+impl BackingStorage for LogBackingStorage {
+    fn next_free_task_id(&self) -> TaskId {
+        TaskId::from(self.index.read().next_free_task_id)
+    }
+
+    fn uncompleted_operations(&self) -> Result<Vec<AnyOperation>> {
+        let Some(location) = self.index.read().operations else {
+            return Ok(Vec::new());
+        };
+        let operations = self.read_value(location)?;
+        pot::from_slice(&operations).context("Unable to deserialize uncompleted operations")
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, fields(operations = operations.len(), task_cache_updates = task_cache_updates.len(), data_updates = data_updates.len()))]
+    fn save_snapshot(
+        &self,
+        operations: Vec<Arc<AnyOperation>>,
+        task_cache_updates: Vec<ChunkedVec<(Arc<CachedTaskType>, TaskId)>>,
+        meta_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+        data_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let snapshot = serialize_snapshot(
+            operations,
+            task_cache_updates,
+            meta_updates,
+            data_updates,
+            |category, task_id| self.lookup_task_data(task_id, category),
+        )?;
+        let count = {
+            let _span = tracing::trace_span!("append to log").entered();
+            self.append_snapshot(snapshot)?
+        };
+        println!("Persisted {count} log records after {:?}", start.elapsed());
+        Ok(())
+    }
+
+    fn start_read_transaction(&self) -> Option<ReadTransaction> {
+        None
+    }
+
+    unsafe fn end_read_transaction(&self, _tx: ReadTransaction) {
+        unreachable!("LogBackingStorage doesn't create read transactions")
+    }
+
+    unsafe fn forward_lookup_task_cache(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_type: &CachedTaskType,
+    ) -> Option<TaskId> {
+        let task_type_bytes = pot::to_vec(task_type)
+            .inspect_err(|err| println!("Looking up task id for {task_type:?} failed: {err:?}"))
+            .ok()?;
+        self.index
+            .read()
+            .forward_task_cache
+            .get(&task_type_bytes)
+            .copied()
+    }
+
+    unsafe fn reverse_lookup_task_cache(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_id: TaskId,
+    ) -> Option<Arc<CachedTaskType>> {
+        fn lookup(
+            this: &LogBackingStorage,
+            task_id: TaskId,
+        ) -> Result<Option<Arc<CachedTaskType>>> {
+            let Some(location) = this.index.read().reverse_task_cache.get(&task_id).copied() else {
+                return Ok(None);
+            };
+            let bytes = this.read_value(location)?;
+            Ok(Some(pot::from_slice(&bytes)?))
+        }
+        lookup(self, task_id)
+            .inspect_err(|err| println!("Looking up task type for {task_id} failed: {err:?}"))
+            .ok()?
+    }
+
+    unsafe fn lookup_data(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Vec<CachedDataItem> {
+        self.lookup_task_data(task_id, category)
+            .inspect_err(|err| println!("Looking up data for {task_id} failed: {err:?}"))
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    fn read_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Vec<CachedDataItem>> {
+        Ok(self
+            .lookup_task_data(task_id, category)?
+            .unwrap_or_default())
+    }
+
+    fn visit_entries(&self, visitor: &mut dyn FnMut(StoredEntry<'_>)) -> Result<()> {
+        let index = self.index.read();
+        for (task_type, task_id) in index.forward_task_cache.iter() {
+            visitor(StoredEntry::TaskCache {
+                task_id: *task_id,
+                task_type,
+            });
+        }
+        for category in [TaskDataCategory::Meta, TaskDataCategory::Data] {
+            for (task_id, location) in index.category(category).iter() {
+                visitor(StoredEntry::TaskData {
+                    task_id: *task_id,
+                    category,
+                    size: location.len as usize,
+                });
+            }
+        }
+        if let Some(location) = index.operations {
+            visitor(StoredEntry::Operations {
+                size: location.len as usize,
+            });
+        }
+        Ok(())
+    }
+
+    fn remove_tasks(&self, task_ids: &[TaskId]) -> Result<()> {
+        self.append(|index, writer| {
+            for task_id in task_ids {
+                let task_type = match index.reverse_task_cache.get(task_id) {
+                    Some(location) => self.read_value(*location)?,
+                    None => Vec::new(),
+                };
+                writer.write(RecordType::RemoveTask, &task_id.to_be_bytes(), &task_type)?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use turbo_tasks::TaskId;
+
+    use super::{scan_log, RecordType, RecordWriter, ValueLocation, HEADER_SIZE};
+
+    fn write_snapshot(log: &mut Vec<u8>, records: &[(RecordType, &[u8], &[u8])]) {
+        let offset = log.len() as u64;
+        let mut writer = RecordWriter::new(&mut *log, offset);
+        for (ty, key, value) in records {
+            writer.write(*ty, key, value).unwrap();
+        }
+        writer.commit().unwrap();
+    }
+
+    #[test]
+    fn scan_committed_records() {
+        let mut log = Vec::new();
+        write_snapshot(
+            &mut log,
+            &[
+                (RecordType::TaskCache, b"task", &7u32.to_be_bytes()),
+                (RecordType::Data, &7u32.to_be_bytes(), b"first"),
+                (RecordType::NextFreeTaskId, &[], &8u32.to_be_bytes()),
+            ],
+        );
+        write_snapshot(
+            &mut log,
+            &[
+                (RecordType::Data, &7u32.to_be_bytes(), b"second"),
+                (RecordType::Meta, &7u32.to_be_bytes(), b"meta"),
+                (RecordType::Operations, &[], b"ops"),
+            ],
+        );
+
+        let index = scan_log(Cursor::new(&log)).unwrap();
+        assert_eq!(index.committed_len, log.len() as u64);
+        assert_eq!(index.next_free_task_id, 8);
+        assert_eq!(
+            index.forward_task_cache.get(&b"task"[..]),
+            Some(&TaskId::from(7))
+        );
+
+        let read = |location: ValueLocation| {
+            &log[location.offset as usize..(location.offset + location.len as u64) as usize]
+        };
+        assert_eq!(read(index.reverse_task_cache[&TaskId::from(7)]), b"task");
+        assert_eq!(read(index.data[&TaskId::from(7)]), b"second");
+        assert_eq!(read(index.meta[&TaskId::from(7)]), b"meta");
+        assert_eq!(read(index.operations.unwrap()), b"ops");
+    }
+
+    #[test]
+    fn remove_task() {
+        let mut log = Vec::new();
+        write_snapshot(
+            &mut log,
+            &[
+                (RecordType::TaskCache, b"task", &3u32.to_be_bytes()),
+                (RecordType::Meta, &3u32.to_be_bytes(), b"meta"),
+                (RecordType::Data, &3u32.to_be_bytes(), b"data"),
+                (RecordType::Data, &4u32.to_be_bytes(), b"other"),
+            ],
+        );
+        write_snapshot(
+            &mut log,
+            &[(RecordType::RemoveTask, &3u32.to_be_bytes(), b"task")],
+        );
+
+        let index = scan_log(Cursor::new(&log)).unwrap();
+        assert!(index.forward_task_cache.is_empty());
+        assert!(index.reverse_task_cache.is_empty());
+        assert!(index.meta.is_empty());
+        assert_eq!(index.data.len(), 1);
+        assert!(index.data.contains_key(&TaskId::from(4)));
+    }
+
+    #[test]
+    fn ignore_uncommitted_records() {
+        let mut log = Vec::new();
+        write_snapshot(
+            &mut log,
+            &[(RecordType::Data, &1u32.to_be_bytes(), b"committed")],
+        );
+        let committed_len = log.len() as u64;
+
+        // A complete record without a commit, followed by a torn record.
+        let mut writer = RecordWriter::new(&mut log, committed_len);
+        writer
+            .write(RecordType::Data, &1u32.to_be_bytes(), b"uncommitted")
+            .unwrap();
+        writer
+            .write(RecordType::Data, &2u32.to_be_bytes(), b"torn")
+            .unwrap();
+        log.truncate(log.len() - 2);
+
+        let index = scan_log(Cursor::new(&log)).unwrap();
+        assert_eq!(index.committed_len, committed_len);
+        assert_eq!(index.data.len(), 1);
+        let location = index.data[&TaskId::from(1)];
+        assert_eq!(location.offset, HEADER_SIZE + 4);
+        assert_eq!(location.len, b"committed".len() as u32);
+    }
+
+    #[test]
+    fn ignore_unknown_record_type() {
+        let mut log = Vec::new();
+        write_snapshot(&mut log, &[(RecordType::Operations, &[], b"ops")]);
+        let committed_len = log.len() as u64;
+        log.extend_from_slice(&[42, 0, 0, 0, 0, 0, 0, 0, 0]);
+        write_snapshot(&mut log, &[(RecordType::Operations, &[], b"later")]);
+
+        let index = scan_log(Cursor::new(&log)).unwrap();
+        assert_eq!(index.committed_len, committed_len);
+    }
+}
@@ -0,0 +1,324 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use turbo_tasks::{backend::CachedTaskType, TaskId};
+
+use crate::{
+    backend::{AnyOperation, TaskDataCategory},
+    backing_storage::{
+        deserialize_task_data, serialize_snapshot, BackingStorage, ReadTransaction, StoredEntry,
+    },
+    data::{CachedDataItem, CachedDataUpdate},
+    utils::chunked_vec::ChunkedVec,
+};
+
+#[derive(Default)]
+struct MemoryStore {
+    forward_task_cache: HashMap<Vec<u8>, TaskId>,
+    reverse_task_cache: HashMap<TaskId, Vec<u8>>,
+    meta: HashMap<TaskId, Vec<u8>>,
+    data: HashMap<TaskId, Vec<u8>>,
+    operations: Vec<u8>,
+    next_free_task_id: u32,
+}
+
+impl MemoryStore {
+    fn category(&self, category: TaskDataCategory) -> &HashMap<TaskId, Vec<u8>> {
+        match category {
+            TaskDataCategory::Meta => &self.meta,
+            TaskDataCategory::Data => &self.data,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// A [BackingStorage] that keeps the serialized snapshots in memory.
+///
+/// It goes through the same serialization as the persistent stores, which
+/// makes it useful to test persistence without touching the filesystem. A
+/// clone shares the stored data, so a new backend can be created from the
+/// data of a previous one.
+#[derive(Clone, Default)]
+pub struct MemoryBackingStorage {
+    store: Arc<Mutex<MemoryStore>>,
+}
+
+impl MemoryBackingStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores a raw task cache entry, e. g. one that can't be deserialized.
+    #[cfg(test)]
+    pub(crate) fn insert_task_type(&self, task_id: TaskId, task_type: Vec<u8>) {
+        let mut store = self.store.lock();
+        store.forward_task_cache.insert(task_type.clone(), task_id);
+        store.reverse_task_cache.insert(task_id, task_type);
+    }
+
+    fn lookup_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Option<Vec<CachedDataItem>>> {
+        let store = self.store.lock();
+        let Some(bytes) = store.category(category).get(&task_id) else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_task_data(task_id, bytes)?))
+    }
+}
+
+impl BackingStorage for MemoryBackingStorage {
+    fn next_free_task_id(&self) -> TaskId {
+        TaskId::from(self.store.lock().next_free_task_id.max(1))
+    }
+
+    fn uncompleted_operations(&self) -> Result<Vec<AnyOperation>> {
+        let store = self.store.lock();
+        if store.operations.is_empty() {
+            return Ok(Vec::new());
+        }
+        pot::from_slice(&store.operations).context("Unable to deserialize uncompleted operations")
+    }
+
+    fn save_snapshot(
+        &self,
+        operations: Vec<Arc<AnyOperation>>,
+        task_cache_updates: Vec<ChunkedVec<(Arc<CachedTaskType>, TaskId)>>,
+        meta_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+        data_updates: Vec<ChunkedVec<CachedDataUpdate>>,
+    ) -> Result<()> {
+        let snapshot = serialize_snapshot(
+            operations,
+            task_cache_updates,
+            meta_updates,
+            data_updates,
+            |category, task_id| self.lookup_task_data(task_id, category),
+        )?;
+
+        let mut store = self.store.lock();
+        for (task_type, task_id) in snapshot.task_cache {
+            store.next_free_task_id = store.next_free_task_id.max(*task_id + 1);
+            store.reverse_task_cache.insert(task_id, task_type.clone());
+            store.forward_task_cache.insert(task_type, task_id);
+        }
+        store.meta.extend(snapshot.meta);
+        store.data.extend(snapshot.data);
+        store.operations = snapshot.operations;
+        Ok(())
+    }
+
+    fn start_read_transaction(&self) -> Option<ReadTransaction> {
+        None
+    }
+
+    unsafe fn end_read_transaction(&self, _tx: ReadTransaction) {
+        unreachable!("MemoryBackingStorage doesn't create read transactions")
+    }
+
+    unsafe fn forward_lookup_task_cache(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_type: &CachedTaskType,
+    ) -> Option<TaskId> {
+        let task_type = pot::to_vec(task_type).ok()?;
+        self.store
+            .lock()
+            .forward_task_cache
+            .get(&task_type)
+            .copied()
+    }
+
+    unsafe fn reverse_lookup_task_cache(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_id: TaskId,
+    ) -> Option<Arc<CachedTaskType>> {
+        let store = self.store.lock();
+        let bytes = store.reverse_task_cache.get(&task_id)?;
+        pot::from_slice(bytes).ok()
+    }
+
+    unsafe fn lookup_data(
+        &self,
+        _tx: Option<ReadTransaction>,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Vec<CachedDataItem> {
+        self.lookup_task_data(task_id, category)
+            .inspect_err(|err| println!("Looking up data for {task_id} failed: {err:?}"))
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    fn read_task_data(
+        &self,
+        task_id: TaskId,
+        category: TaskDataCategory,
+    ) -> Result<Vec<CachedDataItem>> {
+        Ok(self
+            .lookup_task_data(task_id, category)?
+            .unwrap_or_default())
+    }
+
+    fn visit_entries(&self, visitor: &mut dyn FnMut(StoredEntry<'_>)) -> Result<()> {
+        let store = self.store.lock();
+        for (task_id, task_type) in store.reverse_task_cache.iter() {
+            visitor(StoredEntry::TaskCache {
+                task_id: *task_id,
+                task_type,
+            });
+        }
+        for category in [TaskDataCategory::Meta, TaskDataCategory::Data] {
+            for (task_id, bytes) in store.category(category).iter() {
+                visitor(StoredEntry::TaskData {
+                    task_id: *task_id,
+                    category,
+                    size: bytes.len(),
+                });
+            }
+        }
+        if !store.operations.is_empty() {
+            visitor(StoredEntry::Operations {
+                size: store.operations.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn remove_tasks(&self, task_ids: &[TaskId]) -> Result<()> {
+        let mut store = self.store.lock();
+        for task_id in task_ids {
+            if let Some(task_type) = store.reverse_task_cache.remove(task_id) {
+                store.forward_task_cache.remove(&task_type);
+            }
+            store.meta.remove(task_id);
+            store.data.remove(task_id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use turbo_tasks::{KeyValuePair, TaskId};
+
+    use super::MemoryBackingStorage;
+    use crate::{
+        backend::TaskDataCategory,
+        backing_storage::{BackingStorage, StoredEntry},
+        data::{CachedDataItemKey, CachedDataItemValue, CachedDataUpdate, OutputValue},
+        utils::chunked_vec::ChunkedVec,
+    };
+
+    fn update(
+        task: u32,
+        key: CachedDataItemKey,
+        old_value: Option<CachedDataItemValue>,
+        value: Option<CachedDataItemValue>,
+    ) -> ChunkedVec<CachedDataUpdate> {
+        let mut updates = ChunkedVec::new();
+        updates.push(CachedDataUpdate {
+            task: TaskId::from(task),
+            key,
+            value,
+            old_value,
+        });
+        updates
+    }
+
+    fn keys(storage: &MemoryBackingStorage, task: u32) -> HashSet<CachedDataItemKey> {
+        unsafe { storage.lookup_data(None, TaskId::from(task), TaskDataCategory::Data) }
+            .into_iter()
+            .map(|item| item.into_key_and_value().0)
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let storage = MemoryBackingStorage::new();
+        assert_eq!(*storage.next_free_task_id(), 1);
+        assert!(storage.uncompleted_operations().unwrap().is_empty());
+        assert!(keys(&storage, 1).is_empty());
+        let mut entries = 0;
+        storage.visit_entries(&mut |_| entries += 1).unwrap();
+        assert_eq!(entries, 0);
+    }
+
+    #[test]
+    fn snapshots() {
+        let storage = MemoryBackingStorage::new();
+        let child = CachedDataItemKey::Child {
+            task: TaskId::from(2),
+        };
+        let output = CachedDataItemKey::Output {};
+        storage
+            .save_snapshot(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                vec![
+                    update(
+                        1,
+                        child.clone(),
+                        None,
+                        Some(CachedDataItemValue::Child { value: () }),
+                    ),
+                    update(
+                        1,
+                        output.clone(),
+                        None,
+                        Some(CachedDataItemValue::Output {
+                            value: OutputValue::Output(TaskId::from(3)),
+                        }),
+                    ),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            keys(&storage, 1),
+            HashSet::from([child.clone(), output.clone()])
+        );
+
+        // Clones share the stored data and later snapshots are merged with it.
+        let clone = storage.clone();
+        clone
+            .save_snapshot(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                vec![update(
+                    1,
+                    child,
+                    Some(CachedDataItemValue::Child { value: () }),
+                    None,
+                )],
+            )
+            .unwrap();
+        assert_eq!(keys(&storage, 1), HashSet::from([output]));
+
+        let mut data_entries = Vec::new();
+        storage
+            .visit_entries(&mut |entry| {
+                if let StoredEntry::TaskData {
+                    task_id, category, ..
+                } = entry
+                {
+                    data_entries.push((task_id, category));
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            data_entries,
+            vec![(TaskId::from(1), TaskDataCategory::Data)]
+        );
+
+        storage.remove_tasks(&[TaskId::from(1)]).unwrap();
+        assert!(keys(&storage, 1).is_empty());
+    }
+}
@@ -2,6 +2,7 @@ use std::{fmt::Debug, hash::Hash, num::NonZeroU64, ops::Deref};
 
 use dashmap::{mapref::entry::Entry, DashMap};
 use once_cell::sync::Lazy;
+use turbo_tasks_hash::Xxh3Hash64Hasher;
 
 use crate::{
     id::{FunctionId, TraitTypeId, ValueTypeId},
@@ -146,3 +147,24 @@ pub fn get_trait(id: TraitTypeId) -> &'static TraitType {
 pub fn get_trait_type_global_name(id: TraitTypeId) -> &'static str {
     TRAIT_TYPES.get(*id as usize).unwrap().1
 }
+
+/// Identifies the set of registered functions, value types and trait types.
+///
+/// Builds with the same fingerprint can read and execute the same tasks, which
+/// allows to tell whether a persistent cache was written by the current build.
+pub fn fingerprint() -> u64 {
+    let mut names = FUNCTIONS_BY_NAME
+        .iter()
+        .map(|entry| (0u8, *entry.key()))
+        .chain(VALUE_TYPES_BY_NAME.iter().map(|entry| (1u8, *entry.key())))
+        .chain(TRAIT_TYPES_BY_NAME.iter().map(|entry| (2u8, *entry.key())))
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+
+    let mut hasher = Xxh3Hash64Hasher::new();
+    for (kind, name) in names {
+        hasher.write_value(kind);
+        hasher.write_value(name);
+    }
+    hasher.finish()
+}
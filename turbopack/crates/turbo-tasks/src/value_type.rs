@@ -298,6 +298,10 @@ impl TraitType {
         register_trait_type(global_name, self);
     }
 
+    pub fn has_method(&self, name: &str) -> bool {
+        self.methods.get(name).is_some()
+    }
+
     pub fn resolve_span(&'static self, name: &str) -> Span {
         tracing::trace_span!(
             "turbo_tasks::resolve_trait_call",
@@ -3,7 +3,7 @@ use indexmap::IndexSet;
 use turbo_tasks::{RcStr, ValueToString, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{Chunk, ChunkingContext, OutputChunk, OutputChunkRuntimeInfo},
+    chunk::{Chunk, ChunkOutputAsset, ChunkingContext, OutputChunk, OutputChunkRuntimeInfo},
     ident::AssetIdent,
     introspect::{Introspectable, IntrospectableChildren},
     output::{OutputAsset, OutputAssets},
@@ -73,10 +73,13 @@ impl EcmascriptDevChunk {
             this.chunk.chunk_content(),
         ))
     }
+}
 
+#[turbo_tasks::value_impl]
+impl ChunkOutputAsset for EcmascriptDevChunk {
     #[turbo_tasks::function]
-    pub fn chunk(&self) -> Result<Vc<Box<dyn Chunk>>> {
-        Ok(Vc::upcast(self.chunk))
+    fn chunk(&self) -> Vc<Box<dyn Chunk>> {
+        Vc::upcast(self.chunk)
     }
 }
 
@@ -97,17 +100,22 @@ impl OutputAsset for EcmascriptDevChunk {
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
         let chunk_references = this.chunk.references().await?;
+        let source_maps_type = *this.chunking_context.source_maps_type().await?;
         let include_source_map = *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+            .await?
+            && source_maps_type.has_source_map_file();
         let mut references =
             Vec::with_capacity(chunk_references.len() + if include_source_map { 1 } else { 0 });
 
         references.extend(chunk_references.iter().copied());
 
         if include_source_map {
-            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+            references.push(Vc::upcast(SourceMapAsset::new(
+                Vc::upcast(self),
+                source_maps_type,
+            )));
         }
 
         Ok(Vc::cell(references))
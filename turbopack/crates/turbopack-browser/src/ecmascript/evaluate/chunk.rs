@@ -164,21 +164,23 @@ impl EcmascriptDevEvaluateChunk {
             }
         }
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(
-                code,
-                "\n\n//# sourceMappingURL={}.map",
-                urlencoding::encode(filename)
-            )?;
-        }
-
-        let code = code.build().cell();
+        let mut code = code.build().cell();
         if matches!(
             this.chunking_context.await?.minify_type(),
             MinifyType::Minify
         ) {
-            return Ok(minify(chunk_path_vc, code));
+            code = minify(chunk_path_vc, code);
+        }
+
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?
+        {
+            code = code.with_source_mapping_url(
+                chunk_path_vc,
+                this.chunking_context.await?.source_maps_type(),
+            );
         }
 
         Ok(code)
@@ -228,13 +230,18 @@ impl OutputAsset for EcmascriptDevEvaluateChunk {
         let this = self.await?;
         let mut references = Vec::new();
 
+        let source_maps_type = *this.chunking_context.source_maps_type().await?;
         let include_source_map = *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+            .await?
+            && source_maps_type.has_source_map_file();
 
         if include_source_map {
-            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+            references.push(Vc::upcast(SourceMapAsset::new(
+                Vc::upcast(self),
+                source_maps_type,
+            )));
         }
 
         for chunk_data in &*self.chunks_data().await? {
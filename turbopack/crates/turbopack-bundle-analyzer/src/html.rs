@@ -0,0 +1,9 @@
+const TEMPLATE: &str = include_str!("analyze.html");
+const PLACEHOLDER: &str = "__ANALYZE_REPORT__";
+
+/// Renders the self-contained HTML treemap for a report serialized as JSON.
+pub fn render(report_json: &str) -> String {
+    // `<` only appears in JSON strings, where it can be escaped, so the report
+    // can't close the script tag
+    TEMPLATE.replacen(PLACEHOLDER, &report_json.replace('<', "\\u003c"), 1)
+}
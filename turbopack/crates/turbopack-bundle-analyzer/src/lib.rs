@@ -0,0 +1,509 @@
+//! Bundle analysis for turbopack builds.
+//!
+//! Maps the emitted chunks back to the modules they contain and reports the
+//! size of each module at different stages (original source, generated code,
+//! minified and compressed), the references that pulled each module into the
+//! build, and packages that are included more than once. The report can be
+//! emitted as JSON and as a self-contained HTML treemap.
+
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+
+mod compression;
+mod html;
+mod package;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    debug::ValueDebugFormat, trace::TraceRawVcs, RcStr, TryJoinIterExt, ValueToString, Vc,
+};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkOutputAsset, ChunkableModuleReference, ChunkingType},
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    virtual_output::VirtualOutputAsset,
+};
+use turbopack_css::chunk::CssChunkItem;
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunkItem, EcmascriptChunkItemExt},
+    minify::minify,
+};
+
+use self::package::{package_of, read_package_version, PackageInfo};
+
+/// Sizes of a module or an output asset in bytes.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeSizes {
+    /// The size of the module's source. Zero for output assets.
+    pub original: u64,
+    /// The size of the generated code, before minification.
+    pub rendered: u64,
+    pub minified: u64,
+    /// The gzip compressed size of the minified code.
+    pub gzip: u64,
+    /// The brotli compressed size of the minified code.
+    pub brotli: u64,
+}
+
+/// A reference that pulled a module into the build.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeReason {
+    /// The ident of the module that contains the reference.
+    pub issuer: RcStr,
+    pub reference: RcStr,
+    pub chunking_type: ChunkingType,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeModule {
+    pub ident: RcStr,
+    pub path: RcStr,
+    pub package: Option<PackageInfo>,
+    pub sizes: AnalyzeSizes,
+    /// Indices into [AnalyzeReport::chunks].
+    pub chunks: Vec<usize>,
+    pub reasons: Vec<AnalyzeReason>,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeChunk {
+    /// The path of the chunk, relative to the client or server output root.
+    pub path: RcStr,
+    /// Whether the chunk is loaded on the server.
+    pub server: bool,
+    pub sizes: AnalyzeSizes,
+    /// Indices into [AnalyzeReport::modules].
+    pub modules: Vec<usize>,
+}
+
+/// An output asset that is not a chunk, e. g. an image.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeAsset {
+    pub path: RcStr,
+    pub server: bool,
+    pub sizes: AnalyzeSizes,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatedPackage {
+    pub name: RcStr,
+    pub instances: Vec<DuplicatedPackageInstance>,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatedPackageInstance {
+    pub path: RcStr,
+    pub version: Option<RcStr>,
+    pub modules: usize,
+    /// The summed minified size of the modules of this instance.
+    pub minified: u64,
+}
+
+#[turbo_tasks::value(shared)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeReport {
+    pub chunks: Vec<AnalyzeChunk>,
+    pub assets: Vec<AnalyzeAsset>,
+    pub modules: Vec<AnalyzeModule>,
+    pub duplicated_packages: Vec<DuplicatedPackage>,
+}
+
+#[turbo_tasks::value_impl]
+impl AnalyzeReport {
+    /// Analyzes the given client and server output assets. Paths in the report
+    /// are relative to `client_root` or `server_root`.
+    ///
+    /// Only the passed assets are analyzed, referenced output assets need to be
+    /// included by the caller, e. g. with `all_assets_from_entries`.
+    #[turbo_tasks::function]
+    pub async fn new(
+        client_assets: Vc<OutputAssets>,
+        client_root: Vc<FileSystemPath>,
+        server_assets: Vc<OutputAssets>,
+        server_root: Vc<FileSystemPath>,
+    ) -> Result<Vc<Self>> {
+        let mut chunks = Vec::new();
+        let mut other_assets = Vec::new();
+        let mut modules: IndexMap<Vc<Box<dyn Module>>, (Vc<Box<dyn ChunkItem>>, Vec<usize>)> =
+            IndexMap::new();
+        for (assets, output_root, server) in [
+            (client_assets, client_root, false),
+            (server_assets, server_root, true),
+        ] {
+            let output_root = output_root.await?;
+            for &asset in assets.await?.iter() {
+                let path = asset.ident().path().await?;
+                let relative_path: RcStr =
+                    output_root.get_path_to(&path).unwrap_or(&path.path).into();
+                // Source maps are only loaded by dev tools
+                if relative_path.ends_with(".map") {
+                    continue;
+                }
+                let sizes = *output_asset_sizes(asset.content()).await?;
+
+                let Some(chunk_asset) =
+                    Vc::try_resolve_sidecast::<Box<dyn ChunkOutputAsset>>(asset).await?
+                else {
+                    other_assets.push(AnalyzeAsset {
+                        path: relative_path,
+                        server,
+                        sizes,
+                    });
+                    continue;
+                };
+
+                let chunk_index = chunks.len();
+                let mut chunk_modules = Vec::new();
+                for &item in chunk_asset.chunk().chunk_items().await?.iter() {
+                    let module = item.module().resolve().await?;
+                    let entry = modules.entry(module);
+                    chunk_modules.push(entry.index());
+                    entry
+                        .or_insert_with(|| (item, Vec::new()))
+                        .1
+                        .push(chunk_index);
+                }
+                chunks.push(AnalyzeChunk {
+                    path: relative_path,
+                    server,
+                    sizes,
+                    modules: chunk_modules,
+                });
+            }
+        }
+
+        let mut analyzed_modules = modules
+            .iter()
+            .map(|(&module, (item, chunks))| async move {
+                let ident = module.ident();
+                let path = ident.path().await?;
+                let package = match package_of(&path.path) {
+                    Some((name, package_path)) => Some(PackageInfo {
+                        version: read_package_version(
+                            ident.path().root().join(package_path.clone()),
+                        )
+                        .await?,
+                        name,
+                        path: package_path,
+                    }),
+                    None => None,
+                };
+                Ok(AnalyzeModule {
+                    ident: ident.to_string().await?.clone_value(),
+                    path: path.path.clone(),
+                    package,
+                    sizes: *chunk_item_sizes(*item).await?,
+                    chunks: chunks.clone(),
+                    reasons: Vec::new(),
+                })
+            })
+            .try_join()
+            .await?;
+
+        let references = modules
+            .keys()
+            .map(|&module| chunking_references(module))
+            .try_join()
+            .await?;
+        for (issuer_index, references) in references.iter().enumerate() {
+            for (target, reason) in references.iter() {
+                let Some(target_index) = modules.get_index_of(target) else {
+                    continue;
+                };
+                analyzed_modules[target_index].reasons.push(AnalyzeReason {
+                    issuer: analyzed_modules[issuer_index].ident.clone(),
+                    reference: reason.reference.clone(),
+                    chunking_type: reason.chunking_type,
+                });
+            }
+        }
+
+        let duplicated_packages = duplicated_packages(&analyzed_modules);
+
+        Ok(AnalyzeReport {
+            chunks,
+            assets: other_assets,
+            modules: analyzed_modules,
+            duplicated_packages,
+        }
+        .cell())
+    }
+
+    /// Emits the report as `analyze.json` and `analyze.html` into
+    /// `output_dir`.
+    #[turbo_tasks::function]
+    pub async fn output_assets(
+        self: Vc<Self>,
+        output_dir: Vc<FileSystemPath>,
+    ) -> Result<Vc<OutputAssets>> {
+        let report = self.await?;
+        let json = serde_json::to_string_pretty(&*report)?;
+        let html = html::render(&serde_json::to_string(&*report)?);
+        Ok(Vc::cell(vec![
+            Vc::upcast(VirtualOutputAsset::new(
+                output_dir.join("analyze.json".into()),
+                AssetContent::file(File::from(json).into()),
+            )),
+            Vc::upcast(VirtualOutputAsset::new(
+                output_dir.join("analyze.html".into()),
+                AssetContent::file(File::from(html).into()),
+            )),
+        ]))
+    }
+}
+
+/// The size and compressed sizes of an emitted file. Output assets are
+/// already minified, if minification is enabled.
+#[turbo_tasks::function]
+async fn output_asset_sizes(content: Vc<AssetContent>) -> Result<Vc<AnalyzeSizes>> {
+    let FileContent::Content(file) = &*content.file_content().await? else {
+        return Ok(AnalyzeSizes::default().cell());
+    };
+    let bytes = file.content().to_bytes()?;
+    let (gzip, brotli) = compression::compressed_sizes(&bytes)?;
+    Ok(AnalyzeSizes {
+        original: 0,
+        rendered: bytes.len() as u64,
+        minified: bytes.len() as u64,
+        gzip,
+        brotli,
+    }
+    .cell())
+}
+
+/// The contribution of a chunk item to the chunks it's placed in.
+///
+/// Async module info isn't available outside of a chunk, so the generated
+/// code of async modules is slightly smaller than in the emitted chunk.
+#[turbo_tasks::function]
+async fn chunk_item_sizes(item: Vc<Box<dyn ChunkItem>>) -> Result<Vc<AnalyzeSizes>> {
+    let original = item.module().content().len().await?.unwrap_or_default();
+
+    let (rendered, minified) = if let Some(ecmascript_item) =
+        Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkItem>>(item).await?
+    {
+        let code = ecmascript_item.code(None);
+        let rendered = code.await?.source_code().to_bytes()?.into_owned();
+        // The module factory is not always a valid program on its own, e. g. for
+        // code with parse errors, so fall back to the unminified code
+        let minified = match minify(item.asset_ident().path(), code).await {
+            Ok(minified) => minified.source_code().to_bytes()?.into_owned(),
+            Err(_) => rendered.clone(),
+        };
+        (rendered, minified)
+    } else if let Some(css_item) = Vc::try_resolve_downcast::<Box<dyn CssChunkItem>>(item).await? {
+        // CSS is minified when it's parsed, depending on the chunking context
+        let rendered = css_item
+            .content()
+            .await?
+            .inner_code
+            .to_bytes()?
+            .into_owned();
+        (rendered.clone(), rendered)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let (gzip, brotli) = compression::compressed_sizes(&minified)?;
+    Ok(AnalyzeSizes {
+        original,
+        rendered: rendered.len() as u64,
+        minified: minified.len() as u64,
+        gzip,
+        brotli,
+    }
+    .cell())
+}
+
+struct ChunkingReference {
+    reference: RcStr,
+    chunking_type: ChunkingType,
+}
+
+/// The modules referenced by `module` that are placed into chunks, together
+/// with the reference that pulls them in.
+async fn chunking_references(
+    module: Vc<Box<dyn Module>>,
+) -> Result<Vec<(Vc<Box<dyn Module>>, ChunkingReference)>> {
+    let mut result = Vec::new();
+    for &reference in module.references().await?.iter() {
+        let Some(chunkable) =
+            Vc::try_resolve_sidecast::<Box<dyn ChunkableModuleReference>>(reference).await?
+        else {
+            continue;
+        };
+        let Some(chunking_type) = *chunkable.chunking_type().await? else {
+            continue;
+        };
+        let description = reference.to_string().await?;
+        for &target in reference
+            .resolve_reference()
+            .primary_modules()
+            .await?
+            .iter()
+        {
+            result.push((
+                target.resolve().await?,
+                ChunkingReference {
+                    reference: description.clone_value(),
+                    chunking_type,
+                },
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// Packages that are included from more than one directory, e. g. different
+/// versions of the same package in nested `node_modules`.
+fn duplicated_packages(modules: &[AnalyzeModule]) -> Vec<DuplicatedPackage> {
+    let mut packages: IndexMap<&RcStr, IndexMap<&RcStr, DuplicatedPackageInstance>> =
+        IndexMap::new();
+    for module in modules {
+        let Some(package) = &module.package else {
+            continue;
+        };
+        let instance = packages
+            .entry(&package.name)
+            .or_default()
+            .entry(&package.path)
+            .or_insert_with(|| DuplicatedPackageInstance {
+                path: package.path.clone(),
+                version: package.version.clone(),
+                modules: 0,
+                minified: 0,
+            });
+        instance.modules += 1;
+        instance.minified += module.sizes.minified;
+    }
+    packages
+        .into_iter()
+        .filter(|(_, instances)| instances.len() > 1)
+        .map(|(name, instances)| DuplicatedPackage {
+            name: name.clone(),
+            instances: instances.into_values().collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::RcStr;
+
+    use super::{duplicated_packages, AnalyzeModule, AnalyzeSizes, PackageInfo};
+
+    fn module(path: &str, package: Option<(&str, &str, &str)>, minified: u64) -> AnalyzeModule {
+        AnalyzeModule {
+            ident: path.into(),
+            path: path.into(),
+            package: package.map(|(name, version, path)| PackageInfo {
+                name: name.into(),
+                version: Some(version.into()),
+                path: path.into(),
+            }),
+            sizes: AnalyzeSizes {
+                minified,
+                ..Default::default()
+            },
+            chunks: Vec::new(),
+            reasons: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn duplicated_packages_by_path() {
+        let modules = [
+            module("src/index.js", None, 10),
+            module(
+                "node_modules/a/index.js",
+                Some(("a", "2.0.0", "node_modules/a")),
+                100,
+            ),
+            module(
+                "node_modules/a/lib.js",
+                Some(("a", "2.0.0", "node_modules/a")),
+                50,
+            ),
+            module(
+                "node_modules/b/node_modules/a/index.js",
+                Some(("a", "1.0.0", "node_modules/b/node_modules/a")),
+                80,
+            ),
+            module(
+                "node_modules/b/index.js",
+                Some(("b", "1.0.0", "node_modules/b")),
+                20,
+            ),
+        ];
+        let duplicated = duplicated_packages(&modules);
+        assert_eq!(duplicated.len(), 1);
+        assert_eq!(duplicated[0].name, "a");
+        let instances = duplicated[0]
+            .instances
+            .iter()
+            .map(|instance| {
+                (
+                    instance.path.clone(),
+                    instance.version.clone(),
+                    instance.modules,
+                    instance.minified,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            instances,
+            vec![
+                (
+                    RcStr::from("node_modules/a"),
+                    Some(RcStr::from("2.0.0")),
+                    2,
+                    150
+                ),
+                (
+                    RcStr::from("node_modules/b/node_modules/a"),
+                    Some(RcStr::from("1.0.0")),
+                    1,
+                    80
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_duplicated_packages() {
+        let modules = [
+            module(
+                "node_modules/a/index.js",
+                Some(("a", "1.0.0", "node_modules/a")),
+                1,
+            ),
+            module(
+                "node_modules/a/lib.js",
+                Some(("a", "1.0.0", "node_modules/a")),
+                1,
+            ),
+        ];
+        assert!(duplicated_packages(&modules).is_empty());
+    }
+}
+
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack_core::register();
+    turbopack_css::register();
+    turbopack_ecmascript::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
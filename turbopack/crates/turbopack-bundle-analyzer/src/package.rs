@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{debug::ValueDebugFormat, trace::TraceRawVcs, RcStr, Vc};
+use turbo_tasks_fs::{FileJsonContent, FileSystemPath};
+
+/// The package a module belongs to.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInfo {
+    pub name: RcStr,
+    pub version: Option<RcStr>,
+    /// The directory of the package, e. g. `node_modules/a/node_modules/b`.
+    pub path: RcStr,
+}
+
+/// Returns the name and the directory of the innermost `node_modules` package
+/// that contains `path`.
+pub fn package_of(path: &str) -> Option<(RcStr, RcStr)> {
+    let name_start = match path.rfind("/node_modules/") {
+        Some(index) => index + "/node_modules/".len(),
+        None if path.starts_with("node_modules/") => "node_modules/".len(),
+        None => return None,
+    };
+    let rest = &path[name_start..];
+    let mut segments = rest.splitn(3, '/');
+    let first = segments.next().filter(|s| !s.is_empty())?;
+    let name_len = if first.starts_with('@') {
+        let second = segments.next().filter(|s| !s.is_empty())?;
+        first.len() + 1 + second.len()
+    } else {
+        first.len()
+    };
+    let name_end = name_start + name_len;
+    Some((rest[..name_len].into(), path[..name_end].into()))
+}
+
+/// Reads the version from the `package.json` in `package_dir`.
+pub async fn read_package_version(package_dir: Vc<FileSystemPath>) -> Result<Option<RcStr>> {
+    let FileJsonContent::Content(package_json) =
+        &*package_dir.join("package.json".into()).read_json().await?
+    else {
+        return Ok(None);
+    };
+    Ok(package_json["version"].as_str().map(RcStr::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_of;
+
+    fn package(path: &str) -> Option<(String, String)> {
+        package_of(path).map(|(name, dir)| (name.to_string(), dir.to_string()))
+    }
+
+    #[test]
+    fn package_of_paths() {
+        assert_eq!(package("src/index.js"), None);
+        assert_eq!(
+            package("node_modules/react/index.js"),
+            Some(("react".into(), "node_modules/react".into()))
+        );
+        assert_eq!(
+            package("app/node_modules/@scope/pkg/dist/index.js"),
+            Some(("@scope/pkg".into(), "app/node_modules/@scope/pkg".into()))
+        );
+        // The innermost package wins
+        assert_eq!(
+            package("node_modules/a/node_modules/b/lib/b.js"),
+            Some(("b".into(), "node_modules/a/node_modules/b".into()))
+        );
+        assert_eq!(
+            package("node_modules/.pnpm/a@1.0.0/node_modules/a/index.js"),
+            Some((
+                "a".into(),
+                "node_modules/.pnpm/a@1.0.0/node_modules/a".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn package_of_incomplete_paths() {
+        assert_eq!(package("node_modules/"), None);
+        assert_eq!(package("node_modules/@scope"), None);
+        assert_eq!(package("node_modules/@scope/"), None);
+        assert_eq!(
+            package("node_modules/pkg"),
+            Some(("pkg".into(), "node_modules/pkg".into()))
+        );
+    }
+}
@@ -3,14 +3,16 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, ValueEnum};
 use turbopack_cli_utils::issue::IssueSeverityCliOption;
+use turbopack_core::chunk::{ChunkingConfig, SourceMapsType};
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
 pub enum Arguments {
     Build(BuildArguments),
     Dev(DevArguments),
+    Cache(CacheArguments),
 }
 
 impl Arguments {
@@ -19,6 +21,7 @@ impl Arguments {
         match self {
             Arguments::Build(args) => args.common.dir.as_deref(),
             Arguments::Dev(args) => args.common.dir.as_deref(),
+            Arguments::Cache(_) => None,
         }
     }
 }
@@ -61,6 +64,73 @@ pub struct CommonArguments {
     /// MB.
     #[clap(long)]
     pub memory_limit: Option<usize>,
+
+    /// Chunks smaller than this many bytes are merged with other small chunks
+    /// of the same chunk group.
+    #[clap(long)]
+    pub min_chunk_size: Option<usize>,
+
+    /// Chunks larger than this many bytes are split by package or folder.
+    #[clap(long)]
+    pub max_chunk_size: Option<usize>,
+
+    /// The maximum number of chunks a chunk group is split into.
+    #[clap(long)]
+    pub max_chunk_count_per_group: Option<usize>,
+
+    /// Put every node_modules package into its own chunks.
+    #[clap(long)]
+    pub split_by_package: bool,
+
+    /// Embed the CSS of `@import` rules into the importing stylesheet instead
+    /// of emitting it as separate chunk items.
+    #[clap(long)]
+    pub inline_css_imports: bool,
+
+    /// Embed WebAssembly modules smaller than this many bytes into the chunk.
+    #[clap(long)]
+    pub inline_wasm_limit: Option<u64>,
+
+    /// How the source maps of chunks are emitted. Defaults to `full`.
+    #[clap(long, value_enum)]
+    pub source_maps: Option<SourceMapsCliOption>,
+}
+
+impl CommonArguments {
+    /// How chunk items are combined into chunks, see [ChunkingConfig].
+    pub fn chunking_config(&self) -> ChunkingConfig {
+        let default = ChunkingConfig::default();
+        ChunkingConfig {
+            min_chunk_size: self.min_chunk_size.unwrap_or(default.min_chunk_size),
+            max_chunk_size: self.max_chunk_size.unwrap_or(default.max_chunk_size),
+            max_chunk_count_per_group: self.max_chunk_count_per_group,
+            split_by_package: self.split_by_package,
+            ..default
+        }
+    }
+
+    /// How the source maps of chunks are emitted, see [SourceMapsType].
+    pub fn source_maps_type(&self) -> SourceMapsType {
+        match self.source_maps {
+            None | Some(SourceMapsCliOption::Full) => SourceMapsType::Full,
+            Some(SourceMapsCliOption::Hidden) => SourceMapsType::Hidden,
+            Some(SourceMapsCliOption::NoSources) => SourceMapsType::NoSources,
+            Some(SourceMapsCliOption::Inline) => SourceMapsType::Inline,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SourceMapsCliOption {
+    /// A `.map` file referenced by a `sourceMappingURL` comment.
+    Full,
+    /// A `.map` file without a `sourceMappingURL` comment.
+    Hidden,
+    /// Like `full`, but without the original code.
+    #[value(name = "nosources")]
+    NoSources,
+    /// The source map is embedded into the `sourceMappingURL` comment.
+    Inline,
 }
 
 #[derive(Debug, Args)]
@@ -89,6 +159,24 @@ pub struct DevArguments {
     #[clap(long)]
     pub no_open: bool,
 
+    /// Serve over HTTPS and HTTP/2. Uses a self-signed certificate unless
+    /// `--https-cert` and `--https-key` are provided.
+    #[clap(long)]
+    pub https: bool,
+
+    /// Path to a PEM encoded certificate to serve HTTPS with.
+    #[clap(long, requires = "https", requires = "https_key")]
+    pub https_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key of `--https-cert`.
+    #[clap(long, requires = "https", requires = "https_cert")]
+    pub https_key: Option<PathBuf>,
+
+    /// Additional hostnames the self-signed certificate is valid for, e.g.
+    /// the LAN hostname of this machine.
+    #[clap(long, requires = "https")]
+    pub https_hostname: Vec<String>,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -107,4 +195,27 @@ pub struct BuildArguments {
     /// Don't minify build output.
     #[clap(long)]
     pub no_minify: bool,
+
+    /// Write a bundle analysis of the build output to `dist/analyze.json` and
+    /// `dist/analyze.html`.
+    #[clap(long)]
+    pub analyze: bool,
+}
+
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct CacheArguments {
+    /// The directory of the persistent cache, e. g. `.next/cache/turbopack`.
+    #[clap(value_parser)]
+    pub dir: PathBuf,
+
+    /// The number of functions to list, ordered by the size of their stored
+    /// data.
+    #[clap(long, default_value_t = 50)]
+    pub top: usize,
+
+    /// Remove the tasks of functions that are not registered in this build.
+    /// Only possible for caches written by this build.
+    #[clap(long)]
+    pub prune: bool,
 }
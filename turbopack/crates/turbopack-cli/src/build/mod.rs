@@ -7,16 +7,18 @@ use std::{
 
 use anyhow::{bail, Context, Result};
 use turbo_tasks::{
-    RcStr, ReadConsistency, TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc,
+    CollectiblesSource, RcStr, ReadConsistency, TransientInstance, TryJoinIterExt, TurboTasks,
+    Value, Vc,
 };
 use turbo_tasks_fs::FileSystem;
 use turbo_tasks_memory::MemoryBackend;
+use turbopack_bundle_analyzer::AnalyzeReport;
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableModule, ChunkingContext, ChunkingContextExt,
-        EvaluatableAsset, EvaluatableAssets, MinifyType,
+        availability_info::AvailabilityInfo, ChunkableModule, ChunkingConfig, ChunkingContext,
+        ChunkingContextExt, EvaluatableAsset, EvaluatableAssets, MinifyType, SourceMapsType,
     },
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
     issue::{handle_issues, IssueReporter, IssueSeverity},
@@ -58,6 +60,11 @@ pub struct TurbopackBuildBuilder {
     show_all: bool,
     log_detail: bool,
     minify_type: MinifyType,
+    chunking_config: ChunkingConfig,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
+    analyze: bool,
 }
 
 impl TurbopackBuildBuilder {
@@ -76,6 +83,11 @@ impl TurbopackBuildBuilder {
             show_all: false,
             log_detail: false,
             minify_type: MinifyType::Minify,
+            chunking_config: ChunkingConfig::default(),
+            inline_css_imports: false,
+            inline_wasm_limit: None,
+            source_maps_type: SourceMapsType::Full,
+            analyze: false,
         }
     }
 
@@ -109,6 +121,31 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    pub fn chunking_config(mut self, chunking_config: ChunkingConfig) -> Self {
+        self.chunking_config = chunking_config;
+        self
+    }
+
+    pub fn inline_css_imports(mut self, inline_css_imports: bool) -> Self {
+        self.inline_css_imports = inline_css_imports;
+        self
+    }
+
+    pub fn inline_wasm_limit(mut self, inline_wasm_limit: Option<u64>) -> Self {
+        self.inline_wasm_limit = inline_wasm_limit;
+        self
+    }
+
+    pub fn source_maps_type(mut self, source_maps_type: SourceMapsType) -> Self {
+        self.source_maps_type = source_maps_type;
+        self
+    }
+
+    pub fn analyze(mut self, analyze: bool) -> Self {
+        self.analyze = analyze;
+        self
+    }
+
     pub async fn build(self) -> Result<()> {
         let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
             let build_result = build_internal(
@@ -124,6 +161,11 @@ impl TurbopackBuildBuilder {
                 .cell(),
                 self.browserslist_query,
                 self.minify_type,
+                self.chunking_config.cell(),
+                self.inline_css_imports,
+                self.inline_wasm_limit,
+                self.source_maps_type,
+                self.analyze,
             );
 
             // Await the result to propagate any errors.
@@ -165,6 +207,11 @@ async fn build_internal(
     entry_requests: Vc<EntryRequests>,
     browserslist_query: RcStr,
     minify_type: MinifyType,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
+    analyze: bool,
 ) -> Result<Vc<()>> {
     let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
         BrowserEnvironment {
@@ -202,6 +249,10 @@ async fn build_internal(
             },
         )
         .minify_type(minify_type)
+        .chunking_config(chunking_config)
+        .inline_css_imports(inline_css_imports)
+        .inline_wasm_limit(inline_wasm_limit)
+        .source_maps_type(source_maps_type)
         .build(),
     );
 
@@ -308,6 +359,9 @@ async fn build_internal(
     let mut chunks: HashSet<Vc<Box<dyn OutputAsset>>> = HashSet::new();
     for chunk_group in entry_chunk_groups {
         chunks.extend(&*all_assets_from_entries(chunk_group).await?);
+        // Files emitted by webpack loaders via `this.emitFile` are not referenced
+        // by any output asset
+        chunks.extend(chunk_group.peek_collectibles::<Box<dyn OutputAsset>>());
     }
 
     chunks
@@ -316,6 +370,29 @@ async fn build_internal(
         .try_join()
         .await?;
 
+    if analyze {
+        // Sort the chunks to get a stable report
+        let mut chunks = chunks
+            .into_iter()
+            .map(|c| async move { Ok((c.ident().path().await?.path.clone(), c)) })
+            .try_join()
+            .await?;
+        chunks.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let report = AnalyzeReport::new(
+            Vc::cell(chunks.into_iter().map(|(_, c)| c).collect()),
+            build_output_root,
+            OutputAssets::empty(),
+            build_output_root,
+        );
+        report
+            .output_assets(build_output_root)
+            .await?
+            .iter()
+            .map(|asset| asset.content().write(asset.ident().path()))
+            .try_join()
+            .await?;
+    }
+
     Ok(Default::default())
 }
 
@@ -343,7 +420,12 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         } else {
             MinifyType::Minify
         })
-        .show_all(args.common.show_all);
+        .show_all(args.common.show_all)
+        .chunking_config(args.common.chunking_config())
+        .inline_css_imports(args.common.inline_css_imports)
+        .inline_wasm_limit(args.common.inline_wasm_limit)
+        .source_maps_type(args.common.source_maps_type())
+        .analyze(args.analyze);
 
     for entry in normalize_entries(&args.common.entries) {
         builder = builder.entry_request(EntryRequest::Relative(entry));
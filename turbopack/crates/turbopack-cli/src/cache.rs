@@ -0,0 +1,123 @@
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use turbo_tasks::{registry, util::FormatBytes};
+use turbo_tasks_backend::{
+    inspect_cache, prune_unregistered_tasks, BackingStorageKind, CacheReport, ExistingStore,
+    TaskDataCategory,
+};
+
+use crate::arguments::CacheArguments;
+
+/// Prints what is stored in a persistent cache, and optionally removes the
+/// tasks that the current build can't execute anymore.
+///
+/// Functions are checked against the registry of this binary, so they are only
+/// reported as unregistered, and pruning is only possible, for caches written
+/// by this build. Caches of another build or application (e. g. Next.js) are
+/// only reported.
+pub fn inspect(args: &CacheArguments) -> Result<()> {
+    let ExistingStore {
+        kind,
+        storage,
+        registry_fingerprint,
+    } = BackingStorageKind::open_existing(&args.dir)?;
+    let report = inspect_cache(&*storage)?;
+    let same_build = registry_fingerprint == Some(registry::fingerprint());
+
+    println!("{} {} ({kind:?})", "Cache".bold(), args.dir.display());
+    if !same_build {
+        println!(
+            "  {}  written by another build, functions are not checked against this one",
+            "note:".dimmed()
+        );
+    }
+    print_summary(&report, same_build);
+    print_functions(&report, args.top, same_build);
+
+    if args.prune {
+        let removed = prune_unregistered_tasks(&*storage, registry_fingerprint)?;
+        println!();
+        println!(
+            "{} {removed} tasks of unregistered functions",
+            "Pruned".green().bold()
+        );
+    }
+    Ok(())
+}
+
+fn print_summary(report: &CacheReport, same_build: bool) {
+    let unregistered = report.unregistered_functions().count();
+    let unregistered_tasks: usize = report
+        .unregistered_functions()
+        .map(|(_, function)| function.tasks)
+        .sum();
+    println!(
+        "  tasks:        {} of {} functions",
+        report.tasks(),
+        report.functions.len()
+    );
+    if same_build && (unregistered > 0 || !report.unreadable_tasks.is_empty()) {
+        println!(
+            "  {}  {unregistered_tasks} tasks of {unregistered} unregistered functions, {} \
+             unreadable tasks",
+            "prunable:".yellow(),
+            report.unreadable_tasks.len()
+        );
+    }
+    println!(
+        "  meta:         {}",
+        FormatBytes(report.bytes(TaskDataCategory::Meta))
+    );
+    println!(
+        "  data:         {}",
+        FormatBytes(report.bytes(TaskDataCategory::Data))
+    );
+    if report.orphaned_meta_bytes + report.orphaned_data_bytes > 0 {
+        println!(
+            "  without task type: {} meta, {} data",
+            FormatBytes(report.orphaned_meta_bytes),
+            FormatBytes(report.orphaned_data_bytes)
+        );
+    }
+    match report.uncompleted_operations {
+        Some(operations) => println!(
+            "  operations:   {operations} uncompleted ({})",
+            FormatBytes(report.operations_bytes)
+        ),
+        None => println!(
+            "  operations:   {} ({})",
+            "unreadable".yellow(),
+            FormatBytes(report.operations_bytes)
+        ),
+    }
+}
+
+fn print_functions(report: &CacheReport, top: usize, same_build: bool) {
+    let mut functions = report.functions.iter().collect::<Vec<_>>();
+    functions.sort_by_key(|(_, function)| {
+        std::cmp::Reverse(function.task_type_bytes + function.meta_bytes + function.data_bytes)
+    });
+
+    println!();
+    println!(
+        "{:>10} {:>12} {:>12} {:>12}  {}",
+        "tasks", "task cache", "meta", "data", "function"
+    );
+    for (name, function) in functions.iter().take(top) {
+        let line = format!(
+            "{:>10} {:>12} {:>12} {:>12}  {name}",
+            function.tasks,
+            FormatBytes(function.task_type_bytes).to_string(),
+            FormatBytes(function.meta_bytes).to_string(),
+            FormatBytes(function.data_bytes).to_string(),
+        );
+        if function.registered || !same_build {
+            println!("{line}");
+        } else {
+            println!("{} {}", line.yellow(), "(unregistered)".dimmed());
+        }
+    }
+    if functions.len() > top {
+        println!("{:>10} more functions", functions.len() - top);
+    }
+}
@@ -26,7 +26,8 @@ use turbopack_ecmascript_plugins::transform::{
     styled_jsx::StyledJsxTransformer,
 };
 use turbopack_node::{
-    execution_context::ExecutionContext, transforms::postcss::PostCssTransformOptions,
+    execution_context::ExecutionContext,
+    transforms::{postcss::PostCssTransformOptions, sass::SassTransformOptions},
 };
 use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
 
@@ -106,6 +107,7 @@ async fn get_client_module_options_context(
         preset_env_versions: Some(env),
         execution_context: Some(execution_context),
         tree_shaking_mode: Some(TreeShakingMode::ReexportsOnly),
+        enable_sass: Some(SassTransformOptions::default().cell()),
         ..Default::default()
     };
 
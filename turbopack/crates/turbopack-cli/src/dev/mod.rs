@@ -3,7 +3,7 @@ use std::{
     env::current_dir,
     future::{join, Future},
     io::{stdout, Write},
-    net::{IpAddr, SocketAddr},
+    net::{IpAddr, SocketAddr, UdpSocket},
     path::{PathBuf, MAIN_SEPARATOR},
     sync::Arc,
     time::{Duration, Instant},
@@ -21,6 +21,7 @@ use turbo_tasks_memory::MemoryBackend;
 use turbopack::evaluate_context::node_build_environment;
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
+    chunk::{ChunkingConfig, SourceMapsType},
     issue::{IssueReporter, IssueSeverity},
     resolve::parse::Request,
     server_fs::ServerFileSystem,
@@ -31,7 +32,7 @@ use turbopack_dev_server::{
         combined::CombinedContentSource, router::PrefixedRouterContentSource,
         static_assets::StaticAssetsContentSource, ContentSource,
     },
-    DevServer, DevServerBuilder,
+    DevServer, DevServerBuilder, TlsConfig,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 use turbopack_env::dotenv::load_env;
@@ -63,6 +64,11 @@ pub struct TurbopackDevServerBuilder {
     show_all: bool,
     log_detail: bool,
     allow_retry: bool,
+    tls: Option<TlsConfig>,
+    chunking_config: ChunkingConfig,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
 }
 
 impl TurbopackDevServerBuilder {
@@ -87,6 +93,11 @@ impl TurbopackDevServerBuilder {
             show_all: false,
             log_detail: false,
             allow_retry: false,
+            tls: None,
+            chunking_config: ChunkingConfig::default(),
+            inline_css_imports: false,
+            inline_wasm_limit: None,
+            source_maps_type: SourceMapsType::Full,
         }
     }
 
@@ -135,6 +146,37 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    pub fn tls(mut self, tls: TlsConfig) -> TurbopackDevServerBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
+    pub fn chunking_config(mut self, chunking_config: ChunkingConfig) -> TurbopackDevServerBuilder {
+        self.chunking_config = chunking_config;
+        self
+    }
+
+    pub fn inline_css_imports(mut self, inline_css_imports: bool) -> TurbopackDevServerBuilder {
+        self.inline_css_imports = inline_css_imports;
+        self
+    }
+
+    pub fn inline_wasm_limit(
+        mut self,
+        inline_wasm_limit: Option<u64>,
+    ) -> TurbopackDevServerBuilder {
+        self.inline_wasm_limit = inline_wasm_limit;
+        self
+    }
+
+    pub fn source_maps_type(
+        mut self,
+        source_maps_type: SourceMapsType,
+    ) -> TurbopackDevServerBuilder {
+        self.source_maps_type = source_maps_type;
+        self
+    }
+
     pub fn issue_reporter(
         mut self,
         issue_reporter: Box<dyn IssueReporterProvider>,
@@ -187,7 +229,10 @@ impl TurbopackDevServerBuilder {
         let port = self.port.context("port must be set")?;
         let host = self.hostname.context("hostname must be set")?;
 
-        let server = self.find_port(host, port, 10)?;
+        let mut server = self.find_port(host, port, 10)?;
+        if let Some(tls) = &self.tls {
+            server = server.tls(tls)?;
+        }
 
         let turbo_tasks = self.turbo_tasks;
         let project_dir: RcStr = self.project_dir;
@@ -204,6 +249,10 @@ impl TurbopackDevServerBuilder {
             log_level: self.log_level,
         });
         let entry_requests = TransientInstance::new(self.entry_requests);
+        let chunking_config = TransientInstance::new(self.chunking_config);
+        let inline_css_imports = self.inline_css_imports;
+        let inline_wasm_limit = self.inline_wasm_limit;
+        let source_maps_type = self.source_maps_type;
         let tasks = turbo_tasks.clone();
         let issue_provider = self.issue_reporter.unwrap_or_else(|| {
             // Initialize a ConsoleUi reporter if no custom reporter was provided
@@ -217,6 +266,10 @@ impl TurbopackDevServerBuilder {
                 entry_requests.clone(),
                 eager_compile,
                 browserslist_query.clone(),
+                chunking_config.clone(),
+                inline_css_imports,
+                inline_wasm_limit,
+                source_maps_type,
             )
         };
 
@@ -232,6 +285,10 @@ async fn source(
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
     browserslist_query: RcStr,
+    chunking_config: TransientInstance<ChunkingConfig>,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
 ) -> Vc<Box<dyn ContentSource>> {
     let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
     let project_relative: RcStr = project_relative
@@ -290,6 +347,10 @@ async fn source(
         eager_compile,
         NodeEnv::Development.cell(),
         browserslist_query,
+        ChunkingConfig::clone(&chunking_config).cell(),
+        inline_css_imports,
+        inline_wasm_limit,
+        source_maps_type,
     );
     let static_source = Vc::upcast(StaticAssetsContentSource::new(
         Default::default(),
@@ -342,6 +403,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         .port(args.port)
         .log_detail(args.common.log_detail)
         .show_all(args.common.show_all)
+        .chunking_config(args.common.chunking_config())
+        .inline_css_imports(args.common.inline_css_imports)
+        .inline_wasm_limit(args.common.inline_wasm_limit)
+        .source_maps_type(args.common.source_maps_type())
         .log_level(
             args.common
                 .log_level
@@ -357,6 +422,34 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         server = server.allow_retry(args.allow_retry);
     }
 
+    if args.https {
+        server = server.tls(match (&args.https_cert, &args.https_key) {
+            (Some(cert), Some(key)) => TlsConfig::Files {
+                cert: cert.clone(),
+                key: key.clone(),
+            },
+            _ => {
+                let mut hostnames = vec![
+                    "localhost".to_string(),
+                    "127.0.0.1".to_string(),
+                    "::1".to_string(),
+                ];
+                if args.hostname.is_unspecified() {
+                    // Other devices in the network connect to the LAN address
+                    hostnames.extend(
+                        lan_addresses(args.hostname.is_ipv6())
+                            .into_iter()
+                            .map(|ip| ip.to_string()),
+                    );
+                } else if !args.hostname.is_loopback() {
+                    hostnames.push(args.hostname.to_string());
+                }
+                hostnames.extend(args.https_hostname.iter().cloned());
+                TlsConfig::SelfSigned { hostnames }
+            }
+        });
+    }
+
     let server = server.build().await?;
 
     {
@@ -370,10 +463,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         } else {
             addr.ip().to_string()
         };
-        let index_uri = match addr.port() {
-            443 => format!("https://{hostname}"),
-            80 => format!("http://{hostname}"),
-            port => format!("http://{hostname}:{port}"),
+        let scheme = if server.https { "https" } else { "http" };
+        let index_uri = match (addr.port(), server.https) {
+            (443, true) | (80, false) => format!("{scheme}://{hostname}"),
+            (port, _) => format!("{scheme}://{hostname}:{port}"),
         };
         println!(
             "{} - started server on {}, url: {}",
@@ -472,6 +565,25 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
     Ok(())
 }
 
+/// Returns the addresses of this machine in the local network, i.e. the source
+/// addresses of the default routes. Connecting a UDP socket doesn't send any
+/// packets, the remote addresses are only used to look up the route.
+fn lan_addresses(ipv6: bool) -> Vec<IpAddr> {
+    let mut routes = vec![("0.0.0.0:0", "192.0.2.1:9")];
+    if ipv6 {
+        routes.push(("[::]:0", "[2001:db8::1]:9"));
+    }
+    routes
+        .into_iter()
+        .filter_map(|(local, remote)| {
+            let socket = UdpSocket::bind(local).ok()?;
+            socket.connect(remote).ok()?;
+            Some(socket.local_addr().ok()?.ip())
+        })
+        .filter(|ip| !ip.is_unspecified() && !ip.is_loopback())
+        .collect()
+}
+
 #[cfg(feature = "profile")]
 // When profiling, exits the process when no new updates have been received for
 // a given timeout and there are no more tasks in progress.
@@ -5,7 +5,7 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack_browser::{react_refresh::assert_can_resolve_react_refresh, BrowserChunkingContext};
 use turbopack_cli_utils::runtime_entry::{RuntimeEntries, RuntimeEntry};
 use turbopack_core::{
-    chunk::{ChunkableModule, ChunkingContext, EvaluatableAsset},
+    chunk::{ChunkableModule, ChunkingConfig, ChunkingContext, EvaluatableAsset, SourceMapsType},
     environment::Environment,
     file_source::FileSource,
     reference_type::{EntryReferenceSubType, ReferenceType},
@@ -34,6 +34,10 @@ pub fn get_client_chunking_context(
     project_path: Vc<FileSystemPath>,
     server_root: Vc<FileSystemPath>,
     environment: Vc<Environment>,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
 ) -> Vc<Box<dyn ChunkingContext>> {
     Vc::upcast(
         BrowserChunkingContext::builder(
@@ -46,6 +50,10 @@ pub fn get_client_chunking_context(
             RuntimeType::Development,
         )
         .hot_module_replacement()
+        .chunking_config(chunking_config)
+        .inline_css_imports(inline_css_imports)
+        .inline_wasm_limit(inline_wasm_limit)
+        .source_maps_type(source_maps_type)
         .build(),
     )
 }
@@ -89,12 +97,23 @@ pub async fn create_web_entry_source(
     eager_compile: bool,
     node_env: Vc<NodeEnv>,
     browserslist_query: RcStr,
+    chunking_config: Vc<ChunkingConfig>,
+    inline_css_imports: bool,
+    inline_wasm_limit: Option<u64>,
+    source_maps_type: SourceMapsType,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
     let asset_context =
         get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
-    let chunking_context =
-        get_client_chunking_context(project_path, server_root, compile_time_info.environment());
+    let chunking_context = get_client_chunking_context(
+        project_path,
+        server_root,
+        compile_time_info.environment(),
+        chunking_config,
+        inline_css_imports,
+        inline_wasm_limit,
+        source_maps_type,
+    );
     let entries = get_client_runtime_entries(project_path);
 
     let runtime_entries = entries.resolve_entries(asset_context);
@@ -5,6 +5,7 @@
 
 pub mod arguments;
 pub mod build;
+pub mod cache;
 pub(crate) mod contexts;
 pub mod dev;
 pub(crate) mod embed_js;
@@ -14,6 +15,7 @@ pub fn register() {
     turbopack::register();
     turbopack_nodejs::register();
     turbopack_browser::register();
+    turbopack_bundle_analyzer::register();
     turbopack_ecmascript_plugins::register();
     turbopack_resolve::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
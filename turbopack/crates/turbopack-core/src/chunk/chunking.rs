@@ -11,8 +11,8 @@ use tracing::Level;
 use turbo_tasks::{RcStr, ReadRef, TryJoinIterExt, ValueToString, Vc};
 
 use super::{
-    AsyncModuleInfo, Chunk, ChunkItem, ChunkItemsWithAsyncModuleInfo, ChunkType, ChunkingContext,
-    Chunks,
+    AsyncModuleInfo, ChunkItem, ChunkItemsWithAsyncModuleInfo, ChunkType, ChunkingConfig,
+    ChunkingContext, Chunks,
 };
 use crate::output::OutputAssets;
 
@@ -40,8 +40,9 @@ async fn chunk_item_info(
     .cell())
 }
 
-/// Creates chunks based on heuristics for the passed `chunk_items`. Also
-/// attaches `referenced_output_assets` to the first chunk.
+/// Creates chunks based on heuristics for the passed `chunk_items`, honoring
+/// the [ChunkingConfig] of the `chunking_context`. Also attaches
+/// `referenced_output_assets` to the first chunk.
 #[turbo_tasks::function]
 pub async fn make_chunks(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
@@ -65,7 +66,8 @@ pub async fn make_chunks(
             .push((chunk_item, async_info, chunk_item_info));
     }
 
-    let mut chunks = Vec::new();
+    let config = chunking_context.chunking_config().await?;
+    let mut groups = Vec::new();
     for (ty, chunk_items) in map {
         let ty_name = ty.to_string().await?;
 
@@ -82,31 +84,46 @@ pub async fn make_chunks(
             .try_join()
             .await?;
 
-        let mut split_context = SplitContext {
-            ty,
-            chunking_context,
-            chunks: &mut chunks,
-            referenced_output_assets: &mut referenced_output_assets,
-            empty_referenced_output_assets: OutputAssets::empty().resolve().await?,
-        };
+        let chunks = split_chunk_items(
+            chunk_items,
+            format!("{key_prefix}{ty_name}"),
+            *ty.must_keep_item_order().await?,
+            &config,
+        )
+        .await?;
+        groups.push((ty, chunks));
+    }
 
-        if !*ty.must_keep_item_order().await? {
-            app_vendors_split(
-                chunk_items,
-                format!("{key_prefix}{ty_name}"),
-                &mut split_context,
-            )
-            .await?;
-        } else {
-            make_chunk(
-                chunk_items,
-                &mut format!("{key_prefix}{ty_name}"),
-                &mut split_context,
-            )
-            .await?;
+    if let Some(max_chunk_count) = config.max_chunk_count_per_group {
+        let mut chunks_per_type = groups
+            .iter_mut()
+            .map(|(_, chunks)| take(chunks))
+            .collect::<Vec<_>>();
+        limit_chunk_count(&mut chunks_per_type, max_chunk_count, SplitItem::size);
+        for ((_, chunks), limited) in groups.iter_mut().zip(chunks_per_type) {
+            *chunks = limited;
         }
     }
 
+    let empty_referenced_output_assets = OutputAssets::empty().resolve().await?;
+    let chunks = groups
+        .into_iter()
+        .flat_map(|(ty, chunks)| chunks.into_iter().map(move |chunk_items| (ty, chunk_items)))
+        .map(|(ty, chunk_items)| {
+            ty.chunk(
+                chunking_context,
+                chunk_items
+                    .into_iter()
+                    .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
+                    .collect(),
+                replace(
+                    &mut referenced_output_assets,
+                    empty_referenced_output_assets,
+                ),
+            )
+        })
+        .collect();
+
     Ok(Vc::cell(chunks))
 }
 
@@ -117,73 +134,98 @@ type ChunkItemWithInfo = (
     ReadRef<RcStr>,
 );
 
-struct SplitContext<'a> {
-    ty: Vc<Box<dyn ChunkType>>,
-    chunking_context: Vc<Box<dyn ChunkingContext>>,
-    chunks: &'a mut Vec<Vc<Box<dyn Chunk>>>,
-    referenced_output_assets: &'a mut Vc<OutputAssets>,
-    empty_referenced_output_assets: Vc<OutputAssets>,
+/// The properties of a chunk item that decide which chunk it is placed in.
+trait SplitItem {
+    fn size(&self) -> usize;
+    fn name(&self) -> &str;
+}
+
+impl SplitItem for ChunkItemWithInfo {
+    fn size(&self) -> usize {
+        self.2
+    }
+
+    fn name(&self) -> &str {
+        &self.3
+    }
+}
+
+struct SplitContext<'a, T> {
+    config: &'a ChunkingConfig,
+    /// The chunk items of the chunks created so far. The chunks are only
+    /// created after [limit_chunk_count] was applied.
+    chunks: &'a mut Vec<Vec<T>>,
+}
+
+/// Splits the chunk items of a single chunk type into the chunk items of
+/// chunks. `key` is the prefix of the keys of the chunks.
+async fn split_chunk_items<T: SplitItem>(
+    chunk_items: Vec<T>,
+    mut key: String,
+    must_keep_item_order: bool,
+    config: &ChunkingConfig,
+) -> Result<Vec<Vec<T>>> {
+    let mut chunks = Vec::new();
+    let mut split_context = SplitContext {
+        config,
+        chunks: &mut chunks,
+    };
+    if !must_keep_item_order {
+        app_vendors_split(chunk_items, key, &mut split_context).await?;
+    } else {
+        make_chunk(chunk_items, &mut key, &mut split_context).await?;
+    }
+    Ok(chunks)
 }
 
 /// Handle chunk items based on their total size. If the total size is too
 /// small, they will be pushed into `remaining`, if possible. If the total size
 /// is too large, it will return `false` and the caller should hand of the chunk
 /// items to be further split. Otherwise it creates a chunk.
-async fn handle_split_group(
-    chunk_items: &mut Vec<ChunkItemWithInfo>,
+async fn handle_split_group<T: SplitItem>(
+    chunk_items: &mut Vec<T>,
     key: &mut String,
-    split_context: &mut SplitContext<'_>,
-    remaining: Option<&mut Vec<ChunkItemWithInfo>>,
+    split_context: &mut SplitContext<'_, T>,
+    remaining: Option<&mut Vec<T>>,
 ) -> Result<bool> {
-    Ok(match (chunk_size(chunk_items), remaining) {
-        (ChunkSize::Large, _) => false,
-        (ChunkSize::Perfect, _) | (ChunkSize::Small, None) => {
-            make_chunk(take(chunk_items), key, split_context).await?;
-            true
-        }
-        (ChunkSize::Small, Some(remaining)) => {
-            remaining.extend(take(chunk_items));
-            true
-        }
-    })
+    Ok(
+        match (chunk_size(chunk_items, split_context.config), remaining) {
+            (ChunkSize::Large, _) => false,
+            (ChunkSize::Perfect, _) | (ChunkSize::Small, None) => {
+                make_chunk(take(chunk_items), key, split_context).await?;
+                true
+            }
+            (ChunkSize::Small, Some(remaining)) => {
+                remaining.extend(take(chunk_items));
+                true
+            }
+        },
+    )
 }
 
 /// Creates a chunk with the given `chunk_items. `key` should be unique.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(key = display(key)))]
-async fn make_chunk(
-    chunk_items: Vec<ChunkItemWithInfo>,
+async fn make_chunk<T>(
+    chunk_items: Vec<T>,
     key: &mut String,
-    split_context: &mut SplitContext<'_>,
+    split_context: &mut SplitContext<'_, T>,
 ) -> Result<()> {
-    split_context.chunks.push(
-        split_context.ty.chunk(
-            split_context.chunking_context,
-            chunk_items
-                .into_iter()
-                .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
-                .collect(),
-            replace(
-                split_context.referenced_output_assets,
-                split_context.empty_referenced_output_assets,
-            ),
-        ),
-    );
+    split_context.chunks.push(chunk_items);
     Ok(())
 }
 
 /// Split chunk items into app code and vendor code. Continues splitting with
-/// [package_name_split] if necessary.
+/// [package_name_split] if necessary, or always when splitting by package.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(name = display(&name)))]
-async fn app_vendors_split(
-    chunk_items: Vec<ChunkItemWithInfo>,
+async fn app_vendors_split<T: SplitItem>(
+    chunk_items: Vec<T>,
     mut name: String,
-    split_context: &mut SplitContext<'_>,
+    split_context: &mut SplitContext<'_, T>,
 ) -> Result<()> {
     let mut app_chunk_items = Vec::new();
     let mut vendors_chunk_items = Vec::new();
     for item in chunk_items {
-        let (_, _, _, asset_ident) = &item;
-        if is_app_code(asset_ident) {
+        if is_app_code(item.name()) {
             app_chunk_items.push(item);
         } else {
             vendors_chunk_items.push(item);
@@ -202,7 +244,11 @@ async fn app_vendors_split(
         folder_split(app_chunk_items, 0, key.into(), split_context).await?;
     }
     let mut key = format!("{}-vendors", name);
-    if !handle_split_group(
+    if split_context.config.split_by_package {
+        if !vendors_chunk_items.is_empty() {
+            package_name_split(vendors_chunk_items, key, split_context).await?;
+        }
+    } else if !handle_split_group(
         &mut vendors_chunk_items,
         &mut key,
         split_context,
@@ -221,17 +267,17 @@ async fn app_vendors_split(
 }
 
 /// Split chunk items by node_modules package name. Continues splitting with
-/// [folder_split] if necessary.
+/// [folder_split] if necessary. Small packages are merged, unless splitting by
+/// package.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(name = display(&name)))]
-async fn package_name_split(
-    chunk_items: Vec<ChunkItemWithInfo>,
+async fn package_name_split<T: SplitItem>(
+    chunk_items: Vec<T>,
     mut name: String,
-    split_context: &mut SplitContext<'_>,
+    split_context: &mut SplitContext<'_, T>,
 ) -> Result<()> {
-    let mut map = IndexMap::<_, Vec<ChunkItemWithInfo>>::new();
+    let mut map = IndexMap::<_, Vec<T>>::new();
     for item in chunk_items {
-        let (_, _, _, asset_ident) = &item;
-        let package_name = package_name(asset_ident);
+        let package_name = package_name(item.name());
         if let Some(list) = map.get_mut(package_name) {
             list.push(item);
         } else {
@@ -239,9 +285,11 @@ async fn package_name_split(
         }
     }
     let mut remaining = Vec::new();
+    let split_by_package = split_context.config.split_by_package;
     for (package_name, mut list) in map {
         let mut key = format!("{}-{}", name, package_name);
-        if !handle_split_group(&mut list, &mut key, split_context, Some(&mut remaining)).await? {
+        let remaining = (!split_by_package).then_some(&mut remaining);
+        if !handle_split_group(&mut list, &mut key, split_context, remaining).await? {
             folder_split(list, 0, key.into(), split_context).await?;
         }
     }
@@ -255,17 +303,16 @@ async fn package_name_split(
 
 /// Split chunk items by folder structure.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(name = display(&name), location))]
-async fn folder_split(
-    mut chunk_items: Vec<ChunkItemWithInfo>,
+async fn folder_split<T: SplitItem>(
+    mut chunk_items: Vec<T>,
     mut location: usize,
     name: Cow<'_, str>,
-    split_context: &mut SplitContext<'_>,
+    split_context: &mut SplitContext<'_, T>,
 ) -> Result<()> {
-    let mut map = IndexMap::<_, (_, Vec<ChunkItemWithInfo>)>::new();
+    let mut map = IndexMap::<_, (_, Vec<T>)>::new();
     loop {
         for item in chunk_items {
-            let (_, _, _, asset_ident) = &item;
-            let (folder_name, new_location) = folder_name(asset_ident, location);
+            let (folder_name, new_location) = folder_name(item.name(), location);
             if let Some((_, list)) = map.get_mut(folder_name) {
                 list.push(item);
             } else {
@@ -307,8 +354,7 @@ async fn folder_split(
         }
     }
     if !remaining.is_empty() {
-        let (_, _, _, asset_ident) = &remaining[0];
-        let mut key = format!("{}-{}", name, &asset_ident[..location]);
+        let mut key = format!("{}-{}", name, &remaining[0].name()[..location]);
         if !handle_split_group(&mut remaining, &mut key, split_context, None).await? {
             make_chunk(remaining, &mut key, split_context).await?;
         }
@@ -343,9 +389,6 @@ fn folder_name(ident: &str, location: usize) -> (&str, Option<usize>) {
     }
 }
 
-const LARGE_CHUNK: usize = 1_000_000;
-const SMALL_CHUNK: usize = 100_000;
-
 enum ChunkSize {
     Large,
     Perfect,
@@ -354,16 +397,193 @@ enum ChunkSize {
 
 /// Determines the total size of the passed chunk items. Returns too small, too
 /// large or perfect fit.
-fn chunk_size(chunk_items: &[ChunkItemWithInfo]) -> ChunkSize {
+fn chunk_size(chunk_items: &[impl SplitItem], config: &ChunkingConfig) -> ChunkSize {
     let mut total_size = 0;
-    for (_, _, size, _) in chunk_items {
-        total_size += size;
+    for item in chunk_items {
+        total_size += item.size();
     }
-    if total_size >= LARGE_CHUNK {
+    if total_size >= config.max_chunk_size {
         ChunkSize::Large
-    } else if total_size > SMALL_CHUNK {
+    } else if total_size > config.min_chunk_size {
         ChunkSize::Perfect
     } else {
         ChunkSize::Small
     }
 }
+
+/// Merges the smallest chunks with their smaller neighbor until there are at
+/// most `max_chunk_count` chunks. `chunks` contains the chunks per chunk type,
+/// chunks of different types are never merged.
+fn limit_chunk_count<T>(
+    chunks: &mut [Vec<Vec<T>>],
+    max_chunk_count: usize,
+    item_size: impl Fn(&T) -> usize,
+) {
+    let size = |chunk: &Vec<T>| chunk.iter().map(&item_size).sum::<usize>();
+    let mut count = chunks.iter().map(|chunks| chunks.len()).sum::<usize>();
+    while count > max_chunk_count {
+        let Some((ty, index)) = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunks)| chunks.len() > 1)
+            .flat_map(|(ty, chunks)| {
+                chunks
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, chunk)| (ty, index, size(chunk)))
+            })
+            .min_by_key(|&(_, _, size)| size)
+            .map(|(ty, index, _)| (ty, index))
+        else {
+            // Every chunk type has a single chunk left
+            return;
+        };
+        let chunks = &mut chunks[ty];
+        let merge_with_previous = index + 1 == chunks.len()
+            || (index > 0 && size(&chunks[index - 1]) <= size(&chunks[index + 1]));
+        let chunk = chunks.remove(index);
+        if merge_with_previous {
+            chunks[index - 1].extend(chunk);
+        } else {
+            let next = replace(&mut chunks[index], chunk);
+            chunks[index].extend(next);
+        }
+        count -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{limit_chunk_count, split_chunk_items, SplitItem};
+    use crate::chunk::ChunkingConfig;
+
+    impl SplitItem for (usize, &str) {
+        fn size(&self) -> usize {
+            self.0
+        }
+
+        fn name(&self) -> &str {
+            self.1
+        }
+    }
+
+    async fn split(
+        chunk_items: &[(usize, &'static str)],
+        must_keep_item_order: bool,
+        config: ChunkingConfig,
+    ) -> Vec<Vec<&'static str>> {
+        split_chunk_items(
+            chunk_items.to_vec(),
+            "test".to_string(),
+            must_keep_item_order,
+            &config,
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|chunk| chunk.into_iter().map(|(_, name)| name).collect())
+        .collect()
+    }
+
+    fn config(min_chunk_size: usize, max_chunk_size: usize) -> ChunkingConfig {
+        ChunkingConfig {
+            min_chunk_size,
+            max_chunk_size,
+            ..Default::default()
+        }
+    }
+
+    const APP_AND_PACKAGES: &[(usize, &str)] = &[
+        (10, "[project]/src/index.js"),
+        (10, "[project]/node_modules/react/index.js"),
+        (10, "[project]/node_modules/react-dom/index.js"),
+        (10, "[project]/node_modules/@scope/pkg/index.js"),
+    ];
+
+    #[tokio::test]
+    async fn merges_small_chunks() {
+        assert_eq!(
+            split(APP_AND_PACKAGES, false, config(100, 1000)).await,
+            vec![vec![
+                "[project]/src/index.js",
+                "[project]/node_modules/react/index.js",
+                "[project]/node_modules/react-dom/index.js",
+                "[project]/node_modules/@scope/pkg/index.js",
+            ]]
+        );
+    }
+
+    #[tokio::test]
+    async fn splits_by_package() {
+        assert_eq!(
+            split(
+                APP_AND_PACKAGES,
+                false,
+                ChunkingConfig {
+                    split_by_package: true,
+                    ..config(100, 1000)
+                }
+            )
+            .await,
+            vec![
+                vec!["[project]/node_modules/react/index.js"],
+                vec!["[project]/node_modules/react-dom/index.js"],
+                vec!["[project]/node_modules/@scope/pkg/index.js"],
+                vec!["[project]/src/index.js"],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn splits_large_chunks_by_folder() {
+        let chunk_items = [
+            (60, "[project]/src/a/index.js"),
+            (60, "[project]/src/b/index.js"),
+        ];
+        assert_eq!(
+            split(&chunk_items, false, config(10, 100)).await,
+            vec![
+                vec!["[project]/src/a/index.js"],
+                vec!["[project]/src/b/index.js"],
+            ]
+        );
+        // Chunk types that must keep the order of their items aren't split
+        assert_eq!(
+            split(&chunk_items, true, config(10, 100)).await,
+            vec![vec!["[project]/src/a/index.js", "[project]/src/b/index.js"]]
+        );
+    }
+
+    #[test]
+    fn limit_chunk_count_merges_smallest() {
+        let mut chunks = vec![
+            vec![vec![50], vec![1], vec![20], vec![30, 30]],
+            vec![vec![5]],
+        ];
+        limit_chunk_count(&mut chunks, 4, |size| *size);
+        assert_eq!(
+            chunks,
+            vec![vec![vec![50], vec![1, 20], vec![30, 30]], vec![vec![5]]]
+        );
+
+        limit_chunk_count(&mut chunks, 3, |size| *size);
+        assert_eq!(
+            chunks,
+            vec![vec![vec![50, 1, 20], vec![30, 30]], vec![vec![5]]]
+        );
+    }
+
+    #[test]
+    fn limit_chunk_count_keeps_one_chunk_per_type() {
+        let mut chunks = vec![
+            vec![vec![1], vec![2]],
+            vec![vec![3]],
+            vec![vec![4], vec![5]],
+        ];
+        limit_chunk_count(&mut chunks, 1, |size| *size);
+        assert_eq!(
+            chunks,
+            vec![vec![vec![1, 2]], vec![vec![3]], vec![vec![4, 5]]]
+        );
+    }
+}
@@ -35,6 +35,86 @@ pub enum MinifyType {
     NoMinify,
 }
 
+/// How source maps of chunks are emitted and referenced, if a chunking context
+/// references source maps for a chunk at all.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, TaskInput, Clone, Copy, PartialOrd, Ord, Hash, DeterministicHash)]
+pub enum SourceMapsType {
+    /// A `.map` file next to the chunk, referenced by a `sourceMappingURL`
+    /// comment.
+    #[default]
+    Full,
+    /// A `.map` file without a `sourceMappingURL` comment, e. g. to upload it
+    /// to an error reporting service without exposing it to browsers.
+    Hidden,
+    /// Like [SourceMapsType::Full], but without `sourcesContent`. Stack traces
+    /// can be mapped, but the original code is not exposed.
+    NoSources,
+    /// The source map is embedded into the `sourceMappingURL` comment as a
+    /// data URL, no `.map` file is emitted.
+    Inline,
+}
+
+impl SourceMapsType {
+    /// Whether a `.map` file is emitted next to the chunk.
+    pub fn has_source_map_file(&self) -> bool {
+        !matches!(self, SourceMapsType::Inline)
+    }
+
+    /// Whether the chunk contains a `sourceMappingURL` comment.
+    pub fn has_source_mapping_url(&self) -> bool {
+        !matches!(self, SourceMapsType::Hidden)
+    }
+
+    /// Whether the source map contains the original code.
+    pub fn has_sources_content(&self) -> bool {
+        !matches!(self, SourceMapsType::NoSources)
+    }
+}
+
+/// Size targets and limits for splitting the chunk items of a chunk group
+/// into chunks. See [make_chunks](super::chunking::make_chunks).
+///
+/// Sizes are measured with [ChunkType::chunk_item_size](super::ChunkType),
+/// which roughly corresponds to the bytes of unminified code.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ChunkingConfig {
+    /// Chunks smaller than this are merged with other small chunks of the same
+    /// chunk group when possible.
+    pub min_chunk_size: usize,
+    /// Chunks larger than this are split further, as long as their chunk
+    /// items can be divided by package or folder.
+    pub max_chunk_size: usize,
+    /// The maximum number of chunks a chunk group is split into, i. e. the
+    /// number of requests needed to load it. The smallest chunks are merged
+    /// until the group fits, even if that exceeds `max_chunk_size`. Each chunk
+    /// type needs at least one chunk.
+    pub max_chunk_count_per_group: Option<usize>,
+    /// Puts every node_modules package into its own chunks instead of merging
+    /// small packages, so a package update only invalidates its own chunks.
+    pub split_by_package: bool,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            min_chunk_size: 100_000,
+            max_chunk_size: 1_000_000,
+            max_chunk_count_per_group: None,
+            split_by_package: false,
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkingConfig {
+    #[turbo_tasks::function]
+    pub fn default_config() -> Vc<Self> {
+        Self::default().cell()
+    }
+}
+
 #[turbo_tasks::value(shared)]
 pub struct ChunkGroupResult {
     pub assets: Vc<OutputAssets>,
@@ -68,6 +148,28 @@ pub trait ChunkingContext {
     /// Reference Source Map Assets for chunks
     fn reference_chunk_source_maps(self: Vc<Self>, chunk: Vc<Box<dyn OutputAsset>>) -> Vc<bool>;
 
+    /// How source maps of chunks are emitted, when
+    /// [ChunkingContext::reference_chunk_source_maps] is enabled for a chunk.
+    fn source_maps_type(self: Vc<Self>) -> Vc<SourceMapsType> {
+        SourceMapsType::Full.cell()
+    }
+
+    /// Whether `@import`ed stylesheets are inlined into the chunk item of the
+    /// importing stylesheet, wrapped in the `@layer`, `@media` and `@supports`
+    /// conditions of the import, instead of becoming separate chunk items.
+    /// Like bundling, this keeps every stylesheet directly after its imports,
+    /// even when a chunk group is split into multiple CSS chunks.
+    fn inline_css_imports(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// WebAssembly modules smaller than this many bytes are embedded into the
+    /// chunk as base64 and instantiated from the bytes, instead of being
+    /// emitted as a separate `.wasm` file that is loaded with another request.
+    fn inline_wasm_limit(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
     /// Returns a URL (relative or absolute, depending on the asset prefix) to
     /// the static asset based on its `ident`.
     fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<RcStr>>;
@@ -82,6 +184,11 @@ pub trait ChunkingContext {
         Vc::cell(false)
     }
 
+    /// The size targets and limits used to split chunk groups into chunks.
+    fn chunking_config(self: Vc<Self>) -> Vc<ChunkingConfig> {
+        ChunkingConfig::default_config()
+    }
+
     fn async_loader_chunk_item(
         &self,
         module: Vc<Box<dyn ChunkableModule>>,
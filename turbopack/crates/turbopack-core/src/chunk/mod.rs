@@ -33,7 +33,8 @@ use turbo_tasks_hash::DeterministicHash;
 use self::{availability_info::AvailabilityInfo, available_chunk_items::AvailableChunkItems};
 pub use self::{
     chunking_context::{
-        ChunkGroupResult, ChunkingContext, ChunkingContextExt, EntryChunkGroupResult, MinifyType,
+        ChunkGroupResult, ChunkingConfig, ChunkingContext, ChunkingContextExt,
+        EntryChunkGroupResult, MinifyType, SourceMapsType,
     },
     data::{ChunkData, ChunkDataOption, ChunksData},
     evaluate::{EvaluatableAsset, EvaluatableAssetExt, EvaluatableAssets},
@@ -150,6 +151,14 @@ pub trait OutputChunk: Asset {
     fn runtime_info(self: Vc<Self>) -> Vc<OutputChunkRuntimeInfo>;
 }
 
+/// An [OutputAsset] that is the emitted form of a [Chunk]. Allows to map
+/// output files back to the chunk items they contain, e. g. for bundle
+/// analysis.
+#[turbo_tasks::value_trait]
+pub trait ChunkOutputAsset: OutputAsset {
+    fn chunk(self: Vc<Self>) -> Vc<Box<dyn Chunk>>;
+}
+
 /// Specifies how a chunk interacts with other chunks when building a chunk
 /// group
 #[derive(
@@ -1,10 +1,18 @@
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+
 use anyhow::Result;
-use indexmap::IndexMap;
-use turbo_tasks::{RcStr, ValueToString, Vc};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, RcStr, State, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPath};
 use turbo_tasks_hash::hash_xxh3_hash64;
 
 use super::ModuleId;
-use crate::ident::AssetIdent;
+use crate::{
+    asset::AssetContent,
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+};
 
 #[turbo_tasks::value_trait]
 pub trait ModuleIdStrategy {
@@ -28,14 +36,49 @@ impl ModuleIdStrategy for DevModuleIdStrategy {
     }
 }
 
-#[turbo_tasks::value]
+#[turbo_tasks::value(eq = "manual")]
 pub struct GlobalModuleIdStrategy {
     module_id_map: IndexMap<RcStr, ModuleId>,
+    /// The ids of `module_id_map` as strings, as that's how they are compared at
+    /// runtime. Used to detect collisions of the hash fallback.
+    used_ids: HashSet<RcStr>,
+    /// The ids assigned by the hash fallback, with the ident of their module.
+    /// Used to detect collisions between two modules using the fallback.
+    fallback_ids: State<HashMap<RcStr, RcStr>>,
+}
+
+// The fallback ids are not compared, so they are kept when the strategy is
+// recomputed with the same module ids
+impl PartialEq for GlobalModuleIdStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        self.module_id_map == other.module_id_map && self.used_ids == other.used_ids
+    }
 }
 
+impl Eq for GlobalModuleIdStrategy {}
+
 impl GlobalModuleIdStrategy {
     pub async fn new(module_id_map: IndexMap<RcStr, ModuleId>) -> Result<Vc<Self>> {
-        Ok(GlobalModuleIdStrategy { module_id_map }.cell())
+        Self::new_with_reserved_ids(module_id_map, []).await
+    }
+
+    /// Like [GlobalModuleIdStrategy::new], but the hash fallback also avoids
+    /// `reserved_ids`, e.g. the recorded ids of removed modules.
+    pub async fn new_with_reserved_ids(
+        module_id_map: IndexMap<RcStr, ModuleId>,
+        reserved_ids: impl IntoIterator<Item = u64>,
+    ) -> Result<Vc<Self>> {
+        let used_ids = module_id_map
+            .values()
+            .map(|module_id| module_id.to_string().into())
+            .chain(reserved_ids.into_iter().map(|id| id.to_string().into()))
+            .collect();
+        Ok(GlobalModuleIdStrategy {
+            module_id_map,
+            used_ids,
+            fallback_ids: State::new(HashMap::new()),
+        }
+        .cell())
     }
 }
 
@@ -47,11 +90,503 @@ impl ModuleIdStrategy for GlobalModuleIdStrategy {
         if let Some(module_id) = self.module_id_map.get(&ident_string) {
             return Ok(module_id.clone().cell());
         }
-        Ok(ModuleId::String(
-            hash_xxh3_hash64(ident.to_string().await?)
-                .to_string()
-                .into(),
-        )
-        .cell())
+
+        // The module wasn't part of the module graph the map was created from, so it
+        // gets an id from its full hash. That must not be an id that is already
+        // assigned to another module.
+        let full_hash = hash_xxh3_hash64(&ident_string);
+        let module_id = unused_module_id(full_hash, &self.used_ids);
+        if *module_id != *full_hash.to_string() {
+            let colliding_ident = self
+                .module_id_map
+                .iter()
+                .find(|(_, id)| id.to_string() == full_hash.to_string())
+                .map(|(ident, _)| ident.clone())
+                .unwrap_or_default();
+            ModuleIdCollisionIssue {
+                path: ident.path(),
+                ident: ident_string.clone(),
+                colliding_ident,
+                module_id: full_hash.to_string().into(),
+                reassigned: true,
+            }
+            .cell()
+            .emit();
+        }
+
+        // Other modules using the fallback are not known upfront, so a collision
+        // with one of them can only be reported
+        let mut colliding_ident = None;
+        self.fallback_ids.update_conditionally(|fallback_ids| {
+            colliding_ident = record_fallback_id(fallback_ids, &module_id, &ident_string);
+            colliding_ident.is_none()
+        });
+        if let Some(colliding_ident) = colliding_ident {
+            ModuleIdCollisionIssue {
+                path: ident.path(),
+                ident: ident_string,
+                colliding_ident,
+                module_id: module_id.clone(),
+                reassigned: false,
+            }
+            .cell()
+            .emit();
+        }
+        Ok(ModuleId::String(module_id).cell())
+    }
+}
+
+/// Returns `full_hash` as id, or another id derived from it if that is already
+/// used.
+fn unused_module_id(full_hash: u64, used_ids: &HashSet<RcStr>) -> RcStr {
+    let mut module_id: RcStr = full_hash.to_string().into();
+    let mut i = 1u64;
+    while used_ids.contains(&module_id) {
+        module_id = hash_xxh3_hash64(full_hash.wrapping_add(i))
+            .to_string()
+            .into();
+        i += 1;
+    }
+    module_id
+}
+
+/// Records that `ident` uses the fallback id `module_id`. Returns the ident of
+/// another module that already uses it.
+fn record_fallback_id(
+    fallback_ids: &mut HashMap<RcStr, RcStr>,
+    module_id: &RcStr,
+    ident: &RcStr,
+) -> Option<RcStr> {
+    match fallback_ids.entry(module_id.clone()) {
+        Entry::Vacant(entry) => {
+            entry.insert(ident.clone());
+            None
+        }
+        Entry::Occupied(entry) => (entry.get() != ident).then(|| entry.get().clone()),
+    }
+}
+
+/// The number of builds a removed module keeps its recorded id for. Long-term
+/// cached chunks that refer to the module are assumed to be gone afterwards.
+pub const REMOVED_MODULE_ID_BUILDS: u32 = 10;
+
+/// The recorded id of a module that is not part of the build anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct RemovedModuleId {
+    pub id: u64,
+    /// The number of builds the module hasn't been part of.
+    pub builds: u32,
+}
+
+/// Module ids of a previous build, persisted to keep them stable across builds,
+/// similar to webpack's `recordsPath`.
+#[turbo_tasks::value]
+#[derive(Debug, Default)]
+pub struct ModuleIdRecords {
+    /// ident.to_string() -> module id
+    pub modules: IndexMap<RcStr, u64>,
+    /// Module ids of modules that are not part of the build anymore. They are
+    /// kept for [REMOVED_MODULE_ID_BUILDS] builds so the ids are not given to
+    /// other modules, which would make long-term cached chunks refer to the
+    /// wrong module.
+    pub removed: IndexMap<RcStr, RemovedModuleId>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ModuleIdRecordsFile<M, R> {
+    modules: M,
+    #[serde(default)]
+    removed: R,
+}
+
+/// Two modules that would have the same recorded module id.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ModuleIdRecordsCollision {
+    pub ident: RcStr,
+    pub colliding_ident: RcStr,
+    pub module_id: u64,
+}
+
+/// The recorded ids of the modules of a build, see [ModuleIdRecords::reserve].
+#[derive(Debug, Default)]
+pub struct ReservedModuleIds {
+    /// Modules that keep their recorded id.
+    pub modules: IndexMap<RcStr, u64>,
+    /// All ids that must not be assigned to new modules, including the ids of
+    /// removed modules.
+    pub used_ids: IndexSet<u64>,
+    /// Modules that lost their recorded id because another module has it.
+    pub collisions: Vec<ModuleIdRecordsCollision>,
+}
+
+impl ModuleIdRecords {
+    /// Parses the contents of a records file.
+    pub fn from_json(json: serde_json::Value) -> Result<Self> {
+        let file = serde_json::from_value::<
+            ModuleIdRecordsFile<IndexMap<RcStr, u64>, IndexMap<RcStr, RemovedModuleId>>,
+        >(json)?;
+        Ok(ModuleIdRecords {
+            modules: file.modules,
+            removed: file.removed,
+        })
+    }
+
+    /// Serializes the records. The modules are sorted by ident to keep the
+    /// file diffable.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&ModuleIdRecordsFile {
+            modules: self.modules.iter().collect::<BTreeMap<_, _>>(),
+            removed: self.removed.iter().collect::<BTreeMap<_, _>>(),
+        })?)
+    }
+
+    /// Looks up the recorded ids of `idents`, the modules of the current
+    /// build. Modules that were removed and came back get their old id again.
+    /// When the records contain the same id for two modules, only the first
+    /// module keeps it and the other one is reported.
+    pub fn reserve<'a>(&self, idents: impl IntoIterator<Item = &'a RcStr>) -> ReservedModuleIds {
+        let mut reserved = ReservedModuleIds::default();
+        let mut owners = HashMap::new();
+        for ident in idents {
+            let Some(module_id) = self
+                .modules
+                .get(ident)
+                .copied()
+                .or_else(|| self.removed.get(ident).map(|removed| removed.id))
+            else {
+                continue;
+            };
+            match owners.entry(module_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(ident.clone());
+                    reserved.used_ids.insert(module_id);
+                    reserved.modules.insert(ident.clone(), module_id);
+                }
+                Entry::Occupied(entry) => {
+                    reserved.collisions.push(ModuleIdRecordsCollision {
+                        ident: ident.clone(),
+                        colliding_ident: entry.get().clone(),
+                        module_id,
+                    });
+                }
+            }
+        }
+        reserved.used_ids.extend(
+            self.modules
+                .values()
+                .copied()
+                .chain(self.removed.values().map(|removed| removed.id)),
+        );
+        reserved
+    }
+
+    /// Returns the records for the next build from the ids of the current
+    /// build. Recorded modules that are not part of the current build are
+    /// kept as removed, for at most [REMOVED_MODULE_ID_BUILDS] builds.
+    pub fn update(&self, module_id_map: &IndexMap<RcStr, ModuleId>) -> ModuleIdRecords {
+        let modules = module_id_map
+            .iter()
+            .filter_map(|(ident, module_id)| match module_id {
+                ModuleId::Number(id) => Some((ident.clone(), *id)),
+                ModuleId::String(_) => None,
+            })
+            .collect::<IndexMap<_, _>>();
+        let removed = self
+            .modules
+            .iter()
+            .map(|(ident, &id)| (ident, RemovedModuleId { id, builds: 0 }))
+            .chain(
+                self.removed
+                    .iter()
+                    .map(|(ident, &removed)| (ident, removed)),
+            )
+            .filter(|(ident, _)| !module_id_map.contains_key(*ident))
+            .filter_map(|(ident, removed)| {
+                let builds = removed.builds + 1;
+                (builds <= REMOVED_MODULE_ID_BUILDS)
+                    .then(|| (ident.clone(), RemovedModuleId { builds, ..removed }))
+            })
+            .collect();
+        ModuleIdRecords { modules, removed }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleIdRecords {
+    /// Reads the records written by the emit step. A missing file results in
+    /// empty records, so the first build starts from scratch.
+    #[turbo_tasks::function]
+    pub async fn read(path: Vc<FileSystemPath>) -> Result<Vc<Self>> {
+        let error_message = match &*path.read_json().await? {
+            FileJsonContent::NotFound => return Ok(ModuleIdRecords::default().cell()),
+            FileJsonContent::Content(json) => match ModuleIdRecords::from_json(json.clone()) {
+                Ok(records) => return Ok(records.cell()),
+                Err(err) => err.to_string(),
+            },
+            FileJsonContent::Unparseable(err) => err.to_string(),
+        };
+        ModuleIdRecordsIssue {
+            path,
+            error_message: error_message.into(),
+        }
+        .cell()
+        .emit();
+        Ok(ModuleIdRecords::default().cell())
+    }
+
+    /// The contents of the records file, to be emitted as an output asset.
+    #[turbo_tasks::function]
+    pub fn content(&self) -> Result<Vc<AssetContent>> {
+        Ok(AssetContent::file(
+            FileContent::Content(File::from(self.to_json()?)).cell(),
+        ))
+    }
+}
+
+/// An issue with reading the [ModuleIdRecords].
+#[turbo_tasks::value(shared)]
+pub struct ModuleIdRecordsIssue {
+    pub path: Vc<FileSystemPath>,
+    pub error_message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ModuleIdRecordsIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Invalid module id records, all module ids are reassigned".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Config.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.error_message.clone()).cell()))
+    }
+}
+
+/// Two modules would have got the same module id.
+#[turbo_tasks::value(shared)]
+pub struct ModuleIdCollisionIssue {
+    pub path: Vc<FileSystemPath>,
+    pub ident: RcStr,
+    pub colliding_ident: RcStr,
+    pub module_id: RcStr,
+    /// A different id was assigned to `ident`. Otherwise both modules use the
+    /// same id.
+    pub reassigned: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ModuleIdCollisionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        if self.reassigned {
+            IssueSeverity::Warning.cell()
+        } else {
+            IssueSeverity::Error.cell()
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Module id ".into()),
+            StyledString::Code(self.module_id.clone()),
+            StyledString::Text(" is already used by another module".into()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::CodeGen.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Stack(vec![
+                StyledString::Line(vec![
+                    StyledString::Code(self.ident.clone()),
+                    StyledString::Text(" and ".into()),
+                    StyledString::Code(self.colliding_ident.clone()),
+                    StyledString::Text(" have the same module id.".into()),
+                ]),
+                StyledString::Text(if self.reassigned {
+                    "A different id was assigned to the first module, which doesn't stay stable \
+                     across builds."
+                        .into()
+                } else {
+                    "Both modules got the id from the hash of their ident, so one of them is \
+                     loaded in place of the other."
+                        .into()
+                }),
+            ])
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use indexmap::IndexMap;
+    use turbo_tasks::RcStr;
+
+    use super::{
+        record_fallback_id, unused_module_id, ModuleIdRecords, ModuleIdRecordsCollision,
+        RemovedModuleId, REMOVED_MODULE_ID_BUILDS,
+    };
+    use crate::chunk::ModuleId;
+
+    fn module_id_map(ids: &[(&str, u64)]) -> IndexMap<RcStr, ModuleId> {
+        ids.iter()
+            .map(|&(ident, id)| (ident.into(), ModuleId::Number(id)))
+            .collect()
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let first = ModuleIdRecords::default().update(&module_id_map(&[("b", 2), ("a", 1)]));
+        let json = first.to_json().unwrap();
+        let read = ModuleIdRecords::from_json(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(
+            read.modules.into_iter().collect::<Vec<_>>(),
+            vec![("a".into(), 1), ("b".into(), 2)]
+        );
+        assert!(read.removed.is_empty());
+
+        // `a` is removed, its id stays reserved
+        let second = first.update(&module_id_map(&[("b", 2), ("c", 3)]));
+        assert_eq!(
+            second.removed.get("a"),
+            Some(&RemovedModuleId { id: 1, builds: 1 })
+        );
+        let reserved = second.reserve([&"b".into(), &"c".into(), &"d".into()]);
+        assert!(reserved.used_ids.contains(&1));
+        assert_eq!(reserved.modules.get("d"), None);
+
+        // `a` comes back and gets its old id again
+        let reserved = second.reserve([&"a".into()]);
+        assert_eq!(reserved.modules.get("a"), Some(&1));
+        let third = second.update(&module_id_map(&[("a", 1), ("b", 2), ("c", 3)]));
+        assert!(third.removed.is_empty());
+    }
+
+    #[test]
+    fn records_without_removed() {
+        let records =
+            ModuleIdRecords::from_json(serde_json::json!({ "modules": { "a": 1 } })).unwrap();
+        assert_eq!(records.modules.get("a"), Some(&1));
+        assert!(records.removed.is_empty());
+    }
+
+    #[test]
+    fn records_collision() {
+        let records = ModuleIdRecords::from_json(serde_json::json!({
+            "modules": { "a": 1, "b": 1 },
+            "removed": { "c": { "id": 2, "builds": 1 } },
+        }))
+        .unwrap();
+        let reserved = records.reserve([&"a".into(), &"b".into()]);
+        assert_eq!(reserved.modules.get("a"), Some(&1));
+        assert_eq!(reserved.modules.get("b"), None);
+        assert_eq!(
+            reserved.collisions,
+            vec![ModuleIdRecordsCollision {
+                ident: "b".into(),
+                colliding_ident: "a".into(),
+                module_id: 1,
+            }]
+        );
+
+        // a removed module that comes back can't take an id that's in use
+        let reserved = ModuleIdRecords::from_json(serde_json::json!({
+            "modules": { "a": 1 },
+            "removed": { "c": { "id": 1, "builds": 1 } },
+        }))
+        .unwrap()
+        .reserve([&"a".into(), &"c".into()]);
+        assert_eq!(reserved.collisions.len(), 1);
+        assert_eq!(reserved.collisions[0].ident, "c".into());
+    }
+
+    #[test]
+    fn records_prune_removed() {
+        let mut records = ModuleIdRecords::default().update(&module_id_map(&[("a", 1)]));
+        for _ in 0..REMOVED_MODULE_ID_BUILDS {
+            records = records.update(&module_id_map(&[("b", 2)]));
+            assert!(records.reserve([&"b".into()]).used_ids.contains(&1));
+        }
+        assert_eq!(
+            records.removed.get("a"),
+            Some(&RemovedModuleId {
+                id: 1,
+                builds: REMOVED_MODULE_ID_BUILDS
+            })
+        );
+
+        // The id of `a` can be given to another module now
+        records = records.update(&module_id_map(&[("b", 2)]));
+        assert!(records.removed.is_empty());
+        assert!(!records.reserve([&"b".into()]).used_ids.contains(&1));
+    }
+
+    #[test]
+    fn unused_module_id_avoids_used_ids() {
+        let full_hash = 1234u64;
+        let mut used_ids = HashSet::new();
+        assert_eq!(&*unused_module_id(full_hash, &used_ids), "1234");
+
+        used_ids.insert("1234".into());
+        let module_id = unused_module_id(full_hash, &used_ids);
+        assert!(!used_ids.contains(&module_id));
+
+        // Overflowing the hash while seeking wraps around
+        let used_ids = HashSet::from([u64::MAX.to_string().into()]);
+        let module_id = unused_module_id(u64::MAX, &used_ids);
+        assert!(!used_ids.contains(&module_id));
+    }
+
+    #[test]
+    fn fallback_id_collision() {
+        let mut fallback_ids = HashMap::new();
+        assert_eq!(
+            record_fallback_id(&mut fallback_ids, &"1".into(), &"a".into()),
+            None
+        );
+        // The same module can look up its id again
+        assert_eq!(
+            record_fallback_id(&mut fallback_ids, &"1".into(), &"a".into()),
+            None
+        );
+        assert_eq!(
+            record_fallback_id(&mut fallback_ids, &"2".into(), &"b".into()),
+            None
+        );
+        assert_eq!(
+            record_fallback_id(&mut fallback_ids, &"1".into(), &"c".into()),
+            Some("a".into())
+        );
     }
 }
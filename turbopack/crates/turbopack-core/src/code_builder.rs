@@ -5,11 +5,16 @@ use std::{
 };
 
 use anyhow::Result;
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
 use turbo_tasks::Vc;
-use turbo_tasks_fs::rope::{Rope, RopeBuilder};
+use turbo_tasks_fs::{
+    rope::{Rope, RopeBuilder},
+    FileSystemPath,
+};
 use turbo_tasks_hash::hash_xxh3_hash64;
 
 use crate::{
+    chunk::SourceMapsType,
     source_map::{GenerateSourceMap, OptionSourceMap, SourceMap, SourceMapSection},
     source_pos::SourcePos,
 };
@@ -196,4 +201,43 @@ impl Code {
         let hash = hash_xxh3_hash64(code.source_code());
         Vc::cell(hash)
     }
+
+    /// Appends the `sourceMappingURL` comment for this code emitted to `path`,
+    /// as configured by `source_maps_type`. Uses a CSS comment for `.css`
+    /// files.
+    #[turbo_tasks::function]
+    pub async fn with_source_mapping_url(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        source_maps_type: SourceMapsType,
+    ) -> Result<Vc<Code>> {
+        let code = self.await?;
+        if !code.has_source_map() || !source_maps_type.has_source_mapping_url() {
+            return Ok(self);
+        }
+
+        let path = path.await?;
+        let url = if source_maps_type.has_source_map_file() {
+            format!("{}.map", urlencoding::encode(path.file_name()))
+        } else {
+            let map = match *self.generate_source_map().await? {
+                Some(map) => map,
+                None => SourceMap::empty(),
+            };
+            let map = map.to_rope(source_maps_type).await?;
+            format!(
+                "data:application/json;charset=utf-8;base64,{}",
+                Base64Display::new(&map.to_bytes()?, &STANDARD)
+            )
+        };
+
+        let mut builder = CodeBuilder::default();
+        builder.push_code(&code);
+        if path.extension_ref() == Some("css") {
+            write!(builder, "\n/*# sourceMappingURL={url}*/")?;
+        } else {
+            write!(builder, "\n\n//# sourceMappingURL={url}")?;
+        }
+        Ok(builder.build().cell())
+    }
 }
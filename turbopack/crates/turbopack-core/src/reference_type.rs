@@ -38,6 +38,13 @@ pub enum CommonJsReferenceSubType {
 #[derive(Debug, Clone, Hash)]
 pub enum ImportWithType {
     Json,
+    /// A `type` import attribute that has no built-in module type. It can be
+    /// handled by module rules. `part` is the part of the module that is
+    /// imported, like in [EcmaScriptModulesReferenceSubType::ImportPart].
+    Other {
+        ty: RcStr,
+        part: Option<Vc<ModulePart>>,
+    },
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
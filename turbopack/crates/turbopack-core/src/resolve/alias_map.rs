@@ -270,6 +270,96 @@ impl<T> AliasMap<T> {
         }
     }
 
+    /// Looks up a request that may contain dynamic parts in the alias map.
+    ///
+    /// In contrast to [`AliasMap::lookup`], this also returns aliases that only
+    /// match some of the values the dynamic parts can take, e.g. both `./icons/*`
+    /// and `./*.svg` match `./${name}`. The dynamic parts of the request absorb
+    /// the constant parts of the alias they overlap with, so replaced templates
+    /// may over-approximate.
+    ///
+    /// Returns all matching aliases, each together with the part of the request
+    /// it matches, ordered by decreasing prefix length.
+    pub fn lookup_pattern<'a>(&'a self, request: &Pattern) -> Vec<(Pattern, AliasMatch<'a, T>)>
+    where
+        T: AliasTemplate,
+    {
+        if let Pattern::Alternatives(list) = request {
+            return list
+                .iter()
+                .flat_map(|alt| self.lookup_pattern(alt))
+                .collect();
+        }
+
+        let constant_prefix = request.constant_prefix();
+        let mut matches = Vec::new();
+        for (prefix, map) in self.map.iter() {
+            let prefix = String::from_utf8(prefix).expect("invalid UTF-8 key in AliasMap");
+            let stripped_prefix_len = if prefix.len() <= constant_prefix.len() {
+                if !constant_prefix.starts_with(&prefix) {
+                    continue;
+                }
+                prefix.len()
+            } else {
+                // The remainder of the prefix has to be absorbed by a dynamic part.
+                if !request.could_match(&prefix) {
+                    continue;
+                }
+                constant_prefix.len()
+            };
+
+            for (key, template) in map {
+                match key {
+                    AliasKey::Exact => {
+                        if request.is_match(&prefix) {
+                            matches.push((
+                                prefix.len(),
+                                Pattern::Constant(prefix.as_str().into()),
+                                AliasMatch::Exact(template.convert()),
+                            ));
+                        }
+                    }
+                    AliasKey::Wildcard { suffix } => {
+                        let mut capture = request.clone();
+                        capture.strip_prefix(stripped_prefix_len);
+                        let capture_suffix = capture.constant_suffix();
+                        let stripped_suffix_len = if capture_suffix.ends_with(&**suffix) {
+                            suffix.len()
+                        } else if !matches!(capture, Pattern::Constant(_))
+                            && suffix.ends_with(capture_suffix)
+                        {
+                            // The remainder of the suffix is absorbed by a dynamic part.
+                            capture_suffix.len()
+                        } else {
+                            continue;
+                        };
+                        capture.strip_suffix(stripped_suffix_len);
+
+                        let mut matched = Pattern::Concatenation(vec![
+                            Pattern::Constant(prefix.as_str().into()),
+                            capture.clone(),
+                            Pattern::Constant(suffix.clone()),
+                        ]);
+                        matched.normalize();
+                        matches.push((
+                            prefix.len(),
+                            matched,
+                            AliasMatch::Replaced(template.replace(&capture)),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Stable sort, so aliases with the same prefix keep the order of
+        // [PATTERN_KEY_COMPARE].
+        matches.sort_by(|(a, ..), (b, ..)| b.cmp(a));
+        matches
+            .into_iter()
+            .map(|(_, matched, alias_match)| (matched, alias_match))
+            .collect()
+    }
+
     /// Inserts a new alias into the map.
     ///
     /// If the map did not have this alias already, `None` is returned.
@@ -951,4 +1041,72 @@ mod test {
             ]))]
         );
     }
+
+    #[test]
+    fn test_lookup_pattern() {
+        let mut map = AliasMap::new();
+        map.insert(AliasPattern::parse("./*"), "./dist/*.js");
+        map.insert(AliasPattern::parse("./*.css"), "./styles/*.css");
+        map.insert(AliasPattern::parse("./icons/*"), "./dist/icons/*.svg");
+        map.insert(AliasPattern::parse("./package.json"), "./package.json");
+        map.insert(AliasPattern::parse("lib/*"), "./lib/*.js");
+
+        let request =
+            Pattern::Concatenation(vec![Pattern::Constant("./".into()), Pattern::Dynamic]);
+        assert_eq!(
+            map.lookup_pattern(&request),
+            vec![
+                (
+                    Pattern::Constant("./package.json".into()),
+                    super::AliasMatch::Exact(Pattern::Constant("./package.json".into())),
+                ),
+                (
+                    Pattern::Concatenation(vec![
+                        Pattern::Constant("./icons/".into()),
+                        Pattern::Dynamic,
+                    ]),
+                    super::AliasMatch::Replaced(Pattern::Concatenation(vec![
+                        Pattern::Constant("./dist/icons/".into()),
+                        Pattern::Dynamic,
+                        Pattern::Constant(".svg".into()),
+                    ])),
+                ),
+                (
+                    Pattern::Concatenation(vec![
+                        Pattern::Constant("./".into()),
+                        Pattern::Dynamic,
+                        Pattern::Constant(".css".into()),
+                    ]),
+                    super::AliasMatch::Replaced(Pattern::Concatenation(vec![
+                        Pattern::Constant("./styles/".into()),
+                        Pattern::Dynamic,
+                        Pattern::Constant(".css".into()),
+                    ])),
+                ),
+                (
+                    request.clone(),
+                    super::AliasMatch::Replaced(Pattern::Concatenation(vec![
+                        Pattern::Constant("./dist/".into()),
+                        Pattern::Dynamic,
+                        Pattern::Constant(".js".into()),
+                    ])),
+                ),
+            ]
+        );
+
+        // Constant requests behave like `lookup`.
+        assert_eq!(
+            map.lookup_pattern(&Pattern::Constant("./icons/home".into())),
+            vec![
+                (
+                    Pattern::Constant("./icons/home".into()),
+                    super::AliasMatch::Replaced(Pattern::Constant("./dist/icons/home.svg".into())),
+                ),
+                (
+                    Pattern::Constant("./icons/home".into()),
+                    super::AliasMatch::Replaced(Pattern::Constant("./dist/icons/home.js".into())),
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,624 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use serde_yaml::Value as YamlValue;
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+
+use super::options::LockedVersions;
+use crate::issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString};
+
+/// Reads the locked versions of the root project's direct dependencies and of
+/// the dependencies of every locked package from a lockfile. Supported
+/// lockfiles are `pnpm-lock.yaml` and `package-lock.json` (and
+/// `npm-shrinkwrap.json`).
+///
+/// Emits a [LockfileIssue] and returns empty [LockedVersions] when the
+/// lockfile can't be read or parsed.
+#[turbo_tasks::function]
+pub async fn read_locked_versions(lockfile: Vc<FileSystemPath>) -> Result<Vc<LockedVersions>> {
+    let empty = || LockedVersions {
+        lockfile: Some(lockfile),
+        ..Default::default()
+    };
+
+    let content = lockfile.read().await?;
+    let FileContent::Content(content) = &*content else {
+        LockfileIssue {
+            path: lockfile,
+            error_message: "The lockfile does not exist".into(),
+        }
+        .cell()
+        .emit();
+        return Ok(empty().cell());
+    };
+    let text = content.content().to_str()?;
+
+    let file_name = lockfile.await?.file_name().to_string();
+    let locked = match file_name.as_str() {
+        "pnpm-lock.yaml" => parse_pnpm_lock(&text),
+        "package-lock.json" | "npm-shrinkwrap.json" => parse_package_lock(&text),
+        _ => Err(anyhow!(
+            "Unsupported lockfile, expected pnpm-lock.yaml or package-lock.json"
+        )),
+    };
+
+    match locked {
+        Ok(locked) => Ok(LockedVersions {
+            lockfile: Some(lockfile),
+            ..locked
+        }
+        .cell()),
+        Err(err) => {
+            LockfileIssue {
+                path: lockfile,
+                error_message: format!("{err:#}").into(),
+            }
+            .cell()
+            .emit();
+            Ok(empty().cell())
+        }
+    }
+}
+
+/// Extracts the locked versions from a `pnpm-lock.yaml`.
+///
+/// The root project's dependencies are read from the `importers` layout
+/// (workspaces and lockfile v9) or the single project layout, with versions
+/// written as a plain string (v5) or as `{ specifier, version }` (v6+). The
+/// dependencies of packages are read from `snapshots` (v9) or `packages` (v5
+/// and v6).
+fn parse_pnpm_lock(text: &str) -> Result<LockedVersions> {
+    let lock: YamlValue = serde_yaml::from_str(text).context("Invalid YAML")?;
+    let root = match lock.get("importers") {
+        Some(importers) => importers
+            .get(".")
+            .context("pnpm-lock.yaml has no root importer")?,
+        None => &lock,
+    };
+
+    let mut locked = LockedVersions::default();
+    read_pnpm_dependencies(
+        root,
+        &["dependencies", "devDependencies", "optionalDependencies"],
+        &mut locked.versions,
+    );
+
+    let packages = lock.get("snapshots").or_else(|| lock.get("packages"));
+    if let Some(YamlValue::Mapping(packages)) = packages {
+        for (key, entry) in packages {
+            let Some((name, version)) = key.as_str().and_then(split_pnpm_package_key) else {
+                continue;
+            };
+            // Packages installed with different peer dependencies share the
+            // same directory in the store, the first entry wins.
+            let dependencies = locked
+                .dependencies
+                .entry(format!("{name}@{version}").into())
+                .or_default();
+            read_pnpm_dependencies(
+                entry,
+                &["dependencies", "optionalDependencies"],
+                dependencies,
+            );
+        }
+    }
+
+    Ok(locked)
+}
+
+/// Reads the dependency `fields` of a pnpm importer or package entry into
+/// `versions`, skipping dependencies that don't point into the registry.
+fn read_pnpm_dependencies(
+    entry: &YamlValue,
+    fields: &[&str],
+    versions: &mut BTreeMap<RcStr, RcStr>,
+) {
+    for field in fields {
+        let Some(YamlValue::Mapping(dependencies)) = entry.get(field) else {
+            continue;
+        };
+        for (name, entry) in dependencies {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+            let version = match entry {
+                YamlValue::String(version) => version.as_str(),
+                YamlValue::Mapping(_) => match entry.get("version").and_then(|v| v.as_str()) {
+                    Some(version) => version,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if let Some(version) = normalize_pnpm_version(version) {
+                versions
+                    .entry(name.into())
+                    .or_insert_with(|| version.into());
+            }
+        }
+    }
+}
+
+/// Splits a pnpm package key into the package name and its version. Keys are
+/// written as `name@1.0.0(peer@1.0.0)` (v9), `/name@1.0.0(peer@1.0.0)` (v6) or
+/// `/name/1.0.0_peer@1.0.0` (v5).
+fn split_pnpm_package_key(key: &str) -> Option<(&str, &str)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let name_start = if key.starts_with('@') {
+        key.find('/')? + 1
+    } else {
+        0
+    };
+    let name_end = name_start + key[name_start..].find(['/', '@'])?;
+    let name = &key[..name_end];
+    if name.contains(':') {
+        return None;
+    }
+    let version = normalize_pnpm_version(&key[name_end + 1..])?;
+    Some((name, version))
+}
+
+/// Strips the peer dependency suffix from a pnpm version (`1.0.0(react@18.2.0)`
+/// in v6+, `1.0.0_react@18.2.0` in v5). Returns `None` for versions that don't
+/// point into the registry, e.g. `link:` or `file:` dependencies, or aliases
+/// like `other@1.0.0`.
+fn normalize_pnpm_version(version: &str) -> Option<&str> {
+    let end = version.find(['(', '_']).unwrap_or(version.len());
+    let version = &version[..end];
+    if version.is_empty() || version.contains([':', '@', '/']) {
+        return None;
+    }
+    Some(version)
+}
+
+/// Extracts the locked versions from a `package-lock.json`.
+///
+/// Uses the `packages` field (lockfile v2/v3) when available and falls back to
+/// the `dependencies` field (lockfile v1). The dependencies of a package are
+/// resolved the way node resolves them from its install location: from the
+/// closest `node_modules` directory that contains them.
+fn parse_package_lock(text: &str) -> Result<LockedVersions> {
+    let lock: JsonValue = serde_json::from_str(text).context("Invalid JSON")?;
+    let mut locked = LockedVersions::default();
+
+    if let Some(JsonValue::Object(packages)) = lock.get("packages") {
+        let version_at = |path: &str| {
+            let entry = packages.get(path)?;
+            if entry.get("link").and_then(|v| v.as_bool()) == Some(true) {
+                return None;
+            }
+            entry.get("version").and_then(|v| v.as_str())
+        };
+
+        for (key, entry) in packages {
+            let Some(version) = version_at(key) else {
+                continue;
+            };
+            let Some((_, name)) = key.rsplit_once("node_modules/") else {
+                continue;
+            };
+            // Nested installs are not direct dependencies of the root project.
+            if !key[..key.len() - name.len()].contains("/node_modules/") {
+                locked.versions.insert(name.into(), version.into());
+            }
+
+            let dependencies = locked
+                .dependencies
+                .entry(format!("{name}@{version}").into())
+                .or_default();
+            for field in ["dependencies", "optionalDependencies", "peerDependencies"] {
+                let Some(JsonValue::Object(fields)) = entry.get(field) else {
+                    continue;
+                };
+                for dependency in fields.keys() {
+                    let mut base = key.as_str();
+                    loop {
+                        let path = if base.is_empty() {
+                            format!("node_modules/{dependency}")
+                        } else {
+                            format!("{base}/node_modules/{dependency}")
+                        };
+                        if let Some(version) = version_at(&path) {
+                            dependencies
+                                .entry(dependency.as_str().into())
+                                .or_insert_with(|| version.into());
+                            break;
+                        }
+                        if base.is_empty() {
+                            break;
+                        }
+                        base = base
+                            .rfind("/node_modules/")
+                            .map_or("", |index| &base[..index]);
+                    }
+                }
+            }
+        }
+    } else if let Some(JsonValue::Object(dependencies)) = lock.get("dependencies") {
+        for (name, entry) in dependencies {
+            if let Some(version) = npm_v1_version(entry) {
+                locked.versions.insert(name.as_str().into(), version.into());
+            }
+        }
+        read_npm_v1_dependencies(&mut vec![dependencies], dependencies, &mut locked);
+    } else {
+        bail!("package-lock.json has neither a packages nor a dependencies field");
+    }
+
+    Ok(locked)
+}
+
+fn npm_v1_version(entry: &JsonValue) -> Option<&str> {
+    entry
+        .get("version")
+        .and_then(|v| v.as_str())
+        .filter(|version| !version.contains(':'))
+}
+
+/// Walks the nested `dependencies` of a v1 `package-lock.json` and resolves
+/// the `requires` of every package against the enclosing `scopes`, innermost
+/// first.
+fn read_npm_v1_dependencies<'a>(
+    scopes: &mut Vec<&'a JsonMap<String, JsonValue>>,
+    dependencies: &'a JsonMap<String, JsonValue>,
+    locked: &mut LockedVersions,
+) {
+    for (name, entry) in dependencies {
+        let Some(version) = npm_v1_version(entry) else {
+            continue;
+        };
+        let nested = match entry.get("dependencies") {
+            Some(JsonValue::Object(nested)) => Some(nested),
+            _ => None,
+        };
+        scopes.extend(nested);
+
+        if let Some(JsonValue::Object(requires)) = entry.get("requires") {
+            let versions = locked
+                .dependencies
+                .entry(format!("{name}@{version}").into())
+                .or_default();
+            for dependency in requires.keys() {
+                let version = scopes
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(dependency))
+                    .and_then(npm_v1_version);
+                if let Some(version) = version {
+                    versions
+                        .entry(dependency.as_str().into())
+                        .or_insert_with(|| version.into());
+                }
+            }
+        }
+        if let Some(nested) = nested {
+            read_npm_v1_dependencies(scopes, nested, locked);
+            scopes.pop();
+        }
+    }
+}
+
+/// An issue with reading a lockfile for [LockedVersions].
+#[turbo_tasks::value(shared)]
+pub struct LockfileIssue {
+    pub path: Vc<FileSystemPath>,
+    pub error_message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for LockfileIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Error reading lockfile".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Parse.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.error_message.clone()).cell()))
+    }
+}
+
+/// A package was requested from a registry, but the lockfile has no entry for
+/// it, so there is no version to resolve to.
+#[turbo_tasks::value(shared)]
+pub struct MissingLockedVersionIssue {
+    pub path: Vc<FileSystemPath>,
+    pub package_name: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MissingLockedVersionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Package ".into()),
+            StyledString::Code(self.package_name.clone()),
+            StyledString::Text(" is not locked".into()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Resolve.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "The package can't be resolved from the registry because the lockfile doesn't \
+                 contain a version for it. Add it to the dependencies and update the lockfile."
+                    .into(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        normalize_pnpm_version, parse_package_lock, parse_pnpm_lock, split_pnpm_package_key,
+    };
+
+    #[test]
+    fn pnpm_lock_v9_importers() {
+        let versions = parse_pnpm_lock(
+            r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      '@scope/ui':
+        specifier: ^2.0.0
+        version: 2.1.0(react@18.3.1)
+      react:
+        specifier: ^18.3.1
+        version: 18.3.1
+      local:
+        specifier: link:../local
+        version: link:../local
+    devDependencies:
+      typescript:
+        specifier: ^5.0.0
+        version: 5.4.5
+  packages/other:
+    dependencies:
+      lodash:
+        specifier: ^4.0.0
+        version: 4.17.21
+"#,
+        )
+        .unwrap()
+        .versions;
+        assert_eq!(versions.len(), 3);
+        assert_eq!(&*versions["@scope/ui"], "2.1.0");
+        assert_eq!(&*versions["react"], "18.3.1");
+        assert_eq!(&*versions["typescript"], "5.4.5");
+    }
+
+    #[test]
+    fn pnpm_lock_v5_single_project() {
+        let versions = parse_pnpm_lock(
+            r#"
+lockfileVersion: 5.4
+dependencies:
+  react-dom: 18.2.0_react@18.2.0
+  react: 18.2.0
+"#,
+        )
+        .unwrap()
+        .versions;
+        assert_eq!(&*versions["react-dom"], "18.2.0");
+        assert_eq!(&*versions["react"], "18.2.0");
+    }
+
+    #[test]
+    fn pnpm_versions() {
+        assert_eq!(normalize_pnpm_version("1.0.0"), Some("1.0.0"));
+        assert_eq!(
+            normalize_pnpm_version("1.0.0(a@1.0.0)(b@2.0.0)"),
+            Some("1.0.0")
+        );
+        assert_eq!(normalize_pnpm_version("1.0.0_a@1.0.0"), Some("1.0.0"));
+        assert_eq!(normalize_pnpm_version("file:../pkg"), None);
+        assert_eq!(normalize_pnpm_version("string-width@4.2.3"), None);
+    }
+
+    #[test]
+    fn pnpm_package_keys() {
+        assert_eq!(
+            split_pnpm_package_key("react-dom@18.3.1(react@18.3.1)"),
+            Some(("react-dom", "18.3.1"))
+        );
+        assert_eq!(
+            split_pnpm_package_key("/@scope/ui@2.1.0(react@18.3.1)"),
+            Some(("@scope/ui", "2.1.0"))
+        );
+        assert_eq!(
+            split_pnpm_package_key("/react-dom/18.2.0_react@18.2.0"),
+            Some(("react-dom", "18.2.0"))
+        );
+        assert_eq!(
+            split_pnpm_package_key("/@scope/ui/2.1.0"),
+            Some(("@scope/ui", "2.1.0"))
+        );
+        assert_eq!(split_pnpm_package_key("file:../pkg"), None);
+    }
+
+    #[test]
+    fn pnpm_lock_v9_snapshots() {
+        let locked = parse_pnpm_lock(
+            r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      react-dom:
+        specifier: ^18.3.1
+        version: 18.3.1(react@18.3.1)
+packages:
+  loose-envify@1.4.0:
+    resolution: {integrity: sha512-x}
+snapshots:
+  loose-envify@1.4.0:
+    dependencies:
+      js-tokens: 4.0.0
+  react-dom@18.3.1(react@18.3.1):
+    dependencies:
+      loose-envify: 1.4.0
+      react: 18.3.1
+      scheduler: 0.23.2
+  js-tokens@4.0.0: {}
+"#,
+        )
+        .unwrap();
+        assert_eq!(locked.versions.len(), 1);
+        let react_dom = &locked.dependencies["react-dom@18.3.1"];
+        assert_eq!(&*react_dom["loose-envify"], "1.4.0");
+        assert_eq!(&*react_dom["scheduler"], "0.23.2");
+        assert_eq!(
+            &*locked.dependencies["loose-envify@1.4.0"]["js-tokens"],
+            "4.0.0"
+        );
+        assert!(locked.dependencies["js-tokens@4.0.0"].is_empty());
+    }
+
+    #[test]
+    fn pnpm_lock_v6_packages() {
+        let locked = parse_pnpm_lock(
+            r#"
+lockfileVersion: '6.0'
+dependencies:
+  react:
+    specifier: ^18.2.0
+    version: 18.2.0
+packages:
+  /react@18.2.0:
+    resolution: {integrity: sha512-x}
+    dependencies:
+      loose-envify: 1.4.0
+  /@scope/ui@2.1.0(react@18.2.0):
+    resolution: {integrity: sha512-x}
+    dependencies:
+      react: 18.2.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            &*locked.dependencies["react@18.2.0"]["loose-envify"],
+            "1.4.0"
+        );
+        assert_eq!(&*locked.dependencies["@scope/ui@2.1.0"]["react"], "18.2.0");
+    }
+
+    #[test]
+    fn package_lock_v3() {
+        let versions = parse_package_lock(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app" },
+                    "node_modules/@scope/ui": { "version": "2.1.0" },
+                    "node_modules/react": { "version": "18.3.1" },
+                    "node_modules/react/node_modules/loose-envify": { "version": "1.4.0" },
+                    "node_modules/local": { "resolved": "packages/local", "link": true }
+                }
+            }"#,
+        )
+        .unwrap()
+        .versions;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(&*versions["@scope/ui"], "2.1.0");
+        assert_eq!(&*versions["react"], "18.3.1");
+    }
+
+    #[test]
+    fn package_lock_v1() {
+        let versions = parse_package_lock(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "react": { "version": "18.2.0" },
+                    "local": { "version": "file:../local" }
+                }
+            }"#,
+        )
+        .unwrap()
+        .versions;
+        assert_eq!(versions.len(), 1);
+        assert_eq!(&*versions["react"], "18.2.0");
+    }
+
+    #[test]
+    fn package_lock_v3_nested() {
+        let locked = parse_package_lock(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app", "dependencies": { "a": "^1.0.0", "b": "^1.0.0" } },
+                    "node_modules/a": { "version": "1.0.0", "dependencies": { "c": "^2.0.0" } },
+                    "node_modules/a/node_modules/c": { "version": "2.0.0" },
+                    "node_modules/b": {
+                        "version": "1.0.0",
+                        "dependencies": { "c": "^1.0.0" },
+                        "optionalDependencies": { "missing": "*" }
+                    },
+                    "node_modules/c": { "version": "1.0.0", "dependencies": { "a": "^1.0.0" } }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(locked.versions.len(), 3);
+        assert_eq!(&*locked.versions["c"], "1.0.0");
+        assert_eq!(&*locked.dependencies["a@1.0.0"]["c"], "2.0.0");
+        assert_eq!(&*locked.dependencies["b@1.0.0"]["c"], "1.0.0");
+        assert!(!locked.dependencies["b@1.0.0"].contains_key("missing"));
+        assert_eq!(&*locked.dependencies["c@1.0.0"]["a"], "1.0.0");
+        assert!(locked.dependencies["c@2.0.0"].is_empty());
+    }
+
+    #[test]
+    fn package_lock_v1_nested() {
+        let locked = parse_package_lock(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "a": {
+                        "version": "1.0.0",
+                        "requires": { "c": "^2.0.0" },
+                        "dependencies": { "c": { "version": "2.0.0" } }
+                    },
+                    "b": { "version": "1.0.0", "requires": { "c": "^1.0.0" } },
+                    "c": { "version": "1.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(locked.versions.len(), 3);
+        assert_eq!(&*locked.dependencies["a@1.0.0"]["c"], "2.0.0");
+        assert_eq!(&*locked.dependencies["b@1.0.0"]["c"], "1.0.0");
+    }
+}
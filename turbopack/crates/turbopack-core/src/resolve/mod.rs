@@ -16,6 +16,7 @@ use turbo_tasks_fs::{
 };
 
 use self::{
+    lockfile::MissingLockedVersionIssue,
     options::{
         resolve_modules_options, ConditionValue, ImportMapResult, ResolveInPackage,
         ResolveIntoPackage, ResolveModules, ResolveModulesOptions, ResolveOptions,
@@ -43,6 +44,7 @@ use crate::{
 };
 
 mod alias_map;
+pub mod lockfile;
 pub mod node;
 pub mod options;
 pub mod origin;
@@ -1233,6 +1235,7 @@ async fn find_package(
 ) -> Result<Vc<FindPackageResult>> {
     let mut packages = vec![];
     let mut affecting_sources = vec![];
+    let mut missing_locked_version = None;
     let options = options.await?;
     for resolve_modules in &options.modules {
         match resolve_modules {
@@ -1291,7 +1294,42 @@ async fn find_package(
                     }
                 }
             }
-            ResolveModules::Registry(_, _) => todo!(),
+            ResolveModules::Registry(dir, locked_versions) => {
+                let locked_versions = locked_versions.await?;
+                if let Some(lockfile) = locked_versions.lockfile {
+                    affecting_sources.push(Vc::upcast(FileSource::new(lockfile)));
+                }
+                // Packages inside the registry resolve their dependencies with
+                // the versions locked for them, everything else with the
+                // versions of the root project.
+                let issuer = registry_package_of(&*(*dir).await?, &*lookup_path.await?);
+                let version = issuer
+                    .and_then(|issuer| locked_versions.dependencies.get(issuer.as_str()))
+                    .and_then(|dependencies| dependencies.get(&package_name))
+                    .or_else(|| locked_versions.versions.get(&package_name));
+                match version {
+                    Some(version) => {
+                        let package_dir = dir.join(package_name.clone()).join(version.clone());
+                        if let Some(package_dir) =
+                            dir_exists(package_dir, &mut affecting_sources).await?
+                        {
+                            packages.push(FindPackageItem::PackageDirectory(package_dir));
+                        }
+                    }
+                    None => {
+                        missing_locked_version = Some(locked_versions.lockfile.unwrap_or(*dir));
+                    }
+                }
+            }
+        }
+    }
+    // Only report a missing lockfile entry when no other location provided the
+    // package.
+    if let Some(path) = missing_locked_version {
+        if packages.is_empty() {
+            MissingLockedVersionIssue { path, package_name }
+                .cell()
+                .emit();
         }
     }
     Ok(FindPackageResult::cell(FindPackageResult {
@@ -1300,6 +1338,18 @@ async fn find_package(
     }))
 }
 
+/// Returns the package, as `name@version`, that contains `path` inside a
+/// registry directory laid out as `<name>/<version>`.
+fn registry_package_of(dir: &FileSystemPath, path: &FileSystemPath) -> Option<String> {
+    let mut segments = dir.get_path_to(path)?.split('/');
+    let mut name = segments.next()?.to_string();
+    if name.starts_with('@') {
+        name = format!("{name}/{}", segments.next()?);
+    }
+    let version = segments.next()?;
+    Some(format!("{name}@{version}"))
+}
+
 fn merge_results(results: Vec<Vc<ResolveResult>>) -> Vc<ResolveResult> {
     match results.len() {
         0 => ResolveResult::unresolveable().into(),
@@ -2458,29 +2508,44 @@ async fn resolve_into_package(
                     continue;
                 };
 
-                let Some(path) = path.clone().into_string() else {
-                    todo!("pattern into an exports field is not implemented yet");
-                };
+                if let Some(path) = path.as_string() {
+                    let path = if path == "/" {
+                        ".".to_string()
+                    } else {
+                        format!(".{path}")
+                    };
 
-                let path = if &*path == "/" {
-                    ".".to_string()
+                    results.push(
+                        handle_exports_imports_field(
+                            package_path,
+                            package_json_path,
+                            options,
+                            exports_field,
+                            &path,
+                            conditions,
+                            unspecified_conditions,
+                            query,
+                        )
+                        .await?,
+                    );
                 } else {
-                    format!(".{path}")
-                };
+                    let mut path = Pattern::concat([RcStr::from(".").into(), path.clone()]);
+                    path.normalize();
 
-                results.push(
-                    handle_exports_imports_field(
-                        package_path,
-                        package_json_path,
-                        options,
-                        exports_field,
-                        &path,
-                        conditions,
-                        unspecified_conditions,
-                        query,
-                    )
-                    .await?,
-                );
+                    results.push(
+                        handle_exports_field_pattern(
+                            package_path,
+                            package_json_path,
+                            options,
+                            exports_field,
+                            &path,
+                            conditions,
+                            unspecified_conditions,
+                            query,
+                        )
+                        .await?,
+                    );
+                }
 
                 // other options do not apply anymore when an exports
                 // field exist
@@ -2675,6 +2740,79 @@ async fn handle_exports_imports_field(
     ))
 }
 
+/// Resolves a dynamic request into a package (e.g. `import("pkg/" + name)`)
+/// using the `exports` field. In contrast to [handle_exports_imports_field],
+/// every subpath pattern that could match the request contributes results, as
+/// different values of the dynamic parts can select different entries.
+async fn handle_exports_field_pattern(
+    package_path: Vc<FileSystemPath>,
+    package_json_path: Vc<FileSystemPath>,
+    options: Vc<ResolveOptions>,
+    exports_field: &AliasMap<SubpathValue>,
+    path: &Pattern,
+    conditions: &BTreeMap<RcStr, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+    query: Vc<RcStr>,
+) -> Result<Vc<ResolveResult>> {
+    let mut resolved_results = Vec::new();
+
+    let query_str = query.await?;
+    let mut req = Pattern::concat([path.clone(), Pattern::Constant((*query_str).clone())]);
+    req.normalize();
+
+    for (mut subpath, value) in exports_field.lookup_pattern(&req) {
+        // The request keys don't include the query, like the ones of
+        // [handle_exports_imports_field].
+        if !query_str.is_empty()
+            && !matches!(subpath, Pattern::Alternatives(_))
+            && subpath.constant_suffix().ends_with(&**query_str)
+        {
+            subpath.strip_suffix(query_str.len());
+        }
+        let value = value.try_into_self()?;
+        let mut conditions_state = HashMap::new();
+        let mut results = Vec::new();
+        value.add_results(
+            conditions,
+            unspecified_conditions,
+            &mut conditions_state,
+            &mut results,
+        );
+
+        for (result_path, conditions) in results {
+            let Some(result_path) = result_path.with_normalized_path() else {
+                continue;
+            };
+            let request = Request::parse(Value::new(Pattern::Concatenation(vec![
+                Pattern::Constant("./".into()),
+                result_path,
+            ])));
+
+            // Map the request keys of the target files back to the matching
+            // subpaths of the package, e.g. `./dist/a.js` to `./a`.
+            let resolve_result = Box::pin(resolve_internal_inline(package_path, request, options))
+                .await?
+                .with_replaced_request_key_pattern(
+                    request.request_pattern(),
+                    subpath.clone().cell(),
+                );
+            if conditions.is_empty() {
+                resolved_results.push(resolve_result);
+            } else {
+                let mut resolve_result = resolve_result.await?.clone_value();
+                resolve_result.add_conditions(conditions);
+                resolved_results.push(resolve_result.cell());
+            }
+        }
+    }
+
+    // other options do not apply anymore when an exports field exist
+    Ok(merge_results_with_affecting_sources(
+        resolved_results,
+        vec![Vc::upcast(FileSource::new(package_json_path))],
+    ))
+}
+
 /// Resolves a `#dep` import using the containing package.json's `imports`
 /// field. The dep may be a constant string or a pattern, and the values can be
 /// static strings or conditions like `import` or `require` to handle ESM/CJS
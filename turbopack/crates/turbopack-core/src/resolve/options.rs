@@ -16,9 +16,21 @@ use super::{
 };
 use crate::resolve::{parse::Request, plugin::AfterResolvePlugin};
 
+/// The versions of packages pinned by a lockfile, used to pick the package
+/// directory in a [ResolveModules::Registry]. See
+/// [read_locked_versions](super::lockfile::read_locked_versions).
 #[turbo_tasks::value(shared)]
-#[derive(Hash, Debug)]
-pub struct LockedVersions {}
+#[derive(Hash, Debug, Default)]
+pub struct LockedVersions {
+    /// The lockfile the versions were read from, if any.
+    pub lockfile: Option<Vc<FileSystemPath>>,
+    /// Maps package names (including the scope) to the locked version the
+    /// root project depends on.
+    pub versions: BTreeMap<RcStr, RcStr>,
+    /// Maps locked packages, as `name@version`, to the locked versions of
+    /// their own dependencies.
+    pub dependencies: BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>,
+}
 
 #[turbo_tasks::value(transparent)]
 #[derive(Debug)]
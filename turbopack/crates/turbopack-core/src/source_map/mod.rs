@@ -14,8 +14,8 @@ use turbo_tasks_fs::{
 };
 
 use crate::{
-    asset::AssetContent, source::Source, source_pos::SourcePos, virtual_source::VirtualSource,
-    SOURCE_MAP_PREFIX,
+    asset::AssetContent, chunk::SourceMapsType, source::Source, source_pos::SourcePos,
+    virtual_source::VirtualSource, SOURCE_MAP_PREFIX,
 };
 
 pub(crate) mod source_map_asset;
@@ -220,6 +220,28 @@ impl SourceMap {
     }
 }
 
+/// Removes `sourcesContent` from a stringified source map, including the maps
+/// of its sections.
+fn without_sources_content(bytes: &[u8]) -> Result<Vec<u8>> {
+    fn remove_sources_content(map: &mut serde_json::Value) {
+        let Some(map) = map.as_object_mut() else {
+            return;
+        };
+        map.remove("sourcesContent");
+        if let Some(sections) = map.get_mut("sections").and_then(|s| s.as_array_mut()) {
+            for section in sections {
+                if let Some(map) = section.get_mut("map") {
+                    remove_sources_content(map);
+                }
+            }
+        }
+    }
+
+    let mut map = serde_json::from_slice(bytes)?;
+    remove_sources_content(&mut map);
+    Ok(serde_json::to_vec(&map)?)
+}
+
 impl SourceMap {
     pub async fn to_source_map(&self) -> Result<Arc<CrateMapWrapper>> {
         Ok(match self {
@@ -258,14 +280,18 @@ impl SourceMap {
         SourceMap::new_regular(builder.into_sourcemap()).cell()
     }
 
-    /// Stringifies the source map into JSON bytes.
+    /// Stringifies the source map into JSON bytes. `sourcesContent` is
+    /// omitted for [SourceMapsType::NoSources].
     #[turbo_tasks::function]
-    pub async fn to_rope(self: Vc<Self>) -> Result<Vc<Rope>> {
+    pub async fn to_rope(self: Vc<Self>, source_maps_type: SourceMapsType) -> Result<Vc<Rope>> {
         let this = self.await?;
         let rope = match &*this {
             SourceMap::Decoded(r) => {
                 let mut bytes = vec![];
                 r.0.to_writer(&mut bytes)?;
+                if !source_maps_type.has_sources_content() {
+                    bytes = without_sources_content(&bytes)?;
+                }
                 Rope::from(bytes)
             }
 
@@ -273,7 +299,7 @@ impl SourceMap {
                 if s.sections.len() == 1 {
                     let s = &s.sections[0];
                     if s.offset == (0, 0) {
-                        return Ok(s.map.to_rope());
+                        return Ok(s.map.to_rope(source_maps_type));
                     }
                 }
 
@@ -290,7 +316,7 @@ impl SourceMap {
                 let sections = s
                     .sections
                     .iter()
-                    .map(|s| async move { Ok((s.offset, s.map.to_rope().await?)) })
+                    .map(|s| async move { Ok((s.offset, s.map.to_rope(source_maps_type).await?)) })
                     .try_join()
                     .await?;
 
@@ -767,3 +793,25 @@ pub async fn convert_to_turbopack_source_map(
     };
     Ok(Vc::cell(Some(source_map.with_resolved_sources(origin))))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::without_sources_content;
+
+    #[test]
+    fn removes_sources_content() {
+        let map = br#"{"version":3,"sections":[{"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":"AAAA"}}],"sourcesContent":["b"]}"#;
+        let map: serde_json::Value =
+            serde_json::from_slice(&without_sources_content(map).unwrap()).unwrap();
+        assert_eq!(
+            map,
+            serde_json::json!({
+                "version": 3,
+                "sections": [{
+                    "offset": {"line": 0, "column": 0},
+                    "map": {"version": 3, "sources": ["a.js"], "mappings": "AAAA"}
+                }]
+            })
+        );
+    }
+}
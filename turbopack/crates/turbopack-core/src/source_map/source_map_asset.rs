@@ -5,6 +5,7 @@ use turbo_tasks_fs::File;
 
 use crate::{
     asset::{Asset, AssetContent},
+    chunk::SourceMapsType,
     ident::AssetIdent,
     introspect::{Introspectable, IntrospectableChildren},
     output::OutputAsset,
@@ -15,13 +16,18 @@ use crate::{
 #[turbo_tasks::value]
 pub struct SourceMapAsset {
     asset: Vc<Box<dyn OutputAsset>>,
+    source_maps_type: SourceMapsType,
 }
 
 #[turbo_tasks::value_impl]
 impl SourceMapAsset {
     #[turbo_tasks::function]
-    pub fn new(asset: Vc<Box<dyn OutputAsset>>) -> Vc<Self> {
-        SourceMapAsset { asset }.cell()
+    pub fn new(asset: Vc<Box<dyn OutputAsset>>, source_maps_type: SourceMapsType) -> Vc<Self> {
+        SourceMapAsset {
+            asset,
+            source_maps_type,
+        }
+        .cell()
     }
 }
 
@@ -49,7 +55,7 @@ impl Asset for SourceMapAsset {
         } else {
             SourceMap::empty()
         };
-        let sm = sm.to_rope().await?;
+        let sm = sm.to_rope(self.source_maps_type).await?;
         Ok(AssetContent::file(File::from(sm).into()))
     }
 }
@@ -0,0 +1,187 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::fs;
+
+use anyhow::{Context, Result};
+use turbo_tasks::{RcStr, Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::{
+    issue::{IssueDescriptionExt, IssueSeverity, StyledString},
+    reference_type::ReferenceType,
+    resolve::{
+        lockfile::read_locked_versions,
+        options::{ResolveIntoPackage, ResolveModules, ResolveOptions},
+        parse::Request,
+        resolve, ResolveResult,
+    },
+};
+
+static REGISTRATION: Registration = register!(turbopack_core::register);
+
+const PNPM_LOCK: &str = r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      react-dom:
+        specifier: ^18.3.1
+        version: 18.3.1(react@18.3.1)
+      scheduler:
+        specifier: ^0.20.0
+        version: 0.20.0
+snapshots:
+  loose-envify@1.4.0: {}
+  react-dom@18.3.1(react@18.3.1):
+    dependencies:
+      loose-envify: 1.4.0
+      scheduler: 0.23.2
+  scheduler@0.20.0: {}
+  scheduler@0.23.2:
+    dependencies:
+      loose-envify: 1.4.0
+"#;
+
+fn write_package(store: &std::path::Path, name: &str, version: &str) {
+    let dir = store.join(name).join(version);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("package.json"),
+        format!(r#"{{ "name": "{name}", "version": "{version}", "main": "index.js" }}"#),
+    )
+    .unwrap();
+    fs::write(dir.join("index.js"), "").unwrap();
+}
+
+fn resolve_in_registry(
+    lookup_path: Vc<FileSystemPath>,
+    store: Vc<FileSystemPath>,
+    lockfile: Vc<FileSystemPath>,
+    request: &str,
+) -> Vc<ResolveResult> {
+    let options = ResolveOptions {
+        extensions: vec![".js".into()],
+        modules: vec![ResolveModules::Registry(
+            store,
+            read_locked_versions(lockfile),
+        )],
+        into_package: vec![ResolveIntoPackage::MainField {
+            field: "main".into(),
+        }],
+        default_files: vec!["index".into()],
+        ..Default::default()
+    }
+    .cell();
+    resolve(
+        lookup_path,
+        Value::new(ReferenceType::Undefined),
+        Request::parse_string(request.into()),
+        options,
+    )
+}
+
+async fn resolve_from(
+    lookup_path: Vc<FileSystemPath>,
+    store: Vc<FileSystemPath>,
+    lockfile: Vc<FileSystemPath>,
+    request: &str,
+) -> Result<String> {
+    let result = resolve_in_registry(lookup_path, store, lockfile, request);
+    let source = (*result.first_source().await?).context("request is unresolveable")?;
+    Ok(source.ident().path().await?.path.to_string())
+}
+
+#[tokio::test]
+async fn registry_transitive_dependencies() {
+    let root_dir = tempfile::tempdir().unwrap();
+    let store = root_dir.path().join("store");
+    write_package(&store, "react-dom", "18.3.1");
+    write_package(&store, "scheduler", "0.20.0");
+    write_package(&store, "scheduler", "0.23.2");
+    write_package(&store, "loose-envify", "1.4.0");
+    fs::write(root_dir.path().join("pnpm-lock.yaml"), PNPM_LOCK).unwrap();
+    let root: RcStr = root_dir.path().to_str().unwrap().into();
+
+    run(&REGISTRATION, move || {
+        let root = root.clone();
+        async move {
+            let fs = DiskFileSystem::new("project".into(), root, vec![]);
+            let project = fs.root();
+            let store = project.join("store".into());
+            let lockfile = project.join("pnpm-lock.yaml".into());
+
+            // The root project uses the versions of its direct dependencies.
+            assert_eq!(
+                resolve_from(project, store, lockfile, "scheduler").await?,
+                "store/scheduler/0.20.0/index.js"
+            );
+
+            // A package in the store uses the versions locked for it.
+            let react_dom = store.join("react-dom/18.3.1".into());
+            assert_eq!(
+                resolve_from(react_dom, store, lockfile, "scheduler").await?,
+                "store/scheduler/0.23.2/index.js"
+            );
+            assert_eq!(
+                resolve_from(react_dom, store, lockfile, "loose-envify").await?,
+                "store/loose-envify/1.4.0/index.js"
+            );
+
+            // Transitive dependencies resolve from nested directories as well.
+            let scheduler = store.join("scheduler/0.23.2/cjs".into());
+            assert_eq!(
+                resolve_from(scheduler, store, lockfile, "loose-envify").await?,
+                "store/loose-envify/1.4.0/index.js"
+            );
+            anyhow::Ok(())
+        }
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn registry_missing_locked_version() {
+    let root_dir = tempfile::tempdir().unwrap();
+    let store = root_dir.path().join("store");
+    write_package(&store, "react", "18.3.1");
+    fs::write(root_dir.path().join("pnpm-lock.yaml"), PNPM_LOCK).unwrap();
+    let root: RcStr = root_dir.path().to_str().unwrap().into();
+
+    run(&REGISTRATION, move || {
+        let root = root.clone();
+        async move {
+            let fs = DiskFileSystem::new("project".into(), root, vec![]);
+            let project = fs.root();
+            let store = project.join("store".into());
+            let lockfile = project.join("pnpm-lock.yaml".into());
+
+            // react is in the store, but not in the lockfile.
+            let result = resolve_in_registry(project, store, lockfile, "react")
+                .resolve_strongly_consistent()
+                .await?;
+            assert!(result.first_source().await?.is_none());
+
+            let issues = result
+                .peek_issues_with_path()
+                .await?
+                .get_plain_issues()
+                .await?;
+            let title = StyledString::Line(vec![
+                StyledString::Text("Package ".into()),
+                StyledString::Code("react".into()),
+                StyledString::Text(" is not locked".into()),
+            ]);
+            let issue = issues
+                .iter()
+                .find(|issue| issue.title == title)
+                .with_context(|| format!("missing locked version isn't reported in {issues:#?}"))?;
+            assert_eq!(issue.severity, IssueSeverity::Warning);
+            assert!(issue.file_path.ends_with("/pnpm-lock.yaml"), "{issue:#?}");
+            anyhow::Ok(())
+        }
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,84 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileSystem, VirtualFileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::{
+    chunk::SourceMapsType,
+    code_builder::{Code, CodeBuilder},
+    source_map::{GenerateSourceMap, SourceMap},
+};
+
+static REGISTRATION: Registration = register!(turbopack_core::register);
+
+fn code_with_source_map() -> Vc<Code> {
+    let mut builder = CodeBuilder::default();
+    builder.push_source(
+        &"console.log(1);".into(),
+        Some(Vc::upcast::<Box<dyn GenerateSourceMap>>(SourceMap::empty())),
+    );
+    builder.build().cell()
+}
+
+async fn emitted_code(path: &str, source_maps_type: SourceMapsType) -> Result<String> {
+    let path = VirtualFileSystem::new().root().join(path.into());
+    let code = code_with_source_map()
+        .with_source_mapping_url(path, source_maps_type)
+        .await?;
+    Ok(code.source_code().to_str()?.into_owned())
+}
+
+#[tokio::test]
+async fn full_source_map_url() {
+    run(&REGISTRATION, || async {
+        let code = emitted_code("chunk.js", SourceMapsType::Full).await?;
+        assert_eq!(code, "console.log(1);\n\n//# sourceMappingURL=chunk.js.map");
+
+        let code = emitted_code("chunk.css", SourceMapsType::Full).await?;
+        assert_eq!(
+            code,
+            "console.log(1);\n/*# sourceMappingURL=chunk.css.map*/"
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn hidden_source_map_url() {
+    run(&REGISTRATION, || async {
+        assert!(SourceMapsType::Hidden.has_source_map_file());
+        let code = emitted_code("chunk.js", SourceMapsType::Hidden).await?;
+        assert_eq!(code, "console.log(1);");
+        assert!(!code.contains("sourceMappingURL"));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn inline_source_map_url() {
+    run(&REGISTRATION, || async {
+        assert!(!SourceMapsType::Inline.has_source_map_file());
+        let code = emitted_code("chunk.js", SourceMapsType::Inline).await?;
+        let (code, url) = code
+            .split_once("\n\n//# sourceMappingURL=")
+            .expect("inline source map should add a sourceMappingURL comment");
+        assert_eq!(code, "console.log(1);");
+        assert!(!url.ends_with(".map"));
+
+        let data = url
+            .strip_prefix("data:application/json;charset=utf-8;base64,")
+            .expect("inline source map should be a base64 data url");
+        let map = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data)?;
+        let map: serde_json::Value = serde_json::from_slice(&map)?;
+        assert_eq!(map["version"], 3);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -20,7 +20,11 @@ use crate::{
         finalize_css, parse_css, process_css_with_placeholder, CssWithPlaceholderResult,
         FinalCssResult, ParseCss, ParseCssResult, ProcessCss,
     },
-    references::{compose::CssModuleComposeReference, import::ImportAssetReference},
+    references::{
+        compose::CssModuleComposeReference,
+        import::ImportAssetReference,
+        value::{css_module_value_references, CssModuleValueReferences},
+    },
     CssModuleAssetType,
 };
 
@@ -68,6 +72,18 @@ impl CssModuleAsset {
     pub fn source_ident(&self) -> Vc<AssetIdent> {
         self.source.ident()
     }
+
+    /// The `@value ... from ...` references of this CSS module, the same ones
+    /// parsing creates.
+    #[turbo_tasks::function]
+    pub(crate) async fn value_import_references(
+        self: Vc<Self>,
+    ) -> Result<Vc<CssModuleValueReferences>> {
+        Ok(css_module_value_references(
+            self.await?.source,
+            Vc::upcast(self),
+        ))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -187,8 +203,28 @@ impl ChunkItem for CssModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn references(&self) -> Vc<ModuleReferences> {
-        self.module.references()
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        let references = self.module.references();
+        if !*self.chunking_context.inline_css_imports().await? {
+            return Ok(references);
+        }
+        Ok(Vc::cell(
+            references
+                .await?
+                .iter()
+                .map(|&reference| async move {
+                    Ok(
+                        match Vc::try_resolve_downcast_type::<ImportAssetReference>(reference)
+                            .await?
+                        {
+                            Some(import_ref) => Vc::upcast(import_ref.inlined()),
+                            None => reference,
+                        },
+                    )
+                })
+                .try_join()
+                .await?,
+        ))
     }
 
     #[turbo_tasks::function]
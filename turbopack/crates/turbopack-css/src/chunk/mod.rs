@@ -1,7 +1,7 @@
 pub(crate) mod single_item_chunk;
 pub mod source_map;
 
-use std::fmt::Write;
+use std::{collections::HashSet, fmt::Write};
 
 use anyhow::{bail, Result};
 use indexmap::IndexSet;
@@ -11,7 +11,8 @@ use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
         round_chunk_item_size, AsyncModuleInfo, Chunk, ChunkItem, ChunkItemWithAsyncModuleInfo,
-        ChunkType, ChunkableModule, ChunkingContext, ModuleId, OutputChunk, OutputChunkRuntimeInfo,
+        ChunkItems, ChunkOutputAsset, ChunkType, ChunkableModule, ChunkingContext, ModuleId,
+        OutputChunk, OutputChunkRuntimeInfo,
     },
     code_builder::{Code, CodeBuilder},
     ident::AssetIdent,
@@ -58,6 +59,23 @@ impl CssChunk {
         self.content
     }
 
+    /// The chunk items in the order they are written to the chunk. With
+    /// [ChunkingContext::inline_css_imports] every chunk item is preceded by
+    /// the stylesheets it `@import`s. A stylesheet is only written at its
+    /// first occurrence in the chunk.
+    #[turbo_tasks::function]
+    async fn ordered_chunk_items(&self) -> Result<Vc<CssChunkItems>> {
+        let chunk_items = &self.content.await?.chunk_items;
+        if !*self.chunking_context.inline_css_imports().await? {
+            return Ok(Vc::cell(chunk_items.clone()));
+        }
+        let mut items = IndexSet::new();
+        for &chunk_item in chunk_items {
+            items.extend(with_inlined_imports(chunk_item).await?);
+        }
+        Ok(Vc::cell(items.into_iter().collect()))
+    }
+
     #[turbo_tasks::function]
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         use std::io::Write;
@@ -67,7 +85,7 @@ impl CssChunk {
         let mut code = CodeBuilder::default();
         let mut body = CodeBuilder::default();
         let mut external_imports = IndexSet::new();
-        for css_item in &this.content.await?.chunk_items {
+        for (index, css_item) in self.ordered_chunk_items().await?.iter().enumerate() {
             let id = &*css_item.id().await?;
 
             let content = &css_item.content().await?;
@@ -77,13 +95,15 @@ impl CssChunk {
                 }
             }
 
+            if index > 0 {
+                writeln!(body)?;
+            }
             writeln!(body, "/* {} */", id)?;
             let close = write_import_context(&mut body, content.import_context).await?;
 
             body.push_source(&content.inner_code, content.source_map.map(Vc::upcast));
 
             writeln!(body, "{close}")?;
-            writeln!(body)?;
         }
 
         for external_import in external_imports {
@@ -93,22 +113,18 @@ impl CssChunk {
         let built = &body.build();
         code.push_code(built);
 
+        let mut code = code.build().cell();
         if *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?
-            && code.has_source_map()
         {
-            let chunk_path = self.path().await?;
-            writeln!(
-                code,
-                "/*# sourceMappingURL={}.map*/",
-                urlencoding::encode(chunk_path.file_name())
-            )?;
+            code = code.with_source_mapping_url(
+                self.path(),
+                *this.chunking_context.source_maps_type().await?,
+            );
         }
-
-        let c = code.build().cell();
-        Ok(c)
+        Ok(code)
     }
 
     #[turbo_tasks::function]
@@ -120,6 +136,35 @@ impl CssChunk {
     }
 }
 
+/// Returns the stylesheets `@import`ed by `chunk_item`, recursively, followed
+/// by `chunk_item` itself. Imports come first, as their rules must be
+/// overridable by the importing stylesheet. A stylesheet imported multiple
+/// times is placed at its first import.
+pub(crate) async fn with_inlined_imports(
+    chunk_item: Vc<Box<dyn CssChunkItem>>,
+) -> Result<Vec<Vc<Box<dyn CssChunkItem>>>> {
+    let mut items = Vec::new();
+    let mut visited = HashSet::new();
+    // The flag is set when the imports of the item have been visited already
+    let mut stack = vec![(chunk_item.resolve().await?, false)];
+    while let Some((item, imports_visited)) = stack.pop() {
+        if imports_visited {
+            items.push(item);
+            continue;
+        }
+        if !visited.insert(item) {
+            continue;
+        }
+        stack.push((item, true));
+        for import in item.content().await?.imports.iter().rev() {
+            if let CssImport::Internal(_, imported_item) = import {
+                stack.push((imported_item.resolve().await?, false));
+            }
+        }
+    }
+    Ok(items)
+}
+
 pub async fn write_import_context(
     body: &mut impl std::io::Write,
     import_context: Option<Vc<ImportContext>>,
@@ -153,6 +198,9 @@ pub struct CssChunkContent {
     pub referenced_output_assets: Vc<OutputAssets>,
 }
 
+#[turbo_tasks::value(transparent)]
+pub struct CssChunkItems(Vec<Vc<Box<dyn CssChunkItem>>>);
+
 #[turbo_tasks::value_impl]
 impl Chunk for CssChunk {
     #[turbo_tasks::function]
@@ -170,18 +218,47 @@ impl Chunk for CssChunk {
     fn references(self: Vc<Self>) -> Vc<OutputAssets> {
         OutputAsset::references(self)
     }
+
+    #[turbo_tasks::function]
+    async fn chunk_items(&self) -> Result<Vc<ChunkItems>> {
+        let CssChunkContent { chunk_items, .. } = &*self.content.await?;
+        Ok(ChunkItems(chunk_items.iter().map(|item| Vc::upcast(*item)).collect()).cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkOutputAsset for CssChunk {
+    #[turbo_tasks::function]
+    fn chunk(self: Vc<Self>) -> Vc<Box<dyn Chunk>> {
+        Vc::upcast(self)
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl OutputChunk for CssChunk {
     #[turbo_tasks::function]
-    async fn runtime_info(&self) -> Result<Vc<OutputChunkRuntimeInfo>> {
-        let content = self.content.await?;
+    async fn runtime_info(self: Vc<Self>) -> Result<Vc<OutputChunkRuntimeInfo>> {
+        let this = self.await?;
+        let content = this.content.await?;
         let entries_chunk_items = &content.chunk_items;
         let included_ids = entries_chunk_items
             .iter()
             .map(|chunk_item| CssChunkItem::id(*chunk_item))
             .collect();
+        if *this.chunking_context.inline_css_imports().await? {
+            // Imported stylesheets are written into the single item chunk of
+            // the importing stylesheet, like into this chunk
+            let module_chunks: Vec<_> = entries_chunk_items
+                .iter()
+                .map(|item| Vc::upcast(SingleItemCssChunk::new(this.chunking_context, *item)))
+                .collect();
+            return Ok(OutputChunkRuntimeInfo {
+                included_ids: Some(Vc::cell(included_ids)),
+                module_chunks: Some(Vc::cell(module_chunks)),
+                ..Default::default()
+            }
+            .cell());
+        }
         let imports_chunk_items: Vec<_> = entries_chunk_items
             .iter()
             .map(|&chunk_item| async move {
@@ -213,7 +290,7 @@ impl OutputChunk for CssChunk {
             .chunk_items
             .iter()
             .chain(imports_chunk_items.iter())
-            .map(|item| Vc::upcast(SingleItemCssChunk::new(self.chunking_context, *item)))
+            .map(|item| Vc::upcast(SingleItemCssChunk::new(this.chunking_context, *item)))
             .collect();
         Ok(OutputChunkRuntimeInfo {
             included_ids: Some(Vc::cell(included_ids)),
@@ -302,6 +379,11 @@ impl OutputAsset for CssChunk {
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?
+            && this
+                .chunking_context
+                .source_maps_type()
+                .await?
+                .has_source_map_file()
         {
             references.push(Vc::upcast(CssChunkSourceMapAsset::new(self)));
         }
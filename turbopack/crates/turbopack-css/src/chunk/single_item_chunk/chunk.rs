@@ -5,7 +5,7 @@ use turbo_tasks::{RcStr, ValueToString, Vc};
 use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{Chunk, ChunkItem, ChunkingContext},
+    chunk::{Chunk, ChunkItem, ChunkItems, ChunkOutputAsset, ChunkingContext},
     code_builder::{Code, CodeBuilder},
     ident::AssetIdent,
     introspect::Introspectable,
@@ -14,7 +14,7 @@ use turbopack_core::{
 };
 
 use super::source_map::SingleItemCssChunkSourceMapAsset;
-use crate::chunk::{write_import_context, CssChunkItem};
+use crate::chunk::{with_inlined_imports, write_import_context, CssChunkItem};
 
 /// A CSS chunk that only contains a single item. This is used for selectively
 /// loading CSS modules that are part of a larger chunk in development mode, and
@@ -50,31 +50,38 @@ impl SingleItemCssChunk {
         let this = self.await?;
         let mut code = CodeBuilder::default();
 
-        let id = &*this.item.id().await?;
-
-        writeln!(code, "/* {} */", id)?;
-        let content = this.item.content().await?;
-        let close = write_import_context(&mut code, content.import_context).await?;
-
-        code.push_source(&content.inner_code, content.source_map.map(Vc::upcast));
-        write!(code, "{close}")?;
+        // Inlined imports are written like in the chunk of the item
+        let items = if *this.chunking_context.inline_css_imports().await? {
+            with_inlined_imports(this.item).await?
+        } else {
+            vec![this.item]
+        };
+        for (index, item) in items.into_iter().enumerate() {
+            if index > 0 {
+                writeln!(code)?;
+            }
+            let id = &*item.id().await?;
+
+            writeln!(code, "/* {} */", id)?;
+            let content = item.content().await?;
+            let close = write_import_context(&mut code, content.import_context).await?;
+
+            code.push_source(&content.inner_code, content.source_map.map(Vc::upcast));
+            write!(code, "{close}")?;
+        }
 
+        let mut code = code.build().cell();
         if *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?
-            && code.has_source_map()
         {
-            let chunk_path = self.path().await?;
-            write!(
-                code,
-                "\n/*# sourceMappingURL={}.map*/",
-                urlencoding::encode(chunk_path.file_name())
-            )?;
+            code = code.with_source_mapping_url(
+                self.path(),
+                *this.chunking_context.source_maps_type().await?,
+            );
         }
-
-        let c = code.build().cell();
-        Ok(c)
+        Ok(code)
     }
 }
 
@@ -90,6 +97,19 @@ impl Chunk for SingleItemCssChunk {
     fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
         self.chunking_context
     }
+
+    #[turbo_tasks::function]
+    fn chunk_items(&self) -> Vc<ChunkItems> {
+        ChunkItems(vec![Vc::upcast(self.item)]).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkOutputAsset for SingleItemCssChunk {
+    #[turbo_tasks::function]
+    fn chunk(self: Vc<Self>) -> Vc<Box<dyn Chunk>> {
+        Vc::upcast(self)
+    }
 }
 
 #[turbo_tasks::function]
@@ -119,6 +139,11 @@ impl OutputAsset for SingleItemCssChunk {
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?
+            && this
+                .chunking_context
+                .source_maps_type()
+                .await?
+                .has_source_map_file()
         {
             references.push(Vc::upcast(SingleItemCssChunkSourceMapAsset::new(self)));
         }
@@ -3,7 +3,7 @@ use turbo_tasks::Vc;
 use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::Chunk,
+    chunk::{Chunk, ChunkingContext},
     ident::AssetIdent,
     output::OutputAsset,
     source_map::{GenerateSourceMap, SourceMap},
@@ -42,7 +42,8 @@ impl Asset for SingleItemCssChunkSourceMapAsset {
         } else {
             SourceMap::empty()
         };
-        let sm = sm.to_rope().await?;
+        let source_maps_type = *self.chunk.chunking_context().source_maps_type().await?;
+        let sm = sm.to_rope(source_maps_type).await?;
         Ok(AssetContent::file(File::from(sm).into()))
     }
 }
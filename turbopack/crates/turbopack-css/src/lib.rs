@@ -11,6 +11,7 @@ mod code_gen;
 pub mod embed;
 mod lifetime_util;
 mod module_asset;
+mod module_values;
 pub(crate) mod parse;
 pub(crate) mod process;
 pub(crate) mod references;
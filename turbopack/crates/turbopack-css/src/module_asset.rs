@@ -30,8 +30,12 @@ use turbopack_ecmascript::{
 };
 
 use crate::{
-    process::{CssWithPlaceholderResult, ProcessCss},
-    references::{compose::CssModuleComposeReference, internal::InternalCssAssetReference},
+    process::{CssModuleValues, CssWithPlaceholderResult, ProcessCss},
+    references::{
+        compose::CssModuleComposeReference, internal::InternalCssAssetReference,
+        value::CssModuleValueReferences,
+    },
+    CssModuleAsset,
 };
 
 #[turbo_tasks::function]
@@ -209,9 +213,51 @@ impl ModuleCssAsset {
         Ok(Vc::cell(classes))
     }
 
+    /// The values defined or imported with `@value` in this CSS module.
+    #[turbo_tasks::function]
+    pub(crate) async fn values(self: Vc<Self>) -> Result<Vc<CssModuleValues>> {
+        let inner = Vc::try_resolve_sidecast::<Box<dyn ProcessCss>>(self.inner().module())
+            .await?
+            .context("inner asset should be CSS processable")?;
+        Ok(match &*inner.get_css_with_placeholder().await? {
+            CssWithPlaceholderResult::Ok { values, .. } => *values,
+            CssWithPlaceholderResult::Unparseable | CssWithPlaceholderResult::NotFound => {
+                CssModuleValues::empty()
+            }
+        })
+    }
+
+    /// The `@value ... from ...` references of this CSS module. Unlike
+    /// [ModuleCssAsset::values], they don't depend on the imported values.
+    #[turbo_tasks::function]
+    pub(crate) async fn value_import_references(
+        self: Vc<Self>,
+    ) -> Result<Vc<CssModuleValueReferences>> {
+        let inner = Vc::try_resolve_downcast_type::<CssModuleAsset>(self.inner().module())
+            .await?
+            .context("inner asset should be a CSS asset")?;
+        Ok(inner.value_import_references())
+    }
+
+    #[turbo_tasks::function]
+    async fn value_references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
+        let inner = Vc::try_resolve_sidecast::<Box<dyn ProcessCss>>(self.inner().module())
+            .await?
+            .context("inner asset should be CSS processable")?;
+        Ok(match &*inner.get_css_with_placeholder().await? {
+            CssWithPlaceholderResult::Ok {
+                value_references, ..
+            } => *value_references,
+            CssWithPlaceholderResult::Unparseable | CssWithPlaceholderResult::NotFound => {
+                ModuleReferences::empty()
+            }
+        })
+    }
+
     #[turbo_tasks::function]
     async fn module_references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
-        let mut references = vec![];
+        // The modules values are imported from are loaded first, like `composes`
+        let mut references = self.value_references().await?.clone_value();
 
         for (_, class_names) in &*self.classes().await? {
             for class_name in class_names {
@@ -312,8 +358,15 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
     async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
         let classes = self.module.classes().await?;
+        let values = self.module.values().await?;
 
         let mut code = "__turbopack_export_value__({\n".to_string();
+        // Classes take precedence over values with the same name
+        for (name, value) in values.iter() {
+            if !classes.contains_key(name) {
+                writeln!(code, "  {}: {},", StringifyJs(name), StringifyJs(value))?;
+            }
+        }
         for (export_name, class_names) in &*classes {
             let mut exported_class_names = Vec::with_capacity(class_names.len());
 
@@ -0,0 +1,423 @@
+//! Support for `@value` in CSS modules, as implemented by
+//! `postcss-modules-values`:
+//!
+//! ```css
+//! @value primary: #0070f3;
+//! @value small from "./breakpoints.module.css";
+//! @value secondary as accent from "./colors.module.css";
+//!
+//! .button {
+//!   color: primary;
+//! }
+//!
+//! @media small {
+//!   .button {
+//!     background: accent;
+//!   }
+//! }
+//! ```
+//!
+//! Values are substituted in declaration values and in the preludes of
+//! `@media` and `@custom-media` rules before the stylesheet is parsed, as the
+//! parsers don't know about `@value`.
+
+use std::ops::Range;
+
+use indexmap::IndexMap;
+
+/// A `@value` rule at the top level of a stylesheet.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValueRule {
+    /// The byte range of the rule, including the trailing semicolon.
+    pub range: Range<usize>,
+    pub kind: ValueRuleKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValueRuleKind {
+    /// `@value name: value;`
+    Define { name: String, value: String },
+    /// `@value name, other as alias from "./file.module.css";`
+    ///
+    /// `names` maps the local names to the names in the imported module.
+    /// `from` is the unquoted specifier, or the name of a value that contains
+    /// the specifier.
+    Import {
+        names: Vec<(String, String)>,
+        from: String,
+    },
+}
+
+/// A part of a stylesheet ending at a `{`, `}` or `;` (or the end of the
+/// stylesheet) outside of comments, strings and parentheses.
+struct Segment {
+    range: Range<usize>,
+    terminator: Option<u8>,
+    depth: usize,
+}
+
+fn segments(code: &str) -> Vec<Segment> {
+    let bytes = code.as_bytes();
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = skip_comment(bytes, i);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i = skip_string(bytes, i, quote);
+                continue;
+            }
+            b'\\' => i += 1,
+            b'(' => parens += 1,
+            b')' => parens = parens.saturating_sub(1),
+            terminator @ (b'{' | b'}' | b';') if parens == 0 => {
+                segments.push(Segment {
+                    range: start..i,
+                    terminator: Some(terminator),
+                    depth,
+                });
+                match terminator {
+                    b'{' => depth += 1,
+                    b'}' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if start < bytes.len() {
+        segments.push(Segment {
+            range: start..bytes.len(),
+            terminator: None,
+            depth,
+        });
+    }
+    segments
+}
+
+/// Returns the index after the comment starting at `start`.
+fn skip_comment(bytes: &[u8], start: usize) -> usize {
+    bytes[start + 2..]
+        .windows(2)
+        .position(|w| w == b"*/")
+        .map_or(bytes.len(), |end| start + 2 + end + 2)
+}
+
+/// Returns the index after the string starting at `start`.
+fn skip_string(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == quote || b == b'\n' => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+fn trim_start_offset(code: &str, range: Range<usize>) -> usize {
+    let text = &code[range.clone()];
+    range.start + (text.len() - text.trim_start().len())
+}
+
+/// Finds the `@value` rules at the top level of the stylesheet.
+pub fn find_value_rules(code: &str) -> Vec<ValueRule> {
+    segments(code)
+        .into_iter()
+        .filter(|segment| segment.depth == 0 && segment.terminator != Some(b'{'))
+        .filter_map(|segment| {
+            let start = trim_start_offset(code, segment.range.clone());
+            let params = code[start..segment.range.end].strip_prefix("@value")?;
+            if !params.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let end = segment.range.end + usize::from(segment.terminator.is_some());
+            Some(ValueRule {
+                range: start..end,
+                kind: parse_value_rule(params.trim())?,
+            })
+        })
+        .collect()
+}
+
+fn parse_value_rule(params: &str) -> Option<ValueRuleKind> {
+    if let Some((names, from)) = split_import(params) {
+        let names = names
+            .split(',')
+            .map(|name| {
+                let mut parts = name.split_whitespace();
+                match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                    (Some(imported), None, None, None) => {
+                        Some((imported.to_string(), imported.to_string()))
+                    }
+                    (Some(imported), Some("as"), Some(local), None) => {
+                        Some((local.to_string(), imported.to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        return Some(ValueRuleKind::Import {
+            names,
+            from: unquote(from).unwrap_or(from).to_string(),
+        });
+    }
+    let name_end = params
+        .find(|c: char| !is_ident_char(c))
+        .unwrap_or(params.len());
+    if name_end == 0 {
+        return None;
+    }
+    let (name, value) = params.split_at(name_end);
+    let value = value.trim_start();
+    let value = value.strip_prefix(':').unwrap_or(value);
+    Some(ValueRuleKind::Define {
+        name: name.to_string(),
+        value: value.trim().to_string(),
+    })
+}
+
+/// Splits `names from specifier` at the last ` from `.
+fn split_import(params: &str) -> Option<(&str, &str)> {
+    let mut search = params;
+    let mut split = None;
+    while let Some(index) = search.rfind("from") {
+        let before = &search[..index];
+        let after = &params[index + 4..];
+        if before.ends_with(char::is_whitespace) && after.starts_with(char::is_whitespace) {
+            split = Some((before.trim(), after.trim()));
+            break;
+        }
+        search = before;
+    }
+    let (names, from) = split?;
+    (!names.is_empty() && !from.is_empty()).then_some((names, from))
+}
+
+/// Returns the content of a quoted string.
+pub fn unquote(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    value
+        .strip_prefix(quote)
+        .and_then(|value| value.strip_suffix(quote))
+}
+
+/// Replaces the names of values with their values in `text`. Comments,
+/// strings, `url()`s, function names and parts of other identifiers are
+/// left untouched.
+pub fn substitute_values(text: &str, values: &IndexMap<String, String>, out: &mut String) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let mut copied = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(bytes, i),
+            quote @ (b'"' | b'\'') => i = skip_string(bytes, i, quote),
+            b'\\' => {
+                // Skip the escaped character
+                i += 1 + text[i + 1..].chars().next().map_or(0, char::len_utf8);
+            }
+            _ => {
+                let Some(c) = text[i..].chars().next() else {
+                    break;
+                };
+                if !is_ident_char(c) {
+                    i += c.len_utf8();
+                    continue;
+                }
+                let end = text[i..]
+                    .find(|c: char| !is_ident_char(c))
+                    .map_or(text.len(), |len| i + len);
+                let ident = &text[i..end];
+                let prefixed = text[..i].ends_with(['#', '.', '@', '$']);
+                if ident.eq_ignore_ascii_case("url") && text[end..].starts_with('(') {
+                    // Skip the whole `url()`, it may contain unquoted urls
+                    let close = text[end..].find(')').map_or(text.len(), |len| end + len);
+                    i = close;
+                    continue;
+                }
+                if !prefixed && !text[end..].starts_with('(') {
+                    if let Some(value) = values.get(ident) {
+                        out.push_str(&text[copied..i]);
+                        out.push_str(value);
+                        copied = end;
+                    }
+                }
+                i = end;
+            }
+        }
+    }
+    out.push_str(&text[copied.min(text.len())..]);
+}
+
+/// Removes the `@value` rules from the stylesheet and substitutes the values
+/// in declaration values and at-rule preludes.
+pub fn replace_values(
+    code: &str,
+    rules: &[ValueRule],
+    values: &IndexMap<String, String>,
+) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut rules = rules.iter().peekable();
+    let mut copied = 0;
+    for segment in segments(code) {
+        let Range { start, end } = segment.range;
+        if start < copied {
+            continue;
+        }
+        if let Some(rule) = rules.next_if(|rule| rule.range.start < end) {
+            out.push_str(&code[copied..rule.range.start]);
+            // Keep the line numbers intact
+            out.extend(code[rule.range.clone()].matches('\n').map(|_| '\n'));
+            copied = rule.range.end;
+            continue;
+        }
+        let text_start = trim_start_offset(code, start..end);
+        let text = &code[text_start..end];
+        let substitute_from = if let Some(at_rule) = text.strip_prefix('@') {
+            // The prelude of an at-rule, e.g. `@media small`. Other at-rules
+            // like `@import` or `@keyframes` are left untouched.
+            let name_len = at_rule
+                .find(|c: char| !is_ident_char(c))
+                .unwrap_or(at_rule.len());
+            let name = &at_rule[..name_len];
+            (name.eq_ignore_ascii_case("media") || name.eq_ignore_ascii_case("custom-media"))
+                .then_some(text_start + 1 + name_len)
+        } else if segment.terminator == Some(b'{') {
+            // A selector
+            None
+        } else {
+            // The value of a declaration
+            find_colon(text).map(|offset| text_start + offset + 1)
+        };
+        if let Some(substitute_from) = substitute_from {
+            out.push_str(&code[copied..substitute_from]);
+            substitute_values(&code[substitute_from..end], values, &mut out);
+            copied = end;
+        }
+    }
+    out.push_str(&code[copied..]);
+    out
+}
+
+/// Returns the offset of the first `:` outside of comments and strings.
+fn find_colon(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(bytes, i),
+            quote @ (b'"' | b'\'') => i = skip_string(bytes, i, quote),
+            b':' => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::{find_value_rules, replace_values, ValueRuleKind};
+
+    fn values(values: &[(&str, &str)]) -> IndexMap<String, String> {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_definitions_and_imports() {
+        let code = r#"
+@value primary: #0070f3;
+@value spacing 4px;
+@value small, large as desktop from "./breakpoints.module.css";
+.a { color: red; }
+"#;
+        let kinds = find_value_rules(code)
+            .into_iter()
+            .map(|rule| rule.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ValueRuleKind::Define {
+                    name: "primary".into(),
+                    value: "#0070f3".into()
+                },
+                ValueRuleKind::Define {
+                    name: "spacing".into(),
+                    value: "4px".into()
+                },
+                ValueRuleKind::Import {
+                    names: vec![
+                        ("small".into(), "small".into()),
+                        ("desktop".into(), "large".into())
+                    ],
+                    from: "./breakpoints.module.css".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_nested_and_similar_rules() {
+        let code = "@values x: 1; .a { @value y: 2; }";
+        assert_eq!(find_value_rules(code), vec![]);
+    }
+
+    #[test]
+    fn substitutes_in_values_and_preludes() {
+        let code = "@value primary: blue;\n@media small { .primary { color: primary; border: 1px \
+                    solid primary-dark; background: url(primary.png); } }";
+        let rules = find_value_rules(code);
+        let result = replace_values(
+            code,
+            &rules,
+            &values(&[("primary", "blue"), ("small", "(max-width: 600px)")]),
+        );
+        assert_eq!(
+            result,
+            "\n@media (max-width: 600px) { .primary { color: blue; border: 1px solid \
+             primary-dark; background: url(primary.png); } }"
+        );
+    }
+
+    #[test]
+    fn only_substitutes_in_media_preludes() {
+        let code = "@import \"small.css\" small;\n@layer small;\n@keyframes small { from { \
+                    opacity: 0 } }\n@custom-media --small small;\n@MEDIA small {}";
+        let result = replace_values(code, &[], &values(&[("small", "(max-width: 600px)")]));
+        assert_eq!(
+            result,
+            "@import \"small.css\" small;\n@layer small;\n@keyframes small { from { opacity: 0 } \
+             }\n@custom-media --small (max-width: 600px);\n@MEDIA (max-width: 600px) {}"
+        );
+    }
+
+    #[test]
+    fn leaves_strings_and_comments_untouched() {
+        let code = ".a { content: \"primary\"; /* primary */ color: primary }";
+        let result = replace_values(code, &[], &values(&[("primary", "blue")]));
+        assert_eq!(
+            result,
+            ".a { content: \"primary\"; /* primary */ color: blue }"
+        );
+    }
+}
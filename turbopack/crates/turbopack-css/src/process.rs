@@ -34,7 +34,7 @@ use swc_core::{
 };
 use tracing::Instrument;
 use turbo_tasks::{RcStr, ValueToString, Vc};
-use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::ChunkingContext,
@@ -42,7 +42,7 @@ use turbopack_core::{
         Issue, IssueExt, IssueSource, IssueStage, OptionIssueSource, OptionStyledString,
         StyledString,
     },
-    reference::ModuleReferences,
+    reference::{ModuleReference, ModuleReferences},
     reference_type::ImportContext,
     resolve::origin::ResolveOrigin,
     source::Source,
@@ -54,10 +54,12 @@ use turbopack_swc_utils::emitter::IssueEmitter;
 
 use crate::{
     lifetime_util::stylesheet_into_static,
+    module_values::{find_value_rules, replace_values, substitute_values, ValueRuleKind},
     parse::InlineSourcesContentConfig,
     references::{
         analyze_references,
         url::{replace_url_references, resolve_url_reference, UrlAssetReference},
+        value::{css_module_value_references, imports_values_cyclically, CssModuleValueIssue},
     },
     CssModuleAssetType,
 };
@@ -274,6 +276,18 @@ impl StyleSheetLike<'_, '_> {
 #[turbo_tasks::value(transparent)]
 pub struct UnresolvedUrlReferences(pub Vec<(String, Vc<UrlAssetReference>)>);
 
+/// The values of a CSS module, defined or imported with `@value`.
+#[turbo_tasks::value(transparent)]
+pub struct CssModuleValues(IndexMap<String, String>);
+
+#[turbo_tasks::value_impl]
+impl CssModuleValues {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(IndexMap::new())
+    }
+}
+
 #[turbo_tasks::value(shared, serialization = "none", eq = "manual", cell = "new")]
 pub enum ParseCssResult {
     Ok {
@@ -289,6 +303,10 @@ pub enum ParseCssResult {
 
         url_references: Vc<UnresolvedUrlReferences>,
 
+        values: Vc<CssModuleValues>,
+
+        value_references: Vc<ModuleReferences>,
+
         #[turbo_tasks(trace_ignore)]
         options: ParserOptions<'static, 'static>,
     },
@@ -308,6 +326,10 @@ pub enum CssWithPlaceholderResult {
 
         url_references: Vc<UnresolvedUrlReferences>,
 
+        values: Vc<CssModuleValues>,
+
+        value_references: Vc<ModuleReferences>,
+
         #[turbo_tasks(trace_ignore)]
         exports: Option<IndexMap<String, CssModuleExport>>,
 
@@ -351,6 +373,8 @@ pub async fn process_css_with_placeholder(
             stylesheet,
             references,
             url_references,
+            values,
+            value_references,
             code,
             ..
         } => {
@@ -376,6 +400,8 @@ pub async fn process_css_with_placeholder(
                 exports,
                 references: *references,
                 url_references: *url_references,
+                values: *values,
+                value_references: *value_references,
                 placeholders: HashMap::new(),
             }
             .cell())
@@ -523,6 +549,18 @@ async fn process_content(
         }
     }
 
+    let (code, content_vc, values, value_references) = match ty {
+        CssModuleAssetType::Module => {
+            process_module_values(code, content_vc, fs_path_vc, source, origin).await?
+        }
+        CssModuleAssetType::Default => (
+            code,
+            content_vc,
+            CssModuleValues::empty(),
+            ModuleReferences::empty(),
+        ),
+    };
+
     let config = ParserOptions {
         css_modules: match ty {
             CssModuleAssetType::Module => Some(lightningcss::css_modules::Config {
@@ -708,11 +746,101 @@ async fn process_content(
         stylesheet,
         references: Vc::cell(references),
         url_references: Vc::cell(url_references),
+        values,
+        value_references,
         options: config,
     }
     .cell())
 }
 
+/// Resolves the `@value` rules of a CSS module and substitutes the values in
+/// the stylesheet. Returns the new code and its content, the values of the
+/// module and the references to the modules values are imported from.
+async fn process_module_values(
+    code: String,
+    content_vc: Vc<FileContent>,
+    fs_path_vc: Vc<FileSystemPath>,
+    source: Vc<Box<dyn Source>>,
+    origin: Vc<Box<dyn ResolveOrigin>>,
+) -> Result<(
+    String,
+    Vc<FileContent>,
+    Vc<CssModuleValues>,
+    Vc<ModuleReferences>,
+)> {
+    let rules = find_value_rules(&code);
+    if rules.is_empty() {
+        return Ok((
+            code,
+            content_vc,
+            CssModuleValues::empty(),
+            ModuleReferences::empty(),
+        ));
+    }
+
+    let value_references = css_module_value_references(source, origin).await?;
+    let mut value_references = value_references.iter();
+    let mut values = IndexMap::new();
+    let mut references: Vec<Vc<Box<dyn ModuleReference>>> = Vec::new();
+    for rule in &rules {
+        match &rule.kind {
+            ValueRuleKind::Define { name, value } => {
+                // Values can use previously defined values
+                let mut substituted = String::with_capacity(value.len());
+                substitute_values(value, &values, &mut substituted);
+                values.insert(name.clone(), substituted);
+            }
+            ValueRuleKind::Import { names, .. } => {
+                let Some((from, reference)) = value_references.next() else {
+                    bail!("missing reference for `@value` rule");
+                };
+                let reference = *reference;
+                references.push(Vc::upcast(reference));
+                let issue_source = reference.await?.issue_source;
+
+                // The values of a module that imports values from this module
+                // depend on its own values
+                if imports_values_cyclically(reference, source).await? {
+                    CssModuleValueIssue {
+                        file_path: fs_path_vc,
+                        message: format!(
+                            "Values can't be imported from module {from}, as it imports values \
+                             from this module."
+                        )
+                        .into(),
+                        source: Some(issue_source),
+                    }
+                    .cell()
+                    .emit();
+                    continue;
+                }
+
+                let imported = reference.imported_values().await?;
+                for (local, imported_name) in names {
+                    if let Some(value) = imported.get(imported_name) {
+                        values.insert(local.clone(), value.clone());
+                    } else {
+                        CssModuleValueIssue {
+                            file_path: fs_path_vc,
+                            message: format!(
+                                "Value `{imported_name}` is not defined in module {from}."
+                            )
+                            .into(),
+                            source: Some(issue_source),
+                        }
+                        .cell()
+                        .emit();
+                    }
+                }
+            }
+        }
+    }
+
+    let code = replace_values(&code, &rules, &values);
+    let content_vc = FileContent::Content(File::from(code.as_str())).cell();
+    Ok((code, content_vc, Vc::cell(values), Vc::cell(references)))
+}
+
 /// Visitor that lints wrong css module usage.
 ///
 /// ```css
@@ -13,7 +13,7 @@ use swc_core::{
 };
 use turbo_tasks::{RcStr, Value, ValueToString, Vc};
 use turbopack_core::{
-    chunk::{ChunkableModuleReference, ChunkingContext},
+    chunk::{ChunkableModuleReference, ChunkingContext, ChunkingType, ChunkingTypeOption},
     issue::IssueSource,
     reference::ModuleReference,
     reference_type::{CssReferenceSubType, ImportContext},
@@ -195,13 +195,16 @@ where
 }
 
 #[turbo_tasks::value]
-#[derive(Hash, Debug)]
+#[derive(Hash, Debug, Clone)]
 pub struct ImportAssetReference {
     pub origin: Vc<Box<dyn ResolveOrigin>>,
     pub request: Vc<Request>,
     pub attributes: Vc<ImportAttributes>,
     pub import_context: Vc<ImportContext>,
     pub issue_source: Vc<IssueSource>,
+    /// The imported stylesheet is written by the chunk item of the importing
+    /// stylesheet instead of becoming a chunk item itself.
+    pub inline: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -220,8 +223,18 @@ impl ImportAssetReference {
             attributes,
             import_context,
             issue_source,
+            inline: false,
         })
     }
+
+    /// Returns a copy of this reference which inlines the imported
+    /// stylesheet.
+    #[turbo_tasks::function]
+    pub async fn inlined(self: Vc<Self>) -> Result<Vc<Self>> {
+        let mut this = self.await?.clone_value();
+        this.inline = true;
+        Ok(this.cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -278,4 +291,16 @@ impl CodeGenerateable for ImportAssetReference {
 }
 
 #[turbo_tasks::value_impl]
-impl ChunkableModuleReference for ImportAssetReference {}
+impl ChunkableModuleReference for ImportAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(if self.inline {
+            // The content is written by the chunk item of the importing
+            // stylesheet, but the references of the imported stylesheet are
+            // still needed
+            ChunkingType::Passthrough
+        } else {
+            ChunkingType::default()
+        }))
+    }
+}
@@ -33,6 +33,7 @@ pub(crate) mod compose;
 pub(crate) mod import;
 pub(crate) mod internal;
 pub(crate) mod url;
+pub(crate) mod value;
 
 pub type AnalyzedRefs = (
     Vec<Vc<Box<dyn ModuleReference>>>,
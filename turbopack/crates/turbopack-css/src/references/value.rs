@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{RcStr, Value, ValueToString, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack_core::{
+    chunk::ChunkableModuleReference,
+    issue::{
+        Issue, IssueExt, IssueSeverity, IssueSource, IssueStage, OptionIssueSource,
+        OptionStyledString, StyledString,
+    },
+    reference::ModuleReference,
+    reference_type::CssReferenceSubType,
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+    source::Source,
+};
+
+use crate::{
+    module_values::{find_value_rules, substitute_values, unquote, ValueRuleKind},
+    references::css_resolve,
+    CssModuleValues, ModuleCssAsset,
+};
+
+/// A `@value ... from ...` CSS module reference.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct CssModuleValueReference {
+    pub origin: Vc<Box<dyn ResolveOrigin>>,
+    pub request: Vc<Request>,
+    pub issue_source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl CssModuleValueReference {
+    /// Creates a new [`CssModuleValueReference`].
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        issue_source: Vc<IssueSource>,
+    ) -> Vc<Self> {
+        Self::cell(CssModuleValueReference {
+            origin,
+            request,
+            issue_source,
+        })
+    }
+
+    /// The values of the referenced CSS module.
+    #[turbo_tasks::function]
+    pub async fn imported_values(self: Vc<Self>) -> Result<Vc<CssModuleValues>> {
+        let this = self.await?;
+        // Unresolvable requests are reported by the resolving already
+        let Some(module) = *self.resolve_reference().first_module().await? else {
+            return Ok(CssModuleValues::empty());
+        };
+        let Some(css_module) = Vc::try_resolve_downcast_type::<ModuleCssAsset>(module).await?
+        else {
+            CssModuleValueIssue {
+                file_path: this.origin.origin_path(),
+                message: format!(
+                    "Module {from} referenced in `@value ... from {from};` is not a CSS module.",
+                    from = this.request.to_string().await?
+                )
+                .into(),
+                source: Some(this.issue_source),
+            }
+            .cell()
+            .emit();
+            return Ok(CssModuleValues::empty());
+        };
+        Ok(css_module.values())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for CssModuleValueReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        css_resolve(
+            self.origin,
+            self.request,
+            // Values are imported from CSS modules like classes in `composes`
+            Value::new(CssReferenceSubType::Compose),
+            Some(self.issue_source),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for CssModuleValueReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!("value(url) {}", self.request.to_string().await?,).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for CssModuleValueReference {}
+
+/// The `@value ... from ...` references of a CSS module in the order of the
+/// rules, with the specifiers they were created from.
+#[turbo_tasks::value(transparent)]
+pub(crate) struct CssModuleValueReferences(Vec<(RcStr, Vc<CssModuleValueReference>)>);
+
+/// Creates the references of the `@value ... from ...` rules in `source`.
+///
+/// Unlike the values, they don't depend on the modules values are imported
+/// from, so imports can be followed without waiting for their values, see
+/// [imports_values_cyclically].
+#[turbo_tasks::function]
+pub(crate) async fn css_module_value_references(
+    source: Vc<Box<dyn Source>>,
+    origin: Vc<Box<dyn ResolveOrigin>>,
+) -> Result<Vc<CssModuleValueReferences>> {
+    let FileContent::Content(file) = &*source.content().file_content().await? else {
+        return Ok(Vc::cell(Vec::new()));
+    };
+    let Ok(code) = file.content().to_str() else {
+        return Ok(Vc::cell(Vec::new()));
+    };
+    let mut values = IndexMap::new();
+    let mut references = Vec::new();
+    for rule in find_value_rules(&code) {
+        match rule.kind {
+            ValueRuleKind::Define { name, value } => {
+                let mut substituted = String::with_capacity(value.len());
+                substitute_values(&value, &values, &mut substituted);
+                values.insert(name, substituted);
+            }
+            ValueRuleKind::Import { from, .. } => {
+                // The specifier can be stored in a value, e.g.
+                // `@value colors: "./colors.module.css";`
+                let from = RcStr::from(
+                    values
+                        .get(&from)
+                        .and_then(|value| unquote(value))
+                        .unwrap_or(&from),
+                );
+                let reference = CssModuleValueReference::new(
+                    origin,
+                    Request::parse(Value::new(from.clone().into())),
+                    IssueSource::from_byte_offset(source, rule.range.start, rule.range.end),
+                );
+                references.push((from, reference));
+            }
+        }
+    }
+    Ok(Vc::cell(references))
+}
+
+/// Whether the values imported by `reference` depend on the values of
+/// `source` itself, directly or through other CSS modules. Waiting for them
+/// would never finish.
+pub(crate) async fn imports_values_cyclically(
+    reference: Vc<CssModuleValueReference>,
+    source: Vc<Box<dyn Source>>,
+) -> Result<bool> {
+    let ident = source.ident().resolve().await?;
+    let mut visited = HashSet::new();
+    let mut stack = vec![reference];
+    while let Some(reference) = stack.pop() {
+        let Some(module) = *reference.resolve_reference().first_module().await? else {
+            continue;
+        };
+        let Some(module) = Vc::try_resolve_downcast_type::<ModuleCssAsset>(module).await? else {
+            continue;
+        };
+        if !visited.insert(module) {
+            continue;
+        }
+        if module.await?.source.ident().resolve().await? == ident {
+            return Ok(true);
+        }
+        stack.extend(
+            module
+                .value_import_references()
+                .await?
+                .iter()
+                .map(|(_, reference)| *reference),
+        );
+    }
+    Ok(false)
+}
+
+#[turbo_tasks::value(shared)]
+pub(crate) struct CssModuleValueIssue {
+    pub file_path: Vc<FileSystemPath>,
+    pub message: RcStr,
+    pub source: Option<Vc<IssueSource>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CssModuleValueIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("An issue occurred while resolving a CSS module `@value` rule".into())
+            .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Parse.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.message.clone()).cell()))
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(self.source)
+    }
+}
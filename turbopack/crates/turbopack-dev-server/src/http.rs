@@ -1,18 +1,29 @@
 use std::io::{Error, ErrorKind};
 
 use anyhow::{anyhow, Result};
+use async_compression::{
+    tokio::bufread::{BrotliEncoder, GzipEncoder, ZstdEncoder},
+    Level,
+};
 use auto_hash_map::AutoSet;
 use futures::{StreamExt, TryStreamExt};
 use hyper::{
-    header::{HeaderName, CONTENT_ENCODING, CONTENT_LENGTH},
+    header::{
+        HeaderName, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, ETAG, IF_NONE_MATCH, VARY,
+    },
     http::HeaderValue,
     Request, Response,
 };
+use indexmap::IndexMap;
 use mime::Mime;
-use tokio_util::io::{ReaderStream, StreamReader};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::io::AsyncReadExt;
+use tokio_util::io::StreamReader;
 use turbo_tasks::{util::SharedError, CollectiblesSource, ReadRef, TransientInstance, Vc};
 use turbo_tasks_bytes::Bytes;
-use turbo_tasks_fs::FileContent;
+use turbo_tasks_fs::{rope::Rope, FileContent};
+use turbo_tasks_hash::encode_hex;
 use turbopack_core::{
     asset::AssetContent,
     issue::{handle_issues, IssueReporter, IssueSeverity},
@@ -29,6 +40,9 @@ use crate::source::{
 enum GetFromSourceResult {
     Static {
         content: ReadRef<FileContent>,
+        /// The hash of the served content, identifies it in the ETag and the
+        /// cache of compressed bodies.
+        content_hash: u64,
         status_code: u16,
         headers: ReadRef<HeaderList>,
         header_overwrites: ReadRef<HeaderList>,
@@ -50,6 +64,7 @@ async fn get_from_source(
             if let AssetContent::File(file) = &*static_content.content.content().await? {
                 GetFromSourceResult::Static {
                     content: file.await?,
+                    content_hash: *file.hash().await?,
                     status_code: static_content.status_code,
                     headers: static_content.headers.await?,
                     header_overwrites: header_overwrites.await?,
@@ -77,6 +92,16 @@ pub async fn process_request_with_content_source(
     AutoSet<Vc<Box<dyn ContentSourceSideEffect>>>,
 )> {
     let original_path = request.uri().path().to_string();
+    let encoding = request
+        .headers()
+        .get(ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .and_then(negotiate_encoding);
+    let if_none_match = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
     let request = http_request_to_source_request(request).await?;
     let result = get_from_source(source, TransientInstance::new(request));
     let resolved_result = result.resolve_strongly_consistent().await?;
@@ -92,6 +117,7 @@ pub async fn process_request_with_content_source(
     match &*resolved_result.await? {
         GetFromSourceResult::Static {
             content,
+            content_hash,
             status_code,
             headers,
             header_overwrites,
@@ -166,22 +192,38 @@ pub async fn process_request_with_content_source(
                     );
                 }
 
-                let content = file.content();
-                let response = if should_compress {
-                    header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                // Also sent with 304 responses, so caches store the response per encoding
+                if should_compress {
+                    header_map.append(VARY, HeaderValue::from_static("accept-encoding"));
+                }
 
-                    // Grab ropereader stream, coerce anyhow::Error to std::io::Error
-                    let stream_ext = content
-                        .read()
-                        .into_stream()
-                        .map_err(|err| Error::new(ErrorKind::Other, err));
+                // The hash identifies the content, so it can be used to revalidate it. It's a
+                // weak ETag, as the content is encoded differently depending on the request.
+                if !header_map.contains_key(ETAG) {
+                    header_map.insert(ETAG, HeaderValue::try_from(content_etag(*content_hash))?);
+                }
+                if *status_code == 200 {
+                    let etag = header_map.get(ETAG).and_then(|etag| etag.to_str().ok());
+                    if let (Some(etag), Some(if_none_match)) = (etag, &if_none_match) {
+                        if etag_matches(if_none_match, etag) {
+                            return Ok((
+                                response.status(304).body(hyper::Body::empty())?,
+                                side_effects,
+                            ));
+                        }
+                    }
+                }
 
-                    let gzipped_stream =
-                        ReaderStream::new(async_compression::tokio::bufread::GzipEncoder::new(
-                            StreamReader::new(stream_ext),
-                        ));
+                let content = file.content();
+                let response = if let Some(encoding) = encoding.filter(|_| should_compress) {
+                    let body = compressed_body(*content_hash, encoding, content).await?;
+                    header_map.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.name()));
+                    header_map.insert(
+                        CONTENT_LENGTH,
+                        hyper::header::HeaderValue::try_from(body.len().to_string())?,
+                    );
 
-                    response.body(hyper::Body::wrap_stream(gzipped_stream))?
+                    response.body(hyper::Body::from(body))?
                 } else {
                     header_map.insert(
                         CONTENT_LENGTH,
@@ -219,6 +261,154 @@ pub async fn process_request_with_content_source(
     ))
 }
 
+/// The encodings the dev server can compress responses with, in the order of
+/// preference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum ContentEncoding {
+    Brotli,
+    Zstd,
+    Gzip,
+}
+
+impl ContentEncoding {
+    /// Content codings are case-insensitive, see RFC 9110, section 8.4.1.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "br" => ContentEncoding::Brotli,
+            "zstd" => ContentEncoding::Zstd,
+            "gzip" | "x-gzip" => ContentEncoding::Gzip,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ContentEncoding::Brotli => "br",
+            ContentEncoding::Zstd => "zstd",
+            ContentEncoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Picks the encoding with the highest quality value from an `Accept-Encoding`
+/// header, preferring brotli over zstd over gzip when the quality is the same.
+fn negotiate_encoding(accept_encoding: &str) -> Option<ContentEncoding> {
+    let mut best: Option<(ContentEncoding, f32)> = None;
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';');
+        let Some(encoding) = ContentEncoding::from_name(params.next().unwrap_or_default().trim())
+        else {
+            continue;
+        };
+        let quality = match params.find_map(|param| param.trim().strip_prefix("q=")) {
+            Some(quality) => quality.trim().parse::<f32>().unwrap_or(0.0),
+            None => 1.0,
+        };
+        if quality <= 0.0 {
+            continue;
+        }
+        let is_better = best.map_or(true, |(best_encoding, best_quality)| {
+            quality > best_quality || (quality == best_quality && encoding < best_encoding)
+        });
+        if is_better {
+            best = Some((encoding, quality));
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+fn content_etag(content_hash: u64) -> String {
+    format!("W/\"{}\"", encode_hex(content_hash))
+}
+
+/// Compares an `If-None-Match` header with an ETag, using the weak comparison.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    })
+}
+
+/// The total size of compressed bodies that are kept in memory.
+const COMPRESSED_BODIES_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// Compressed response bodies by the hash of the content and the encoding. The
+/// hash covers the served bytes, so entries never become stale. The oldest
+/// entries are evicted when the cache gets too large.
+#[derive(Default)]
+struct CompressedBodies {
+    bodies: IndexMap<(u64, ContentEncoding), hyper::body::Bytes>,
+    size: usize,
+}
+
+impl CompressedBodies {
+    fn get(&self, key: &(u64, ContentEncoding)) -> Option<hyper::body::Bytes> {
+        self.bodies.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: (u64, ContentEncoding), body: hyper::body::Bytes) {
+        if body.len() > COMPRESSED_BODIES_MAX_SIZE {
+            return;
+        }
+        self.size += body.len();
+        if let Some(previous) = self.bodies.insert(key, body) {
+            self.size -= previous.len();
+        }
+        while self.size > COMPRESSED_BODIES_MAX_SIZE {
+            let Some((_, evicted)) = self.bodies.shift_remove_index(0) else {
+                break;
+            };
+            self.size -= evicted.len();
+        }
+    }
+}
+
+static COMPRESSED_BODIES: Lazy<Mutex<CompressedBodies>> = Lazy::new(Default::default);
+
+/// Compresses the content, or returns the cached result for the same content.
+async fn compressed_body(
+    content_hash: u64,
+    encoding: ContentEncoding,
+    content: &Rope,
+) -> Result<hyper::body::Bytes> {
+    let key = (content_hash, encoding);
+    if let Some(body) = COMPRESSED_BODIES.lock().get(&key) {
+        return Ok(body);
+    }
+
+    // Grab ropereader stream, coerce anyhow::Error to std::io::Error
+    let reader = StreamReader::new(
+        content
+            .read()
+            .into_stream()
+            .map_err(|err| Error::new(ErrorKind::Other, err)),
+    );
+    let mut compressed = Vec::new();
+    match encoding {
+        ContentEncoding::Brotli => {
+            // The highest qualities are too slow to compress on the fly
+            BrotliEncoder::with_quality(reader, Level::Precise(5))
+                .read_to_end(&mut compressed)
+                .await?
+        }
+        ContentEncoding::Zstd => {
+            ZstdEncoder::new(reader)
+                .read_to_end(&mut compressed)
+                .await?
+        }
+        ContentEncoding::Gzip => {
+            GzipEncoder::new(reader)
+                .read_to_end(&mut compressed)
+                .await?
+        }
+    };
+
+    let body = hyper::body::Bytes::from(compressed);
+    COMPRESSED_BODIES.lock().insert(key, body.clone());
+    Ok(body)
+}
+
 async fn http_request_to_source_request(request: Request<hyper::Body>) -> Result<SourceRequest> {
     let (parts, body) = request.into_parts();
 
@@ -243,3 +433,74 @@ async fn http_request_to_source_request(request: Request<hyper::Body>) -> Result
         body: Body::new(bytes),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_encoding() {
+        assert_eq!(
+            negotiate_encoding("gzip, deflate, br, zstd"),
+            Some(ContentEncoding::Brotli)
+        );
+        assert_eq!(
+            negotiate_encoding("gzip, zstd"),
+            Some(ContentEncoding::Zstd)
+        );
+        assert_eq!(
+            negotiate_encoding("br;q=0.5, gzip;q=0.8"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            negotiate_encoding("br;q=0, gzip"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            negotiate_encoding("GZIP, Br;q=0.5"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(negotiate_encoding("deflate, identity"), None);
+        assert_eq!(negotiate_encoding(""), None);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = content_etag(0xabc);
+        assert!(etag.starts_with("W/\""));
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(etag.trim_start_matches("W/"), &etag));
+        assert!(etag_matches(&format!("\"other\", {etag}"), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches(&content_etag(0xabcd), &etag));
+    }
+
+    #[test]
+    fn test_compressed_bodies_eviction() {
+        let mut bodies = CompressedBodies::default();
+        let half = hyper::body::Bytes::from(vec![0; COMPRESSED_BODIES_MAX_SIZE / 2]);
+        let first = (1, ContentEncoding::Gzip);
+        let second = (2, ContentEncoding::Gzip);
+        let third = (2, ContentEncoding::Brotli);
+
+        bodies.insert(first, half.clone());
+        bodies.insert(second, half.clone());
+        assert!(bodies.get(&first).is_some());
+        assert_eq!(bodies.size, COMPRESSED_BODIES_MAX_SIZE);
+
+        // The oldest entry is evicted
+        bodies.insert(third, half.clone());
+        assert!(bodies.get(&first).is_none());
+        assert!(bodies.get(&second).is_some());
+        assert!(bodies.get(&third).is_some());
+        assert_eq!(bodies.size, COMPRESSED_BODIES_MAX_SIZE);
+
+        // Bodies larger than the cache are not stored
+        bodies.insert(
+            first,
+            hyper::body::Bytes::from(vec![0; COMPRESSED_BODIES_MAX_SIZE + 1]),
+        );
+        assert!(bodies.get(&first).is_none());
+        assert_eq!(bodies.size, COMPRESSED_BODIES_MAX_SIZE);
+    }
+}
@@ -11,10 +11,12 @@ mod http;
 pub mod introspect;
 mod invalidation;
 pub mod source;
+mod tls;
 pub mod update;
 
 use std::{
     collections::VecDeque,
+    fmt,
     future::Future,
     net::{SocketAddr, TcpListener},
     pin::Pin,
@@ -24,13 +26,14 @@ use std::{
 
 use anyhow::{Context, Result};
 use hyper::{
-    server::{conn::AddrIncoming, Builder},
+    server::conn::AddrIncoming,
     service::{make_service_fn, service_fn},
     Request, Response, Server,
 };
 use parking_lot::Mutex;
 use socket2::{Domain, Protocol, Socket, Type};
 use tokio::task::JoinHandle;
+use tokio_rustls::rustls::ServerConfig;
 use tracing::{event, info_span, Instrument, Level, Span};
 use turbo_tasks::{
     run_once_with_reason, trace::TraceRawVcs, util::FormatDuration, TurboTasksApi, Vc,
@@ -40,6 +43,9 @@ use turbopack_core::{
     issue::{handle_issues, IssueReporter, IssueSeverity},
 };
 
+#[doc(hidden)]
+pub use self::http::process_request_with_content_source;
+pub use self::tls::TlsConfig;
 use self::{source::ContentSource, update::UpdateServer};
 use crate::{
     invalidation::{ServerRequest, ServerRequestSideEffects},
@@ -60,18 +66,32 @@ where
     }
 }
 
-#[derive(TraceRawVcs, Debug)]
+#[derive(TraceRawVcs)]
 pub struct DevServerBuilder {
     #[turbo_tasks(trace_ignore)]
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
-    server: Builder<AddrIncoming>,
+    incoming: AddrIncoming,
+    #[turbo_tasks(trace_ignore)]
+    tls: Option<Arc<ServerConfig>>,
+}
+
+impl fmt::Debug for DevServerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DevServerBuilder")
+            .field("addr", &self.addr)
+            .field("incoming", &self.incoming)
+            .field("tls", &self.tls.is_some())
+            .finish()
+    }
 }
 
 #[derive(TraceRawVcs)]
 pub struct DevServer {
     #[turbo_tasks(trace_ignore)]
     pub addr: SocketAddr,
+    /// Whether the server is served over HTTPS.
+    pub https: bool,
     #[turbo_tasks(trace_ignore)]
     pub future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
 }
@@ -106,12 +126,30 @@ impl DevServer {
         let addr = listener
             .local_addr()
             .context("not able to get bound address")?;
-        let server = Server::from_tcp(listener).context("Not able to start server")?;
-        Ok(DevServerBuilder { addr, server })
+        listener
+            .set_nonblocking(true)
+            .context("not able to set socket to non-blocking")?;
+        let incoming = AddrIncoming::from_listener(
+            tokio::net::TcpListener::from_std(listener).context("Not able to start server")?,
+        )
+        .context("Not able to start server")?;
+        Ok(DevServerBuilder {
+            addr,
+            incoming,
+            tls: None,
+        })
     }
 }
 
 impl DevServerBuilder {
+    /// Serves over HTTPS with the given certificate. HTTP/2 is negotiated with
+    /// browsers that support it, which avoids the limit of concurrent
+    /// HTTP/1.1 connections when loading many chunks.
+    pub fn tls(mut self, config: &TlsConfig) -> Result<Self> {
+        self.tls = Some(config.server_config()?);
+        Ok(self)
+    }
+
     pub fn serve(
         self,
         turbo_tasks: Arc<dyn TurboTasksApi>,
@@ -278,10 +316,12 @@ impl DevServerBuilder {
                 anyhow::Ok(service_fn(handler))
             }
         });
-        let server = self.server.serve(make_svc);
+        let https = self.tls.is_some();
+        let server = Server::builder(tls::accept(self.incoming, self.tls)).serve(make_svc);
 
         DevServer {
             addr: self.addr,
+            https,
             future: Box::pin(async move {
                 server.await?;
                 Ok(())
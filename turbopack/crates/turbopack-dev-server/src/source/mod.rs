@@ -27,7 +27,7 @@ use turbopack_core::version::{Version, VersionedContent};
 
 use self::{
     headers::Headers, issue_context::IssueFilePathContentSource, query::Query,
-    route_tree::RouteTree,
+    route_tree::RouteTree, wrapping_source::ContentSourceProcessor,
 };
 
 /// The result of proxying a request to another HTTP server.
@@ -508,6 +508,10 @@ pub struct Rewrite {
     /// A [HeaderList] which will overwrite the values used during the lookup
     /// process. All headers not present in this list will be deleted.
     pub request_headers: Option<Vc<HeaderList>>,
+
+    /// [ContentSourceProcessor]s which will be applied, in order, to the
+    /// eventual, fully resolved content result.
+    pub processors: Vec<Vc<Box<dyn ContentSourceProcessor>>>,
 }
 
 pub struct RewriteBuilder {
@@ -521,6 +525,7 @@ impl RewriteBuilder {
                 ty: RewriteType::Location { path_and_query },
                 response_headers: None,
                 request_headers: None,
+                processors: Vec::new(),
             },
         }
     }
@@ -537,6 +542,7 @@ impl RewriteBuilder {
                 },
                 response_headers: None,
                 request_headers: None,
+                processors: Vec::new(),
             },
         }
     }
@@ -547,6 +553,7 @@ impl RewriteBuilder {
                 ty: RewriteType::Sources { sources },
                 response_headers: None,
                 request_headers: None,
+                processors: Vec::new(),
             },
         }
     }
@@ -14,6 +14,7 @@ use super::{
     headers::{HeaderValue, Headers},
     query::Query,
     request::SourceRequest,
+    wrapping_source::ContentSourceProcessor,
     ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataVary,
     GetContentSourceContent, HeaderList, ProxyResult, RewriteType, StaticContent,
 };
@@ -46,6 +47,7 @@ pub async fn resolve_source_request(
     let mut current_asset_path: RcStr = urlencoding::decode(&original_path[1..])?.into();
     let mut request_overwrites = (*request).clone();
     let mut response_header_overwrites = Vec::new();
+    let mut processors = Vec::new();
     let mut route_tree = source.get_routes().resolve_strongly_consistent().await?;
     'routes: loop {
         let mut sources = route_tree.get(current_asset_path.clone());
@@ -54,7 +56,18 @@ pub async fn resolve_source_request(
                 let content_vary = get_content.vary().strongly_consistent().await?;
                 let content_data =
                     request_to_data(&request_overwrites, &request, &content_vary).await?;
-                let content = get_content.get(current_asset_path.clone(), Value::new(content_data));
+                let mut content =
+                    get_content.get(current_asset_path.clone(), Value::new(content_data));
+                if !processors.is_empty()
+                    && !matches!(
+                        &*content.strongly_consistent().await?,
+                        ContentSourceContent::Rewrite(_)
+                    )
+                {
+                    for processor in &processors {
+                        content = processor.process(content);
+                    }
+                }
                 match &*content.strongly_consistent().await? {
                     ContentSourceContent::Rewrite(rewrite) => {
                         let rewrite = rewrite.await?;
@@ -62,6 +75,9 @@ pub async fn resolve_source_request(
                         if let Some(headers) = &rewrite.response_headers {
                             response_header_overwrites.extend(headers.await?.iter().cloned());
                         }
+                        // processors of later rewrites are nested in the ones of earlier
+                        // rewrites, so they are applied first
+                        processors.splice(0..0, rewrite.processors.iter().copied());
                         if let Some(headers) = &rewrite.request_headers {
                             request_overwrites.headers.clear();
                             for (name, value) in &*headers.await? {
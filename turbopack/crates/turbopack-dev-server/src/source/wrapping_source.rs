@@ -2,8 +2,9 @@ use anyhow::Result;
 use turbo_tasks::{RcStr, Value, Vc};
 
 use super::{
-    ContentSourceContent, ContentSourceData, ContentSourceDataVary, GetContentSourceContent,
-    Rewrite, RewriteType,
+    route_tree::{MapGetContentSourceContent, RouteTree},
+    ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataVary, ContentSources,
+    GetContentSourceContent, Rewrite, RewriteType,
 };
 
 /// A ContentSourceProcessor handles the final processing of an eventual
@@ -21,7 +22,9 @@ pub trait ContentSourceProcessor {
 /// A WrappedGetContentSourceContent simply wraps the get_content of a
 /// [ContentSourceResult], allowing us to process whatever
 /// [ContentSourceContent] it would have returned.
-
+///
+/// Rewrites returned by the inner get_content are followed too, so the content
+/// they eventually resolve to is processed as well.
 #[turbo_tasks::value]
 pub struct WrappedGetContentSourceContent {
     inner: Vc<Box<dyn GetContentSourceContent>>,
@@ -58,9 +61,16 @@ impl GetContentSourceContent for WrappedGetContentSourceContent {
             return Ok(ContentSourceContent::Rewrite(
                 Rewrite {
                     ty: match &rewrite.ty {
-                        RewriteType::Location { .. } | RewriteType::ContentSource { .. } => todo!(
-                            "Rewrites for WrappedGetContentSourceContent are not implemented yet"
-                        ),
+                        // Locations are looked up in the sources of the server, so the
+                        // processor is applied to the content they resolve to
+                        RewriteType::Location { .. } => rewrite.ty.clone(),
+                        RewriteType::ContentSource {
+                            source,
+                            path_and_query,
+                        } => RewriteType::ContentSource {
+                            source: Vc::upcast(WrappedContentSource::new(*source, self.processor)),
+                            path_and_query: path_and_query.clone(),
+                        },
                         RewriteType::Sources { sources } => RewriteType::Sources {
                             sources: Vc::cell(
                                 sources
@@ -78,6 +88,15 @@ impl GetContentSourceContent for WrappedGetContentSourceContent {
                     },
                     response_headers: rewrite.response_headers,
                     request_headers: rewrite.request_headers,
+                    processors: match &rewrite.ty {
+                        RewriteType::Location { .. } => rewrite
+                            .processors
+                            .iter()
+                            .copied()
+                            .chain([self.processor])
+                            .collect(),
+                        _ => rewrite.processors.clone(),
+                    },
                 }
                 .cell(),
             )
@@ -86,3 +105,59 @@ impl GetContentSourceContent for WrappedGetContentSourceContent {
         Ok(self.processor.process(res))
     }
 }
+
+/// A [ContentSource] that processes all content of the inner [ContentSource]
+/// with a [ContentSourceProcessor], including the content of rewrites.
+#[turbo_tasks::value]
+pub struct WrappedContentSource {
+    inner: Vc<Box<dyn ContentSource>>,
+    processor: Vc<Box<dyn ContentSourceProcessor>>,
+}
+
+#[turbo_tasks::value_impl]
+impl WrappedContentSource {
+    #[turbo_tasks::function]
+    pub fn new(
+        inner: Vc<Box<dyn ContentSource>>,
+        processor: Vc<Box<dyn ContentSourceProcessor>>,
+    ) -> Vc<Self> {
+        WrappedContentSource { inner, processor }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for WrappedContentSource {
+    #[turbo_tasks::function]
+    fn get_routes(&self) -> Vc<RouteTree> {
+        self.inner.get_routes().map_routes(Vc::upcast(
+            WrappedContentSourceMapper {
+                processor: self.processor,
+            }
+            .cell(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn get_children(&self) -> Vc<ContentSources> {
+        Vc::cell(vec![self.inner])
+    }
+}
+
+#[turbo_tasks::value]
+struct WrappedContentSourceMapper {
+    processor: Vc<Box<dyn ContentSourceProcessor>>,
+}
+
+#[turbo_tasks::value_impl]
+impl MapGetContentSourceContent for WrappedContentSourceMapper {
+    #[turbo_tasks::function]
+    fn map_get_content(
+        &self,
+        get_content: Vc<Box<dyn GetContentSourceContent>>,
+    ) -> Vc<Box<dyn GetContentSourceContent>> {
+        Vc::upcast(WrappedGetContentSourceContent::new(
+            get_content,
+            self.processor,
+        ))
+    }
+}
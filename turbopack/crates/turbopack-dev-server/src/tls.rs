@@ -0,0 +1,205 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context as TaskContext, Poll},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use futures::{Stream, StreamExt};
+use hyper::server::{
+    accept::Accept,
+    conn::{AddrIncoming, AddrStream},
+};
+use rcgen::{CertificateParams, SanType};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_rustls::{
+    rustls::{Certificate, PrivateKey, ServerConfig},
+    server::TlsStream,
+    TlsAcceptor,
+};
+
+/// Connections that don't complete the TLS handshake in this time are closed.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum number of concurrent TLS handshakes.
+const MAX_CONCURRENT_HANDSHAKES: usize = 64;
+
+/// The certificate the dev server uses to serve HTTPS.
+#[derive(Debug, Clone)]
+pub enum TlsConfig {
+    /// A PEM encoded certificate (chain) and private key.
+    Files { cert: PathBuf, key: PathBuf },
+    /// A self-signed certificate, generated on startup, that is valid for the
+    /// given hostnames and IP addresses. Browsers show a warning for it, so
+    /// use a locally trusted certificate (e.g. created by `mkcert`) for APIs
+    /// that need a secure context.
+    SelfSigned { hostnames: Vec<String> },
+}
+
+impl TlsConfig {
+    /// Creates the server configuration, which offers HTTP/2 and HTTP/1.1 via
+    /// ALPN.
+    pub(crate) fn server_config(&self) -> Result<Arc<ServerConfig>> {
+        let (certs, key) = match self {
+            TlsConfig::Files { cert, key } => (read_certs(cert)?, read_private_key(key)?),
+            TlsConfig::SelfSigned { hostnames } => {
+                let cert = self_signed_certificate(hostnames)
+                    .context("unable to generate a self-signed certificate")?;
+                (
+                    vec![Certificate(cert.serialize_der()?)],
+                    PrivateKey(cert.serialize_private_key_der()),
+                )
+            }
+        };
+        let mut config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("invalid certificate or private key")?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+}
+
+/// Generates a certificate for `hostnames`. IP addresses are added as IP
+/// address SANs, as browsers don't match them against DNS names.
+fn self_signed_certificate(hostnames: &[String]) -> Result<rcgen::Certificate> {
+    let mut params = CertificateParams::new(Vec::<String>::new());
+    params.subject_alt_names = hostnames
+        .iter()
+        .map(|hostname| match hostname.parse::<IpAddr>() {
+            Ok(ip) => SanType::IpAddress(ip),
+            Err(_) => SanType::DnsName(hostname.clone()),
+        })
+        .collect();
+    Ok(rcgen::Certificate::from_params(params)?)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let file = File::open(path)
+        .with_context(|| format!("unable to read certificate {}", path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .with_context(|| format!("unable to parse certificate {}", path.display()))?;
+    if certs.is_empty() {
+        bail!("no certificate found in {}", path.display());
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKey> {
+    let file = File::open(path)
+        .with_context(|| format!("unable to read private key {}", path.display()))?;
+    let items = rustls_pemfile::read_all(&mut BufReader::new(file))
+        .with_context(|| format!("unable to parse private key {}", path.display()))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .with_context(|| format!("no private key found in {}", path.display()))
+}
+
+/// A connection to the dev server, with or without TLS.
+pub(crate) enum DevServerStream {
+    Plain(AddrStream),
+    Tls(Box<TlsStream<AddrStream>>),
+}
+
+impl AsyncRead for DevServerStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            DevServerStream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            DevServerStream::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for DevServerStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            DevServerStream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            DevServerStream::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            DevServerStream::Plain(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            DevServerStream::Tls(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            DevServerStream::Plain(stream) => stream.is_write_vectored(),
+            DevServerStream::Tls(stream) => stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            DevServerStream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            DevServerStream::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            DevServerStream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            DevServerStream::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Accepts connections from `incoming`, and performs the TLS handshake when a
+/// TLS configuration is given. Failed handshakes are dropped instead of
+/// stopping the server, which happens when a browser rejects a self-signed
+/// certificate.
+pub(crate) fn accept(
+    mut incoming: AddrIncoming,
+    tls: Option<Arc<ServerConfig>>,
+) -> impl Accept<Conn = DevServerStream, Error = io::Error> {
+    let connections = futures::stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx));
+    let connections: Pin<Box<dyn Stream<Item = io::Result<DevServerStream>> + Send>> = match tls {
+        None => Box::pin(connections.map(|conn| conn.map(DevServerStream::Plain))),
+        Some(config) => {
+            let acceptor = TlsAcceptor::from(config);
+            Box::pin(
+                connections
+                    .filter_map(|conn| async move { conn.ok() })
+                    .map(move |conn| {
+                        let handshake = acceptor.accept(conn);
+                        async move { tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await }
+                    })
+                    .buffer_unordered(MAX_CONCURRENT_HANDSHAKES)
+                    .filter_map(|result| async move {
+                        match result {
+                            Ok(Ok(stream)) => Some(Ok(DevServerStream::Tls(Box::new(stream)))),
+                            Ok(Err(_)) | Err(_) => None,
+                        }
+                    }),
+            )
+        }
+    };
+    hyper::server::accept::from_stream(connections)
+}
@@ -0,0 +1,102 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::{env::current_dir, sync::Arc, time::SystemTime};
+
+use hyper::{client::conn, Body, Request, StatusCode, Version};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    rustls::{
+        client::{ServerCertVerified, ServerCertVerifier},
+        Certificate, ClientConfig, Error as TlsError, ServerName,
+    },
+    TlsConnector,
+};
+use turbo_tasks::{TransientInstance, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+use turbopack_core::issue::IssueSeverity;
+use turbopack_dev_server::{source::NoContentSource, DevServer, TlsConfig};
+
+static REGISTRATION: Registration = register!(turbopack_dev_server::register);
+
+/// Accepts the self-signed certificate of the dev server.
+struct AcceptAnyCertificate;
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+#[tokio::test]
+async fn negotiates_http2() {
+    run(&REGISTRATION, || async {
+        let current_dir = current_dir()?;
+        let server = DevServer::listen("127.0.0.1:0".parse()?)?
+            .tls(&TlsConfig::SelfSigned {
+                hostnames: vec!["localhost".to_string(), "127.0.0.1".to_string()],
+            })?
+            .serve(
+                turbo_tasks::turbo_tasks(),
+                || Vc::upcast(NoContentSource::new()),
+                Arc::new(move || {
+                    Vc::upcast(ConsoleUi::new(TransientInstance::new(LogOptions {
+                        current_dir: current_dir.clone(),
+                        project_dir: current_dir.clone(),
+                        show_all: false,
+                        log_detail: false,
+                        log_level: IssueSeverity::Warning,
+                    })))
+                }),
+            );
+        let addr = server.addr;
+        let server = tokio::spawn(server.future);
+
+        let mut config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(
+                ServerName::try_from("localhost")?,
+                TcpStream::connect(addr).await?,
+            )
+            .await?;
+        let (_, connection) = stream.get_ref();
+        assert_eq!(connection.alpn_protocol(), Some(&b"h2"[..]));
+        // 127.0.0.1 is an IP address SAN (tag 7) instead of a DNS name
+        let certificate = &connection.peer_certificates().unwrap()[0];
+        assert!(certificate
+            .0
+            .windows(6)
+            .any(|bytes| bytes == [0x87, 4, 127, 0, 0, 1]));
+
+        let (mut sender, connection) = conn::Builder::new()
+            .http2_only(true)
+            .handshake(stream)
+            .await?;
+        tokio::spawn(connection);
+        let response = sender
+            .send_request(
+                Request::get(format!("https://localhost:{}/", addr.port())).body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.version(), Version::HTTP_2);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        server.abort();
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
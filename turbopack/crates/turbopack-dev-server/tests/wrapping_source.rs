@@ -0,0 +1,246 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use hyper::Request;
+use turbo_tasks::{RawVc, RcStr, TransientInstance, TransientValue, Vc};
+use turbo_tasks_fs::File;
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::{
+    asset::AssetContent,
+    issue::{CapturedIssues, IssueReporter, IssueSeverity},
+    version::VersionedContentExt,
+};
+use turbopack_dev_server::{
+    process_request_with_content_source,
+    source::{
+        combined::CombinedContentSource,
+        route_tree::{BaseSegment, RouteTree, RouteTrees, RouteType},
+        wrapping_source::{ContentSourceProcessor, WrappedContentSource},
+        ContentSource, ContentSourceContent, HeaderList, RewriteBuilder,
+    },
+};
+
+static REGISTRATION: Registration = register!(turbopack_dev_server::register);
+
+/// Serves fixed content on exact paths.
+#[turbo_tasks::value(shared)]
+struct TestContentSource {
+    routes: Vec<(RcStr, Vc<ContentSourceContent>)>,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for TestContentSource {
+    #[turbo_tasks::function]
+    fn get_routes(&self) -> Vc<RouteTree> {
+        let routes = self
+            .routes
+            .iter()
+            .map(|(path, content)| {
+                RouteTree::new_route(
+                    BaseSegment::from_static_pathname(path).collect(),
+                    RouteType::Exact,
+                    Vc::upcast(*content),
+                )
+            })
+            .collect();
+        Vc::<RouteTrees>::cell(routes).merge()
+    }
+}
+
+/// Appends an `x-processed` header with its name to static content.
+#[turbo_tasks::value(shared)]
+struct HeaderProcessor {
+    name: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSourceProcessor for HeaderProcessor {
+    #[turbo_tasks::function]
+    async fn process(&self, content: Vc<ContentSourceContent>) -> Result<Vc<ContentSourceContent>> {
+        let ContentSourceContent::Static(static_content) = *content.await? else {
+            return Ok(content);
+        };
+        let static_content = static_content.await?;
+        let mut headers = static_content.headers.await?.clone_value();
+        headers.push(("x-processed".into(), self.name.clone()));
+        Ok(ContentSourceContent::static_with_headers(
+            static_content.content,
+            static_content.status_code,
+            HeaderList::new(headers),
+        ))
+    }
+}
+
+/// Ignores all issues, the tests only look at the responses.
+#[turbo_tasks::value(shared)]
+struct IgnoreIssues;
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for IgnoreIssues {
+    #[turbo_tasks::function]
+    fn report_issues(
+        &self,
+        _issues: TransientInstance<CapturedIssues>,
+        _source: TransientValue<RawVc>,
+        _min_failing_severity: Vc<IssueSeverity>,
+    ) -> Vc<bool> {
+        Vc::cell(false)
+    }
+}
+
+fn source(routes: Vec<(&str, Vc<ContentSourceContent>)>) -> Vc<Box<dyn ContentSource>> {
+    Vc::upcast(
+        TestContentSource {
+            routes: routes
+                .into_iter()
+                .map(|(path, content)| (path.into(), content))
+                .collect(),
+        }
+        .cell(),
+    )
+}
+
+fn wrapped(inner: Vc<Box<dyn ContentSource>>, name: &str) -> Vc<Box<dyn ContentSource>> {
+    Vc::upcast(WrappedContentSource::new(
+        inner,
+        Vc::upcast(HeaderProcessor { name: name.into() }.cell()),
+    ))
+}
+
+fn page() -> Vc<ContentSourceContent> {
+    ContentSourceContent::static_content(AssetContent::file(File::from("page").into()).versioned())
+}
+
+fn rewrite(rewrite: RewriteBuilder) -> Vc<ContentSourceContent> {
+    ContentSourceContent::Rewrite(rewrite.build()).cell()
+}
+
+/// Requests `path` from `source` and returns the `x-processed` headers of the
+/// response.
+async fn processed_headers(
+    source: Vc<Box<dyn ContentSource>>,
+    path: &str,
+) -> Result<Vec<(RcStr, RcStr)>> {
+    let request = Request::get(path).body(hyper::Body::empty())?;
+    let (response, _) =
+        process_request_with_content_source(source, request, Vc::upcast(IgnoreIssues.cell()))
+            .await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "{path} responded with {}",
+        response.status()
+    );
+    response
+        .headers()
+        .get_all("x-processed")
+        .iter()
+        .map(|value| Ok(("x-processed".into(), value.to_str()?.into())))
+        .collect()
+}
+
+fn processed(names: &[&str]) -> Vec<(RcStr, RcStr)> {
+    names
+        .iter()
+        .map(|name| ("x-processed".into(), (*name).into()))
+        .collect()
+}
+
+#[tokio::test]
+async fn location_rewrite() {
+    run(&REGISTRATION, || async {
+        // The location is looked up in all sources of the server, not only in
+        // the wrapped one
+        let server = Vc::upcast(CombinedContentSource::new(vec![
+            wrapped(
+                source(vec![(
+                    "rewrite",
+                    rewrite(RewriteBuilder::new("/page".into())),
+                )]),
+                "wrapped",
+            ),
+            source(vec![("page", page())]),
+        ]));
+        assert_eq!(
+            processed_headers(server, "/rewrite").await?,
+            processed(&["wrapped"])
+        );
+        assert_eq!(processed_headers(server, "/page").await?, processed(&[]));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn nested_location_rewrite() {
+    run(&REGISTRATION, || async {
+        let server = Vc::upcast(CombinedContentSource::new(vec![
+            wrapped(
+                wrapped(
+                    source(vec![(
+                        "rewrite",
+                        rewrite(RewriteBuilder::new("/page".into())),
+                    )]),
+                    "inner",
+                ),
+                "outer",
+            ),
+            wrapped(source(vec![("page", page())]), "page"),
+        ]));
+        assert_eq!(
+            processed_headers(server, "/rewrite").await?,
+            processed(&["page", "inner", "outer"])
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn content_source_rewrite() {
+    run(&REGISTRATION, || async {
+        let target = source(vec![("page", page())]);
+        let server = wrapped(
+            source(vec![(
+                "rewrite",
+                rewrite(RewriteBuilder::new_source_with_path_and_query(
+                    target,
+                    "/page".into(),
+                )),
+            )]),
+            "wrapped",
+        );
+        assert_eq!(
+            processed_headers(server, "/rewrite").await?,
+            processed(&["wrapped"])
+        );
+        assert_eq!(processed_headers(target, "/page").await?, processed(&[]));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn sources_rewrite() {
+    run(&REGISTRATION, || async {
+        let server = wrapped(
+            source(vec![(
+                "rewrite",
+                rewrite(RewriteBuilder::new_sources(Vc::cell(vec![Vc::upcast(
+                    page(),
+                )]))),
+            )]),
+            "wrapped",
+        );
+        assert_eq!(
+            processed_headers(server, "/rewrite").await?,
+            processed(&["wrapped"])
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -6,7 +6,10 @@ use turbo_tasks::{
 };
 use turbo_tasks_hash::hash_xxh3_hash64;
 use turbopack_core::{
-    chunk::ModuleId,
+    chunk::{
+        module_id_strategies::{ModuleIdRecords, ReservedModuleIds},
+        ModuleId,
+    },
     module::{Module, Modules},
     reference::ModuleReference,
 };
@@ -170,6 +173,23 @@ const JS_MAX_SAFE_INTEGER: u64 = (1u64 << 53) - 1;
 pub async fn merge_preprocessed_module_ids(
     preprocessed_module_ids: Vec<Vc<PreprocessedChildrenIdents>>,
 ) -> Result<IndexMap<RcStr, ModuleId>> {
+    let (module_id_map, _) = merge_preprocessed_module_ids_with_records(
+        preprocessed_module_ids,
+        &ModuleIdRecords::default(),
+    )
+    .await?;
+    Ok(module_id_map)
+}
+
+/// Like [merge_preprocessed_module_ids], but modules that have an id in
+/// `records` keep it, so ids don't change when other modules are added or
+/// removed. New modules get an id that is not recorded for any module,
+/// including removed ones. Also returns the [ReservedModuleIds], which report
+/// the modules that lost their recorded id to another module.
+pub async fn merge_preprocessed_module_ids_with_records(
+    preprocessed_module_ids: Vec<Vc<PreprocessedChildrenIdents>>,
+    records: &ModuleIdRecords,
+) -> Result<(IndexMap<RcStr, ModuleId>, ReservedModuleIds)> {
     let mut merged_module_ids = IndexMap::new();
 
     for preprocessed_module_ids in preprocessed_module_ids {
@@ -178,28 +198,69 @@ pub async fn merge_preprocessed_module_ids(
         }
     }
 
+    // Recorded ids are reserved first, so new modules can't take them.
+    let mut reserved = records.reserve(merged_module_ids.keys());
+
     // 5% fill rate, as done in Webpack
     // https://github.com/webpack/webpack/blob/27cf3e59f5f289dfc4d76b7a1df2edbc4e651589/lib/ids/IdHelpers.js#L366-L405
-    let optimal_range = merged_module_ids.len() * 20;
+    // The ids of removed modules count towards the fill rate, as they stay reserved. The
+    // recorded ids of the current modules are already counted as modules.
+    let reserved_only_ids = reserved.used_ids.len() - reserved.modules.len();
+    let optimal_range = (merged_module_ids.len() + reserved_only_ids) * 20;
     let digit_mask = std::cmp::min(
         10u64.pow((optimal_range as f64).log10().ceil() as u32),
         JS_MAX_SAFE_INTEGER,
     );
 
     let mut module_id_map = IndexMap::new();
-    let mut used_ids = IndexSet::new();
 
     for (module_ident, full_hash) in merged_module_ids.iter() {
-        let mut trimmed_hash = full_hash % digit_mask;
-        let mut i = 1;
-        while used_ids.contains(&trimmed_hash) {
-            // If the id is already used, seek to find another available id.
-            trimmed_hash = hash_xxh3_hash64(full_hash + i) % digit_mask;
-            i += 1;
+        if let Some(&recorded_id) = reserved.modules.get(module_ident) {
+            module_id_map.insert(module_ident.clone(), ModuleId::Number(recorded_id));
+            continue;
         }
-        used_ids.insert(trimmed_hash);
+        let trimmed_hash = unused_module_id(*full_hash, digit_mask, &reserved.used_ids);
+        reserved.used_ids.insert(trimmed_hash);
         module_id_map.insert(module_ident.clone(), ModuleId::Number(trimmed_hash));
     }
 
-    Ok(module_id_map)
+    Ok((module_id_map, reserved))
+}
+
+/// Trims `full_hash` to the digits of `digit_mask`. If the id is already used,
+/// seeks to find another available id.
+fn unused_module_id(full_hash: u64, digit_mask: u64, used_ids: &IndexSet<u64>) -> u64 {
+    let mut trimmed_hash = full_hash % digit_mask;
+    let mut i = 1u64;
+    while used_ids.contains(&trimmed_hash) {
+        trimmed_hash = hash_xxh3_hash64(full_hash.wrapping_add(i)) % digit_mask;
+        i += 1;
+    }
+    trimmed_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexSet;
+
+    use super::unused_module_id;
+
+    #[test]
+    fn unused_module_id_avoids_used_ids() {
+        assert_eq!(unused_module_id(1234, 100, &IndexSet::new()), 34);
+
+        let used_ids = IndexSet::from([34]);
+        let id = unused_module_id(1234, 100, &used_ids);
+        assert!(id < 100);
+        assert_ne!(id, 34);
+    }
+
+    #[test]
+    fn unused_module_id_wraps_around() {
+        let digit_mask = 1000;
+        let used_ids = IndexSet::from([u64::MAX % digit_mask]);
+        let id = unused_module_id(u64::MAX, digit_mask, &used_ids);
+        assert!(id < digit_mask);
+        assert!(!used_ids.contains(&id));
+    }
 }
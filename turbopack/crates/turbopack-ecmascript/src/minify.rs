@@ -1,4 +1,4 @@
-use std::{io::Write, sync::Arc};
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use swc_core::{
@@ -112,12 +112,6 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
             ParseResultSourceMap::new(cm, src_map_buf, original_map).cell(),
         )),
     );
-
-    write!(
-        builder,
-        "\n\n//# sourceMappingURL={}.map",
-        urlencoding::encode(path.file_name())
-    )?;
     Ok(builder.build().cell())
 }
 
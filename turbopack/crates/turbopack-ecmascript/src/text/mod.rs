@@ -5,6 +5,7 @@ use turbopack_core::{
     asset::{Asset, AssetContent},
     ident::AssetIdent,
     source::Source,
+    source_transform::SourceTransform,
 };
 
 use crate::utils::StringifyJs;
@@ -54,3 +55,23 @@ impl Asset for TextContentFileSource {
         Ok(AssetContent::file(content))
     }
 }
+
+/// A [SourceTransform] that turns a source into a [TextContentFileSource].
+#[turbo_tasks::value]
+pub struct TextContentSourceTransform;
+
+#[turbo_tasks::value_impl]
+impl TextContentSourceTransform {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        TextContentSourceTransform.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for TextContentSourceTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(TextContentFileSource::new(source))
+    }
+}
@@ -0,0 +1,36 @@
+/// Reads the dimensions of an AVIF image from the `ispe` (image spatial
+/// extents) property in its container, so no AV1 decoder is needed.
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let meta = find_box(bytes, b"meta")?;
+    // `meta` is a full box, the content starts after the version and flags
+    let iprp = find_box(meta.get(4..)?, b"iprp")?;
+    let ipco = find_box(iprp, b"ipco")?;
+    // The first `ispe` belongs to the primary image in all common encoders
+    let ispe = find_box(ipco, b"ispe")?;
+    let width = u32::from_be_bytes(ispe.get(4..8)?.try_into().ok()?);
+    let height = u32::from_be_bytes(ispe.get(8..12)?.try_into().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Returns the content of the first ISO base media file format box with the
+/// type `ty` in `bytes`.
+fn find_box<'a>(mut bytes: &'a [u8], ty: &[u8; 4]) -> Option<&'a [u8]> {
+    while bytes.len() >= 8 {
+        let (header_len, size) = match u32::from_be_bytes(bytes[0..4].try_into().ok()?) {
+            // The box extends to the end of the file
+            0 => (8, bytes.len() as u64),
+            // The size is stored as 64 bit integer after the type
+            1 => (16, u64::from_be_bytes(bytes.get(8..16)?.try_into().ok()?)),
+            size => (8, size as u64),
+        };
+        let size = usize::try_from(size).ok()?;
+        if size < header_len || size > bytes.len() {
+            return None;
+        }
+        if &bytes[4..8] == ty {
+            return Some(&bytes[header_len..size]);
+        }
+        bytes = &bytes[size..];
+    }
+    None
+}
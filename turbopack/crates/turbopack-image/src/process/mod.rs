@@ -1,3 +1,4 @@
+mod avif;
 pub mod svg;
 
 use std::{io::Cursor, str::FromStr};
@@ -134,7 +135,7 @@ enum ImageBuffer {
 }
 
 fn load_image_internal(
-    ident: Vc<AssetIdent>,
+    #[cfg_attr(feature = "webp", allow(unused_variables))] ident: Vc<AssetIdent>,
     bytes: &[u8],
     extension: Option<&str>,
 ) -> Result<(ImageBuffer, Option<ImageFormat>)> {
@@ -160,21 +161,10 @@ fn load_image_internal(
     // This is a stop gap until we have proper encoding/decoding in majority of the
     // platforms
 
-    #[cfg(not(feature = "avif"))]
+    // AVIF images can only be decoded with the native decoder. The metadata is
+    // still read from the container, see `get_meta_data`.
+    #[cfg(not(feature = "avif-native"))]
     if matches!(format, Some(ImageFormat::Avif)) {
-        ImageProcessingIssue {
-            path: ident.path(),
-            message: StyledString::Text(
-                "This version of Turbopack does not support AVIF images, will emit without \
-                 optimization or encoding"
-                    .into(),
-            )
-            .cell(),
-            title: Some(StyledString::Text("AVIF image not supported".into()).cell()),
-            issue_severity: Some(IssueSeverity::Warning.into()),
-        }
-        .cell()
-        .emit();
         return Ok((ImageBuffer::Raw(bytes.to_vec()), format));
     }
 
@@ -224,6 +214,16 @@ fn compute_blur_data(
     }
 }
 
+/// Whether [encode_image] supports the format in the current build.
+fn can_encode(format: ImageFormat) -> bool {
+    match format {
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Ico | ImageFormat::Bmp => true,
+        ImageFormat::WebP => cfg!(feature = "webp"),
+        ImageFormat::Avif => cfg!(feature = "avif"),
+        _ => false,
+    }
+}
+
 fn encode_image(image: DynamicImage, format: ImageFormat, quality: u8) -> Result<(Vec<u8>, Mime)> {
     let mut buf = Vec::new();
     let (width, height) = image.dimensions();
@@ -376,7 +376,21 @@ pub async fn get_meta_data(
     };
 
     match image {
-        ImageBuffer::Raw(..) => Ok(ImageMetaData::fallback_value(None).cell()),
+        ImageBuffer::Raw(..) => {
+            if matches!(format, Some(ImageFormat::Avif)) {
+                if let Some((width, height)) = avif::dimensions(&bytes) {
+                    return Ok(ImageMetaData {
+                        width,
+                        height,
+                        mime_type: Some(Mime::from_str("image/avif")?),
+                        // The image can't be decoded to compute a real placeholder
+                        blur_placeholder: blur_placeholder.map(|_| BlurPlaceholder::fallback()),
+                    }
+                    .cell());
+                }
+            }
+            Ok(ImageMetaData::fallback_value(None).cell())
+        }
         ImageBuffer::Decoded(image) => {
             let (width, height) = image.dimensions();
             let blur_placeholder = if let Some(blur_placeholder) = blur_placeholder {
@@ -430,7 +444,7 @@ pub async fn optimize(
     };
     match image {
         ImageBuffer::Raw(buffer) => {
-            #[cfg(not(feature = "avif"))]
+            #[cfg(not(feature = "avif-native"))]
             if matches!(format, Some(ImageFormat::Avif)) {
                 return Ok(FileContent::Content(
                     File::from(buffer).with_content_type(Mime::from_str("image/avif")?),
@@ -460,6 +474,18 @@ pub async fn optimize(
             .cell())
         }
         ImageBuffer::Decoded(image) => {
+            let format = format.unwrap_or(ImageFormat::Jpeg);
+            if !can_encode(format) {
+                // Emit the original image instead of failing, e.g. re-encoding a GIF would
+                // drop the animation
+                return Ok(
+                    FileContent::Content(File::from(bytes.into_owned()).with_content_type(
+                        image_format_to_mime_type(format)?.unwrap_or(mime::IMAGE_JPEG),
+                    ))
+                    .cell(),
+                );
+            }
+
             let (width, height) = image.dimensions();
             let image = if width > max_width || height > max_height {
                 image.resize(max_width, max_height, FilterType::Lanczos3)
@@ -467,7 +493,6 @@ pub async fn optimize(
                 image
             };
 
-            let format = format.unwrap_or(ImageFormat::Jpeg);
             let (data, mime_type) = encode_image(image, format, quality)?;
 
             Ok(FileContent::Content(File::from(data).with_content_type(mime_type)).cell())
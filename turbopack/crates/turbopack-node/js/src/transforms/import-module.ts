@@ -0,0 +1,8 @@
+// @ts-ignore
+import * as importedModule from "MODULE";
+
+// Evaluates a module for `this.importModule` of a webpack loader. The exports
+// are sent back to the loader serialized as JSON.
+export default function importModule() {
+  return importedModule;
+}
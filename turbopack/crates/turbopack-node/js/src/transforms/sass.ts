@@ -0,0 +1,170 @@
+// @ts-ignore
+import sass from "@vercel/turbopack/sass";
+import { relative, isAbsolute, join, sep, dirname } from "path";
+import { fileURLToPath, pathToFileURL } from "url";
+import type { Ipc } from "../ipc/evaluate";
+import type { IpcInfoMessage, IpcRequestMessage } from "./webpack-loaders";
+
+const contextDir = process.cwd();
+
+function toPath(file: string) {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath)) {
+    throw new Error(
+      `Cannot depend on path (${file}) outside of root directory (${contextDir})`
+    );
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+}
+
+/**
+ * Like `toPath`, but returns `undefined` for files outside of the root
+ * directory.
+ */
+function toProjectPath(file: string) {
+  const relPath = relative(contextDir, file);
+  if (
+    isAbsolute(relPath) ||
+    relPath === ".." ||
+    relPath.startsWith(`..${sep}`)
+  ) {
+    return undefined;
+  }
+  return toPath(file);
+}
+
+function fromPath(path: string) {
+  return join(contextDir, sep !== "/" ? path.replaceAll("/", sep) : path);
+}
+
+// Resolve the way sass-loader does: prefer the `sass` and `style` entries of
+// packages and fall back to the defaults.
+const RESOLVE_OPTIONS = {
+  aliasFields: undefined,
+  conditionNames: ["sass", "style", "..."],
+  noPackageJson: false,
+  extensions: [".sass", ".scss", ".css"],
+  mainFields: ["sass", "style", "..."],
+  noExportsField: false,
+  mainFiles: ["_index", "index"],
+  noModules: false,
+  preferRelative: true,
+};
+
+// Options that can't be passed through the configuration, as they are functions
+// or are set by the transform itself. `additionalData` is prepended by the
+// caller already.
+const IGNORED_OPTIONS = [
+  "implementation",
+  "additionalData",
+  "prependData",
+  "importer",
+  "importers",
+  "functions",
+  "logger",
+  "url",
+  "syntax",
+  "sourceMap",
+  "sourceMapIncludeSources",
+];
+
+export default async function transform(
+  ipc: Ipc<IpcInfoMessage, IpcRequestMessage>,
+  content: string,
+  name: string,
+  syntax: "scss" | "indented",
+  options: Record<string, any> | null
+) {
+  options = options ?? {};
+  const resourcePath = fromPath(name);
+  const compileOptions: Record<string, any> = Object.fromEntries(
+    Object.entries(options).filter(([key]) => !IGNORED_OPTIONS.includes(key))
+  );
+
+  const resolve = async (lookupPath: string, request: string) => {
+    const result = (await ipc.sendRequest({
+      type: "resolve",
+      options: RESOLVE_OPTIONS,
+      lookupPath: toPath(lookupPath),
+      request,
+    })) as { path: string };
+    return fromPath(result.path);
+  };
+
+  // Relative loads are handled by Sass itself. Everything else is resolved
+  // through Turbopack, so aliases and the `exports` of packages are respected.
+  const importer = {
+    async findFileUrl(url: string, context: { containingUrl: URL | null }) {
+      if (url.startsWith("sass:")) {
+        return null;
+      }
+      // The webpack convention for imports from node_modules
+      const request = url.startsWith("~") ? url.slice(1) : url;
+      const lookupPath =
+        context.containingUrl?.protocol === "file:"
+          ? dirname(fileURLToPath(context.containingUrl))
+          : dirname(resourcePath);
+      try {
+        return pathToFileURL(await resolve(lookupPath, request));
+      } catch {}
+      // Partials and index files are not known to the resolver. Sass applies
+      // its own resolution when the URL points into the package instead.
+      const match = /^((?:@[^/]+\/)?[^/@]+)\/(.+)$/.exec(request);
+      if (!match) {
+        return null;
+      }
+      const [, packageName, subpath] = match;
+      try {
+        const packageJson = await resolve(
+          lookupPath,
+          `${packageName}/package.json`
+        );
+        return pathToFileURL(join(dirname(packageJson), subpath));
+      } catch {
+        return null;
+      }
+    },
+  };
+
+  const result = await sass.compileStringAsync(content, {
+    ...compileOptions,
+    url: pathToFileURL(resourcePath),
+    syntax,
+    sourceMap: true,
+    sourceMapIncludeSources: true,
+    importers: [importer],
+  });
+
+  for (const url of result.loadedUrls) {
+    if (url.protocol !== "file:") {
+      continue;
+    }
+    const file = fileURLToPath(url);
+    if (file === resourcePath) {
+      continue;
+    }
+    // Files outside of the root directory can't be watched
+    const path = toProjectPath(file);
+    if (path !== undefined) {
+      ipc.sendInfo({
+        type: "fileDependency",
+        path,
+      });
+    }
+  }
+
+  let map;
+  if (result.sourceMap) {
+    map = {
+      ...result.sourceMap,
+      sources: result.sourceMap.sources.map((source: string) =>
+        source.startsWith("file:") ? fileURLToPath(source) : source
+      ),
+    };
+  }
+
+  return {
+    css: result.css,
+    map: map && JSON.stringify(map),
+  };
+}
@@ -42,15 +42,38 @@ export type IpcInfoMessage =
     logType: string;
     args: any[];
     trace?: StackFrame[];
+  }
+  | {
+    type: "unsupportedApi";
+    name: string;
+  };
+
+export type IpcRequestMessage =
+  | {
+    type: "resolve";
+    options: any;
+    lookupPath: string;
+    request: string;
+  }
+  | {
+    type: "loadModule";
+    lookupPath: string;
+    request: string;
+  }
+  | {
+    type: "importModule";
+    lookupPath: string;
+    request: string;
   };
 
-export type IpcRequestMessage = {
-  type: "resolve";
-  options: any;
-  lookupPath: string;
-  request: string;
+type EmittedFile = {
+  file: string;
+  content: string | { binary: string };
 };
 
+// Parts of the webpack loader context that Turbopack can't provide
+const UNSUPPORTED_APIS = ["_compiler", "_compilation", "fs"];
+
 type LoaderConfig =
   | string
   | {
@@ -78,6 +101,17 @@ const toPath = (file: string) => {
 const fromPath = (path: string) => {
   return join(contextDir, sep !== "/" ? path.replaceAll("/", sep) : path);
 };
+/**
+ * Like `toPath`, but returns `undefined` for files outside of the root
+ * directory.
+ */
+const toProjectPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath) || relPath === ".." || relPath.startsWith(`..${sep}`)) {
+    return undefined;
+  }
+  return toPath(file);
+};
 
 const LogType = Object.freeze({
   error: "error",
@@ -179,10 +213,19 @@ const transform = (
       typeof loader === "string" ? { loader, options: {} } : loader
     );
 
+    const assets: EmittedFile[] = [];
+
+    const resolvedLoaders = loadersWithOptions.map((loader) => ({
+      loader: __turbopack_external_require__.resolve(loader.loader, {
+        paths: [resourceDir],
+      }),
+      options: loader.options,
+    }));
+
     runLoaders(
       {
         resource: resource + query,
-        context: {
+        context: withUnsupportedApis(ipc, {
           _module: {
             // For debugging purpose, if someone find context is not full compatible to
             // webpack they can guess this comes from turbopack
@@ -328,6 +371,84 @@ const transform = (
           },
           emitWarning: makeErrorEmitter("warning", ipc),
           emitError: makeErrorEmitter("error", ipc),
+          emitFile(name: string, content: string | Buffer) {
+            assets.push({
+              file: name,
+              content: Buffer.isBuffer(content)
+                ? { binary: content.toString("base64") }
+                : content,
+            });
+          },
+          loadModule(
+            this: { context: string; addDependency(file: string): void },
+            request: string,
+            callback: (
+              err: Error | null,
+              source?: string | Buffer,
+              sourceMap?: unknown,
+              module?: unknown
+            ) => void
+          ) {
+            Promise.resolve()
+              .then(() =>
+                ipc.sendRequest({
+                  type: "loadModule",
+                  lookupPath: toPath(this.context),
+                  request,
+                })
+              )
+              .then(
+                (unknownResult) => {
+                  const result = unknownResult as {
+                    path: string;
+                    source: string | { binary: string };
+                  };
+                  const source =
+                    typeof result.source === "string"
+                      ? result.source
+                      : Buffer.from(result.source.binary, "base64");
+                  this.addDependency(fromPath(result.path));
+                  callback(null, source, undefined, {
+                    resource: fromPath(result.path),
+                  });
+                },
+                (err) => callback(err)
+              );
+          },
+          importModule(
+            this: { context: string; addDependency(file: string): void },
+            request: string,
+            _options: unknown,
+            callback?: (err?: Error | null, exports?: unknown) => void
+          ) {
+            const promise = Promise.resolve()
+              .then(() =>
+                ipc.sendRequest({
+                  type: "importModule",
+                  lookupPath: toPath(this.context),
+                  request,
+                })
+              )
+              .then((unknownResult) => {
+                const result = unknownResult as {
+                  exports: unknown;
+                  dependencies: string[];
+                };
+                for (const dep of result.dependencies) {
+                  this.addDependency(fromPath(dep));
+                }
+                // Exports are serialized as JSON, so functions and classes are lost
+                return result.exports;
+              });
+            if (callback) {
+              promise.then(
+                (exports) => callback(null, exports),
+                (err) => callback(err)
+              );
+            } else {
+              return promise;
+            }
+          },
           getLogger(name: unknown) {
             const logFn = (logType: string, ...args: unknown[]) => {
               let trace;
@@ -438,14 +559,9 @@ const transform = (
               },
             };
           },
-        },
+        }),
 
-        loaders: loadersWithOptions.map((loader) => ({
-          loader: __turbopack_external_require__.resolve(loader.loader, {
-            paths: [resourceDir],
-          }),
-          options: loader.options,
-        })),
+        loaders: resolvedLoaders,
         readResource: (_filename, callback) => {
           // TODO assuming the filename === resource, but loaders might change that
           callback(null, Buffer.from(content, "utf-8"));
@@ -468,6 +584,16 @@ const transform = (
         }
         if (!result.result) return reject(new Error("No result from loaders"));
         const [source, map] = result.result;
+        // Used to validate persistently cached results. Results that depend on
+        // files outside of the project can't be validated and are not cached.
+        const files = result.fileDependencies.map(toProjectPath);
+        const dirs = result.contextDependencies.map(toProjectPath);
+        const dependencies = [...files, ...dirs].every((dep) => dep !== undefined)
+          ? {
+            files,
+            dirs: dirs.map((path) => ({ path, glob: "**" })),
+          }
+          : undefined;
         resolve({
           source: Buffer.isBuffer(source) ? { binary: source.toString('base64') } : source,
           map:
@@ -476,6 +602,8 @@ const transform = (
               : typeof map === "object"
                 ? JSON.stringify(map)
                 : undefined,
+          assets,
+          dependencies,
         });
       }
     );
@@ -484,6 +612,25 @@ const transform = (
 
 export { transform as default };
 
+/**
+ * Reports the use of parts of the loader context that Turbopack can't
+ * provide, instead of failing with an unhelpful error in the loader.
+ */
+function withUnsupportedApis(
+  ipc: Ipc<IpcInfoMessage, IpcRequestMessage>,
+  context: any
+) {
+  for (const name of UNSUPPORTED_APIS) {
+    Object.defineProperty(context, name, {
+      get() {
+        ipc.sendInfo({ type: "unsupportedApi", name });
+        return undefined;
+      },
+    });
+  }
+  return context;
+}
+
 function makeErrorEmitter(
   severity: "warning" | "error",
   ipc: Ipc<IpcInfoMessage, IpcRequestMessage>
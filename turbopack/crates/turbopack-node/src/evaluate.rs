@@ -57,9 +57,17 @@ enum EvalJavaScriptOutgoingMessage<'a> {
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum EvalJavaScriptIncomingMessage {
-    Info { data: JsonValue },
-    Request { id: u64, data: JsonValue },
-    End { data: Option<String> },
+    Info {
+        data: JsonValue,
+    },
+    Request {
+        id: u64,
+        data: JsonValue,
+    },
+    End {
+        data: Option<String>,
+        rss: Option<u64>,
+    },
     Error(StructuredError),
 }
 
@@ -416,7 +424,12 @@ async fn pull_operation<T: EvaluateContext>(
                 // Issue emitted, we want to break but don't want to return an error
                 break ControlFlow::Break(Ok(None));
             }
-            EvalJavaScriptIncomingMessage::End { data } => break ControlFlow::Break(Ok(data)),
+            EvalJavaScriptIncomingMessage::End { data, rss } => {
+                if let Some(rss) = rss {
+                    operation.report_memory_usage(rss);
+                }
+                break ControlFlow::Break(Ok(data));
+            }
             EvalJavaScriptIncomingMessage::Info { data } => {
                 evaluate_context
                     .info(state, serde_json::from_value(data)?, pool)
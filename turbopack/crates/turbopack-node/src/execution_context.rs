@@ -8,6 +8,9 @@ pub struct ExecutionContext {
     pub project_path: Vc<FileSystemPath>,
     pub chunking_context: Vc<Box<dyn ChunkingContext>>,
     pub env: Vc<Box<dyn ProcessEnv>>,
+    /// Where files emitted by webpack loaders via `this.emitFile` are written.
+    /// Defaults to the output root of the `chunking_context`.
+    pub emit_root: Option<Vc<FileSystemPath>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -22,6 +25,16 @@ impl ExecutionContext {
             project_path,
             chunking_context,
             env,
+            emit_root: None,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn with_emit_root(&self, emit_root: Vc<FileSystemPath>) -> Vc<Self> {
+        ExecutionContext {
+            emit_root: Some(emit_root),
+            ..*self
         }
         .cell()
     }
@@ -9,6 +9,7 @@ use std::{collections::HashMap, iter::once, thread::available_parallelism};
 use anyhow::{bail, Result};
 use indexmap::IndexSet;
 pub use node_entry::{NodeEntry, NodeRenderingEntries, NodeRenderingEntry};
+pub use pool::{configure_node_js_pools, node_js_pools_stats, NodeJsPoolOptions, NodeJsPoolsStats};
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
     Completion, Completions, RcStr, TryJoinIterExt, ValueToString, Vc,
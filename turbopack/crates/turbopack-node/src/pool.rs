@@ -7,13 +7,19 @@ use std::{
     mem::take,
     path::{Path, PathBuf},
     process::{ExitStatus, Stdio},
-    sync::Arc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Weak,
+    },
+    thread::available_parallelism,
     time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
 use futures::join;
 use indexmap::IndexSet;
+use once_cell::sync::{Lazy, OnceCell};
 use owo_colors::{OwoColorize, Style};
 use parking_lot::Mutex;
 use serde::{de::DeserializeOwned, Serialize};
@@ -24,8 +30,9 @@ use tokio::{
     },
     net::{TcpListener, TcpStream},
     process::{Child, ChildStderr, ChildStdout, Command},
+    runtime::Handle,
     select,
-    sync::{OwnedSemaphorePermit, Semaphore},
+    sync::{OwnedSemaphorePermit, Semaphore, SemaphorePermit},
     time::{sleep, timeout},
 };
 use turbo_tasks::{duration_span, RcStr, Vc};
@@ -67,6 +74,414 @@ impl FormattingMode {
     }
 }
 
+/// Options of all Node.js pools, see [configure_node_js_pools]. Options that
+/// aren't set fall back to the environment variables of [NodeJsPoolConfig].
+#[derive(Clone, Debug, Default)]
+pub struct NodeJsPoolOptions {
+    pub shared: Option<bool>,
+    pub max_workers: Option<usize>,
+    pub max_worker_rss_mb: Option<u64>,
+    pub worker_idle_timeout_ms: Option<u64>,
+}
+
+static OPTIONS: OnceCell<NodeJsPoolOptions> = OnceCell::new();
+
+/// Configures all Node.js pools, e. g. from the config of the project. Only
+/// the first call takes effect, and only when no pool has been created yet.
+pub fn configure_node_js_pools(options: NodeJsPoolOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+/// Configuration of all Node.js pools, read from the [NodeJsPoolOptions] or
+/// the environment once.
+struct NodeJsPoolConfig {
+    /// `TURBOPACK_NODE_POOL=shared`: All pools share one concurrency limit and
+    /// warm Node.js processes. Idle processes of other pools are stopped when
+    /// a pool needs to boot up a process and the worker limit is reached.
+    shared: bool,
+    /// `TURBOPACK_NODE_POOL_MAX_WORKERS`: The number of workers across all
+    /// pools in the shared mode. Defaults to the available parallelism.
+    max_workers: usize,
+    /// `TURBOPACK_NODE_WORKER_MAX_RSS_MB`: Processes with a larger resident
+    /// set size after an evaluation are recycled instead of being reused.
+    max_worker_rss: Option<u64>,
+    /// `TURBOPACK_NODE_WORKER_IDLE_TIMEOUT_MS`: Processes that are idle for
+    /// longer are stopped.
+    idle_timeout: Option<Duration>,
+}
+
+impl NodeJsPoolConfig {
+    fn exceeds_max_worker_rss(&self, rss: u64) -> bool {
+        self.max_worker_rss.is_some_and(|max_rss| rss > max_rss)
+    }
+}
+
+fn env_var<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok()?.parse().ok()
+}
+
+static CONFIG: Lazy<NodeJsPoolConfig> = Lazy::new(|| {
+    let options = OPTIONS.get().cloned().unwrap_or_default();
+    NodeJsPoolConfig {
+        shared: options.shared.unwrap_or_else(|| {
+            std::env::var("TURBOPACK_NODE_POOL").is_ok_and(|mode| mode == "shared")
+        }),
+        max_workers: options
+            .max_workers
+            .or_else(|| env_var("TURBOPACK_NODE_POOL_MAX_WORKERS"))
+            .filter(|&max_workers| max_workers > 0)
+            .unwrap_or_else(|| available_parallelism().map_or(1, |v| v.get())),
+        max_worker_rss: options
+            .max_worker_rss_mb
+            .or_else(|| env_var("TURBOPACK_NODE_WORKER_MAX_RSS_MB"))
+            .map(|mb| mb * 1024 * 1024),
+        idle_timeout: options
+            .worker_idle_timeout_ms
+            .or_else(|| env_var("TURBOPACK_NODE_WORKER_IDLE_TIMEOUT_MS"))
+            .map(Duration::from_millis),
+    }
+});
+
+/// Limits the number of concurrent operations of all pools in the shared mode.
+static SHARED_CONCURRENCY_SEMAPHORE: Lazy<Arc<Semaphore>> =
+    Lazy::new(|| Arc::new(Semaphore::new(CONFIG.max_workers)));
+
+/// The idle processes of all pools, used for stats and to stop idle processes
+/// of other pools in the shared mode.
+static POOLS: Lazy<Mutex<Vec<Weak<IdleProcesses>>>> = Lazy::new(Default::default);
+
+static SPARE_PROCESSES: Lazy<Mutex<HashMap<SpareProcessKey, SpawnedProcess>>> =
+    Lazy::new(Default::default);
+
+static RECYCLED_WORKERS: AtomicU32 = AtomicU32::new(0);
+static IDLE_TIMED_OUT_WORKERS: AtomicU32 = AtomicU32::new(0);
+
+/// Waits for the path of the entrypoint on stdin and loads it, so the process
+/// looks like it was started with `node <entrypoint> <port>`.
+const BOOTSTRAP_SCRIPT: &str = r#"process.stdin.once("data", (data) => {
+  process.stdin.destroy();
+  const entrypoint = data.toString().trim();
+  process.argv.splice(1, 0, entrypoint);
+  require(entrypoint);
+});"#;
+
+/// Statistics of all Node.js pools, which show the pressure on webpack
+/// loaders, PostCSS and other Node.js evaluations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NodeJsPoolsStats {
+    /// The number of pools, i. e. distinct entrypoints like loader chains.
+    pub pools: u32,
+    /// Busy, idle and booting processes.
+    pub workers: u32,
+    pub booting_workers: u32,
+    pub idle_workers: u32,
+    /// Warm processes in the shared mode that haven't loaded an entrypoint yet.
+    pub spare_workers: u32,
+    /// Operations that are waiting for or running on a process.
+    pub queued_tasks: u32,
+    /// Processes that have been stopped as they exceeded the max RSS.
+    pub recycled_workers: u32,
+    /// Processes that have been stopped as they exceeded the idle timeout.
+    pub idle_timed_out_workers: u32,
+}
+
+/// Returns the current statistics of all Node.js pools.
+pub fn node_js_pools_stats() -> NodeJsPoolsStats {
+    let mut result = NodeJsPoolsStats {
+        spare_workers: SPARE_PROCESSES.lock().len() as u32,
+        recycled_workers: RECYCLED_WORKERS.load(Ordering::Relaxed),
+        idle_timed_out_workers: IDLE_TIMED_OUT_WORKERS.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+    for pool in live_pools() {
+        let stats = pool.stats.lock();
+        result.pools += 1;
+        result.workers += stats.workers;
+        result.booting_workers += stats.booting_workers;
+        result.queued_tasks += stats.queued_tasks;
+        result.idle_workers += pool.semaphore.available_permits() as u32;
+    }
+    result
+}
+
+fn live_pools() -> Vec<Arc<IdleProcesses>> {
+    let mut pools = POOLS.lock();
+    let mut live = Vec::with_capacity(pools.len());
+    pools.retain(|pool| match pool.upgrade() {
+        Some(pool) => {
+            live.push(pool);
+            true
+        }
+        None => false,
+    });
+    live
+}
+
+fn worker_count<P>(pools: &[Arc<IdleProcesses<P>>]) -> usize {
+    pools
+        .iter()
+        .map(|pool| pool.stats.lock().workers as usize)
+        .sum()
+}
+
+/// Makes room for a new process within the worker limit of the shared mode.
+/// Spare processes count as workers and are stopped first, as they haven't
+/// loaded an entrypoint yet.
+fn make_room_for_worker(own: &Arc<IdleProcesses>) {
+    let pools = live_pools();
+    let spare_workers = {
+        let mut spares = SPARE_PROCESSES.lock();
+        let excess = (worker_count(&pools) + spares.len() + 1).saturating_sub(CONFIG.max_workers);
+        let stopped = spares.keys().take(excess).cloned().collect::<Vec<_>>();
+        for key in stopped {
+            spares.remove(&key);
+        }
+        spares.len()
+    };
+    stop_idle_processes_of_other_pools(own, &pools, spare_workers, CONFIG.max_workers);
+}
+
+/// Stops the longest idle processes of other pools until a new process can be
+/// booted up without exceeding `max_workers`, including `other_workers` that
+/// don't belong to a pool.
+fn stop_idle_processes_of_other_pools<P>(
+    own: &Arc<IdleProcesses<P>>,
+    pools: &[Arc<IdleProcesses<P>>],
+    other_workers: usize,
+    max_workers: usize,
+) {
+    loop {
+        if worker_count(pools) + other_workers < max_workers {
+            return;
+        }
+        let Some(pool) = pools
+            .iter()
+            .filter(|pool| !Arc::ptr_eq(pool, own))
+            .filter_map(|pool| Some((pool.longest_idle_since()?, pool)))
+            .min_by_key(|(idle_since, _)| *idle_since)
+            .map(|(_, pool)| pool)
+        else {
+            return;
+        };
+        if !pool.stop_longest_idle(Duration::ZERO) {
+            return;
+        }
+    }
+}
+
+/// The idle processes of a pool.
+struct IdleProcesses<P = NodeJsPoolProcess> {
+    /// Sorted by the time they became idle.
+    processes: Mutex<Vec<(Instant, P)>>,
+    /// Semaphore to wait for an idle process to become available
+    semaphore: Semaphore,
+    stats: Arc<Mutex<NodeJsPoolStats>>,
+}
+
+impl<P> IdleProcesses<P> {
+    fn new(stats: Arc<Mutex<NodeJsPoolStats>>) -> Self {
+        Self {
+            processes: Mutex::new(Vec::new()),
+            semaphore: Semaphore::new(0),
+            stats,
+        }
+    }
+
+    /// Takes the process that became idle last. Requires a permit of the
+    /// semaphore.
+    fn pop(&self, permit: SemaphorePermit<'_>) -> P {
+        let (_, process) = self.processes.lock().pop().unwrap();
+        permit.forget();
+        process
+    }
+
+    fn longest_idle_since(&self) -> Option<Instant> {
+        self.processes
+            .lock()
+            .first()
+            .map(|(idle_since, _)| *idle_since)
+    }
+
+    /// Stops the process that is idle for the longest time, when it's idle for
+    /// at least `min_idle_time`.
+    fn stop_longest_idle(&self, min_idle_time: Duration) -> bool {
+        let Ok(permit) = self.semaphore.try_acquire() else {
+            return false;
+        };
+        let process = {
+            let mut processes = self.processes.lock();
+            if !processes
+                .first()
+                .is_some_and(|(idle_since, _)| idle_since.elapsed() >= min_idle_time)
+            {
+                return false;
+            }
+            processes.remove(0)
+        };
+        permit.forget();
+        self.stats.lock().remove_worker();
+        drop(process);
+        true
+    }
+}
+
+impl<P: Send + 'static> IdleProcesses<P> {
+    /// Makes the process available to the next operation. It's stopped when
+    /// it's still idle after `idle_timeout`.
+    fn push(self: &Arc<Self>, process: P, idle_timeout: Option<Duration>) {
+        self.processes.lock().push((Instant::now(), process));
+        self.semaphore.add_permits(1);
+
+        if let (Some(idle_timeout), Ok(handle)) = (idle_timeout, Handle::try_current()) {
+            let idle = Arc::downgrade(self);
+            handle.spawn(async move {
+                sleep(idle_timeout).await;
+                if let Some(idle) = idle.upgrade() {
+                    while idle.stop_longest_idle(idle_timeout) {
+                        IDLE_TIMED_OUT_WORKERS.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SpareProcessKey {
+    cwd: PathBuf,
+    env: Vec<(RcStr, RcStr)>,
+}
+
+impl SpareProcessKey {
+    fn new(cwd: &Path, env: &HashMap<RcStr, RcStr>) -> Self {
+        let mut env = env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        env.sort();
+        Self {
+            cwd: cwd.to_path_buf(),
+            env,
+        }
+    }
+}
+
+/// Takes the warm process for `cwd` and `env` and starts a new one in the
+/// background for the next pool that boots up a process, as long as there is
+/// room for it within the worker limit.
+fn take_spare_process(cwd: &Path, env: &HashMap<RcStr, RcStr>) -> Option<SpawnedProcess> {
+    let key = SpareProcessKey::new(cwd, env);
+    let spare = SPARE_PROCESSES.lock().remove(&key);
+    // The process that is booted up with the taken spare isn't counted yet
+    let workers = worker_count(&live_pools()) + SPARE_PROCESSES.lock().len() + 1;
+    if workers >= CONFIG.max_workers {
+        return spare;
+    }
+    let Ok(handle) = Handle::try_current() else {
+        return spare;
+    };
+    let cwd = cwd.to_path_buf();
+    let env = env.clone();
+    handle.spawn(async move {
+        let Ok(process) = SpawnedProcess::spawn(&cwd, &env, None, false).await else {
+            return;
+        };
+        let started = process.started;
+        {
+            let workers = worker_count(&live_pools());
+            let mut spares = SPARE_PROCESSES.lock();
+            if workers + spares.len() >= CONFIG.max_workers {
+                return;
+            }
+            spares.entry(key).or_insert(process);
+        }
+        if let Some(idle_timeout) = CONFIG.idle_timeout {
+            sleep(idle_timeout).await;
+            let key = SpareProcessKey::new(&cwd, &env);
+            let mut spares = SPARE_PROCESSES.lock();
+            if spares
+                .get(&key)
+                .is_some_and(|process| process.started == started)
+            {
+                spares.remove(&key);
+                IDLE_TIMED_OUT_WORKERS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    });
+    spare
+}
+
+/// A Node.js process that has been spawned, but hasn't connected yet.
+struct SpawnedProcess {
+    child: Child,
+    listener: TcpListener,
+    started: Instant,
+}
+
+impl SpawnedProcess {
+    /// Spawns Node.js with the `entrypoint`. Without an entrypoint, the
+    /// process waits for [SpawnedProcess::load_entrypoint].
+    async fn spawn(
+        cwd: &Path,
+        env: &HashMap<RcStr, RcStr>,
+        entrypoint: Option<&Path>,
+        debug: bool,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("binding to a port")?;
+        let port = listener.local_addr().context("getting port")?.port();
+        let mut cmd = Command::new("node");
+        cmd.current_dir(cwd);
+        if debug {
+            cmd.arg("--inspect-brk");
+        }
+        if let Some(entrypoint) = entrypoint {
+            cmd.arg(entrypoint);
+        } else {
+            cmd.arg("-e").arg(BOOTSTRAP_SCRIPT);
+            cmd.stdin(Stdio::piped());
+        }
+        cmd.arg(port.to_string());
+        cmd.env_clear();
+        cmd.env(
+            "PATH",
+            std::env::var("PATH").expect("the PATH environment variable should always be set"),
+        );
+        #[cfg(target_family = "windows")]
+        cmd.env(
+            "SystemRoot",
+            std::env::var("SystemRoot")
+                .expect("the SystemRoot environment variable should always be set"),
+        );
+        cmd.envs(env);
+        cmd.stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        let child = cmd.spawn().context("spawning node pooled process")?;
+
+        Ok(Self {
+            child,
+            listener,
+            started: Instant::now(),
+        })
+    }
+
+    async fn load_entrypoint(&mut self, entrypoint: &Path) -> Result<()> {
+        let mut stdin = self
+            .child
+            .stdin
+            .take()
+            .context("spare Node.js process has no stdin")?;
+        stdin
+            .write_all(format!("{}\n", entrypoint.display()).as_bytes())
+            .await
+            .context("sending the entrypoint to the spare Node.js process")?;
+        Ok(())
+    }
+}
+
 struct NodeJsPoolProcess {
     child: Option<Child>,
     connection: TcpStream,
@@ -308,9 +723,7 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
 
 impl NodeJsPoolProcess {
     async fn new(
-        cwd: &Path,
-        env: &HashMap<RcStr, RcStr>,
-        entrypoint: &Path,
+        spawned: SpawnedProcess,
         assets_for_source_mapping: Vc<AssetsForSourceMapping>,
         assets_root: Vc<FileSystemPath>,
         project_dir: Vc<FileSystemPath>,
@@ -319,34 +732,11 @@ impl NodeJsPoolProcess {
         debug: bool,
     ) -> Result<Self> {
         let guard = Box::new(duration_span!("Node.js process startup"));
-        let listener = TcpListener::bind("127.0.0.1:0")
-            .await
-            .context("binding to a port")?;
-        let port = listener.local_addr().context("getting port")?.port();
-        let mut cmd = Command::new("node");
-        cmd.current_dir(cwd);
-        if debug {
-            cmd.arg("--inspect-brk");
-        }
-        cmd.arg(entrypoint);
-        cmd.arg(port.to_string());
-        cmd.env_clear();
-        cmd.env(
-            "PATH",
-            std::env::var("PATH").expect("the PATH environment variable should always be set"),
-        );
-        #[cfg(target_family = "windows")]
-        cmd.env(
-            "SystemRoot",
-            std::env::var("SystemRoot")
-                .expect("the SystemRoot environment variable should always be set"),
-        );
-        cmd.envs(env);
-        cmd.stderr(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.kill_on_drop(true);
-
-        let mut child = cmd.spawn().context("spawning node pooled process")?;
+        let SpawnedProcess {
+            mut child,
+            listener,
+            ..
+        } = spawned;
 
         let timeout = if debug {
             Duration::MAX
@@ -684,6 +1074,10 @@ enum AcquiredPermits {
 ///
 /// The worker will *not* use the env of the parent process by default. All env
 /// vars need to be provided to make the execution as pure as possible.
+///
+/// In the shared mode (see [NodeJsPoolConfig]) the concurrency is limited
+/// across all pools instead and processes are booted up from warm spare
+/// processes.
 #[turbo_tasks::value(into = "new", cell = "new", serialization = "none", eq = "manual")]
 pub struct NodeJsPool {
     cwd: PathBuf,
@@ -693,7 +1087,7 @@ pub struct NodeJsPool {
     pub assets_root: Vc<FileSystemPath>,
     pub project_dir: Vc<FileSystemPath>,
     #[turbo_tasks(trace_ignore, debug_ignore)]
-    processes: Arc<Mutex<Vec<NodeJsPoolProcess>>>,
+    idle: Arc<IdleProcesses>,
     /// Semaphore to limit the number of concurrent operations in general
     #[turbo_tasks(trace_ignore, debug_ignore)]
     concurrency_semaphore: Arc<Semaphore>,
@@ -701,9 +1095,6 @@ pub struct NodeJsPool {
     /// (excludes one-off processes)
     #[turbo_tasks(trace_ignore, debug_ignore)]
     bootup_semaphore: Arc<Semaphore>,
-    /// Semaphore to wait for an idle process to become available
-    #[turbo_tasks(trace_ignore, debug_ignore)]
-    idle_process_semaphore: Arc<Semaphore>,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     shared_stdout: SharedOutputSet,
     #[turbo_tasks(trace_ignore, debug_ignore)]
@@ -726,6 +1117,16 @@ impl NodeJsPool {
         concurrency: usize,
         debug: bool,
     ) -> Self {
+        let stats: Arc<Mutex<NodeJsPoolStats>> = Default::default();
+        let idle = Arc::new(IdleProcesses::new(stats.clone()));
+        POOLS.lock().push(Arc::downgrade(&idle));
+        let concurrency_semaphore = if debug {
+            Arc::new(Semaphore::new(1))
+        } else if CONFIG.shared {
+            SHARED_CONCURRENCY_SEMAPHORE.clone()
+        } else {
+            Arc::new(Semaphore::new(concurrency))
+        };
         Self {
             cwd,
             entrypoint,
@@ -733,17 +1134,20 @@ impl NodeJsPool {
             assets_for_source_mapping,
             assets_root,
             project_dir,
-            processes: Arc::new(Mutex::new(Vec::new())),
-            concurrency_semaphore: Arc::new(Semaphore::new(if debug { 1 } else { concurrency })),
+            idle,
+            concurrency_semaphore,
             bootup_semaphore: Arc::new(Semaphore::new(1)),
-            idle_process_semaphore: Arc::new(Semaphore::new(0)),
             shared_stdout: Arc::new(Mutex::new(IndexSet::new())),
             shared_stderr: Arc::new(Mutex::new(IndexSet::new())),
             debug,
-            stats: Default::default(),
+            stats,
         }
     }
 
+    fn is_shared(&self) -> bool {
+        CONFIG.shared && !self.debug
+    }
+
     async fn acquire_process(&self) -> Result<(NodeJsPoolProcess, AcquiredPermits)> {
         {
             self.stats.lock().add_queued_task();
@@ -759,21 +1163,24 @@ impl NodeJsPool {
         };
 
         select! {
-            idle_process_permit = self.idle_process_semaphore.clone().acquire_owned() => {
+            idle_process_permit = self.idle.semaphore.acquire() => {
                 let idle_process_permit = idle_process_permit.context("acquiring idle process permit")?;
-                let process = {
-                    let mut processes = self.processes.lock();
-                    processes.pop().unwrap()
-                };
-                idle_process_permit.forget();
+                let process = self.idle.pop(idle_process_permit);
                 Ok((process, AcquiredPermits::Idle { concurrency_permit }))
             },
             bootup_permit = bootup => {
                 let bootup_permit = bootup_permit.context("acquiring bootup permit")?;
+                let spare = if self.is_shared() {
+                    let spare = take_spare_process(self.cwd.as_path(), &self.env);
+                    make_room_for_worker(&self.idle);
+                    spare
+                } else {
+                    None
+                };
                 {
                     self.stats.lock().add_booting_worker();
                 }
-                let (process, bootup_time) = self.create_process().await?;
+                let (process, bootup_time) = self.create_process(spare).await?;
                 // Update the worker count
                 {
                     let mut stats = self.stats.lock();
@@ -787,12 +1194,29 @@ impl NodeJsPool {
         }
     }
 
-    async fn create_process(&self) -> Result<(NodeJsPoolProcess, Duration), anyhow::Error> {
+    async fn create_process(
+        &self,
+        spare: Option<SpawnedProcess>,
+    ) -> Result<(NodeJsPoolProcess, Duration), anyhow::Error> {
         let start = Instant::now();
+        let spawned = if self.is_shared() {
+            let mut spawned = match spare {
+                Some(spare) => spare,
+                None => SpawnedProcess::spawn(self.cwd.as_path(), &self.env, None, false).await?,
+            };
+            spawned.load_entrypoint(self.entrypoint.as_path()).await?;
+            spawned
+        } else {
+            SpawnedProcess::spawn(
+                self.cwd.as_path(),
+                &self.env,
+                Some(self.entrypoint.as_path()),
+                self.debug,
+            )
+            .await?
+        };
         let process = NodeJsPoolProcess::new(
-            self.cwd.as_path(),
-            &self.env,
-            self.entrypoint.as_path(),
+            spawned,
             self.assets_for_source_mapping,
             self.assets_root,
             self.project_dir,
@@ -812,11 +1236,11 @@ impl NodeJsPool {
         Ok(NodeJsOperation {
             process: Some(process),
             permits,
-            processes: self.processes.clone(),
-            idle_process_semaphore: self.idle_process_semaphore.clone(),
+            idle: self.idle.clone(),
             start: Instant::now(),
             stats: self.stats.clone(),
             allow_process_reuse: true,
+            exceeded_max_rss: false,
         })
     }
 }
@@ -826,11 +1250,11 @@ pub struct NodeJsOperation {
     // This is used for drop
     #[allow(dead_code)]
     permits: AcquiredPermits,
-    processes: Arc<Mutex<Vec<NodeJsPoolProcess>>>,
-    idle_process_semaphore: Arc<Semaphore>,
+    idle: Arc<IdleProcesses>,
     start: Instant,
     stats: Arc<Mutex<NodeJsPoolStats>>,
     allow_process_reuse: bool,
+    exceeded_max_rss: bool,
 }
 
 impl NodeJsOperation {
@@ -915,6 +1339,15 @@ impl NodeJsOperation {
         }
     }
 
+    /// Reports the resident set size of the process after an evaluation. The
+    /// process is recycled at the end of the operation when it exceeds
+    /// `TURBOPACK_NODE_WORKER_MAX_RSS_MB`.
+    pub fn report_memory_usage(&mut self, rss: u64) {
+        if CONFIG.exceeds_max_worker_rss(rss) {
+            self.exceeded_max_rss = true;
+        }
+    }
+
     pub async fn apply_source_mapping<'a>(
         &self,
         text: &'a str,
@@ -940,9 +1373,127 @@ impl Drop for NodeJsOperation {
                 }
             }
             if self.allow_process_reuse {
-                self.processes.lock().push(process);
-                self.idle_process_semaphore.add_permits(1);
+                if self.exceeded_max_rss {
+                    self.stats.lock().remove_worker();
+                    RECYCLED_WORKERS.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    self.idle.push(process, CONFIG.idle_timeout);
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(workers: u32) -> Arc<IdleProcesses<&'static str>> {
+        let stats = Arc::new(Mutex::new(NodeJsPoolStats {
+            workers,
+            ..Default::default()
+        }));
+        Arc::new(IdleProcesses::new(stats))
+    }
+
+    fn idle_processes(pool: &IdleProcesses<&'static str>) -> Vec<&'static str> {
+        pool.processes
+            .lock()
+            .iter()
+            .map(|(_, process)| *process)
+            .collect()
+    }
+
+    fn push(pool: &Arc<IdleProcesses<&'static str>>, process: &'static str) {
+        pool.push(process, None);
+        // Make sure the idle times differ
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_worker_limit() {
+        let own = pool(2);
+        let a = pool(2);
+        let b = pool(1);
+        push(&own, "own");
+        push(&a, "a1");
+        push(&b, "b");
+        push(&a, "a2");
+        let pools = [own.clone(), a.clone(), b.clone()];
+
+        // 5 workers and a spare process, 3 need to be stopped to boot up a new
+        // one with a limit of 4
+        stop_idle_processes_of_other_pools(&own, &pools, 1, 4);
+        assert_eq!(worker_count(&pools), 2);
+        assert_eq!(idle_processes(&own), ["own"]);
+        assert_eq!(idle_processes(&a), Vec::<&str>::new());
+        assert_eq!(idle_processes(&b), Vec::<&str>::new());
+        assert_eq!(a.semaphore.available_permits(), 0);
+
+        // Processes of the own pool are not stopped
+        stop_idle_processes_of_other_pools(&own, &pools, 0, 1);
+        assert_eq!(idle_processes(&own), ["own"]);
+    }
+
+    #[test]
+    fn test_worker_limit_oldest_first() {
+        let own = pool(0);
+        let a = pool(2);
+        let b = pool(1);
+        push(&a, "a1");
+        push(&b, "b");
+        push(&a, "a2");
+        let pools = [own.clone(), a.clone(), b.clone()];
+
+        stop_idle_processes_of_other_pools(&own, &pools, 0, 2);
+        assert_eq!(worker_count(&pools), 1);
+        assert_eq!(idle_processes(&a), ["a2"]);
+        assert_eq!(idle_processes(&b), Vec::<&str>::new());
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        let idle_timeout = Duration::from_millis(200);
+        let pool = pool(2);
+        pool.push("reused", Some(idle_timeout));
+        pool.push("idle", Some(idle_timeout));
+        let timed_out = IDLE_TIMED_OUT_WORKERS.load(Ordering::Relaxed);
+
+        // Using a process resets its idle time
+        sleep(Duration::from_millis(100)).await;
+        let permit = pool.semaphore.try_acquire().unwrap();
+        assert_eq!(pool.pop(permit), "idle");
+        let permit = pool.semaphore.try_acquire().unwrap();
+        assert_eq!(pool.pop(permit), "reused");
+        pool.push("reused", Some(idle_timeout));
+
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(idle_processes(&pool), ["reused"]);
+        assert_eq!(pool.semaphore.available_permits(), 1);
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(idle_processes(&pool), Vec::<&str>::new());
+        assert_eq!(pool.semaphore.available_permits(), 0);
+        assert_eq!(pool.stats.lock().workers, 1);
+        assert!(IDLE_TIMED_OUT_WORKERS.load(Ordering::Relaxed) > timed_out);
+    }
+
+    #[test]
+    fn test_max_worker_rss() {
+        let config = NodeJsPoolConfig {
+            shared: false,
+            max_workers: 1,
+            max_worker_rss: Some(100 * 1024 * 1024),
+            idle_timeout: None,
+        };
+        assert!(!config.exceeds_max_worker_rss(50 * 1024 * 1024));
+        assert!(!config.exceeds_max_worker_rss(100 * 1024 * 1024));
+        assert!(config.exceeds_max_worker_rss(150 * 1024 * 1024));
+
+        let config = NodeJsPoolConfig {
+            max_worker_rss: None,
+            ..config
+        };
+        assert!(!config.exceeds_max_worker_rss(u64::MAX));
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, RcStr, Value, Vc};
+use turbo_tasks_env::ProcessEnv;
+use turbo_tasks_fs::{
+    glob::Glob, to_sys_path, DirectoryEntry, File, FileContent, FileSystemPath, LinkContent,
+    ReadGlobResult,
+};
+use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
+use turbopack_core::{
+    reference_type::ReferenceType,
+    resolve::{
+        find_context_file, node::node_cjs_resolve_options, parse::Request, resolve,
+        FindContextFileResult,
+    },
+};
+
+use super::webpack::WebpackLoaderItems;
+
+/// Bump this when the format of the cached results changes.
+const LOADER_CACHE_VERSION: u32 = 3;
+
+/// Environment variables that commonly change the output of loaders, e.g. of
+/// `babel-loader`.
+const KEY_ENV_VARS: [&str; 2] = ["NODE_ENV", "BABEL_ENV"];
+
+/// Entries that were written longer ago are removed.
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The oldest entries are removed when all entries are larger than this.
+const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Where the results of webpack loaders are persisted, see [loader_cache_key].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct LoaderCacheOptions {
+    pub dir: Vc<FileSystemPath>,
+    /// The mode of the build, e.g. `development` or `production`. Results are
+    /// only reused by builds of the same mode.
+    pub mode: RcStr,
+}
+
+/// The part of the loader result that lists the dependencies reported by the
+/// loaders, relative to the project path. Missing when the result depends on
+/// files outside of the project, which can't be validated.
+#[derive(Deserialize, Debug, Default)]
+struct LoaderResultDependencies {
+    dependencies: Option<LoaderDependencies>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct LoaderDependencies {
+    #[serde(default)]
+    files: Vec<RcStr>,
+    #[serde(default)]
+    dirs: Vec<LoaderDirDependency>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LoaderDirDependency {
+    path: RcStr,
+    glob: RcStr,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoaderCacheEntry {
+    /// Files the result depends on, with the hash of their content.
+    files: Vec<(RcStr, u64)>,
+    /// Globs the result depends on, with the hash of all matched files.
+    dirs: Vec<(LoaderDirDependency, u64)>,
+    /// The result of the loaders as returned by the Node.js process.
+    result: RcStr,
+}
+
+/// Computes the cache key of a loader evaluation from the source content, the
+/// identity and options of the loaders, the mode of the build and the
+/// environment variables in [KEY_ENV_VARS] of the loader evaluation.
+///
+/// Loaders are identified by the content of the file they resolve to from
+/// `resource_dir` and the `package.json` of their package, so updating a
+/// loader invalidates its cached results.
+pub async fn loader_cache_key(
+    mode: &str,
+    env: Vc<Box<dyn ProcessEnv>>,
+    resource_dir: Vc<FileSystemPath>,
+    content: &str,
+    resource_path: &str,
+    query: &str,
+    loaders: Vc<WebpackLoaderItems>,
+) -> Result<u64> {
+    let mut hasher = Xxh3Hash64Hasher::new();
+    hasher.write_value(LOADER_CACHE_VERSION);
+    hasher.write_value(mode);
+    for name in KEY_ENV_VARS {
+        hasher.write_value(env.read(name.into()).await?.as_deref());
+    }
+    hasher.write_value(content);
+    hasher.write_value(resource_path);
+    hasher.write_value(query);
+    for item in loaders.await?.iter() {
+        hasher.write_ref(&item.loader);
+        hasher.write_value(serde_json::to_string(&item.options)?);
+        hasher.write_value(*loader_identity(resource_dir, item.loader.clone()).await?);
+    }
+    Ok(hasher.finish())
+}
+
+/// Hashes the file a loader resolves to, like `require.resolve` does in the
+/// loader runner, and the `package.json` of its package.
+#[turbo_tasks::function]
+async fn loader_identity(resource_dir: Vc<FileSystemPath>, loader: RcStr) -> Result<Vc<u64>> {
+    let mut hasher = Xxh3Hash64Hasher::new();
+    let result = resolve(
+        resource_dir,
+        Value::new(ReferenceType::Undefined),
+        Request::parse_string(loader),
+        node_cjs_resolve_options(resource_dir.root()),
+    );
+    // An unresolvable loader fails the evaluation, which is never cached
+    if let Some(source) = *result.first_source().await? {
+        let path = source.ident().path();
+        hasher.write_ref(&path.await?.path);
+        hasher.write_value(*path.read().hash().await?);
+        let package_json = find_context_file(path.parent(), Vc::cell(vec!["package.json".into()]));
+        if let FindContextFileResult::Found(package_json, _) = *package_json.await? {
+            hasher.write_value(*package_json.read().hash().await?);
+        }
+    }
+    Ok(Vc::cell(hasher.finish()))
+}
+
+fn cache_file(cache_dir: Vc<FileSystemPath>, key: u64) -> Vc<FileSystemPath> {
+    cache_dir.join(format!("{}.json", encode_hex(key)).into())
+}
+
+/// Returns the result cached in `cache_dir` for `key` when none of its
+/// dependencies have changed. Reading the entry and its dependencies makes
+/// them dependencies of the current task.
+pub async fn read_loader_cache(
+    cache_dir: Vc<FileSystemPath>,
+    project_path: Vc<FileSystemPath>,
+    key: u64,
+) -> Result<Option<RcStr>> {
+    evict_loader_cache(cache_dir).await?;
+    let FileContent::Content(file) = &*cache_file(cache_dir, key).read().await? else {
+        return Ok(None);
+    };
+    let Ok(entry) = serde_json::from_reader::<_, LoaderCacheEntry>(file.read()) else {
+        return Ok(None);
+    };
+    for (path, hash) in entry.files {
+        if *project_path.join(path).read().hash().await? != hash {
+            return Ok(None);
+        }
+    }
+    for (dir, hash) in entry.dirs {
+        let glob = project_path
+            .join(dir.path)
+            .read_glob(Glob::new(dir.glob), false);
+        if *glob_content_hash(glob).await? != hash {
+            return Ok(None);
+        }
+    }
+    Ok(Some(entry.result))
+}
+
+/// Stores the `result` for `key` in `cache_dir`, together with the hashes of
+/// its dependencies. Results without dependencies, i.e. that depend on files
+/// outside of the project, are not stored.
+pub async fn write_loader_cache(
+    cache_dir: Vc<FileSystemPath>,
+    project_path: Vc<FileSystemPath>,
+    key: u64,
+    result: RcStr,
+) -> Result<()> {
+    let Ok(LoaderResultDependencies {
+        dependencies: Some(dependencies),
+    }) = serde_json::from_str(&result)
+    else {
+        return Ok(());
+    };
+    let mut entry = LoaderCacheEntry {
+        files: Vec::new(),
+        dirs: Vec::new(),
+        result,
+    };
+    for path in dependencies.files {
+        let hash = *project_path.join(path.clone()).read().hash().await?;
+        entry.files.push((path, hash));
+    }
+    for dir in dependencies.dirs {
+        let glob = project_path
+            .join(dir.path.clone())
+            .read_glob(Glob::new(dir.glob.clone()), false);
+        let hash = *glob_content_hash(glob).await?;
+        entry.dirs.push((dir, hash));
+    }
+    cache_file(cache_dir, key)
+        .write(FileContent::Content(File::from(serde_json::to_string(&entry)?)).cell())
+        .await?;
+    Ok(())
+}
+
+/// Removes the entries in `cache_dir` that were written more than [MAX_AGE]
+/// ago, and the oldest entries while all entries are larger than [MAX_SIZE].
+///
+/// Only runs once per cache directory, before any of its entries is read.
+#[turbo_tasks::function]
+async fn evict_loader_cache(cache_dir: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+    let Some(path) = to_sys_path(cache_dir).await? else {
+        return Ok(Completion::new());
+    };
+    let Ok(entries) = fs::read_dir(path) else {
+        return Ok(Completion::new());
+    };
+    let now = SystemTime::now();
+    let mut kept = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() || path.extension() != Some("json".as_ref()) {
+            continue;
+        }
+        let written = metadata.modified().unwrap_or(now);
+        if now.duration_since(written).unwrap_or_default() > MAX_AGE {
+            let _ = fs::remove_file(&path);
+        } else {
+            kept.push((written, metadata.len(), path));
+        }
+    }
+    kept.sort();
+    let mut size = kept.iter().map(|(_, len, _)| len).sum::<u64>();
+    for (_, len, path) in kept {
+        if size <= MAX_SIZE {
+            break;
+        }
+        let _ = fs::remove_file(path);
+        size -= len;
+    }
+    Ok(Completion::new())
+}
+
+/// Hashes the names and contents of all entries matched by a glob.
+#[turbo_tasks::function]
+async fn glob_content_hash(glob: Vc<ReadGlobResult>) -> Result<Vc<u64>> {
+    let glob = glob.await?;
+    let mut hasher = Xxh3Hash64Hasher::new();
+    let mut results = glob.results.iter().collect::<Vec<_>>();
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, entry) in results {
+        hasher.write_ref(name);
+        match *entry {
+            DirectoryEntry::File(file) => {
+                hasher.write_value(*file.read().hash().await?);
+            }
+            DirectoryEntry::Symlink(symlink) => {
+                if let LinkContent::Link { target, .. } = &*symlink.read_link().await? {
+                    hasher.write_ref(target);
+                }
+            }
+            DirectoryEntry::Directory(_) | DirectoryEntry::Other(_) | DirectoryEntry::Error => {}
+        }
+    }
+    let mut inner = glob.inner.iter().collect::<Vec<_>>();
+    inner.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, &inner) in inner {
+        hasher.write_ref(name);
+        hasher.write_value(*glob_content_hash(*inner).await?);
+    }
+    Ok(Vc::cell(hasher.finish()))
+}
@@ -1,3 +1,5 @@
+pub mod loader_cache;
 pub mod postcss;
+pub mod sass;
 mod util;
 pub mod webpack;
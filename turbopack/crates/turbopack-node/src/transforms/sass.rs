@@ -0,0 +1,383 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sourcemap::{SourceMap as RegularMap, SourceMapBuilder};
+use turbo_tasks::{Completion, RcStr, Value, Vc};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::{AssetContext, ProcessResult},
+    file_source::FileSource,
+    ident::AssetIdent,
+    issue::IssueDescriptionExt,
+    reference_type::{InnerAssets, ReferenceType},
+    resolve::options::ImportMapping,
+    source::Source,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
+    source_transform::SourceTransform,
+};
+use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
+
+use super::webpack::WebpackLoaderContext;
+use crate::{
+    embed_js::embed_file_path, execution_context::ExecutionContext,
+    transforms::webpack::evaluate_webpack_loader,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[turbo_tasks::value(serialization = "custom")]
+struct SassProcessingResult {
+    css: RcStr,
+    map: Option<RcStr>,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Default)]
+pub struct SassTransformOptions {
+    /// The `sass` package to compile with. Defaults to the `sass` package of
+    /// the project.
+    pub sass_package: Option<Vc<ImportMapping>>,
+    /// Options passed to the Sass compiler, like `sassOptions` in
+    /// `next.config.js`. `additionalData` (or `prependData`) is prepended to
+    /// every file.
+    pub sass_options: Option<Vc<JsonValue>>,
+    pub placeholder_for_future_extensions: u8,
+}
+
+/// Compiles Sass and SCSS files to CSS. The result is renamed to `*.css` (or
+/// `*.module.css` for `*.module.scss` and `*.module.sass`), so it's processed
+/// by the CSS rules afterwards.
+#[turbo_tasks::value]
+pub struct SassTransform {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    resolve_options_context: Vc<ResolveOptionsContext>,
+    sass_options: Option<Vc<JsonValue>>,
+}
+
+#[turbo_tasks::value_impl]
+impl SassTransform {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: Vc<Box<dyn AssetContext>>,
+        execution_context: Vc<ExecutionContext>,
+        resolve_options_context: Vc<ResolveOptionsContext>,
+        sass_options: Option<Vc<JsonValue>>,
+    ) -> Vc<Self> {
+        SassTransform {
+            evaluate_context,
+            execution_context,
+            resolve_options_context,
+            sass_options,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for SassTransform {
+    #[turbo_tasks::function]
+    fn transform(self: Vc<Self>, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            SassTransformedAsset {
+                transform: self,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct SassTransformedAsset {
+    transform: Vc<SassTransform>,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        let ident = self.source.ident();
+        let path = ident.path().await?;
+        let rename_as =
+            if path.path.ends_with(".module.scss") || path.path.ends_with(".module.sass") {
+                "*.module.css"
+            } else {
+                "*.css"
+            };
+        Ok(ident.rename_as(rename_as.into()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        Ok(self
+            .process()
+            .issue_file_path(this.source.ident().path(), "Sass compilation")
+            .await?
+            .await?
+            .content)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(Vc::cell(self.process().await?.source_map))
+    }
+}
+
+#[turbo_tasks::value]
+struct ProcessSassResult {
+    content: Vc<AssetContent>,
+    source_map: Option<Vc<SourceMap>>,
+}
+
+#[turbo_tasks::function]
+fn sass_executor(evaluate_context: Vc<Box<dyn AssetContext>>) -> Vc<ProcessResult> {
+    evaluate_context.process(
+        Vc::upcast(FileSource::new(embed_file_path(
+            "transforms/sass.ts".into(),
+        ))),
+        Value::new(ReferenceType::Internal(InnerAssets::empty())),
+    )
+}
+
+#[turbo_tasks::value_impl]
+impl SassTransformedAsset {
+    #[turbo_tasks::function]
+    async fn process(&self) -> Result<Vc<ProcessSassResult>> {
+        let transform = self.transform.await?;
+        let ExecutionContext {
+            project_path,
+            chunking_context,
+            env,
+            ..
+        } = *transform.execution_context.await?;
+
+        let source_content = self.source.content();
+        let AssetContent::File(file) = *source_content.await? else {
+            bail!("Sass transform only support transforming files");
+        };
+        let FileContent::Content(content) = &*file.await? else {
+            return Ok(ProcessSassResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                source_map: None,
+            }
+            .cell());
+        };
+        let content = content.content().to_str()?;
+        let sass_options = match transform.sass_options {
+            Some(sass_options) => (*sass_options.await?).clone(),
+            None => JsonValue::Null,
+        };
+        // Like sass-loader, `additionalData` is prepended to the content of
+        // every file. The lines are removed from the source map again.
+        let additional_data = sass_options
+            .get("additionalData")
+            .or_else(|| sass_options.get("prependData"))
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
+        let compiled_content = match &additional_data {
+            Some(additional_data) => format!("{additional_data}\n{content}"),
+            None => content.to_string(),
+        };
+        let evaluate_context = transform.evaluate_context;
+
+        let sass_executor = sass_executor(evaluate_context).module();
+        let sass_fs_path = self.source.ident().path().await?;
+        let syntax = if sass_fs_path.extension_ref() == Some("sass") {
+            "indented"
+        } else {
+            "scss"
+        };
+
+        // The Sass compiler runs with the project as the current working
+        // directory, so it needs a path relative to the project.
+        let Some(sass_path) = project_path.await?.get_relative_path_to(&sass_fs_path) else {
+            bail!("Sass files outside of the project can't be compiled");
+        };
+
+        let config_value = evaluate_webpack_loader(WebpackLoaderContext {
+            module_asset: sass_executor,
+            cwd: project_path,
+            env,
+            context_ident_for_issue: self.source.ident(),
+            asset_context: evaluate_context,
+            chunking_context,
+            resolve_options_context: Some(transform.resolve_options_context),
+            args: vec![
+                Vc::cell(compiled_content.clone().into()),
+                Vc::cell(sass_path.into()),
+                Vc::cell(syntax.into()),
+                Vc::cell(sass_options),
+            ],
+            additional_invalidation: Completion::immutable(),
+        })
+        .await?;
+
+        let SingleValue::Single(val) = config_value.try_into_single().await? else {
+            // An error happened, which has already been converted into an issue.
+            return Ok(ProcessSassResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                source_map: None,
+            }
+            .cell());
+        };
+        let processed: SassProcessingResult = parse_json_with_source_context(val.to_str()?)
+            .context("Unable to deserialize response from Sass compilation")?;
+
+        let source_map = match (processed.map, additional_data) {
+            (Some(source_map), Some(additional_data)) => {
+                let source_map = RegularMap::from_slice(source_map.as_bytes())
+                    .context("Unable to parse the source map of the Sass compilation")?;
+                Some(
+                    SourceMap::new_regular(without_additional_data(
+                        &source_map,
+                        &compiled_content,
+                        &content,
+                        additional_data.matches('\n').count() as u32 + 1,
+                    ))
+                    .cell(),
+                )
+            }
+            (Some(source_map), None) => SourceMap::new_from_file_content(
+                FileContent::Content(File::from(source_map)).cell(),
+            )
+            .await?
+            .map(|source_map| source_map.cell()),
+            (None, _) => None,
+        };
+        let file = File::from(processed.css);
+        let content = AssetContent::File(FileContent::Content(file).cell()).cell();
+        Ok(ProcessSassResult {
+            content,
+            source_map,
+        }
+        .cell())
+    }
+}
+
+/// Removes the `prepended_lines` lines of `additionalData` from the mappings
+/// into the compiled file, which is the source with `compiled_content` as
+/// content, and restores its original `content`. Code generated from
+/// `additionalData` itself isn't mapped to a source anymore.
+fn without_additional_data(
+    map: &RegularMap,
+    compiled_content: &str,
+    content: &str,
+    prepended_lines: u32,
+) -> RegularMap {
+    let compiled_source = (0..map.get_source_count())
+        .find(|&src_id| map.get_source_contents(src_id) == Some(compiled_content));
+    let mut builder = SourceMapBuilder::new(map.get_file());
+    for (src_id, source) in map.sources().enumerate() {
+        let src_id = src_id as u32;
+        let new_src_id = builder.add_source(source);
+        let contents = if Some(src_id) == compiled_source {
+            Some(content)
+        } else {
+            map.get_source_contents(src_id)
+        };
+        builder.set_source_contents(new_src_id, contents);
+    }
+    for name in map.names() {
+        builder.add_name(name);
+    }
+    for token in map.tokens() {
+        let raw = token.get_raw_token();
+        let name_id = token.has_name().then_some(raw.name_id);
+        let (src_line, src_id) = if !token.has_source() {
+            (0, None)
+        } else if Some(raw.src_id) != compiled_source {
+            (raw.src_line, Some(raw.src_id))
+        } else {
+            match raw.src_line.checked_sub(prepended_lines) {
+                Some(src_line) => (src_line, Some(raw.src_id)),
+                None => (0, None),
+            }
+        };
+        let name_id = src_id.and(name_id);
+        builder.add_raw(
+            raw.dst_line,
+            raw.dst_col,
+            src_line,
+            if src_id.is_some() { raw.src_col } else { 0 },
+            src_id,
+            name_id,
+            false,
+        );
+    }
+    builder.into_sourcemap()
+}
+
+#[cfg(test)]
+mod tests {
+    use sourcemap::{SourceMap as RegularMap, SourceMapBuilder};
+
+    use super::without_additional_data;
+
+    const ADDITIONAL_DATA: &str = "$color: red;\n@use \"sass:math\";";
+    const CONTENT: &str = ".a {\n  color: $color;\n}\n";
+
+    #[test]
+    fn removes_additional_data_lines() {
+        let compiled_content = format!("{ADDITIONAL_DATA}\n{CONTENT}");
+        let mut builder = SourceMapBuilder::new(None);
+        let src = builder.add_source("/project/src/a.scss");
+        builder.set_source_contents(src, Some(&compiled_content));
+        let other = builder.add_source("/project/src/_mixins.scss");
+        builder.set_source_contents(other, Some("@mixin m {}"));
+        // `.a {` and `color: red;` from the file, one declaration from
+        // `additionalData` and one from another file
+        builder.add_raw(0, 0, 3, 0, Some(src), None, false);
+        builder.add_raw(1, 2, 4, 2, Some(src), None, false);
+        builder.add_raw(2, 2, 0, 0, Some(src), None, false);
+        builder.add_raw(3, 2, 0, 0, Some(other), None, false);
+        let map = builder.into_sourcemap();
+
+        let map = without_additional_data(&map, &compiled_content, CONTENT, 3);
+        assert_eq!(map.get_source_contents(0), Some(CONTENT));
+        assert_eq!(map.get_source_contents(1), Some("@mixin m {}"));
+        let tokens = map
+            .tokens()
+            .map(|token| {
+                (
+                    token.get_dst_line(),
+                    token.has_source().then(|| token.get_src_line()),
+                    token.get_source(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (0, Some(0), Some("/project/src/a.scss")),
+                (1, Some(1), Some("/project/src/a.scss")),
+                (2, None, None),
+                (3, Some(0), Some("/project/src/_mixins.scss")),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_maps_of_other_files() {
+        let mut builder = SourceMapBuilder::new(None);
+        let src = builder.add_source("/project/src/b.scss");
+        builder.set_source_contents(src, Some(".b {}"));
+        builder.add_raw(0, 0, 5, 0, Some(src), None, false);
+        let map: RegularMap = builder.into_sourcemap();
+
+        let map = without_additional_data(&map, "unrelated", CONTENT, 3);
+        let token = map.get_token(0).unwrap();
+        assert_eq!(token.get_src_line(), 5);
+        assert_eq!(map.get_source_contents(0), Some(".b {}"));
+    }
+}
@@ -1,13 +1,15 @@
-use std::mem::take;
+use std::{collections::BTreeMap, mem::take};
 
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use either::Either;
+use indexmap::indexmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use serde_with::serde_as;
 use turbo_tasks::{
-    trace::TraceRawVcs, Completion, RcStr, TaskInput, TryJoinIterExt, Value, ValueToString, Vc,
+    trace::TraceRawVcs, CollectiblesSource, Completion, RcStr, TaskInput, TryJoinIterExt, Value,
+    ValueToString, Vc,
 };
 use turbo_tasks_bytes::stream::SingleValue;
 use turbo_tasks_env::ProcessEnv;
@@ -23,6 +25,8 @@ use turbopack_core::{
     ident::AssetIdent,
     issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
     module::Module,
+    output::OutputAsset,
+    reference::all_modules_and_affecting_sources,
     reference_type::{InnerAssets, ReferenceType},
     resolve::{
         options::{ConditionValue, ResolveInPackage, ResolveIntoPackage, ResolveOptions},
@@ -33,6 +37,7 @@ use turbopack_core::{
     source::Source,
     source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
     source_transform::SourceTransform,
+    virtual_output::VirtualOutputAsset,
     virtual_source::VirtualSource,
 };
 use turbopack_resolve::{
@@ -40,12 +45,14 @@ use turbopack_resolve::{
     resolve_options_context::ResolveOptionsContext,
 };
 
-use super::util::{emitted_assets_to_virtual_sources, EmittedAsset};
+use super::loader_cache::{
+    loader_cache_key, read_loader_cache, write_loader_cache, LoaderCacheOptions,
+};
 use crate::{
     debug::should_debug,
-    embed_js::embed_file_path,
+    embed_js::{embed_file, embed_file_path},
     evaluate::{
-        compute, custom_evaluate, get_evaluate_pool, EvaluateContext, EvaluationIssue,
+        compute, custom_evaluate, evaluate, get_evaluate_pool, EvaluateContext, EvaluationIssue,
         JavaScriptEvaluation, JavaScriptStreamSender,
     },
     execution_context::ExecutionContext,
@@ -70,7 +77,16 @@ struct WebpackLoadersProcessingResult {
     source: Either<RcStr, BytesBase64>,
     map: Option<RcStr>,
     #[turbo_tasks(trace_ignore)]
-    assets: Option<Vec<EmittedAsset>>,
+    assets: Option<Vec<WebpackEmittedFile>>,
+}
+
+/// A file emitted by a loader via `this.emitFile`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WebpackEmittedFile {
+    file: RcStr,
+    #[serde(with = "either::serde_untagged")]
+    content: Either<RcStr, BytesBase64>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -91,6 +107,9 @@ pub struct WebpackLoaders {
     loaders: Vc<WebpackLoaderItems>,
     rename_as: Option<RcStr>,
     resolve_options_context: Vc<ResolveOptionsContext>,
+    /// Persists the results of the loaders, see [super::loader_cache]. Only
+    /// valid for deterministic loaders.
+    loader_cache: Option<Vc<LoaderCacheOptions>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -102,6 +121,7 @@ impl WebpackLoaders {
         loaders: Vc<WebpackLoaderItems>,
         rename_as: Option<RcStr>,
         resolve_options_context: Vc<ResolveOptionsContext>,
+        loader_cache: Option<Vc<LoaderCacheOptions>>,
     ) -> Vc<Self> {
         WebpackLoaders {
             evaluate_context,
@@ -109,6 +129,7 @@ impl WebpackLoaders {
             loaders,
             rename_as,
             resolve_options_context,
+            loader_cache,
         }
         .cell()
     }
@@ -168,7 +189,6 @@ impl GenerateSourceMap for WebpackLoadersProcessedAsset {
 struct ProcessWebpackLoadersResult {
     content: Vc<AssetContent>,
     source_map: Option<Vc<SourceMap>>,
-    assets: Vec<Vc<VirtualSource>>,
 }
 
 #[turbo_tasks::function]
@@ -181,6 +201,22 @@ fn webpack_loaders_executor(evaluate_context: Vc<Box<dyn AssetContext>>) -> Vc<P
     )
 }
 
+#[turbo_tasks::function]
+fn import_module_executor(
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    module: Vc<Box<dyn Module>>,
+) -> Vc<ProcessResult> {
+    evaluate_context.process(
+        Vc::upcast(VirtualSource::new(
+            module.ident().path().join("import-module.ts".into()),
+            AssetContent::File(embed_file("transforms/import-module.ts".into())).cell(),
+        )),
+        Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
+            "MODULE".into() => module
+        }))),
+    )
+}
+
 #[turbo_tasks::value_impl]
 impl WebpackLoadersProcessedAsset {
     #[turbo_tasks::function]
@@ -192,6 +228,7 @@ impl WebpackLoadersProcessedAsset {
             project_path,
             chunking_context,
             env,
+            emit_root,
         } = *transform.execution_context.await?;
         let source_content = this.source.content();
         let AssetContent::File(file) = *source_content.await? else {
@@ -200,7 +237,6 @@ impl WebpackLoadersProcessedAsset {
         let FileContent::Content(content) = &*file.await? else {
             return Ok(ProcessWebpackLoadersResult {
                 content: AssetContent::File(FileContent::NotFound.cell()).cell(),
-                assets: Vec::new(),
                 source_map: None,
             }
             .cell());
@@ -221,39 +257,72 @@ impl WebpackLoadersProcessedAsset {
                 project_path.await?
             ));
         };
-        let loaders = transform.loaders.await?;
-        let config_value = evaluate_webpack_loader(WebpackLoaderContext {
-            module_asset: webpack_loaders_executor,
-            cwd: project_path,
-            env,
-            context_ident_for_issue: this.source.ident(),
-            asset_context: evaluate_context,
-            chunking_context,
-            resolve_options_context: Some(transform.resolve_options_context),
-            args: vec![
-                Vc::cell(content.into()),
-                // We need to pass the query string to the loader
-                Vc::cell(resource_path.to_string().into()),
-                Vc::cell(this.source.ident().query().await?.to_string().into()),
-                Vc::cell(json!(*loaders)),
-            ],
-            additional_invalidation: Completion::immutable(),
-        })
-        .await?;
+        let loaders = json!(*transform.loaders.await?);
+        let query = this.source.ident().query().await?.to_string();
+
+        let cache = if let Some(loader_cache) = transform.loader_cache {
+            let loader_cache = loader_cache.await?;
+            let key = loader_cache_key(
+                &loader_cache.mode,
+                env,
+                resource_fs_path.parent(),
+                &content,
+                &resource_path,
+                &query,
+                transform.loaders,
+            )
+            .await?;
+            Some((loader_cache.dir, key))
+        } else {
+            None
+        };
+        let cached = if let Some((cache_dir, key)) = cache {
+            read_loader_cache(cache_dir, project_path, key).await?
+        } else {
+            None
+        };
 
-        let SingleValue::Single(val) = config_value.try_into_single().await? else {
-            // An error happened, which has already been converted into an issue.
-            return Ok(ProcessWebpackLoadersResult {
-                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
-                assets: Vec::new(),
-                source_map: None,
+        let val = if let Some(cached) = cached {
+            cached
+        } else {
+            let evaluation = evaluate_webpack_loader(WebpackLoaderContext {
+                module_asset: webpack_loaders_executor,
+                cwd: project_path,
+                env,
+                context_ident_for_issue: this.source.ident(),
+                asset_context: evaluate_context,
+                chunking_context,
+                resolve_options_context: Some(transform.resolve_options_context),
+                args: vec![
+                    Vc::cell(content.into()),
+                    // We need to pass the query string to the loader
+                    Vc::cell(resource_path.to_string().into()),
+                    Vc::cell(query.into()),
+                    Vc::cell(loaders),
+                ],
+                additional_invalidation: Completion::immutable(),
+            });
+            let config_value = evaluation.await?;
+
+            let SingleValue::Single(val) = config_value.try_into_single().await? else {
+                // An error happened, which has already been converted into an issue.
+                return Ok(ProcessWebpackLoadersResult {
+                    content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                    source_map: None,
+                }
+                .cell());
+            };
+            let val: RcStr = val.to_str()?.into();
+            // Issues are not replayed from the cache, so results with issues are not cached
+            if let Some((cache_dir, key)) = cache {
+                if evaluation.peek_collectibles::<Box<dyn Issue>>().is_empty() {
+                    write_loader_cache(cache_dir, project_path, key, val.clone()).await?;
+                }
             }
-            .cell());
+            val
         };
-        let processed: WebpackLoadersProcessingResult = parse_json_with_source_context(
-            val.to_str()?,
-        )
-        .context("Unable to deserializate response from webpack loaders transform operation")?;
+        let processed: WebpackLoadersProcessingResult = parse_json_with_source_context(&val)
+            .context("Unable to deserializate response from webpack loaders transform operation")?;
 
         // handle SourceMap
         let source_map = if let Some(source_map) = processed.map {
@@ -267,11 +336,29 @@ impl WebpackLoadersProcessedAsset {
             Either::Left(str) => File::from(str),
             Either::Right(bytes) => File::from(bytes.binary),
         };
-        let assets = emitted_assets_to_virtual_sources(processed.assets);
+        // Emitted files are collected as output assets by the consumer of the
+        // module graph, like issues. Sorted to make it deterministic.
+        let emitted_files = processed
+            .assets
+            .into_iter()
+            .flatten()
+            .map(|WebpackEmittedFile { file, content }| (file, content))
+            .collect::<BTreeMap<_, _>>();
+        let emit_root = emit_root.unwrap_or_else(|| chunking_context.output_root());
+        for (file, content) in emitted_files {
+            let content = match content {
+                Either::Left(str) => File::from(str),
+                Either::Right(bytes) => File::from(bytes.binary),
+            };
+            let asset = VirtualOutputAsset::new(
+                emit_root.join(file),
+                AssetContent::File(FileContent::Content(content).cell()).cell(),
+            );
+            turbo_tasks::emit(Vc::upcast::<Box<dyn OutputAsset>>(asset));
+        }
         let content = AssetContent::File(FileContent::Content(file).cell()).cell();
         Ok(ProcessWebpackLoadersResult {
             content,
-            assets,
             source_map,
         }
         .cell())
@@ -339,6 +426,9 @@ pub enum InfoMessage {
         error: StructuredError,
     },
     Log(LogInfo),
+    UnsupportedApi {
+        name: RcStr,
+    },
 }
 
 #[derive(Debug, Clone, TaskInput, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -364,12 +454,28 @@ pub enum RequestMessage {
         lookup_path: RcStr,
         request: RcStr,
     },
+    #[serde(rename_all = "camelCase")]
+    LoadModule { lookup_path: RcStr, request: RcStr },
+    #[serde(rename_all = "camelCase")]
+    ImportModule { lookup_path: RcStr, request: RcStr },
 }
 
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum ResponseMessage {
-    Resolve { path: RcStr },
+    Resolve {
+        path: RcStr,
+    },
+    LoadModule {
+        path: RcStr,
+        #[serde(with = "either::serde_untagged")]
+        source: Either<RcStr, BytesBase64>,
+    },
+    ImportModule {
+        exports: JsonValue,
+        /// The files of the imported module graph, relative to the project.
+        dependencies: Vec<RcStr>,
+    },
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, TaskInput, Serialize, Deserialize, Debug)]
@@ -477,6 +583,14 @@ impl EvaluateContext for WebpackLoaderContext {
             InfoMessage::Log(log) => {
                 state.push(log);
             }
+            InfoMessage::UnsupportedApi { name } => {
+                UnsupportedLoaderApiIssue {
+                    context_ident: self.context_ident_for_issue,
+                    name,
+                }
+                .cell()
+                .emit();
+            }
         }
         Ok(())
     }
@@ -493,41 +607,76 @@ impl EvaluateContext for WebpackLoaderContext {
                 lookup_path,
                 request,
             } => {
-                let Some(resolve_options_context) = self.resolve_options_context else {
-                    bail!("Resolve options are not available in this context");
+                let (_, path) = self
+                    .resolve_request(lookup_path, request, Some(webpack_options))
+                    .await?;
+                Ok(ResponseMessage::Resolve { path })
+            }
+            RequestMessage::LoadModule {
+                lookup_path,
+                request,
+            } => {
+                let (source, path) = self.resolve_request(lookup_path, request, None).await?;
+                let module = self
+                    .asset_context
+                    .process(source, Value::new(ReferenceType::Undefined))
+                    .module();
+                let AssetContent::File(file) = *module.content().await? else {
+                    bail!("Loading {} doesn't result in a file", path);
                 };
-                let lookup_path = self.cwd.join(lookup_path);
-                let request = Request::parse(Value::new(Pattern::Constant(request)));
-                let options = resolve_options(lookup_path, resolve_options_context);
-
-                let options = apply_webpack_resolve_options(options, webpack_options);
-
-                let resolved = resolve(
-                    lookup_path,
-                    Value::new(ReferenceType::Undefined),
-                    request,
-                    options,
-                );
-
-                let request_str = request.to_string().await?;
-                let lookup_path_str = lookup_path.to_string().await?;
-                if let Some(source) = *resolved.first_source().await? {
-                    if let Some(path) = self
-                        .cwd
-                        .await?
-                        .get_relative_path_to(&*source.ident().path().await?)
-                    {
-                        Ok(ResponseMessage::Resolve { path })
-                    } else {
-                        bail!(
-                            "Resolving {} in {} ends up on a different filesystem",
-                            request_str,
-                            lookup_path_str
-                        );
-                    }
-                } else {
-                    bail!("Unable to resolve {} in {}", request_str, lookup_path_str);
-                }
+                let FileContent::Content(content) = &*file.await? else {
+                    bail!("Loading {} doesn't result in a file", path);
+                };
+                let source = match content.content().to_str() {
+                    Ok(str) => Either::Left(str.into()),
+                    Err(_) => Either::Right(BytesBase64 {
+                        binary: content.content().to_bytes()?.into_owned(),
+                    }),
+                };
+                Ok(ResponseMessage::LoadModule { path, source })
+            }
+            RequestMessage::ImportModule {
+                lookup_path,
+                request,
+            } => {
+                let (source, _) = self.resolve_request(lookup_path, request, None).await?;
+                let module = self
+                    .asset_context
+                    .process(source, Value::new(ReferenceType::Undefined))
+                    .module();
+                let exports = evaluate(
+                    import_module_executor(self.asset_context, module).module(),
+                    self.cwd,
+                    self.env,
+                    source.ident(),
+                    self.asset_context,
+                    self.chunking_context,
+                    None,
+                    vec![],
+                    Completion::immutable(),
+                    should_debug("webpack_loader"),
+                )
+                .await?;
+                let SingleValue::Single(exports) = exports.try_into_single().await? else {
+                    // An error happened, which has already been converted into an issue.
+                    bail!("Unable to import {}", source.ident().to_string().await?);
+                };
+                let exports = parse_json_with_source_context(exports.to_str()?)
+                    .context("Unable to deserialize the exports of the imported module")?;
+                let cwd = self.cwd.await?;
+                let dependencies = all_modules_and_affecting_sources(module)
+                    .await?
+                    .iter()
+                    .map(|&module| async move { module.ident().path().await })
+                    .try_join()
+                    .await?
+                    .iter()
+                    .filter_map(|path| cwd.get_relative_path_to(path))
+                    .collect();
+                Ok(ResponseMessage::ImportModule {
+                    exports,
+                    dependencies,
+                })
             }
         }
     }
@@ -569,6 +718,55 @@ impl EvaluateContext for WebpackLoaderContext {
     }
 }
 
+impl WebpackLoaderContext {
+    /// Resolves a request of a loader from `lookup_path`. Returns the source
+    /// and its path relative to the cwd.
+    async fn resolve_request(
+        &self,
+        lookup_path: RcStr,
+        request: RcStr,
+        webpack_options: Option<WebpackResolveOptions>,
+    ) -> Result<(Vc<Box<dyn Source>>, RcStr)> {
+        let Some(resolve_options_context) = self.resolve_options_context else {
+            bail!("Resolve options are not available in this context");
+        };
+        let lookup_path = self.cwd.join(lookup_path);
+        let request = Request::parse(Value::new(Pattern::Constant(request)));
+        let mut options = resolve_options(lookup_path, resolve_options_context);
+
+        if let Some(webpack_options) = webpack_options {
+            options = apply_webpack_resolve_options(options, webpack_options);
+        }
+
+        let resolved = resolve(
+            lookup_path,
+            Value::new(ReferenceType::Undefined),
+            request,
+            options,
+        );
+
+        let request_str = request.to_string().await?;
+        let lookup_path_str = lookup_path.to_string().await?;
+        if let Some(source) = *resolved.first_source().await? {
+            if let Some(path) = self
+                .cwd
+                .await?
+                .get_relative_path_to(&*source.ident().path().await?)
+            {
+                Ok((source, path))
+            } else {
+                bail!(
+                    "Resolving {} in {} ends up on a different filesystem",
+                    request_str,
+                    lookup_path_str
+                );
+            }
+        } else {
+            bail!("Unable to resolve {} in {}", request_str, lookup_path_str);
+        }
+    }
+}
+
 #[turbo_tasks::function]
 async fn apply_webpack_resolve_options(
     resolve_options: Vc<ResolveOptions>,
@@ -695,6 +893,52 @@ impl Issue for BuildDependencyIssue {
     }
 }
 
+/// A loader accessed a part of the webpack loader context that is not
+/// supported by Turbopack.
+#[turbo_tasks::value(shared)]
+pub struct UnsupportedLoaderApiIssue {
+    pub context_ident: Vc<AssetIdent>,
+    pub name: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnsupportedLoaderApiIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Unsupported webpack loader API".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Unsupported.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.context_ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text("A loader accessed ".into()),
+                StyledString::Code(format!("this.{}", self.name).into()),
+                StyledString::Text(
+                    ", which is not available in Turbopack. The loader might not work as expected."
+                        .into(),
+                ),
+            ])
+            .cell(),
+        ))
+    }
+}
+
 /// A hack to invalidate when any file in a directory changes. Need to be
 /// awaited before files are accessed.
 #[turbo_tasks::function]
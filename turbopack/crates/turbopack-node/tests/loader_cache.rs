@@ -0,0 +1,161 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_env::{EnvMap, ProcessEnv};
+use turbo_tasks_fs::{DiskFileSystem, File, FileContent, FileSystem, FileSystemPath};
+use turbo_tasks_hash::encode_hex;
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_node::transforms::{
+    loader_cache::{loader_cache_key, read_loader_cache, write_loader_cache},
+    webpack::{WebpackLoaderItem, WebpackLoaderItems},
+};
+
+static REGISTRATION: Registration = register!(turbopack_node::register);
+
+const RESULT: &str =
+    r#"{"source":"export default 1","dependencies":{"files":["src/theme.json"],"dirs":[]}}"#;
+
+/// A result that depends on files outside of the project.
+const EXTERNAL_RESULT: &str = r#"{"source":"export default 1"}"#;
+
+fn env(node_env: &str) -> Vc<Box<dyn ProcessEnv>> {
+    Vc::upcast(Vc::<EnvMap>::cell(IndexMap::from([(
+        "NODE_ENV".into(),
+        node_env.into(),
+    )])))
+}
+
+async fn write(path: Vc<FileSystemPath>, content: &str) -> Result<()> {
+    path.write(FileContent::Content(File::from(content)).cell())
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn loader_cache() {
+    run(&REGISTRATION, || async {
+        let root = tempfile::tempdir()?;
+        let fs = DiskFileSystem::new(
+            "project".into(),
+            root.path().to_str().unwrap().into(),
+            vec![],
+        );
+        let project = fs.root();
+        let src = project.join("src".into());
+        let theme = project.join("src/theme.json".into());
+        let package_json = project.join("node_modules/svg-loader/package.json".into());
+        let cache_dir = project.join(".next/cache/turbopack-loaders".into());
+        write(theme, "{}").await?;
+        write(
+            package_json,
+            r#"{ "name": "svg-loader", "version": "1.0.0" }"#,
+        )
+        .await?;
+        write(
+            project.join("node_modules/svg-loader/index.js".into()),
+            "module.exports = (source) => source",
+        )
+        .await?;
+
+        let loaders: Vc<WebpackLoaderItems> = Vc::cell(vec![WebpackLoaderItem {
+            loader: "svg-loader".into(),
+            options: Default::default(),
+        }]);
+        let key_for = |mode: &'static str, node_env: &'static str, content: &'static str| {
+            loader_cache_key(
+                mode,
+                env(node_env),
+                src,
+                content,
+                "src/icon.svg",
+                "",
+                loaders,
+            )
+        };
+        let key = |content: &'static str| key_for("development", "development", content);
+
+        // Nothing is cached at first
+        let icon = key("<svg/>").await?;
+        assert_eq!(read_loader_cache(cache_dir, project, icon).await?, None);
+
+        write_loader_cache(cache_dir, project, icon, RESULT.into()).await?;
+        assert_eq!(
+            read_loader_cache(cache_dir, project, icon).await?,
+            Some(RcStr::from(RESULT))
+        );
+
+        // Another source is a miss
+        let other = key("<svg></svg>").await?;
+        assert_ne!(other, icon);
+        assert_eq!(read_loader_cache(cache_dir, project, other).await?, None);
+
+        // So are other build modes and environments
+        assert_ne!(key_for("production", "development", "<svg/>").await?, icon);
+        assert_ne!(key_for("development", "production", "<svg/>").await?, icon);
+
+        // Results that can't be validated are not stored
+        write_loader_cache(cache_dir, project, other, EXTERNAL_RESULT.into()).await?;
+        assert_eq!(read_loader_cache(cache_dir, project, other).await?, None);
+
+        // Changing a dependency reported by the loader invalidates the result
+        write(theme, r#"{ "dark": true }"#).await?;
+        assert_eq!(read_loader_cache(cache_dir, project, icon).await?, None);
+
+        // Updating the loader changes the key
+        write(
+            package_json,
+            r#"{ "name": "svg-loader", "version": "1.0.1" }"#,
+        )
+        .await?;
+        assert_ne!(key("<svg/>").await?, icon);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn loader_cache_eviction() {
+    run(&REGISTRATION, || async {
+        let root = tempfile::tempdir()?;
+        let fs = DiskFileSystem::new(
+            "project".into(),
+            root.path().to_str().unwrap().into(),
+            vec![],
+        );
+        let project = fs.root();
+        let cache_dir = project.join(".next/cache/turbopack-loaders".into());
+        let sys_cache_dir = root.path().join(".next/cache/turbopack-loaders");
+        std::fs::create_dir_all(&sys_cache_dir)?;
+        let entry = |key: u64| sys_cache_dir.join(format!("{}.json", encode_hex(key)));
+
+        let entry_content = serde_json::json!({
+            "files": [],
+            "dirs": [],
+            "result": "{}",
+        })
+        .to_string();
+        std::fs::write(entry(1), &entry_content)?;
+        std::fs::write(entry(2), &entry_content)?;
+        // Entry 1 was written more than a week ago
+        std::fs::File::options()
+            .write(true)
+            .open(entry(1))?
+            .set_modified(SystemTime::now() - Duration::from_secs(8 * 24 * 60 * 60))?;
+
+        assert_eq!(read_loader_cache(cache_dir, project, 1).await?, None);
+        assert!(!entry(1).exists());
+        assert_eq!(
+            read_loader_cache(cache_dir, project, 2).await?,
+            Some(RcStr::from("{}"))
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
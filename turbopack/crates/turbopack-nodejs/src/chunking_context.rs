@@ -9,8 +9,8 @@ use turbopack_core::{
         availability_info::AvailabilityInfo,
         chunk_group::{make_chunk_group, MakeChunkGroupResult},
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
-        Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext,
-        EntryChunkGroupResult, EvaluatableAssets, MinifyType, ModuleId,
+        Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingConfig, ChunkingContext,
+        EntryChunkGroupResult, EvaluatableAssets, MinifyType, ModuleId, SourceMapsType,
     },
     environment::Environment,
     ident::AssetIdent,
@@ -44,6 +44,11 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    pub fn source_maps_type(mut self, source_maps_type: SourceMapsType) -> Self {
+        self.chunking_context.source_maps_type = source_maps_type;
+        self
+    }
+
     pub fn runtime_type(mut self, runtime_type: RuntimeType) -> Self {
         self.chunking_context.runtime_type = runtime_type;
         self
@@ -59,6 +64,21 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    pub fn chunking_config(mut self, chunking_config: Vc<ChunkingConfig>) -> Self {
+        self.chunking_context.chunking_config = chunking_config;
+        self
+    }
+
+    pub fn inline_css_imports(mut self, inline_css_imports: bool) -> Self {
+        self.chunking_context.inline_css_imports = inline_css_imports;
+        self
+    }
+
+    pub fn inline_wasm_limit(mut self, inline_wasm_limit: Option<u64>) -> Self {
+        self.chunking_context.inline_wasm_limit = inline_wasm_limit;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> Vc<NodeJsChunkingContext> {
         NodeJsChunkingContext::new(Value::new(self.chunking_context))
@@ -88,10 +108,18 @@ pub struct NodeJsChunkingContext {
     runtime_type: RuntimeType,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// How source maps of chunks are emitted
+    source_maps_type: SourceMapsType,
     /// Whether to use manifest chunks for lazy compilation
     manifest_chunks: bool,
     /// The strategy to use for generating module ids
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    /// The size targets and limits for chunking
+    chunking_config: Vc<ChunkingConfig>,
+    /// Whether `@import`ed stylesheets are inlined into the importing stylesheet
+    inline_css_imports: bool,
+    /// WebAssembly modules smaller than this many bytes are embedded into chunks
+    inline_wasm_limit: Option<u64>,
 }
 
 impl NodeJsChunkingContext {
@@ -116,8 +144,12 @@ impl NodeJsChunkingContext {
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
+                source_maps_type: SourceMapsType::Full,
                 manifest_chunks: false,
                 module_id_strategy: Vc::upcast(DevModuleIdStrategy::new()),
+                chunking_config: ChunkingConfig::default_config(),
+                inline_css_imports: false,
+                inline_wasm_limit: None,
             },
         }
     }
@@ -136,6 +168,11 @@ impl NodeJsChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    /// Returns the source maps type.
+    pub fn source_maps_type(&self) -> SourceMapsType {
+        self.source_maps_type
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -367,6 +404,26 @@ impl ChunkingContext for NodeJsChunkingContext {
         self.module_id_strategy.get_module_id(ident)
     }
 
+    #[turbo_tasks::function]
+    fn chunking_config(&self) -> Vc<ChunkingConfig> {
+        self.chunking_config
+    }
+
+    #[turbo_tasks::function]
+    fn source_maps_type(&self) -> Vc<SourceMapsType> {
+        self.source_maps_type.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn inline_css_imports(&self) -> Vc<bool> {
+        Vc::cell(self.inline_css_imports)
+    }
+
+    #[turbo_tasks::function]
+    fn inline_wasm_limit(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.inline_wasm_limit)
+    }
+
     #[turbo_tasks::function]
     async fn async_loader_chunk_item(
         self: Vc<Self>,
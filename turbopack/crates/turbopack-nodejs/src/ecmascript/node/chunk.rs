@@ -3,7 +3,7 @@ use indexmap::IndexSet;
 use turbo_tasks::{RcStr, ValueToString, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{Chunk, ChunkingContext},
+    chunk::{Chunk, ChunkOutputAsset, ChunkingContext},
     ident::AssetIdent,
     introspect::{Introspectable, IntrospectableChildren},
     output::{OutputAsset, OutputAssets},
@@ -64,6 +64,14 @@ impl EcmascriptBuildNodeChunk {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ChunkOutputAsset for EcmascriptBuildNodeChunk {
+    #[turbo_tasks::function]
+    fn chunk(&self) -> Vc<Box<dyn Chunk>> {
+        Vc::upcast(self.chunk)
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl OutputAsset for EcmascriptBuildNodeChunk {
     #[turbo_tasks::function]
@@ -76,10 +84,12 @@ impl OutputAsset for EcmascriptBuildNodeChunk {
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
         let chunk_references = this.chunk.references().await?;
+        let source_maps_type = *this.chunking_context.source_maps_type().await?;
         let include_source_map = *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+            .await?
+            && source_maps_type.has_source_map_file();
         let mut references =
             Vec::with_capacity(chunk_references.len() + if include_source_map { 1 } else { 0 });
 
@@ -88,7 +98,10 @@ impl OutputAsset for EcmascriptBuildNodeChunk {
         }
 
         if include_source_map {
-            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+            references.push(Vc::upcast(SourceMapAsset::new(
+                Vc::upcast(self),
+                source_maps_type,
+            )));
         }
 
         Ok(Vc::cell(references))
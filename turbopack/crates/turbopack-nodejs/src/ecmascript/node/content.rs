@@ -68,7 +68,6 @@ impl EcmascriptBuildNodeChunkContent {
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
         let chunk_path_vc = this.chunk.ident().path();
-        let chunk_path = chunk_path_vc.await?;
 
         let mut code = CodeBuilder::default();
 
@@ -88,21 +87,23 @@ impl EcmascriptBuildNodeChunkContent {
 
         write!(code, "\n}};")?;
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(
-                code,
-                "\n\n//# sourceMappingURL={}.map",
-                urlencoding::encode(filename)
-            )?;
-        }
-
-        let code = code.build().cell();
+        let mut code = code.build().cell();
         if matches!(
             this.chunking_context.await?.minify_type(),
             MinifyType::Minify
         ) {
-            return Ok(minify(chunk_path_vc, code));
+            code = minify(chunk_path_vc, code);
+        }
+
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(this.chunk))
+            .await?
+        {
+            code = code.with_source_mapping_url(
+                chunk_path_vc,
+                this.chunking_context.await?.source_maps_type(),
+            );
         }
 
         Ok(code)
@@ -84,7 +84,19 @@ impl EcmascriptBuildNodeRuntimeChunk {
             }
         }
 
-        Ok(Code::cell(code.build()))
+        let mut code = code.build().cell();
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?
+        {
+            code = code.with_source_mapping_url(
+                self.ident().path(),
+                this.chunking_context.await?.source_maps_type(),
+            );
+        }
+
+        Ok(code)
     }
 }
 
@@ -114,12 +126,17 @@ impl OutputAsset for EcmascriptBuildNodeRuntimeChunk {
         let this = self.await?;
         let mut references = vec![];
 
+        let source_maps_type = *this.chunking_context.source_maps_type().await?;
         if *this
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?
+            && source_maps_type.has_source_map_file()
         {
-            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))))
+            references.push(Vc::upcast(SourceMapAsset::new(
+                Vc::upcast(self),
+                source_maps_type,
+            )))
         }
 
         Ok(Vc::cell(references))
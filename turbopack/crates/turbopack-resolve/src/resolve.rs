@@ -3,6 +3,7 @@ use turbo_tasks::Vc;
 use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbopack_core::resolve::{
     find_context_file,
+    lockfile::read_locked_versions,
     options::{
         ConditionValue, ImportMap, ImportMapping, ResolutionConditions, ResolveInPackage,
         ResolveIntoPackage, ResolveModules, ResolveOptions,
@@ -222,6 +223,12 @@ async fn base_resolve_options(
             if let Some(dir) = opt.enable_node_modules {
                 mods.push(ResolveModules::Nested(dir, vec!["node_modules".into()]));
             }
+            if let Some((dir, lockfile)) = opt.enable_registry {
+                mods.push(ResolveModules::Registry(
+                    dir,
+                    read_locked_versions(lockfile),
+                ));
+            }
             mods
         },
         into_package: {
@@ -31,6 +31,13 @@ pub struct ResolveOptionsContext {
     /// directory
     pub enable_node_modules: Option<Vc<FileSystemPath>>,
     #[serde(default)]
+    /// Enable resolving packages from a registry-style package store. The
+    /// first path is the store directory (laid out as
+    /// `@scope/module/version/<path-in-package>`), the second path is the
+    /// lockfile (`pnpm-lock.yaml` or `package-lock.json`) that pins the
+    /// versions.
+    pub enable_registry: Option<(Vc<FileSystemPath>, Vc<FileSystemPath>)>,
+    #[serde(default)]
     /// Mark well-known Node.js modules as external imports and load them using
     /// native `require`. e.g. url, querystring, os
     pub enable_node_externals: bool,
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    context::AssetContext,
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+    source::Source,
+};
+use turbopack_ecmascript::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<RcStr> {
+    Vc::cell("data uri".into())
+}
+
+/// A module that exports the content of a source as a `data:` URI, instead of
+/// emitting it as a separate file like [StaticModuleAsset](crate::StaticModuleAsset).
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct DataUriModuleAsset {
+    pub source: Vc<Box<dyn Source>>,
+    pub asset_context: Vc<Box<dyn AssetContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl DataUriModuleAsset {
+    #[turbo_tasks::function]
+    pub fn new(source: Vc<Box<dyn Source>>, asset_context: Vc<Box<dyn AssetContext>>) -> Vc<Self> {
+        Self::cell(DataUriModuleAsset {
+            source,
+            asset_context,
+        })
+    }
+
+    /// The `data:` URI of the source. The mime type is taken from the file, or
+    /// guessed from its extension.
+    #[turbo_tasks::function]
+    async fn data_uri(&self) -> Result<Vc<RcStr>> {
+        let FileContent::Content(file) = &*self.source.content().file_content().await? else {
+            return Ok(Vc::cell("data:,".into()));
+        };
+        let mime = match file.content_type() {
+            Some(mime) => mime.clone(),
+            None => mime_guess::from_path(&*self.source.ident().path().await?.path)
+                .first_or_octet_stream(),
+        };
+        let data = file.content().to_bytes()?;
+        Ok(Vc::cell(
+            format!(
+                "data:{mime};base64,{}",
+                Base64Display::new(&data, &STANDARD)
+            )
+            .into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for DataUriModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source
+            .ident()
+            .with_modifier(modifier())
+            .with_layer(self.asset_context.layer())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for DataUriModuleAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for DataUriModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        Vc::upcast(DataUriChunkItem::cell(DataUriChunkItem {
+            module: self,
+            chunking_context,
+        }))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for DataUriModuleAsset {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.into()
+    }
+}
+
+#[turbo_tasks::value]
+struct DataUriChunkItem {
+    module: Vc<DataUriModuleAsset>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for DataUriChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for DataUriChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__({});",
+                StringifyJs(&*self.module.data_uri().await?)
+            )
+            .into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
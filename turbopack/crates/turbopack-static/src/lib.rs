@@ -7,11 +7,15 @@
 //!
 //! When referred to from CSS assets, the reference is replaced with the asset's
 //! path.
+//!
+//! [DataUriModuleAsset](data_uri::DataUriModuleAsset) exports the content of
+//! the asset as a `data:` URI instead.
 
 #![feature(min_specialization)]
 #![feature(arbitrary_self_types)]
 #![feature(arbitrary_self_types_pointers)]
 
+pub mod data_uri;
 pub mod fixed;
 pub mod output_asset;
 
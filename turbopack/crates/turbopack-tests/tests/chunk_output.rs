@@ -0,0 +1,661 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::collections::{HashSet, VecDeque};
+
+use anyhow::{bail, Result};
+use dunce::canonicalize;
+use indexmap::IndexMap;
+use turbo_tasks::{RcStr, ReadRef, TryJoinIterExt, TurboTasks, Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileContent, FileSystem, FileSystemPath};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{module_options::ModuleOptionsContext, ModuleAssetContext};
+use turbopack_browser::{BrowserChunkingContext, BrowserChunkingContextBuilder};
+use turbopack_bundle_analyzer::{AnalyzeModule, AnalyzeReport};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        ChunkableModule, ChunkingConfig, ChunkingContextExt, ChunkingType, EvaluatableAssets,
+        SourceMapsType,
+    },
+    compile_time_info::CompileTimeInfo,
+    context::AssetContext,
+    environment::{BrowserEnvironment, Environment, ExecutionEnvironment, NodeJsEnvironment},
+    file_source::FileSource,
+    issue::{IssueDescriptionExt, IssueSeverity, PlainIssue, StyledString},
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_nodejs::NodeJsChunkingContext;
+use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
+
+fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack::register();
+    turbopack_browser::register();
+    turbopack_bundle_analyzer::register();
+    turbopack_ecmascript_runtime::register();
+    turbopack_nodejs::register();
+    turbopack_resolve::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_chunk_output.rs"));
+}
+
+/// Returns the path of the fixture in `tests/chunk_output/<fixture>`.
+fn fixture_path(fixture: &str) -> Result<Vc<FileSystemPath>> {
+    let fixtures = canonicalize(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/chunk_output"))?;
+    let fs = DiskFileSystem::new("fixtures".into(), fixtures.to_str().unwrap().into(), vec![]);
+    Ok(fs.root().join(fixture.into()))
+}
+
+/// Processes `entry` in `project_path` as an entry module for `env`.
+fn entry_module(
+    project_path: Vc<FileSystemPath>,
+    entry: &str,
+    env: Vc<Environment>,
+) -> Vc<Box<dyn Module>> {
+    let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
+        Default::default(),
+        CompileTimeInfo::new(env),
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+        Vc::cell("test".into()),
+    ));
+    asset_context
+        .process(
+            Vc::upcast(FileSource::new(project_path.join(entry.into()))),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module()
+}
+
+/// Chunks `entry` of the fixture in `tests/chunk_output/<fixture>` with a
+/// chunking context configured by `configure`. Returns the output path and the
+/// assets of the root chunk group.
+async fn entry_chunks(
+    fixture: &str,
+    entry: &str,
+    configure: impl FnOnce(BrowserChunkingContextBuilder) -> BrowserChunkingContextBuilder,
+) -> Result<(Vc<FileSystemPath>, Vc<OutputAssets>)> {
+    let project_path = fixture_path(fixture)?;
+    let output_path = project_path.join("output".into());
+
+    let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
+        BrowserEnvironment {
+            dom: true,
+            web_worker: false,
+            service_worker: false,
+            browserslist_query: "Chrome 102".into(),
+        }
+        .into(),
+    )));
+    let chunking_context = configure(BrowserChunkingContext::builder(
+        project_path,
+        output_path,
+        output_path,
+        output_path.join("chunks".into()),
+        output_path.join("static".into()),
+        env,
+        RuntimeType::Development,
+    ))
+    .build();
+
+    let entry_module = entry_module(project_path, entry, env);
+    let Some(entry_module) =
+        Vc::try_resolve_downcast::<Box<dyn ChunkableModule>>(entry_module).await?
+    else {
+        bail!("{entry} is not chunkable");
+    };
+    Ok((
+        output_path,
+        chunking_context.root_chunk_group_assets(entry_module),
+    ))
+}
+
+/// Chunks `entry` of the fixture in `tests/chunk_output/<fixture>` into an
+/// entry chunk for Node.js. Returns the output path and the entry chunk.
+async fn node_entry_chunks(
+    fixture: &str,
+    entry: &str,
+    source_maps_type: SourceMapsType,
+) -> Result<(Vc<FileSystemPath>, Vc<OutputAssets>)> {
+    let project_path = fixture_path(fixture)?;
+    let output_path = project_path.join("output".into());
+
+    let env = Environment::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+        NodeJsEnvironment::default().into(),
+    )));
+    let chunking_context = NodeJsChunkingContext::builder(
+        project_path,
+        output_path,
+        output_path,
+        output_path.join("chunks".into()),
+        output_path.join("static".into()),
+        env,
+        RuntimeType::Development,
+    )
+    .source_maps_type(source_maps_type)
+    .build();
+
+    let entry_chunk = chunking_context.root_entry_chunk_group_asset(
+        output_path.join(format!("{entry}.entry.js").into()),
+        entry_module(project_path, entry, env),
+        OutputAssets::empty(),
+        EvaluatableAssets::empty(),
+    );
+    Ok((output_path, Vc::cell(vec![entry_chunk])))
+}
+
+/// Returns the content of the output assets of [entry_chunks], by path
+/// relative to the output directory, in breadth-first order starting with the
+/// chunks of the root chunk group.
+async fn chunk_output(
+    fixture: &'static str,
+    entry: &'static str,
+    configure: impl FnOnce(BrowserChunkingContextBuilder) -> BrowserChunkingContextBuilder
+        + Send
+        + 'static,
+) -> Result<IndexMap<RcStr, String>> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let (output_path, chunks) = entry_chunks(fixture, entry, configure).await?;
+        output_content(output_path, chunks).await
+    })
+    .await
+}
+
+/// Like [chunk_output], but for the Node.js entry chunk of [node_entry_chunks].
+async fn node_chunk_output(
+    fixture: &'static str,
+    entry: &'static str,
+    source_maps_type: SourceMapsType,
+) -> Result<IndexMap<RcStr, String>> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let (output_path, chunks) = node_entry_chunks(fixture, entry, source_maps_type).await?;
+        output_content(output_path, chunks).await
+    })
+    .await
+}
+
+/// Returns the content of `assets` and the assets they reference, by path
+/// relative to `output_path`, in breadth-first order.
+async fn output_content(
+    output_path: Vc<FileSystemPath>,
+    assets: Vc<OutputAssets>,
+) -> Result<IndexMap<RcStr, String>> {
+    let output_path = output_path.await?;
+    let mut output = IndexMap::new();
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<_> = assets.await?.iter().copied().collect();
+    while let Some(asset) = queue.pop_front() {
+        let path = asset.ident().path().resolve().await?;
+        if !seen.insert(path) {
+            continue;
+        }
+        if let FileContent::Content(file) = &*asset.content().file_content().await? {
+            if let Some(path) = output_path.get_path_to(&*path.await?) {
+                output.insert(
+                    path.into(),
+                    String::from_utf8_lossy(&file.content().to_bytes()?).into_owned(),
+                );
+            }
+        }
+        queue.extend(
+            asset
+                .references()
+                .await?
+                .iter()
+                .copied()
+                .map(|asset| async move {
+                    Ok(Vc::try_resolve_downcast::<Box<dyn OutputAsset>>(asset).await?)
+                })
+                .try_join()
+                .await?
+                .into_iter()
+                .flatten(),
+        );
+    }
+    Ok(output)
+}
+
+/// Returns the issues emitted while chunking `entry` of the fixture in
+/// `tests/chunk_output/<fixture>`.
+async fn chunk_issues(
+    fixture: &'static str,
+    entry: &'static str,
+) -> Result<Vec<ReadRef<PlainIssue>>> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let (_, chunks) = entry_chunks(fixture, entry, |builder| builder).await?;
+        let _ = chunks.resolve_strongly_consistent().await?;
+        chunks
+            .peek_issues_with_path()
+            .await?
+            .get_plain_issues()
+            .await
+    })
+    .await
+}
+
+/// Returns the content of the first CSS chunk, i. e. the one of the root chunk
+/// group.
+fn css_chunk(output: &IndexMap<RcStr, String>) -> &str {
+    output
+        .iter()
+        .find(|(path, _)| path.ends_with(".css"))
+        .map(|(_, content)| content.as_str())
+        .expect("no CSS chunk emitted")
+}
+
+/// Returns the path and the content of the first JavaScript chunk.
+fn js_chunk(output: &IndexMap<RcStr, String>) -> (&str, &str) {
+    output
+        .iter()
+        .find(|(path, _)| path.ends_with(".js"))
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .expect("no JavaScript chunk emitted")
+}
+
+/// Returns the factory of a module in the JavaScript chunks. `module` is the
+/// module id without the fixture, e.g. `index.js [test] (ecmascript)`.
+fn module_factory<'a>(output: &'a IndexMap<RcStr, String>, module: &str) -> &'a str {
+    output
+        .iter()
+        .filter(|(chunk, _)| chunk.ends_with(".js"))
+        .flat_map(|(_, code)| code.split("\n\"["))
+        .find(|factory| {
+            factory
+                .split_once("\": ")
+                .is_some_and(|(id, _)| id.ends_with(&format!("/{module}")))
+        })
+        .unwrap_or_else(|| panic!("{module} is missing in {output:#?}"))
+}
+
+fn position(code: &str, selector: &str) -> usize {
+    code.find(&format!("{selector} {{"))
+        .unwrap_or_else(|| panic!("{selector} is missing in\n{code}"))
+}
+
+#[tokio::test]
+async fn inline_css_imports() {
+    let output = chunk_output("css-import-order", "index.css", |builder| {
+        builder.inline_css_imports(true)
+    })
+    .await
+    .unwrap();
+    let code = css_chunk(&output);
+
+    // Every stylesheet follows its imports, in import order
+    let positions =
+        [".shared", ".a", ".b", ".c", ".index"].map(|selector| position(code, selector));
+    assert!(
+        positions.windows(2).all(|pair| pair[0] < pair[1]),
+        "unexpected order in\n{code}"
+    );
+    // A stylesheet imported multiple times is only placed at its first import
+    assert_eq!(code.matches(".shared {").count(), 1, "{code}");
+    // The conditions of the import wrap the imported stylesheet
+    let layer = code.find("@layer c").expect("@layer c is missing");
+    assert!(layer > positions[2] && layer < positions[3], "{code}");
+}
+
+#[tokio::test]
+async fn inline_css_imports_split_chunks() {
+    let config = || {
+        ChunkingConfig {
+            split_by_package: true,
+            ..Default::default()
+        }
+        .cell()
+    };
+    let css_chunks = |output: &IndexMap<RcStr, String>| {
+        output.keys().filter(|path| path.ends_with(".css")).count()
+    };
+
+    // The stylesheet of the package is put into a chunk of its own
+    let output = chunk_output("css-import-split", "index.css", move |builder| {
+        builder.chunking_config(config())
+    })
+    .await
+    .unwrap();
+    assert_eq!(css_chunks(&output), 2, "{output:#?}");
+
+    // Unless it's inlined into the chunk of the importing stylesheet
+    let output = chunk_output("css-import-split", "index.css", move |builder| {
+        builder.chunking_config(config()).inline_css_imports(true)
+    })
+    .await
+    .unwrap();
+    assert_eq!(css_chunks(&output), 1, "{output:#?}");
+    let code = css_chunk(&output);
+    let media = code.find("@media print").expect("@media print is missing");
+    assert!(media < position(code, ".theme"), "{code}");
+    assert!(
+        position(code, ".theme") < position(code, ".index"),
+        "{code}"
+    );
+}
+
+async fn source_maps_output(source_maps_type: SourceMapsType) -> IndexMap<RcStr, String> {
+    chunk_output("source-maps", "index.js", move |builder| {
+        builder.source_maps_type(source_maps_type)
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn full_source_maps() {
+    let output = source_maps_output(SourceMapsType::Full).await;
+    let (path, code) = js_chunk(&output);
+    let file_name = path.rsplit('/').next().unwrap();
+    assert!(
+        code.contains(&format!("\n//# sourceMappingURL={file_name}.map")),
+        "{code}"
+    );
+    let map = &output[&*format!("{path}.map")];
+    assert!(map.contains("sourcesContent"), "{map}");
+}
+
+#[tokio::test]
+async fn hidden_source_maps() {
+    let output = source_maps_output(SourceMapsType::Hidden).await;
+    let (path, code) = js_chunk(&output);
+    assert!(!code.contains("sourceMappingURL"), "{code}");
+    let map = &output[&*format!("{path}.map")];
+    assert!(map.contains("sourcesContent"), "{map}");
+}
+
+#[tokio::test]
+async fn no_sources_source_maps() {
+    let output = source_maps_output(SourceMapsType::NoSources).await;
+    let (path, code) = js_chunk(&output);
+    let file_name = path.rsplit('/').next().unwrap();
+    assert!(
+        code.contains(&format!("\n//# sourceMappingURL={file_name}.map")),
+        "{code}"
+    );
+    let map = &output[&*format!("{path}.map")];
+    assert!(!map.contains("sourcesContent"), "{map}");
+}
+
+#[tokio::test]
+async fn inline_source_maps() {
+    let output = source_maps_output(SourceMapsType::Inline).await;
+    let (path, code) = js_chunk(&output);
+    assert!(
+        code.contains("\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,"),
+        "{code}"
+    );
+    assert!(!output.contains_key(&*format!("{path}.map")));
+}
+
+#[tokio::test]
+async fn node_inline_source_maps() {
+    let output = node_chunk_output("source-maps", "index.js", SourceMapsType::Inline)
+        .await
+        .unwrap();
+    // The runtime chunk and the chunk of the module. The entry chunk only
+    // contains generated code without a source map.
+    let chunks = output
+        .iter()
+        .filter(|(path, _)| path.ends_with(".js") && *path != "index.js.entry.js")
+        .collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 2, "{output:#?}");
+    assert!(
+        chunks.iter().any(|(path, _)| path.contains("runtime")),
+        "{output:#?}"
+    );
+    for (path, code) in chunks {
+        assert!(
+            code.contains("\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,"),
+            "{path}: {code}"
+        );
+        assert!(!output.contains_key(&*format!("{path}.map")));
+    }
+}
+
+async fn wasm_output(inline_wasm_limit: u64) -> IndexMap<RcStr, String> {
+    chunk_output("wasm", "index.js", move |builder| {
+        builder.inline_wasm_limit(Some(inline_wasm_limit))
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn inline_wasm_below_limit() {
+    // add.wasm has 41 bytes
+    let output = wasm_output(42).await;
+    assert!(!output.keys().any(|path| path.ends_with(".wasm")));
+    assert!(
+        output
+            .values()
+            .any(|code| code.contains("Uint8Array.from(atob(")),
+        "{output:#?}"
+    );
+}
+
+#[tokio::test]
+async fn emit_wasm_at_limit() {
+    let output = wasm_output(41).await;
+    assert!(
+        output.keys().any(|path| path.ends_with(".wasm")),
+        "{:#?}",
+        output.keys()
+    );
+    assert!(!output
+        .values()
+        .any(|code| code.contains("Uint8Array.from(atob(")));
+}
+
+#[tokio::test]
+async fn node_wasm_async_modules() {
+    let output = node_chunk_output("wasm", "bindgen.js", SourceMapsType::None)
+        .await
+        .unwrap();
+    // The WebAssembly module is read from the disk
+    assert!(
+        output.keys().any(|path| path.ends_with(".wasm")),
+        "{:#?}",
+        output.keys()
+    );
+
+    // Instantiating happens in a top level await
+    let wasm = module_factory(&output, "bindgen/pkg_bg.wasm [test] (wasm module)");
+    assert!(wasm.contains("__turbopack_wasm__("), "{wasm}");
+    assert!(wasm.contains("}, true);"), "{wasm}");
+
+    // Which makes the glue code and all importers async modules, without a top
+    // level await of their own
+    for path in ["bindgen/pkg_bg.js", "bindgen/pkg.js", "bindgen.js"] {
+        let factory = module_factory(&output, &format!("{path} [test] (ecmascript)"));
+        assert!(factory.contains("__turbopack_async_module__("), "{factory}");
+        assert!(factory.contains("}, false);"), "{factory}");
+    }
+}
+
+#[tokio::test]
+async fn wasm_component_issue() {
+    let issues = chunk_issues("wasm", "component.js").await.unwrap();
+    let [issue] = &issues[..] else {
+        panic!("expected a single issue, got {issues:#?}");
+    };
+    assert_eq!(issue.severity, IssueSeverity::Error);
+    assert_eq!(
+        issue.title,
+        StyledString::Text("WebAssembly components are not supported".into())
+    );
+    assert!(
+        issue.file_path.ends_with("/wasm/component.wasm"),
+        "{issue:#?}"
+    );
+}
+
+#[tokio::test]
+async fn css_module_values() {
+    let output = chunk_output("css-module-values", "index.js", |builder| builder)
+        .await
+        .unwrap();
+    let css = css_chunk(&output);
+    assert!(css.contains("color: #0070f3;"), "{css}");
+    assert!(css.contains("@media (max-width: 600px)"), "{css}");
+    assert!(
+        output
+            .values()
+            .any(|code| code.contains(r##""primary": "#0070f3","##)
+                && code.contains(r#""small": "(max-width: 600px)","#)),
+        "values are not exported in {output:#?}"
+    );
+}
+
+#[tokio::test]
+async fn css_module_missing_value_issue() {
+    let issues = chunk_issues("css-module-values", "index.js").await.unwrap();
+    let [issue] = &issues[..] else {
+        panic!("expected a single issue, got {issues:#?}");
+    };
+    assert_eq!(issue.severity, IssueSeverity::Error);
+    assert!(
+        issue
+            .file_path
+            .ends_with("/css-module-values/button.module.css"),
+        "{issue:#?}"
+    );
+    assert_eq!(
+        issue.description,
+        Some(StyledString::Text(
+            "Value `missing` is not defined in module ./colors.module.css.".into()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn css_module_value_cycle_issues() {
+    let issues = chunk_issues("css-module-values", "cycle.js").await.unwrap();
+    let mut cycles = issues
+        .iter()
+        .filter_map(|issue| match &issue.description {
+            Some(StyledString::Text(text)) if text.contains("imports values from this module") => {
+                Some(issue.file_path.rsplit('/').next().unwrap().to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    cycles.sort();
+    cycles.dedup();
+    assert_eq!(
+        cycles,
+        ["a.module.css", "b.module.css", "self.module.css"],
+        "{issues:#?}"
+    );
+}
+
+/// Analyzes the client assets of `entry` of the fixture and all assets they
+/// reference.
+async fn analyze_report(
+    fixture: &'static str,
+    entry: &'static str,
+) -> Result<ReadRef<AnalyzeReport>> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let (output_path, chunks) = entry_chunks(fixture, entry, |builder| builder).await?;
+        AnalyzeReport::new(
+            all_assets_from_entries(chunks),
+            output_path,
+            OutputAssets::empty(),
+            output_path,
+        )
+        .await
+    })
+    .await
+}
+
+fn analyzed_module<'a>(report: &'a AnalyzeReport, path: &str) -> (usize, &'a AnalyzeModule) {
+    report
+        .modules
+        .iter()
+        .enumerate()
+        .find(|(_, module)| module.path == path)
+        .unwrap_or_else(|| panic!("{path} is not in the report"))
+}
+
+#[tokio::test]
+async fn analyze_chunk_modules() {
+    let report = analyze_report("analyze", "index.js").await.unwrap();
+    for path in ["analyze/index.js", "analyze/a.js", "analyze/b.js"] {
+        let (index, module) = analyzed_module(&report, path);
+        assert!(!module.chunks.is_empty(), "{path} is in no chunk");
+        for &chunk in &module.chunks {
+            let chunk = &report.chunks[chunk];
+            assert!(!chunk.server);
+            assert!(
+                chunk.modules.contains(&index),
+                "{} doesn't contain {path}",
+                chunk.path
+            );
+        }
+    }
+    // The dynamically imported module is placed into a separate chunk, the
+    // shared module into the chunks of both
+    let (_, a) = analyzed_module(&report, "analyze/a.js");
+    let (_, b) = analyzed_module(&report, "analyze/b.js");
+    let (_, shared) = analyzed_module(&report, "analyze/shared.js");
+    assert!(b.chunks.iter().all(|chunk| !a.chunks.contains(chunk)));
+    assert!(a.chunks.iter().any(|chunk| shared.chunks.contains(chunk)));
+    assert!(b.chunks.iter().any(|chunk| shared.chunks.contains(chunk)));
+}
+
+#[tokio::test]
+async fn analyze_reasons() {
+    let report = analyze_report("analyze", "index.js").await.unwrap();
+    let (_, index) = analyzed_module(&report, "analyze/index.js");
+    assert!(index.reasons.is_empty());
+
+    let (_, a) = analyzed_module(&report, "analyze/a.js");
+    let [reason] = &a.reasons[..] else {
+        panic!("expected a single reason for a.js");
+    };
+    assert!(
+        reason.issuer.contains("analyze/index.js"),
+        "{}",
+        reason.issuer
+    );
+    assert!(!matches!(reason.chunking_type, ChunkingType::Async));
+
+    let (_, b) = analyzed_module(&report, "analyze/b.js");
+    let [reason] = &b.reasons[..] else {
+        panic!("expected a single reason for b.js");
+    };
+    assert!(
+        reason.issuer.contains("analyze/index.js"),
+        "{}",
+        reason.issuer
+    );
+    assert!(matches!(reason.chunking_type, ChunkingType::Async));
+
+    let (_, shared) = analyzed_module(&report, "analyze/shared.js");
+    let mut issuers = shared
+        .reasons
+        .iter()
+        .map(|reason| reason.issuer.rsplit('/').next().unwrap().to_string())
+        .collect::<Vec<_>>();
+    issuers.sort();
+    assert_eq!(issuers, ["a.js", "b.js"]);
+}
@@ -0,0 +1,4 @@
+import { a } from "./a.js";
+
+console.log(a);
+import("./b.js").then(({ b }) => console.log(b));
@@ -0,0 +1,4 @@
+import a from "./a.module.css";
+import self from "./self.module.css";
+
+console.log(a, self);
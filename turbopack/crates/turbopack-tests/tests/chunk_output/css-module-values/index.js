@@ -0,0 +1,3 @@
+import styles from "./button.module.css";
+
+console.log(styles);
@@ -0,0 +1,3 @@
+import { add } from "./bindgen/pkg.js";
+
+console.log(add(1, 2));
@@ -0,0 +1,2 @@
+import * as wasm from "./pkg_bg.wasm";
+export * from "./pkg_bg.js";
@@ -0,0 +1,19 @@
+// Shaped like the glue code older versions of `wasm-bindgen` generate for the
+// bundler target, which imports the WebAssembly module it belongs to
+import * as wasm from "./pkg_bg.wasm";
+
+export function add(a, b) {
+  return wasm.add(a, b);
+}
+
+export function add_offset(a) {
+  return wasm.add_offset(a);
+}
+
+export function __wbg_double(x) {
+  return x * 2;
+}
+
+export function offset(x) {
+  return x + 100;
+}
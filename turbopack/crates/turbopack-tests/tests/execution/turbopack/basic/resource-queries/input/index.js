@@ -0,0 +1,24 @@
+import value from "./file.js";
+import source from "./file.js?raw";
+import text from "./text.txt?raw";
+import url from "./text.txt?url";
+import inline from "./text.txt?inline";
+
+it("should evaluate the module without a query", () => {
+  expect(value).toBe("file");
+});
+
+it("should export the content as a string for ?raw", () => {
+  expect(source).toBe('export default "file";\n');
+  expect(text).toBe("Hello world\n");
+});
+
+it("should export the URL of the emitted asset for ?url", () => {
+  expect(url).toMatch(/^\/.*\.txt$/);
+});
+
+it("should export a data URI for ?inline", () => {
+  expect(inline).toBe(
+    `data:text/plain;base64,${Buffer.from("Hello world\n").toString("base64")}`
+  );
+});
@@ -0,0 +1,14 @@
+const load = (name) => import(`pkg/${name}`);
+
+it("should resolve dynamic imports through exports patterns", async () => {
+  expect((await load("a")).default).toBe("a");
+  expect((await load("b")).default).toBe("b");
+});
+
+it("should not resolve files that aren't exported", async () => {
+  await expect(load("private")).rejects.toThrowError();
+  const modules = Object.keys(__turbopack_modules__);
+  expect(modules).not.toContainEqual(
+    expect.stringMatching(/input\/node_modules\/pkg\/private/)
+  );
+});
@@ -0,0 +1,17 @@
+describe("wasm-bindgen with cyclic glue code", () => {
+  it("should instantiate the WebAssembly module imported by the entry point", async () => {
+    const { add, add_offset } = await import("./pkg/pkg.js");
+
+    expect(add(2, 3)).toEqual(10);
+    expect(add_offset(1)).toEqual(101);
+  });
+
+  it("should read the other imports from glue code that isn't evaluated yet", async () => {
+    // offset is read when the WebAssembly module is instantiated, while the
+    // glue code waits for the WebAssembly module
+    const { add, add_offset } = await import("./glue-first/pkg_bg.js");
+
+    expect(add_offset(1)).toEqual(101);
+    expect(add(2, 3)).toEqual(10);
+  });
+});
@@ -0,0 +1,23 @@
+describe("wasm-bindgen", () => {
+  it("should make importing WebAssembly async", async () => {
+    // pkg_bg.wasm is an async module, so we require it and await inside this function to make sure the entrypoint isn't async.
+    const wasm = require("./pkg_bg.wasm");
+
+    expect(wasm).toBeInstanceOf(Promise);
+    await wasm;
+  });
+
+  it("should import the glue functions", async () => {
+    const { add } = await import("./pkg_bg.wasm");
+
+    // add doubles the sum with __wbg_double of the glue code
+    expect(add(2, 3)).toEqual(10);
+  });
+
+  it("should pass the exports to the glue code", async () => {
+    await import("./pkg_bg.wasm");
+    const { add } = await import("./pkg_bg.js");
+
+    expect(add(1, 2)).toEqual(6);
+  });
+});
@@ -0,0 +1,14 @@
+// Shaped like the glue code `wasm-bindgen` generates for the bundler target
+let wasm;
+
+export function __wbg_set_wasm(val) {
+  wasm = val;
+}
+
+export function add(a, b) {
+  return wasm.add(a, b);
+}
+
+export function __wbg_double(x) {
+  return x * 2;
+}
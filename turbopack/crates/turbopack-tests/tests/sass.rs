@@ -0,0 +1,171 @@
+#![cfg(test)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+mod util;
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value as JsonValue};
+use sourcemap::SourceMap as RegularMap;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_env::CommandLineProcessEnv;
+use turbo_tasks_fs::{DiskFileSystem, FileContent, FileSystem};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::evaluate_context::{node_build_environment, node_evaluate_asset_context};
+use turbopack_core::{
+    asset::Asset,
+    chunk::SourceMapsType,
+    file_source::FileSource,
+    resolve::options::{ImportMap, ImportMapping},
+    source_map::GenerateSourceMap,
+    source_transform::SourceTransform,
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_node::{execution_context::ExecutionContext, transforms::sass::SassTransform};
+use turbopack_nodejs::NodeJsChunkingContext;
+use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
+
+use crate::util::REPO_ROOT;
+
+fn register() {
+    turbo_tasks::register();
+    turbo_tasks_env::register();
+    turbo_tasks_fs::register();
+    turbopack::register();
+    turbopack_nodejs::register();
+    turbopack_ecmascript_runtime::register();
+    turbopack_resolve::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_sass.rs"));
+}
+
+/// Compiles `tests/sass/index.scss` with the `sass` package of the repository.
+/// `@theme` is aliased to `tests/sass/theme.scss`. Returns the CSS and its
+/// source map.
+async fn compile(sass_options: JsonValue) -> Result<(String, RegularMap)> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let fs = DiskFileSystem::new("workspace".into(), REPO_ROOT.clone(), vec![]);
+        let root = fs.root();
+        let project_path = root.join("turbopack/crates/turbopack-tests/tests/sass".into());
+        let output_path = project_path.join("output".into());
+
+        let chunking_context = NodeJsChunkingContext::builder(
+            root,
+            output_path,
+            output_path,
+            output_path,
+            output_path,
+            node_build_environment(),
+            RuntimeType::Development,
+        )
+        .build();
+        let execution_context = ExecutionContext::new(
+            root,
+            Vc::upcast(chunking_context),
+            Vc::upcast(CommandLineProcessEnv::new()),
+        );
+
+        let mut sass_import_map = ImportMap::empty();
+        sass_import_map.insert_exact_alias(
+            "@vercel/turbopack/sass",
+            ImportMapping::PrimaryAlternative("sass".into(), Some(project_path)).cell(),
+        );
+        let mut import_map = ImportMap::empty();
+        import_map.insert_exact_alias(
+            "@theme",
+            ImportMapping::PrimaryAlternative("./theme.scss".into(), Some(project_path)).cell(),
+        );
+        let transform = SassTransform::new(
+            node_evaluate_asset_context(
+                execution_context,
+                Some(sass_import_map.cell()),
+                None,
+                "sass".into(),
+                true,
+            ),
+            execution_context,
+            ResolveOptionsContext {
+                enable_node_modules: Some(root),
+                import_map: Some(import_map.cell()),
+                ..Default::default()
+            }
+            .cell(),
+            Some(Vc::cell(sass_options)),
+        );
+
+        let source = Vc::upcast::<Box<dyn SourceTransform>>(transform).transform(Vc::upcast(
+            FileSource::new(project_path.join("index.scss".into())),
+        ));
+        let FileContent::Content(css) = &*source.content().file_content().await? else {
+            bail!("index.scss failed to compile");
+        };
+        let css = css.content().to_str()?.into_owned();
+        let Some(generator) =
+            Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(source).await?
+        else {
+            bail!("the compiled Sass has no source map");
+        };
+        let Some(map) = *generator.generate_source_map().await? else {
+            bail!("the compiled Sass has no source map");
+        };
+        let map = map.to_rope(SourceMapsType::Full).await?;
+        Ok((css, RegularMap::from_slice(&map.to_bytes()?)?))
+    })
+    .await
+}
+
+/// Returns the source line and the source of the mapping of the first line of
+/// `css` that contains `code`.
+fn original_position(css: &str, map: &RegularMap, code: &str) -> (u32, String) {
+    let (line, column) = css
+        .lines()
+        .enumerate()
+        .find_map(|(line, content)| Some((line, content.find(code)?)))
+        .unwrap_or_else(|| panic!("{code} is missing in\n{css}"));
+    let token = map
+        .lookup_token(line as u32, column as u32)
+        .unwrap_or_else(|| panic!("{code} is not mapped"));
+    (
+        token.get_src_line(),
+        token.get_source().unwrap_or_default().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn resolves_packages_and_aliases() {
+    let (css, _) = compile(json!({ "prependData": "$size: 10px;" }))
+        .await
+        .unwrap();
+    // `~pkg/colors` is resolved from node_modules
+    assert!(css.contains("color: blue;"), "{css}");
+    // `@theme` is resolved through the import map
+    assert!(css.contains("background: white;"), "{css}");
+    // `pkg/mixins` is the `_mixins.scss` partial in the package
+    assert!(css.contains("border-radius: 4px;"), "{css}");
+    assert!(css.contains("width: 10px;"), "{css}");
+}
+
+#[tokio::test]
+async fn maps_additional_data_to_original_lines() {
+    let (css, map) = compile(json!({ "additionalData": "$size: 10px;\n$unused: 0;" }))
+        .await
+        .unwrap();
+    assert!(css.contains("width: 10px;"), "{css}");
+
+    let (line, source) = original_position(&css, &map, ".index");
+    assert!(source.ends_with("/tests/sass/index.scss"), "{source}");
+    assert_eq!(line, 4);
+    assert_eq!(original_position(&css, &map, "width").0, 7);
+    let (line, source) = original_position(&css, &map, "border-radius");
+    assert!(source.ends_with("/pkg/_mixins.scss"), "{source}");
+    assert_eq!(line, 1);
+
+    // The original content of the file is embedded, without `additionalData`
+    let index = map
+        .sources()
+        .position(|source| source.ends_with("/tests/sass/index.scss"))
+        .unwrap();
+    let contents = map.get_source_contents(index as u32).unwrap();
+    assert!(contents.starts_with("@use \"~pkg/colors\";"), "{contents}");
+}
@@ -0,0 +1,555 @@
+use std::{cmp::Reverse, path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::{
+    reader::TraceReader, span_bottom_up_ref::SpanBottomUpRef, span_ref::SpanRef, store::Store,
+    store_container::StoreContainer,
+};
+
+/// A limit that fails the analysis when exceeded. Times are in microseconds
+/// and allocations in bytes, like in the store.
+#[derive(Debug)]
+pub enum Threshold {
+    /// Wall time of the whole trace.
+    Duration(u64),
+    /// Sum of the CPU time of all spans.
+    Cpu(u64),
+    /// Sum of all allocations.
+    Allocations(u64),
+    /// Sum of all allocations that were not deallocated.
+    PersistentAllocations(u64),
+    /// Largest amount of memory that was allocated at the same time.
+    PeakAllocations(u64),
+    /// Aggregated self time of all spans with this name.
+    SelfTime { name: String, max: u64 },
+}
+
+#[derive(Debug)]
+pub struct AnalyzeOptions {
+    pub top: usize,
+    pub json: bool,
+    pub thresholds: Vec<Threshold>,
+}
+
+impl AnalyzeOptions {
+    /// Parses `--top <n>`, `--json`, `--max-duration <ms>`, `--max-cpu <ms>`,
+    /// `--max-allocations <MB>`, `--max-persistent-allocations <MB>`,
+    /// `--max-peak-allocations <MB>` and `--max-self-time <name>=<ms>`.
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut options = AnalyzeOptions {
+            top: 20,
+            json: false,
+            thresholds: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--json" => options.json = true,
+                "--top" => options.top = value()?.parse().context("invalid --top")?,
+                "--max-duration" => options.thresholds.push(Threshold::Duration(ms(value()?)?)),
+                "--max-cpu" => options.thresholds.push(Threshold::Cpu(ms(value()?)?)),
+                "--max-allocations" => options
+                    .thresholds
+                    .push(Threshold::Allocations(mb(value()?)?)),
+                "--max-persistent-allocations" => options
+                    .thresholds
+                    .push(Threshold::PersistentAllocations(mb(value()?)?)),
+                "--max-peak-allocations" => options
+                    .thresholds
+                    .push(Threshold::PeakAllocations(mb(value()?)?)),
+                "--max-self-time" => {
+                    let value = value()?;
+                    let Some((name, max)) = value.rsplit_once('=') else {
+                        bail!("--max-self-time expects <name>=<ms>, got {value}");
+                    };
+                    options.thresholds.push(Threshold::SelfTime {
+                        name: name.to_string(),
+                        max: ms(max)?,
+                    });
+                }
+                _ => bail!("unknown argument {arg}"),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn ms(value: &str) -> Result<u64> {
+    let value: f64 = value
+        .parse()
+        .with_context(|| format!("invalid milliseconds {value}"))?;
+    Ok((value * 1000.0) as u64)
+}
+
+fn mb(value: &str) -> Result<u64> {
+    let value: f64 = value
+        .parse()
+        .with_context(|| format!("invalid megabytes {value}"))?;
+    Ok((value * 1024.0 * 1024.0) as u64)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    spans: u64,
+    duration: u64,
+    cpu: u64,
+    allocations: u64,
+    persistent_allocations: u64,
+    peak_allocations: u64,
+    allocation_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpanSummary<'a> {
+    category: &'a str,
+    name: &'a str,
+    start: u64,
+    self_duration: u64,
+    self_cpu: u64,
+    total_cpu: u64,
+    self_allocations: u64,
+    total_allocations: u64,
+}
+
+impl<'a> SpanSummary<'a> {
+    fn new(span: SpanRef<'a>) -> Self {
+        let (category, name) = span.nice_name();
+        Self {
+            category,
+            name,
+            start: span.start(),
+            self_duration: span.corrected_self_time(),
+            self_cpu: span.self_time(),
+            total_cpu: span.total_time(),
+            self_allocations: span.self_allocations(),
+            total_allocations: span.total_allocations(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupSummary<'a> {
+    name: &'a str,
+    count: u64,
+    self_duration: u64,
+    self_cpu: u64,
+    self_allocations: u64,
+    self_allocation_count: u64,
+}
+
+impl<'a> GroupSummary<'a> {
+    fn new(bottom_up: &SpanBottomUpRef<'a>) -> Self {
+        Self {
+            name: bottom_up.group_name(),
+            count: bottom_up.self_span_count(),
+            self_duration: bottom_up.corrected_self_time(),
+            self_cpu: bottom_up.self_time(),
+            self_allocations: bottom_up.self_allocations(),
+            self_allocation_count: bottom_up.self_allocation_count(),
+        }
+    }
+}
+
+/// The result of analyzing a trace. All times are in microseconds and all
+/// allocations in bytes. Durations are corrected for concurrency, i. e. time
+/// that is spent in parallel is split between the spans.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Analysis<'a> {
+    summary: Summary,
+    top_self_time: Vec<SpanSummary<'a>>,
+    top_aggregated_self_time: Vec<GroupSummary<'a>>,
+    top_allocations: Vec<SpanSummary<'a>>,
+    top_aggregated_allocations: Vec<GroupSummary<'a>>,
+    violations: Vec<String>,
+}
+
+fn analyze_store<'a>(store: &'a Store, options: &AnalyzeOptions) -> Analysis<'a> {
+    let root = store.root_span();
+    let spans = store
+        .spans
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(index, span)| SpanRef { span, store, index })
+        .collect::<Vec<_>>();
+    let groups = root.bottom_up().collect::<Vec<_>>();
+
+    let summary = Summary {
+        spans: spans.len() as u64,
+        duration: root.end().saturating_sub(root.start()),
+        cpu: root.total_time(),
+        allocations: root.total_allocations(),
+        persistent_allocations: root.total_persistent_allocations(),
+        peak_allocations: store.peak_allocations(),
+        allocation_count: root.total_allocation_count(),
+    };
+
+    let mut violations = Vec::new();
+    for threshold in options.thresholds.iter() {
+        let (label, value, max, format): (String, u64, u64, fn(u64) -> String) = match threshold {
+            Threshold::Duration(max) => ("duration".into(), summary.duration, *max, format_time),
+            Threshold::Cpu(max) => ("cpu".into(), summary.cpu, *max, format_time),
+            Threshold::Allocations(max) => (
+                "allocations".into(),
+                summary.allocations,
+                *max,
+                format_bytes,
+            ),
+            Threshold::PersistentAllocations(max) => (
+                "persistent allocations".into(),
+                summary.persistent_allocations,
+                *max,
+                format_bytes,
+            ),
+            Threshold::PeakAllocations(max) => (
+                "peak allocations".into(),
+                summary.peak_allocations,
+                *max,
+                format_bytes,
+            ),
+            Threshold::SelfTime { name, max } => {
+                let value = groups
+                    .iter()
+                    .filter(|group| group.group_name() == name)
+                    .map(|group| group.corrected_self_time())
+                    .sum();
+                (format!("self time of {name}"), value, *max, format_time)
+            }
+        };
+        if value > max {
+            violations.push(format!(
+                "{label} is {}, exceeding {}",
+                format(value),
+                format(max)
+            ));
+        }
+    }
+
+    Analysis {
+        summary,
+        top_self_time: top(&spans, options.top, |span| span.corrected_self_time())
+            .map(|span| SpanSummary::new(*span))
+            .collect(),
+        top_aggregated_self_time: top(&groups, options.top, |group| group.corrected_self_time())
+            .map(GroupSummary::new)
+            .collect(),
+        top_allocations: top(&spans, options.top, |span| span.self_allocations())
+            .map(|span| SpanSummary::new(*span))
+            .collect(),
+        top_aggregated_allocations: top(&groups, options.top, |group| group.self_allocations())
+            .map(GroupSummary::new)
+            .collect(),
+        violations,
+    }
+}
+
+fn top<T>(items: &[T], count: usize, value: impl Fn(&T) -> u64) -> impl Iterator<Item = &T> {
+    let mut sorted = items.iter().collect::<Vec<_>>();
+    sorted.sort_by_cached_key(|item| Reverse(value(item)));
+    sorted.into_iter().take(count)
+}
+
+pub fn read_store(path: PathBuf) -> Result<Arc<StoreContainer>> {
+    let store = Arc::new(StoreContainer::new());
+    TraceReader::read_once(store.clone(), path)?;
+    Ok(store)
+}
+
+/// Reads the trace file and prints the analysis as text or JSON. Returns false
+/// when a threshold is exceeded.
+pub fn analyze_trace(path: PathBuf, options: &AnalyzeOptions) -> Result<bool> {
+    let store = read_store(path)?;
+    let store = store.read();
+    let analysis = analyze_store(&store, options);
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&analysis)?);
+    } else {
+        print_analysis(&analysis);
+    }
+    Ok(analysis.violations.is_empty())
+}
+
+fn print_analysis(analysis: &Analysis<'_>) {
+    let Summary {
+        spans,
+        duration,
+        cpu,
+        allocations,
+        persistent_allocations,
+        peak_allocations,
+        allocation_count,
+    } = analysis.summary;
+    println!(
+        "{spans} spans, {} duration, {} cpu, {} allocated ({allocation_count} allocations), {} \
+         persistent, {} peak",
+        format_time(duration),
+        format_time(cpu),
+        format_bytes(allocations),
+        format_bytes(persistent_allocations),
+        format_bytes(peak_allocations),
+    );
+
+    println!("\nTop spans by self time:");
+    for span in analysis.top_self_time.iter() {
+        println!(
+            "{:>10} {:>10} cpu  {} {}",
+            format_time(span.self_duration),
+            format_time(span.self_cpu),
+            span.category,
+            span.name
+        );
+    }
+    println!("\nTop span names by aggregated self time:");
+    for group in analysis.top_aggregated_self_time.iter() {
+        println!(
+            "{:>10} {:>10} cpu {:>8}x  {}",
+            format_time(group.self_duration),
+            format_time(group.self_cpu),
+            group.count,
+            group.name
+        );
+    }
+    println!("\nTop spans by allocations:");
+    for span in analysis.top_allocations.iter() {
+        println!(
+            "{:>10} {:>10} total  {} {}",
+            format_bytes(span.self_allocations),
+            format_bytes(span.total_allocations),
+            span.category,
+            span.name
+        );
+    }
+    println!("\nTop span names by aggregated allocations:");
+    for group in analysis.top_aggregated_allocations.iter() {
+        println!(
+            "{:>10} {:>10} allocations {:>8}x  {}",
+            format_bytes(group.self_allocations),
+            group.self_allocation_count,
+            group.count,
+            group.name
+        );
+    }
+
+    if !analysis.violations.is_empty() {
+        println!("\nThresholds exceeded:");
+        for violation in analysis.violations.iter() {
+            println!("  {violation}");
+        }
+    }
+}
+
+pub fn format_time(micros: u64) -> String {
+    if micros >= 1_000_000 {
+        format!("{:.2}s", micros as f64 / 1_000_000.0)
+    } else if micros >= 1_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{micros}µs")
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 * 1024 {
+        format!("{:.2} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    } else if bytes >= 1024 * 1024 {
+        format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.2} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use turbopack_trace_utils::tracing::TraceRow;
+
+    use super::*;
+
+    fn start(ts: u64, id: u64, parent: Option<u64>, name: &'static str) -> TraceRow<'static> {
+        TraceRow::Start {
+            ts,
+            id,
+            parent,
+            name: name.into(),
+            target: "test".into(),
+            values: Vec::new(),
+        }
+    }
+
+    fn allocation(ts: u64, allocations: u64, deallocations: u64) -> TraceRow<'static> {
+        TraceRow::Allocation {
+            ts,
+            thread_id: 1,
+            allocations,
+            allocation_count: if allocations > 0 { 5 } else { 0 },
+            deallocations,
+            deallocation_count: if deallocations > 0 { 1 } else { 0 },
+        }
+    }
+
+    /// A `root` span with a `child` span, which allocate 1000 and 500 bytes and
+    /// deallocate 1200 bytes after the child ended. The store subtracts 32
+    /// bytes and 4 allocations per span for the tracing itself.
+    fn write_fixture(name: &str) -> Result<PathBuf> {
+        let rows = [
+            start(0, 1, None, "root"),
+            TraceRow::Enter {
+                ts: 0,
+                id: 1,
+                thread_id: 1,
+            },
+            allocation(10, 1032, 0),
+            start(20, 2, Some(1), "child"),
+            TraceRow::Enter {
+                ts: 20,
+                id: 2,
+                thread_id: 1,
+            },
+            allocation(30, 532, 0),
+            TraceRow::Exit {
+                ts: 80,
+                id: 2,
+                thread_id: 1,
+            },
+            TraceRow::End { ts: 80, id: 2 },
+            allocation(90, 0, 1200),
+            TraceRow::Exit {
+                ts: 100,
+                id: 1,
+                thread_id: 1,
+            },
+            TraceRow::End { ts: 100, id: 1 },
+        ];
+        let mut trace = b"TRACEv0".to_vec();
+        for row in rows.iter() {
+            trace.extend(postcard::to_stdvec(row)?);
+        }
+        let path = std::env::temp_dir().join(format!(
+            "turbopack-trace-server-{}-{name}.trace",
+            std::process::id()
+        ));
+        fs::write(&path, trace)?;
+        Ok(path)
+    }
+
+    fn options(args: &[&str]) -> Result<AnalyzeOptions> {
+        AnalyzeOptions::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_analyze_store() -> Result<()> {
+        let path = write_fixture("analyze")?;
+        let store = read_store(path.clone())?;
+        fs::remove_file(path)?;
+        let store = store.read();
+        let analysis = analyze_store(&store, &options(&["--top", "1"])?);
+
+        let summary = &analysis.summary;
+        assert_eq!(summary.spans, 2);
+        assert_eq!(summary.duration, 100);
+        assert_eq!(summary.cpu, 100);
+        assert_eq!(summary.allocations, 1500);
+        assert_eq!(summary.persistent_allocations, 500);
+        // Includes the allocations of the tracing itself
+        assert_eq!(summary.peak_allocations, 1564);
+        assert_eq!(summary.allocation_count, 2);
+
+        let top_self_time = &analysis.top_self_time;
+        assert_eq!(top_self_time.len(), 1);
+        assert_eq!(top_self_time[0].name, "child");
+        assert_eq!(top_self_time[0].self_duration, 60);
+        let top_allocations = &analysis.top_allocations;
+        assert_eq!(top_allocations[0].name, "root");
+        assert_eq!(top_allocations[0].self_allocations, 1000);
+        assert_eq!(top_allocations[0].total_allocations, 1500);
+        assert!(analysis.violations.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_thresholds() -> Result<()> {
+        let path = write_fixture("thresholds")?;
+        let store = read_store(path.clone())?;
+        fs::remove_file(path)?;
+        let store = store.read();
+
+        let within = options(&[
+            "--max-duration",
+            "0.1",
+            "--max-peak-allocations",
+            "1",
+            "--max-self-time",
+            "child=0.06",
+        ])?;
+        assert!(analyze_store(&store, &within).violations.is_empty());
+
+        let exceeded = options(&[
+            "--max-cpu",
+            "0.05",
+            "--max-peak-allocations",
+            "0.001",
+            "--max-self-time",
+            "child=0.01",
+        ])?;
+        assert_eq!(
+            analyze_store(&store, &exceeded).violations,
+            [
+                "cpu is 100µs, exceeding 50µs",
+                "peak allocations is 1.53 KB, exceeding 1.02 KB",
+                "self time of child is 60µs, exceeding 10µs",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_trace() -> Result<()> {
+        let path = write_fixture("analyze_trace")?;
+        assert!(analyze_trace(path.clone(), &options(&["--json"])?)?);
+        assert!(!analyze_trace(
+            path.clone(),
+            &options(&["--max-duration", "0.01"])?
+        )?);
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_corrupt_trace() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "turbopack-trace-server-{}-corrupt.json",
+            std::process::id()
+        ));
+        fs::write(&path, br#"[{"ph":"X","ts":"not a number"}]"#)?;
+        let result = analyze_trace(path.clone(), &options(&[])?);
+        fs::remove_file(path)?;
+        assert!(result.is_err());
+
+        assert!(analyze_trace(
+            std::env::temp_dir().join("turbopack-trace-server-missing.trace"),
+            &options(&[])?
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert!(options(&["--top"]).is_err());
+        assert!(options(&["--max-self-time", "name"]).is_err());
+        assert!(options(&["--unknown"]).is_err());
+    }
+}
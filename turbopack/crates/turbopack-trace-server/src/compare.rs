@@ -0,0 +1,285 @@
+use std::{cmp::Reverse, collections::HashMap, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analyze::{format_time, read_store},
+    span_ref::SpanRef,
+    store::Store,
+};
+
+/// Aggregated values of all spans with the same name. Times are in
+/// microseconds, durations are corrected for concurrency.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct NameStats {
+    /// Number of spans, i. e. the number of task executions for turbo-tasks
+    /// functions.
+    pub count: u64,
+    pub total_duration: u64,
+    pub total_cpu: u64,
+    pub self_duration: u64,
+    pub self_cpu: u64,
+    pub allocations: u64,
+    pub allocation_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonRow {
+    pub name: String,
+    pub baseline: NameStats,
+    pub candidate: NameStats,
+}
+
+impl ComparisonRow {
+    pub fn total_duration_delta(&self) -> i64 {
+        self.candidate.total_duration as i64 - self.baseline.total_duration as i64
+    }
+}
+
+fn stats_by_name(store: &Store) -> HashMap<&str, NameStats> {
+    let mut stats: HashMap<&str, NameStats> = HashMap::new();
+    for (index, span) in store.spans.iter().enumerate().skip(1) {
+        let span = SpanRef { span, store, index };
+        let name = span.group_name();
+        let entry = stats.entry(name).or_default();
+        entry.count += 1;
+        entry.self_duration += span.corrected_self_time();
+        entry.self_cpu += span.self_time();
+        entry.allocations += span.self_allocations();
+        entry.allocation_count += span.self_allocation_count();
+        // Recursive spans are already included in the total time of their
+        // outermost span
+        let mut parent = span.parent();
+        let mut recursive = false;
+        while let Some(p) = parent {
+            if p.group_name() == name {
+                recursive = true;
+                break;
+            }
+            parent = p.parent();
+        }
+        if !recursive {
+            entry.total_duration += span.corrected_total_time();
+            entry.total_cpu += span.total_time();
+        }
+    }
+    stats
+}
+
+/// Compares the spans of two traces by name. Rows are sorted by the largest
+/// change in total duration first and can be filtered by a substring of the
+/// name.
+pub fn compare_stores(baseline: &Store, candidate: &Store, query: &str) -> Vec<ComparisonRow> {
+    let mut baseline = stats_by_name(baseline);
+    let mut rows = stats_by_name(candidate)
+        .into_iter()
+        .map(|(name, candidate)| ComparisonRow {
+            name: name.to_string(),
+            baseline: baseline.remove(name).unwrap_or_default(),
+            candidate,
+        })
+        .collect::<Vec<_>>();
+    rows.extend(baseline.into_iter().map(|(name, baseline)| ComparisonRow {
+        name: name.to_string(),
+        baseline,
+        candidate: NameStats::default(),
+    }));
+    rows.retain(|row| row.name.contains(query));
+    rows.sort_by_key(|row| Reverse(row.total_duration_delta().unsigned_abs()));
+    rows
+}
+
+#[derive(Debug)]
+pub struct CompareOptions {
+    pub top: usize,
+    pub json: bool,
+    pub query: String,
+}
+
+impl CompareOptions {
+    /// Parses `--top <n>`, `--json` and `--query <substring of span name>`.
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut options = CompareOptions {
+            top: 50,
+            json: false,
+            query: String::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--json" => options.json = true,
+                "--top" => options.top = value()?.parse().context("invalid --top")?,
+                "--query" => options.query = value()?.clone(),
+                _ => bail!("unknown argument {arg}"),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Reads a baseline and a candidate trace file and prints the span names with
+/// the largest changes in total duration as text or JSON.
+pub fn compare_traces(
+    baseline: PathBuf,
+    candidate: PathBuf,
+    options: &CompareOptions,
+) -> Result<()> {
+    let baseline = read_store(baseline)?;
+    let candidate = read_store(candidate)?;
+    let mut rows = compare_stores(&baseline.read(), &candidate.read(), &options.query);
+    rows.truncate(options.top);
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        print_comparison(&rows);
+    }
+    Ok(())
+}
+
+fn print_comparison(rows: &[ComparisonRow]) {
+    println!(
+        "{:>22} {:>22} {:>22} {:>18} {:>22}  name",
+        "total duration", "self duration", "cpu", "count", "allocations"
+    );
+    for ComparisonRow {
+        name,
+        baseline,
+        candidate,
+    } in rows
+    {
+        println!(
+            "{:>22} {:>22} {:>22} {:>18} {:>22}  {name}",
+            format_delta(
+                baseline.total_duration,
+                candidate.total_duration,
+                format_time
+            ),
+            format_delta(baseline.self_duration, candidate.self_duration, format_time),
+            format_delta(baseline.total_cpu, candidate.total_cpu, format_time),
+            format_delta(baseline.count, candidate.count, |count| count.to_string()),
+            format_delta(
+                baseline.allocation_count,
+                candidate.allocation_count,
+                |count| count.to_string()
+            ),
+        );
+    }
+}
+
+fn format_delta(baseline: u64, candidate: u64, format: impl Fn(u64) -> String) -> String {
+    let (sign, delta) = if candidate >= baseline {
+        ('+', candidate - baseline)
+    } else {
+        ('-', baseline - candidate)
+    };
+    if baseline == 0 {
+        format!("{sign}{} (new)", format(delta))
+    } else {
+        let percent = delta as f64 * 100.0 / baseline as f64;
+        format!("{sign}{} ({sign}{percent:.0}%)", format(delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::span::SpanIndex;
+
+    fn add(
+        store: &mut Store,
+        parent: Option<SpanIndex>,
+        name: &str,
+        start: u64,
+        end: u64,
+    ) -> SpanIndex {
+        let outdated_spans = &mut HashSet::new();
+        let index = store.add_span(
+            parent,
+            start,
+            String::new(),
+            name.to_string(),
+            Vec::new(),
+            outdated_spans,
+        );
+        store.add_self_time(index, start, end, outdated_spans);
+        store.complete_span(index);
+        index
+    }
+
+    #[test]
+    fn test_stats_by_name() {
+        let mut store = Store::new();
+        let root = add(&mut store, None, "root", 0, 10);
+        let outer = add(&mut store, Some(root), "recursive", 10, 20);
+        add(&mut store, Some(outer), "recursive", 20, 30);
+        add(&mut store, Some(root), "leaf", 30, 35);
+        add(&mut store, Some(root), "leaf", 35, 40);
+
+        let stats = stats_by_name(&store);
+        assert_eq!(stats.len(), 3);
+        let leaf = stats["leaf"];
+        assert_eq!(leaf.count, 2);
+        assert_eq!(leaf.self_cpu, 10);
+        assert_eq!(leaf.total_cpu, 10);
+        // The nested span is only counted in the total of the outer one
+        let recursive = stats["recursive"];
+        assert_eq!(recursive.count, 2);
+        assert_eq!(recursive.self_cpu, 20);
+        assert_eq!(recursive.total_cpu, 20);
+        let root = stats["root"];
+        assert_eq!(root.self_cpu, 10);
+        assert_eq!(root.total_cpu, 40);
+    }
+
+    #[test]
+    fn test_compare_stores() {
+        let mut baseline = Store::new();
+        let root = add(&mut baseline, None, "root", 0, 10);
+        add(&mut baseline, Some(root), "faster", 10, 60);
+        add(&mut baseline, Some(root), "removed", 60, 65);
+        add(&mut baseline, Some(root), "unchanged", 65, 70);
+
+        let mut candidate = Store::new();
+        let root = add(&mut candidate, None, "root", 0, 10);
+        add(&mut candidate, Some(root), "faster", 10, 30);
+        add(&mut candidate, Some(root), "added", 30, 50);
+        add(&mut candidate, Some(root), "unchanged", 50, 55);
+
+        let rows = compare_stores(&baseline, &candidate, "");
+        let deltas = rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.total_duration_delta()))
+            .collect::<Vec<_>>();
+        assert_eq!(deltas[0], ("faster", -30));
+        assert_eq!(deltas[1], ("added", 20));
+        assert_eq!(deltas[2], ("root", -15));
+        assert_eq!(deltas[3], ("removed", -5));
+        assert_eq!(deltas[4], ("unchanged", 0));
+        let added = &rows[1];
+        assert_eq!(added.baseline.count, 0);
+        assert_eq!(added.candidate.count, 1);
+        let removed = &rows[3];
+        assert_eq!(removed.baseline.count, 1);
+        assert_eq!(removed.candidate.count, 0);
+
+        let rows = compare_stores(&baseline, &candidate, "change");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "unchanged");
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(0, 1500, format_time), "+1.50ms (new)");
+        assert_eq!(format_delta(200, 100, |v| v.to_string()), "-100 (-50%)");
+        assert_eq!(format_delta(100, 150, |v| v.to_string()), "+50 (+50%)");
+    }
+}
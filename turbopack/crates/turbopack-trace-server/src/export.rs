@@ -0,0 +1,225 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    reader::TraceReader, span_ref::SpanRef, store::Store, store_container::StoreContainer,
+};
+
+/// Reads the trace file at `input` and writes its spans to `output` in the
+/// Chrome Trace Event format, which can be opened in Perfetto and Chrome
+/// DevTools.
+pub fn export_chrome_trace(input: PathBuf, output: &Path) -> Result<()> {
+    let store = Arc::new(StoreContainer::new());
+    TraceReader::read_once(store.clone(), input)?;
+    let file =
+        File::create(output).with_context(|| format!("Unable to create {}", output.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_chrome_trace(&store.read(), &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ChromeEvent<'a> {
+    name: &'a str,
+    cat: &'a str,
+    ph: &'static str,
+    ts: u64,
+    dur: u64,
+    pid: u32,
+    tid: usize,
+    args: IndexMap<&'a str, &'a str>,
+}
+
+/// Spans of the same parent run in parallel, but events on a Chrome trace
+/// thread must be nested. So every span is put on the lane of its parent when
+/// it's free, or on another lane that is free for the whole span.
+#[derive(Default)]
+struct Lanes {
+    busy_until: Vec<u64>,
+}
+
+impl Lanes {
+    fn allocate(&mut self, start: u64, end: u64) -> usize {
+        if let Some(lane) = self.busy_until.iter().position(|&busy| busy <= start) {
+            self.busy_until[lane] = end;
+            lane
+        } else {
+            self.busy_until.push(end);
+            self.busy_until.len() - 1
+        }
+    }
+}
+
+fn assign_lanes<'a>(
+    span: SpanRef<'a>,
+    lane: usize,
+    lanes: &mut Lanes,
+    spans: &mut Vec<(SpanRef<'a>, usize)>,
+) {
+    spans.push((span, lane));
+    let mut children = span.children().collect::<Vec<_>>();
+    children.sort_by_key(|child| child.start());
+    let mut lane_free_at = span.start();
+    for child in children {
+        let child_lane = if child.start() >= lane_free_at {
+            lane_free_at = child.end();
+            lane
+        } else {
+            lanes.allocate(child.start(), child.end())
+        };
+        assign_lanes(child, child_lane, lanes, spans);
+    }
+}
+
+fn write_chrome_trace(store: &Store, writer: &mut impl Write) -> Result<()> {
+    let mut lanes = Lanes::default();
+    let mut spans = Vec::new();
+    for span in store.root_spans() {
+        let lane = lanes.allocate(span.start(), span.end());
+        assign_lanes(span, lane, &mut lanes, &mut spans);
+    }
+
+    writer.write_all(b"{\"traceEvents\":[\n")?;
+    writer.write_all(
+        br#"{"name":"process_name","ph":"M","pid":1,"tid":0,"args":{"name":"turbopack"}}"#,
+    )?;
+    // Children before their parents, like trace events are usually written
+    for (span, lane) in spans.into_iter().rev() {
+        let (_, name) = span.nice_name();
+        let event = ChromeEvent {
+            name,
+            cat: &span.span.category,
+            ph: "X",
+            ts: span.start(),
+            dur: span.end().saturating_sub(span.start()),
+            pid: 1,
+            tid: lane + 1,
+            args: span.args().collect(),
+        };
+        writer.write_all(b",\n")?;
+        serde_json::to_writer(&mut *writer, &event)?;
+    }
+    writer.write_all(b"\n],\"displayTimeUnit\":\"ms\"}\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Reverse, collections::HashSet, fs};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::span::SpanIndex;
+
+    fn add(
+        store: &mut Store,
+        parent: Option<SpanIndex>,
+        name: &str,
+        start: u64,
+        end: u64,
+    ) -> SpanIndex {
+        let outdated_spans = &mut HashSet::new();
+        let index = store.add_span(
+            parent,
+            start,
+            "cat".to_string(),
+            name.to_string(),
+            Vec::new(),
+            outdated_spans,
+        );
+        store.set_total_time(index, start, end - start, outdated_spans);
+        store.complete_span(index);
+        index
+    }
+
+    #[test]
+    fn test_lanes() -> Result<()> {
+        let mut store = Store::new();
+        let root = add(&mut store, None, "root", 0, 100);
+        add(&mut store, Some(root), "a", 0, 50);
+        add(&mut store, Some(root), "b", 10, 30);
+        add(&mut store, Some(root), "c", 60, 90);
+        add(&mut store, None, "second", 20, 40);
+
+        let mut output = Vec::new();
+        write_chrome_trace(&store, &mut output)?;
+        let trace: Value = serde_json::from_slice(&output)?;
+        let mut events = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                assert_eq!(event["cat"], "cat");
+                (
+                    event["name"].as_str().unwrap().to_string(),
+                    event["tid"].as_u64().unwrap(),
+                    event["ts"].as_u64().unwrap(),
+                    event["dur"].as_u64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        events.sort_by_key(|&(_, tid, ts, dur)| (tid, ts, Reverse(dur)));
+        // Overlapping siblings are moved to other lanes
+        assert_eq!(
+            events,
+            [
+                ("root".to_string(), 1, 0, 100),
+                ("a".to_string(), 1, 0, 50),
+                ("c".to_string(), 1, 60, 30),
+                ("b".to_string(), 2, 10, 20),
+                ("second".to_string(), 3, 20, 20),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_round_trip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "turbopack-trace-server-export-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir)?;
+        let input = dir.join("input.json");
+        let output = dir.join("output.json");
+        fs::write(
+            &input,
+            br#"[
+                {"ph":"X","name":"child","ts":10,"dur":20,"pid":1,"tid":1},
+                {"ph":"X","name":"parent","ts":0,"dur":100,"pid":1,"tid":1},
+                {"ph":"X","name":"other","ts":50,"dur":10,"pid":1,"tid":2}
+            ]"#,
+        )?;
+        export_chrome_trace(input.clone(), &output)?;
+
+        let exported = Arc::new(StoreContainer::new());
+        TraceReader::read_once(exported.clone(), output)?;
+        assert_eq!(
+            exported.read().span_tree(),
+            ["parent 0..100", "  child 10..30", "other 50..60"]
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_missing_file() {
+        let dir = std::env::temp_dir();
+        assert!(export_chrome_trace(
+            dir.join("turbopack-trace-server-missing.json"),
+            &dir.join("turbopack-trace-server-missing-output.json"),
+        )
+        .is_err());
+    }
+}
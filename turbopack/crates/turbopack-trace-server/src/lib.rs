@@ -2,11 +2,23 @@
 #![feature(hash_raw_entry)]
 #![feature(box_patterns)]
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use anyhow::Result;
+
+pub use self::{
+    analyze::{analyze_trace, AnalyzeOptions, Threshold},
+    compare::{compare_traces, CompareOptions},
+};
 use self::{reader::TraceReader, server::serve, store_container::StoreContainer};
 
+mod analyze;
 mod bottom_up;
+mod compare;
+mod export;
 mod reader;
 mod self_time_tree;
 mod server;
@@ -24,7 +36,13 @@ pub fn start_turbopack_trace_server(path: PathBuf) {
     let store = Arc::new(StoreContainer::new());
     let reader = TraceReader::spawn(store.clone(), path);
 
-    serve(store, 5747);
+    serve(store, None, 5747);
 
     reader.join().unwrap();
 }
+
+/// Writes the spans of a trace file in any of the supported formats to a
+/// Chrome Trace Event JSON file.
+pub fn export_turbopack_trace(input: PathBuf, output: &Path) -> Result<()> {
+    export::export_chrome_trace(input, output)
+}
@@ -2,11 +2,23 @@
 #![feature(hash_raw_entry)]
 #![feature(box_patterns)]
 
-use std::{collections::HashSet, sync::Arc};
+use std::{path::Path, sync::Arc};
 
-use self::{reader::TraceReader, server::serve, store_container::StoreContainer};
+use anyhow::Result;
 
+use self::{
+    analyze::{analyze_trace, AnalyzeOptions},
+    compare::{compare_traces, CompareOptions},
+    export::export_chrome_trace,
+    reader::TraceReader,
+    server::serve,
+    store_container::StoreContainer,
+};
+
+mod analyze;
 mod bottom_up;
+mod compare;
+mod export;
 mod reader;
 mod self_time_tree;
 mod server;
@@ -20,17 +32,64 @@ mod u64_empty_string;
 mod u64_string;
 mod viewer;
 
-fn main() {
-    let args: HashSet<String> = std::env::args().skip(1).collect();
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("export") => {
+            let input = args.get(1).expect("missing argument: trace file path");
+            let output = args.get(2).expect("missing argument: output file path");
+            return export_chrome_trace(input.into(), Path::new(output));
+        }
+        Some("analyze") => {
+            let input = args.get(1).expect("missing argument: trace file path");
+            let options = AnalyzeOptions::parse(&args[2..])?;
+            if !analyze_trace(input.into(), &options)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some("compare") => {
+            let baseline = args
+                .get(1)
+                .expect("missing argument: baseline trace file path");
+            let candidate = args
+                .get(2)
+                .expect("missing argument: candidate trace file path");
+            let options = CompareOptions::parse(&args[3..])?;
+            compare_traces(baseline.into(), candidate.into(), &options)?;
+            return Ok(());
+        }
+        _ => {}
+    }
 
+    let mut baseline = None;
+    let mut positional = Vec::new();
     let mut iter = args.iter();
-    let arg = iter.next().expect("missing argument: trace file path");
-    let port = iter.next().map_or(5747, |s| s.parse().unwrap());
+    while let Some(arg) = iter.next() {
+        if arg == "--baseline" {
+            baseline = Some(iter.next().expect("missing value for --baseline"));
+        } else {
+            positional.push(arg);
+        }
+    }
+    let arg = positional
+        .first()
+        .expect("missing argument: trace file path");
+    let port = positional.get(1).map_or(5747, |s| s.parse().unwrap());
 
     let store = Arc::new(StoreContainer::new());
     let reader = TraceReader::spawn(store.clone(), arg.into());
 
-    serve(store, port);
+    // The baseline reader keeps running in the background like the main one
+    let baseline = baseline.map(|path| {
+        let baseline = Arc::new(StoreContainer::new());
+        TraceReader::spawn(baseline.clone(), path.into());
+        baseline
+    });
+
+    serve(store, baseline, port);
 
     reader.join().unwrap();
+    Ok(())
 }
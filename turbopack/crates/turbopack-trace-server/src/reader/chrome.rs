@@ -0,0 +1,387 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{
+    slices::{Slice, SliceTracks},
+    TraceFormat,
+};
+use crate::{span::SpanIndex, store::Store, store_container::StoreContainer};
+
+/// Checks if the buffer contains a Chrome trace in the JSON object format
+/// (`{"traceEvents": [...]}`) or the JSON array format (`[{"ph": ...}, ...]`).
+pub fn is_chrome_trace(buffer: &[u8]) -> bool {
+    let buffer = &buffer[skip_whitespace(buffer)..];
+    match buffer.first() {
+        Some(b'{') => {
+            // Perfetto traces start with a newline too, but not with a JSON key
+            let rest = &buffer[1..];
+            matches!(rest.get(skip_whitespace(rest)), Some(b'"' | b'}') | None)
+        }
+        Some(b'[') => {
+            // Next.js traces are arrays too, but their events have no phase
+            let head = &buffer[..buffer.len().min(4096)];
+            let first_event = head
+                .iter()
+                .position(|&b| b == b'}')
+                .map_or(head, |end| &head[..end]);
+            first_event.windows(4).any(|w| w == b"\"ph\"")
+        }
+        _ => false,
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum Track {
+    Thread {
+        pid: String,
+        tid: String,
+    },
+    Async {
+        pid: String,
+        cat: String,
+        id: String,
+    },
+}
+
+#[derive(PartialEq, Eq)]
+enum State {
+    Start,
+    Array,
+    Done,
+}
+
+/// Reads traces in the Chrome Trace Event format, as written by Chrome
+/// DevTools, Node.js (`--trace-events-enabled`) and webpack's
+/// `ProfilingPlugin`.
+pub struct ChromeTraceFormat {
+    store: Arc<StoreContainer>,
+    state: State,
+    tracks: SliceTracks<Track>,
+    thread_names: HashMap<(String, String), String>,
+    outdated_spans: HashSet<SpanIndex>,
+}
+
+impl ChromeTraceFormat {
+    pub fn new(store: Arc<StoreContainer>) -> Self {
+        Self {
+            store,
+            state: State::Start,
+            tracks: SliceTracks::new(),
+            thread_names: HashMap::new(),
+            outdated_spans: HashSet::new(),
+        }
+    }
+
+    fn process(&mut self, store: &mut Store, event: ChromeEvent<'_>) {
+        let ChromeEvent {
+            name,
+            cat,
+            ph,
+            ts,
+            dur,
+            pid,
+            tid,
+            id,
+            args,
+        } = event;
+        let pid = pid.map(|pid| pid.to_string()).unwrap_or_default();
+        let tid = tid.map(|tid| tid.to_string()).unwrap_or_default();
+        let start = micros(ts);
+        let args = match args {
+            Value::Object(args) => args
+                .into_iter()
+                .map(|(k, v)| match v {
+                    Value::String(s) => (k, s),
+                    v => (k, v.to_string()),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        match ph.as_ref() {
+            "M" => {
+                if name == "thread_name" {
+                    if let Some((_, thread_name)) = args.into_iter().find(|(k, _)| k == "name") {
+                        self.thread_names.insert((pid, tid), thread_name);
+                    }
+                }
+            }
+            "X" | "B" | "i" | "I" | "R" => {
+                let mut args = args;
+                if let Some(thread_name) = self.thread_names.get(&(pid.clone(), tid.clone())) {
+                    args.push(("thread".to_string(), thread_name.clone()));
+                }
+                let slice = Slice {
+                    start,
+                    category: cat.into_owned(),
+                    name: name.into_owned(),
+                    args,
+                };
+                let track = Track::Thread { pid, tid };
+                if ph == "B" {
+                    self.tracks.begin(track, slice);
+                } else {
+                    let end = start + dur.map_or(0, micros);
+                    self.tracks
+                        .complete(store, track, slice, end, &mut self.outdated_spans);
+                }
+            }
+            "E" => {
+                let track = Track::Thread { pid, tid };
+                self.tracks
+                    .end(store, &track, start, args, &mut self.outdated_spans);
+            }
+            "b" | "n" => {
+                let slice = Slice {
+                    start,
+                    category: cat.to_string(),
+                    name: name.into_owned(),
+                    args,
+                };
+                let track = Track::Async {
+                    pid,
+                    cat: cat.into_owned(),
+                    id: id.map(|id| id.to_string()).unwrap_or_default(),
+                };
+                if ph == "b" {
+                    self.tracks.begin(track, slice);
+                } else {
+                    self.tracks
+                        .complete(store, track, slice, start, &mut self.outdated_spans);
+                }
+            }
+            "e" => {
+                let track = Track::Async {
+                    pid,
+                    cat: cat.into_owned(),
+                    id: id.map(|id| id.to_string()).unwrap_or_default(),
+                };
+                self.tracks
+                    .end(store, &track, start, args, &mut self.outdated_spans);
+            }
+            // Counters, flow events, samples and object snapshots have no span equivalent
+            _ => {}
+        }
+    }
+
+    fn read_object(&mut self, buffer: &[u8]) -> Result<usize> {
+        // The object format can only be read as a whole
+        if buffer.trim_ascii_end().last() != Some(&b'}') {
+            return Ok(0);
+        }
+        let trace: ChromeTraceObject<'_> = match serde_json::from_slice(buffer) {
+            Ok(trace) => trace,
+            Err(err) if err.is_eof() => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        let store = self.store.clone();
+        let mut store = store.write();
+        for event in trace.trace_events {
+            self.process(&mut store, event);
+        }
+        store.invalidate_outdated_spans(&self.outdated_spans);
+        self.outdated_spans.clear();
+        self.state = State::Done;
+        Ok(buffer.len())
+    }
+
+    fn read_array(&mut self, buffer: &[u8]) -> Result<usize> {
+        let store = self.store.clone();
+        let mut store = store.write();
+        let mut index = 0;
+        loop {
+            index += buffer[index..]
+                .iter()
+                .position(|&b| !b.is_ascii_whitespace() && b != b',')
+                .unwrap_or(buffer.len() - index);
+            if index == buffer.len() {
+                break;
+            }
+            if buffer[index] == b']' {
+                self.state = State::Done;
+                index = buffer.len();
+                break;
+            }
+            let mut events =
+                serde_json::Deserializer::from_slice(&buffer[index..]).into_iter::<ChromeEvent>();
+            match events.next() {
+                Some(Ok(event)) => {
+                    index += events.byte_offset();
+                    self.process(&mut store, event);
+                }
+                Some(Err(err)) if err.is_eof() => break,
+                Some(Err(err)) => return Err(err.into()),
+                None => break,
+            }
+        }
+        store.invalidate_outdated_spans(&self.outdated_spans);
+        self.outdated_spans.clear();
+        Ok(index)
+    }
+}
+
+impl TraceFormat for ChromeTraceFormat {
+    fn read(&mut self, buffer: &[u8]) -> Result<usize> {
+        match self.state {
+            State::Start => {
+                let start = skip_whitespace(buffer);
+                match buffer.get(start) {
+                    None => Ok(start),
+                    Some(b'{') => Ok(start + self.read_object(&buffer[start..])?),
+                    Some(_) => {
+                        // Skip the `[`
+                        self.state = State::Array;
+                        Ok(start + 1 + self.read_array(&buffer[start + 1..])?)
+                    }
+                }
+            }
+            State::Array => self.read_array(buffer),
+            State::Done => Ok(buffer.len()),
+        }
+    }
+}
+
+fn skip_whitespace(buffer: &[u8]) -> usize {
+    buffer
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(buffer.len())
+}
+
+/// Timestamps and durations are in microseconds, but may have fractions.
+fn micros(value: f64) -> u64 {
+    value.max(0.0).round() as u64
+}
+
+#[derive(Deserialize)]
+struct ChromeTraceObject<'a> {
+    #[serde(borrow, rename = "traceEvents")]
+    trace_events: Vec<ChromeEvent<'a>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EventId {
+    Number(u64),
+    String(String),
+}
+
+impl Display for EventId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventId::Number(n) => write!(f, "{}", n),
+            EventId::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChromeEvent<'a> {
+    #[serde(borrow, default)]
+    name: Cow<'a, str>,
+    #[serde(borrow, default)]
+    cat: Cow<'a, str>,
+    #[serde(borrow)]
+    ph: Cow<'a, str>,
+    #[serde(default)]
+    ts: f64,
+    dur: Option<f64>,
+    pid: Option<EventId>,
+    tid: Option<EventId>,
+    id: Option<EventId>,
+    #[serde(default)]
+    args: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(format: &mut ChromeTraceFormat, data: &[u8]) -> Result<()> {
+        let read = format.read(data)?;
+        assert_eq!(read, data.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_chrome_trace() {
+        assert!(is_chrome_trace(br#"{"traceEvents":[]}"#));
+        assert!(is_chrome_trace(b"\n  {\n\"traceEvents\":[]}"));
+        assert!(is_chrome_trace(br#"[{"name":"a","ph":"X","ts":0}]"#));
+        assert!(is_chrome_trace(br#"[{"ph":"X","name":"a","ts":0}]"#));
+        // Next.js traces
+        assert!(!is_chrome_trace(
+            br#"[{"name":"a","duration":1,"timestamp":0}]"#
+        ));
+        // A Perfetto packet whose length is `{`
+        assert!(!is_chrome_trace(b"\n{\x08\x01"));
+        assert!(!is_chrome_trace(b"TRACEv0"));
+    }
+
+    #[test]
+    fn test_object_format() -> Result<()> {
+        let store = Arc::new(StoreContainer::new());
+        let mut format = ChromeTraceFormat::new(store.clone());
+        let data = br#"{"traceEvents":[
+            {"ph":"M","name":"thread_name","pid":1,"tid":2,"args":{"name":"main"}},
+            {"ph":"X","name":"child","cat":"c","ts":10.4,"dur":5,"pid":1,"tid":2},
+            {"ph":"X","name":"parent","ts":0,"dur":100,"pid":1,"tid":2,"args":{"n":1}},
+            {"ph":"X","name":"other thread","ts":10,"dur":5,"pid":1,"tid":3}
+        ]}"#;
+        // The object can only be read when it's complete
+        assert_eq!(format.read(&data[..data.len() - 1])?, 0);
+        read_all(&mut format, data)?;
+        let store = store.read();
+        assert_eq!(
+            store.span_tree(),
+            ["parent 0..100", "  child 10..15", "other thread 10..15"]
+        );
+        let parent = store.root_spans().find(|s| s.start() == 0).unwrap();
+        assert_eq!(
+            parent.args().collect::<Vec<_>>(),
+            [("n", "1"), ("thread", "main")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_format() -> Result<()> {
+        let store = Arc::new(StoreContainer::new());
+        let mut format = ChromeTraceFormat::new(store.clone());
+        let data = br#"[
+            {"ph":"B","name":"outer","ts":0,"pid":1,"tid":1},
+            {"ph":"B","name":"inner","ts":5,"pid":1,"tid":1},
+            {"ph":"E","ts":10,"pid":1,"tid":1},
+            {"ph":"b","name":"async","cat":"a","id":"0x1","ts":2,"pid":1},
+            {"ph":"e","cat":"a","id":"0x1","ts":30,"pid":1},
+            {"ph":"E","ts":20,"pid":1,"tid":1},
+            {"ph":"C","name":"counter","ts":0,"pid":1,"args":{"value":1}}
+        ]"#;
+        // Events are read as soon as they are complete, even without the end
+        // of the array
+        let split = data.windows(5).position(|w| w == b"inner").unwrap();
+        let read = format.read(&data[..split])?;
+        assert!(read > 0 && read < split);
+        read_all(&mut format, &data[read..])?;
+        assert_eq!(
+            store.read().span_tree(),
+            ["outer 0..20", "  inner 5..10", "async 2..30"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_event() {
+        let store = Arc::new(StoreContainer::new());
+        let mut format = ChromeTraceFormat::new(store);
+        assert!(format.read(br#"[{"name":"no phase"}]"#).is_err());
+    }
+}
@@ -1,9 +1,13 @@
+mod chrome;
 mod heaptrack;
 mod nextjs;
+mod perfetto;
+mod slices;
 mod turbopack;
 
 use std::{
     env,
+    fmt::Arguments,
     fs::File,
     io::{self, BufReader, Read, Seek, SeekFrom},
     path::PathBuf,
@@ -12,11 +16,17 @@ use std::{
     time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use flate2::bufread::GzDecoder;
 
 use crate::{
-    reader::{heaptrack::HeaptrackFormat, nextjs::NextJsFormat, turbopack::TurbopackFormat},
+    reader::{
+        chrome::{is_chrome_trace, ChromeTraceFormat},
+        heaptrack::HeaptrackFormat,
+        nextjs::NextJsFormat,
+        perfetto::{is_perfetto_trace, PerfettoFormat},
+        turbopack::TurbopackFormat,
+    },
     store_container::StoreContainer,
 };
 
@@ -70,18 +80,45 @@ impl TraceFile {
 pub struct TraceReader {
     store: Arc<StoreContainer>,
     path: PathBuf,
+    /// Keep reading when the trace file grows or is replaced.
+    follow: bool,
 }
 
 impl TraceReader {
     pub fn spawn(store: Arc<StoreContainer>, path: PathBuf) -> JoinHandle<()> {
-        let mut reader = Self { store, path };
+        let mut reader = Self {
+            store,
+            path,
+            follow: true,
+        };
         std::thread::spawn(move || reader.run())
     }
 
+    /// Reads the trace file once until its current end. Fails when the file
+    /// can't be opened or read. Progress is logged to stderr, so it doesn't
+    /// mix with the output of the command.
+    pub fn read_once(store: Arc<StoreContainer>, path: PathBuf) -> Result<()> {
+        let mut reader = Self {
+            store,
+            path,
+            follow: false,
+        };
+        if !reader.try_read()? {
+            bail!("Unable to read trace file at {}", reader.path.display());
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self) {
         let mut file_warning_printed = false;
         loop {
-            let read_success = self.try_read();
+            let read_success = match self.try_read() {
+                Ok(read_success) => read_success,
+                Err(err) => {
+                    println!("{err:#}");
+                    true
+                }
+            };
             if !file_warning_printed && !read_success {
                 println!("Unable to read trace file at {:?}, waiting...", self.path);
                 file_warning_printed = true;
@@ -90,6 +127,14 @@ impl TraceReader {
         }
     }
 
+    fn log(&self, message: Arguments<'_>) {
+        if self.follow {
+            println!("{message}");
+        } else {
+            eprintln!("{message}");
+        }
+    }
+
     fn trace_file_from_file(&self, file: File) -> io::Result<TraceFile> {
         let path = &self.path.to_string_lossy();
         Ok(if path.ends_with(".zst") {
@@ -101,17 +146,22 @@ impl TraceReader {
         })
     }
 
-    fn try_read(&mut self) -> bool {
+    /// Returns false when the file can't be opened, and an error when it
+    /// can't be read or parsed.
+    fn try_read(&mut self) -> Result<bool> {
         let Ok(mut file) = File::open(&self.path) else {
-            return false;
+            return Ok(false);
         };
-        println!("Trace file opened");
+        self.log(format_args!("Trace file opened"));
         let stop_at = env::var("STOP_AT")
             .unwrap_or_default()
             .parse()
             .map_or(u64::MAX, |v: u64| v * 1024 * 1024);
         if stop_at != u64::MAX {
-            println!("Will stop reading file at {} MB", stop_at / 1024 / 1024)
+            self.log(format_args!(
+                "Will stop reading file at {} MB",
+                stop_at / 1024 / 1024
+            ));
         }
 
         {
@@ -124,15 +174,11 @@ impl TraceReader {
         let mut current_read = 0;
         let mut initial_read = { file.seek(SeekFrom::End(0)).ok() };
         if file.seek(SeekFrom::Start(0)).is_err() {
-            return false;
+            return Ok(false);
         }
-        let mut file = match self.trace_file_from_file(file) {
-            Ok(f) => f,
-            Err(err) => {
-                println!("Error creating zstd decoder: {err}");
-                return false;
-            }
-        };
+        let mut file = self
+            .trace_file_from_file(file)
+            .context("Error creating zstd decoder")?;
 
         let mut buffer = Vec::new();
         let mut index = 0;
@@ -145,7 +191,7 @@ impl TraceReader {
                         if let Some(value) =
                             self.wait_for_more_data(&mut file, &mut initial_read, format.as_deref())
                         {
-                            return value;
+                            return Ok(value);
                         }
                     } else {
                         // If we have partially consumed some data, and we are at buffer capacity,
@@ -159,6 +205,10 @@ impl TraceReader {
                             if buffer.starts_with(b"TRACEv0") {
                                 index = 7;
                                 format = Some(Box::new(TurbopackFormat::new(self.store.clone())));
+                            } else if is_chrome_trace(&buffer) {
+                                format = Some(Box::new(ChromeTraceFormat::new(self.store.clone())));
+                            } else if is_perfetto_trace(&buffer) {
+                                format = Some(Box::new(PerfettoFormat::new(self.store.clone())));
                             } else if buffer.starts_with(b"[{\"name\"") {
                                 format = Some(Box::new(NextJsFormat::new(self.store.clone())));
                             } else if buffer.starts_with(b"v ") {
@@ -170,15 +220,7 @@ impl TraceReader {
                             }
                         }
                         if let Some(format) = &mut format {
-                            match format.read(&buffer[index..]) {
-                                Ok(bytes_read) => {
-                                    index += bytes_read;
-                                }
-                                Err(err) => {
-                                    println!("Trace file error: {err}");
-                                    return true;
-                                }
-                            }
+                            index += format.read(&buffer[index..]).context("Trace file error")?;
                             if self.store.want_to_read() {
                                 thread::yield_now();
                             }
@@ -195,24 +237,30 @@ impl TraceReader {
                                     let uncompressed = current_read / (1024 * 1024);
                                     let total = *total / (1024 * 1024);
                                     let stats = format.stats();
-                                    print!("{}% read ({}/{} MB)", percentage, read, total);
+                                    let mut progress =
+                                        format!("{}% read ({}/{} MB)", percentage, read, total);
                                     if uncompressed != read {
-                                        print!(" ({} MB uncompressed)", uncompressed);
+                                        progress += &format!(" ({} MB uncompressed)", uncompressed);
                                     }
-                                    if stats.is_empty() {
-                                        println!();
-                                    } else {
-                                        println!(" - {}", stats);
+                                    if !stats.is_empty() {
+                                        progress += &format!(" - {}", stats);
                                     }
+                                    self.log(format_args!("{progress}"));
                                 }
                             }
                             if current_read >= stop_at {
+                                if !self.follow {
+                                    self.log(format_args!(
+                                        "Stopped reading file as requested by STOP_AT env var."
+                                    ));
+                                    return Ok(true);
+                                }
                                 println!(
                                     "Stopped reading file as requested by STOP_AT env var. \
                                      Waiting for new file..."
                                 );
                                 self.wait_for_new_file(&mut file);
-                                return true;
+                                return Ok(true);
                             }
                         }
                     }
@@ -222,12 +270,14 @@ impl TraceReader {
                         if let Some(value) =
                             self.wait_for_more_data(&mut file, &mut initial_read, format.as_deref())
                         {
-                            return value;
+                            return Ok(value);
                         }
-                    } else {
+                    } else if self.follow {
                         // Error reading file, maybe it was removed
                         println!("Error reading trace file: {err:?}");
-                        return true;
+                        return Ok(true);
+                    } else {
+                        return Err(err).context("Error reading trace file");
                     }
                 }
             }
@@ -246,12 +296,18 @@ impl TraceReader {
         if let Some(total) = initial_read.take() {
             if let Some(format) = format {
                 let stats = format.stats();
-                println!("{}", stats);
+                self.log(format_args!("{}", stats));
             }
             if total > MIN_INITIAL_REPORT_SIZE {
-                println!("Initial read completed ({} MB)", total / (1024 * 1024));
+                self.log(format_args!(
+                    "Initial read completed ({} MB)",
+                    total / (1024 * 1024)
+                ));
             }
         }
+        if !self.follow {
+            return Some(true);
+        }
         loop {
             // No more data to read, sleep for a while to wait for more data
             thread::sleep(Duration::from_millis(100));
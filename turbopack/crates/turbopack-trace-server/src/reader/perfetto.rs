@@ -0,0 +1,574 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::{
+    slices::{Slice, SliceTracks},
+    TraceFormat,
+};
+use crate::{span::SpanIndex, store::Store, store_container::StoreContainer};
+
+/// Perfetto traces are a `Trace` message with `TracePacket`s in field 1. The
+/// tag of that field (`0x0a`) is also a newline, so text traces starting with
+/// an empty line would match it. The length and the fields of the first packet
+/// need to be valid protobuf too.
+pub fn is_perfetto_trace(buffer: &[u8]) -> bool {
+    let mut remaining = buffer;
+    if read_varint(&mut remaining) != Some(TRACE_PACKET_TAG) {
+        return false;
+    }
+    let Some(len) = read_varint(&mut remaining).and_then(|len| usize::try_from(len).ok()) else {
+        return false;
+    };
+    // The first packet might not be read completely yet
+    let mut packet = &remaining[..remaining.len().min(len)];
+    let mut fields = 0;
+    while !packet.is_empty() {
+        match read_field(&mut packet) {
+            Ok(Some((0, _))) | Err(_) => return false,
+            Ok(Some(_)) => fields += 1,
+            Ok(None) => break,
+        }
+    }
+    fields > 0
+}
+
+// Field numbers from perfetto/protos/perfetto/trace/*.proto
+const TRACE_PACKET: u32 = 1;
+const TRACE_PACKET_TAG: u64 = (TRACE_PACKET as u64) << 3 | 2;
+const PACKET_TIMESTAMP: u32 = 8;
+const PACKET_SEQUENCE_ID: u32 = 10;
+const PACKET_TRACK_EVENT: u32 = 11;
+const PACKET_INTERNED_DATA: u32 = 12;
+const PACKET_SEQUENCE_FLAGS: u32 = 13;
+const PACKET_TRACK_DESCRIPTOR: u32 = 60;
+const SEQ_INCREMENTAL_STATE_CLEARED: u64 = 1;
+
+const EVENT_CATEGORY_IIDS: u32 = 3;
+const EVENT_DEBUG_ANNOTATIONS: u32 = 4;
+const EVENT_TYPE: u32 = 9;
+const EVENT_NAME_IID: u32 = 10;
+const EVENT_TRACK_UUID: u32 = 11;
+const EVENT_CATEGORIES: u32 = 22;
+const EVENT_NAME: u32 = 23;
+const TYPE_SLICE_BEGIN: u64 = 1;
+const TYPE_SLICE_END: u64 = 2;
+const TYPE_INSTANT: u64 = 3;
+
+const INTERNED_EVENT_CATEGORIES: u32 = 1;
+const INTERNED_EVENT_NAMES: u32 = 2;
+const INTERNED_DEBUG_ANNOTATION_NAMES: u32 = 3;
+
+const DESCRIPTOR_UUID: u32 = 1;
+const DESCRIPTOR_NAME: u32 = 2;
+const DESCRIPTOR_PROCESS: u32 = 3;
+const DESCRIPTOR_THREAD: u32 = 4;
+const PROCESS_NAME: u32 = 6;
+const THREAD_NAME: u32 = 5;
+
+const ANNOTATION_NAME_IID: u32 = 1;
+const ANNOTATION_BOOL: u32 = 2;
+const ANNOTATION_UINT: u32 = 3;
+const ANNOTATION_INT: u32 = 4;
+const ANNOTATION_DOUBLE: u32 = 5;
+const ANNOTATION_STRING: u32 = 6;
+const ANNOTATION_NAME: u32 = 10;
+
+#[derive(PartialEq, Eq, Hash)]
+enum Track {
+    Uuid(u64),
+    /// Events without a track uuid are on the default track of their sequence.
+    Sequence(u64),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Interned {
+    Category,
+    EventName,
+    AnnotationName,
+}
+
+/// Reads the track events of Perfetto protobuf traces, as written by Perfetto's
+/// SDK and the Chrome and Android tracing services.
+pub struct PerfettoFormat {
+    store: Arc<StoreContainer>,
+    tracks: SliceTracks<Track>,
+    track_names: HashMap<u64, String>,
+    interned: HashMap<(u64, Interned, u64), String>,
+    outdated_spans: HashSet<SpanIndex>,
+}
+
+impl PerfettoFormat {
+    pub fn new(store: Arc<StoreContainer>) -> Self {
+        Self {
+            store,
+            tracks: SliceTracks::new(),
+            track_names: HashMap::new(),
+            interned: HashMap::new(),
+            outdated_spans: HashSet::new(),
+        }
+    }
+
+    fn process_packet(&mut self, store: &mut Store, packet: &[u8]) -> Result<()> {
+        let mut timestamp = 0;
+        let mut sequence_id = 0;
+        let mut sequence_flags = 0;
+        let mut track_event = None;
+        let mut interned_data = None;
+        let mut track_descriptor = None;
+        for field in Fields(packet) {
+            match field? {
+                (PACKET_TIMESTAMP, Value::Varint(v)) => timestamp = v,
+                (PACKET_SEQUENCE_ID, Value::Varint(v)) => sequence_id = v,
+                (PACKET_SEQUENCE_FLAGS, Value::Varint(v)) => sequence_flags = v,
+                (PACKET_TRACK_EVENT, Value::Bytes(b)) => track_event = Some(b),
+                (PACKET_INTERNED_DATA, Value::Bytes(b)) => interned_data = Some(b),
+                (PACKET_TRACK_DESCRIPTOR, Value::Bytes(b)) => track_descriptor = Some(b),
+                _ => {}
+            }
+        }
+
+        if sequence_flags & SEQ_INCREMENTAL_STATE_CLEARED != 0 {
+            self.interned
+                .retain(|&(sequence, _, _), _| sequence != sequence_id);
+        }
+        if let Some(interned_data) = interned_data {
+            self.process_interned_data(sequence_id, interned_data)?;
+        }
+        if let Some(track_descriptor) = track_descriptor {
+            self.process_track_descriptor(track_descriptor)?;
+        }
+        if let Some(track_event) = track_event {
+            // Perfetto timestamps are in nanoseconds
+            self.process_track_event(store, sequence_id, timestamp / 1000, track_event)?;
+        }
+        Ok(())
+    }
+
+    fn process_interned_data(&mut self, sequence_id: u64, data: &[u8]) -> Result<()> {
+        for field in Fields(data) {
+            let (kind, entry) = match field? {
+                (INTERNED_EVENT_CATEGORIES, Value::Bytes(b)) => (Interned::Category, b),
+                (INTERNED_EVENT_NAMES, Value::Bytes(b)) => (Interned::EventName, b),
+                (INTERNED_DEBUG_ANNOTATION_NAMES, Value::Bytes(b)) => (Interned::AnnotationName, b),
+                _ => continue,
+            };
+            // All interned entries have the iid in field 1 and the name in field 2
+            let mut iid = 0;
+            let mut name = String::new();
+            for field in Fields(entry) {
+                match field? {
+                    (1, Value::Varint(v)) => iid = v,
+                    (2, Value::Bytes(b)) => name = String::from_utf8_lossy(b).into_owned(),
+                    _ => {}
+                }
+            }
+            self.interned.insert((sequence_id, kind, iid), name);
+        }
+        Ok(())
+    }
+
+    fn process_track_descriptor(&mut self, data: &[u8]) -> Result<()> {
+        let mut uuid = None;
+        let mut name = None;
+        for field in Fields(data) {
+            match field? {
+                (DESCRIPTOR_UUID, Value::Varint(v)) => uuid = Some(v),
+                (DESCRIPTOR_NAME, Value::Bytes(b)) => {
+                    name = Some(String::from_utf8_lossy(b).into_owned())
+                }
+                (DESCRIPTOR_PROCESS, Value::Bytes(b)) if name.is_none() => {
+                    name = find_string(b, PROCESS_NAME)?;
+                }
+                (DESCRIPTOR_THREAD, Value::Bytes(b)) if name.is_none() => {
+                    name = find_string(b, THREAD_NAME)?;
+                }
+                _ => {}
+            }
+        }
+        if let (Some(uuid), Some(name)) = (uuid, name) {
+            self.track_names.insert(uuid, name);
+        }
+        Ok(())
+    }
+
+    fn process_track_event(
+        &mut self,
+        store: &mut Store,
+        sequence_id: u64,
+        ts: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut ty = 0;
+        let mut track_uuid = None;
+        let mut name = None;
+        let mut categories = Vec::new();
+        let mut args = Vec::new();
+        for field in Fields(data) {
+            match field? {
+                (EVENT_TYPE, Value::Varint(v)) => ty = v,
+                (EVENT_TRACK_UUID, Value::Varint(v)) => track_uuid = Some(v),
+                (EVENT_NAME, Value::Bytes(b)) => {
+                    name = Some(String::from_utf8_lossy(b).into_owned())
+                }
+                (EVENT_NAME_IID, Value::Varint(iid)) => {
+                    name = self.lookup(sequence_id, Interned::EventName, iid)
+                }
+                (EVENT_CATEGORIES, Value::Bytes(b)) => {
+                    categories.push(String::from_utf8_lossy(b).into_owned())
+                }
+                (EVENT_CATEGORY_IIDS, Value::Varint(iid)) => {
+                    categories.extend(self.lookup(sequence_id, Interned::Category, iid))
+                }
+                (EVENT_CATEGORY_IIDS, Value::Bytes(packed)) => {
+                    let mut packed = packed;
+                    while !packed.is_empty() {
+                        let iid = read_varint(&mut packed).context("Invalid packed varint")?;
+                        categories.extend(self.lookup(sequence_id, Interned::Category, iid));
+                    }
+                }
+                (EVENT_DEBUG_ANNOTATIONS, Value::Bytes(b)) => {
+                    args.push(self.debug_annotation(sequence_id, b)?)
+                }
+                _ => {}
+            }
+        }
+
+        let track = match track_uuid {
+            Some(uuid) => Track::Uuid(uuid),
+            None => Track::Sequence(sequence_id),
+        };
+        match ty {
+            TYPE_SLICE_BEGIN | TYPE_INSTANT => {
+                if let Some(track_name) = track_uuid.and_then(|uuid| self.track_names.get(&uuid)) {
+                    args.push(("track".to_string(), track_name.clone()));
+                }
+                let slice = Slice {
+                    start: ts,
+                    category: categories.join(","),
+                    name: name.unwrap_or_default(),
+                    args,
+                };
+                if ty == TYPE_SLICE_BEGIN {
+                    self.tracks.begin(track, slice);
+                } else {
+                    self.tracks
+                        .complete(store, track, slice, ts, &mut self.outdated_spans);
+                }
+            }
+            TYPE_SLICE_END => {
+                self.tracks
+                    .end(store, &track, ts, args, &mut self.outdated_spans);
+            }
+            // Counters have no span equivalent
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn debug_annotation(&self, sequence_id: u64, data: &[u8]) -> Result<(String, String)> {
+        let mut name = String::new();
+        let mut value = String::new();
+        for field in Fields(data) {
+            match field? {
+                (ANNOTATION_NAME, Value::Bytes(b)) => {
+                    name = String::from_utf8_lossy(b).into_owned()
+                }
+                (ANNOTATION_NAME_IID, Value::Varint(iid)) => {
+                    name = self
+                        .lookup(sequence_id, Interned::AnnotationName, iid)
+                        .unwrap_or_default()
+                }
+                (ANNOTATION_BOOL, Value::Varint(v)) => value = (v != 0).to_string(),
+                (ANNOTATION_UINT, Value::Varint(v)) => value = v.to_string(),
+                (ANNOTATION_INT, Value::Varint(v)) => value = (v as i64).to_string(),
+                (ANNOTATION_DOUBLE, Value::Fixed64(v)) => value = f64::from_bits(v).to_string(),
+                (ANNOTATION_STRING, Value::Bytes(b)) => {
+                    value = String::from_utf8_lossy(b).into_owned()
+                }
+                _ => {}
+            }
+        }
+        Ok((name, value))
+    }
+
+    fn lookup(&self, sequence_id: u64, kind: Interned, iid: u64) -> Option<String> {
+        self.interned.get(&(sequence_id, kind, iid)).cloned()
+    }
+}
+
+impl TraceFormat for PerfettoFormat {
+    fn read(&mut self, buffer: &[u8]) -> Result<usize> {
+        let store = self.store.clone();
+        let mut store = store.write();
+        let mut bytes_read = 0;
+        let mut remaining = buffer;
+        // Stop at the first incomplete packet, it will be read with more data
+        while let Some((field, value)) = read_field(&mut remaining)? {
+            if let (TRACE_PACKET, Value::Bytes(packet)) = (field, value) {
+                self.process_packet(&mut store, packet)?;
+            }
+            bytes_read = buffer.len() - remaining.len();
+        }
+        store.invalidate_outdated_spans(&self.outdated_spans);
+        self.outdated_spans.clear();
+        Ok(bytes_read)
+    }
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32,
+}
+
+fn read_varint(buffer: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for (i, &byte) in buffer.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            *buffer = &buffer[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Reads a single field. Returns `None` when the buffer ends before the field
+/// does.
+fn read_field<'a>(buffer: &mut &'a [u8]) -> Result<Option<(u32, Value<'a>)>> {
+    let mut remaining = *buffer;
+    let Some(tag) = read_varint(&mut remaining) else {
+        return Ok(None);
+    };
+    let field = (tag >> 3) as u32;
+    let value = match tag & 7 {
+        0 => {
+            let Some(value) = read_varint(&mut remaining) else {
+                return Ok(None);
+            };
+            Value::Varint(value)
+        }
+        1 => {
+            let Some((bytes, rest)) = remaining.split_first_chunk::<8>() else {
+                return Ok(None);
+            };
+            remaining = rest;
+            Value::Fixed64(u64::from_le_bytes(*bytes))
+        }
+        2 => {
+            let Some(len) = read_varint(&mut remaining) else {
+                return Ok(None);
+            };
+            let Ok(len) = usize::try_from(len) else {
+                bail!("Invalid length {len} of protobuf field {field}");
+            };
+            if remaining.len() < len {
+                return Ok(None);
+            }
+            let (bytes, rest) = remaining.split_at(len);
+            remaining = rest;
+            Value::Bytes(bytes)
+        }
+        5 => {
+            let Some((_, rest)) = remaining.split_first_chunk::<4>() else {
+                return Ok(None);
+            };
+            remaining = rest;
+            Value::Fixed32
+        }
+        wire_type => bail!("Unsupported protobuf wire type {wire_type} of field {field}"),
+    };
+    *buffer = remaining;
+    Ok(Some((field, value)))
+}
+
+/// Iterates over the fields of a complete message.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Result<(u32, Value<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        match read_field(&mut self.0) {
+            Ok(Some(field)) => Some(Ok(field)),
+            Ok(None) => {
+                self.0 = &[];
+                Some(Err(anyhow::anyhow!("Truncated protobuf message")))
+            }
+            Err(err) => {
+                self.0 = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn find_string(data: &[u8], field: u32) -> Result<Option<String>> {
+    for entry in Fields(data) {
+        if let (f, Value::Bytes(b)) = entry? {
+            if f == field {
+                return Ok(Some(String::from_utf8_lossy(b).into_owned()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn uint(field: u32, value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint((field as u64) << 3, &mut out);
+        varint(value, &mut out);
+        out
+    }
+
+    fn bytes(field: u32, value: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint((field as u64) << 3 | 2, &mut out);
+        varint(value.len() as u64, &mut out);
+        out.extend_from_slice(value);
+        out
+    }
+
+    fn message(fields: &[Vec<u8>]) -> Vec<u8> {
+        fields.concat()
+    }
+
+    fn packet(timestamp_ns: u64, fields: &[Vec<u8>]) -> Vec<u8> {
+        let mut packet = message(&[
+            uint(PACKET_TIMESTAMP, timestamp_ns),
+            uint(PACKET_SEQUENCE_ID, 1),
+        ]);
+        packet.extend(fields.concat());
+        bytes(TRACE_PACKET, &packet)
+    }
+
+    fn event(ty: u64, fields: &[Vec<u8>]) -> Vec<u8> {
+        let mut event = uint(EVENT_TYPE, ty);
+        event.extend(fields.concat());
+        bytes(PACKET_TRACK_EVENT, &event)
+    }
+
+    fn trace() -> Vec<u8> {
+        [
+            packet(
+                0,
+                &[bytes(
+                    PACKET_TRACK_DESCRIPTOR,
+                    &message(&[
+                        uint(DESCRIPTOR_UUID, 7),
+                        bytes(DESCRIPTOR_THREAD, &bytes(THREAD_NAME, b"worker")),
+                    ]),
+                )],
+            ),
+            packet(
+                0,
+                &[
+                    uint(PACKET_SEQUENCE_FLAGS, SEQ_INCREMENTAL_STATE_CLEARED),
+                    bytes(
+                        PACKET_INTERNED_DATA,
+                        &bytes(
+                            INTERNED_EVENT_NAMES,
+                            &message(&[uint(1, 1), bytes(2, b"interned")]),
+                        ),
+                    ),
+                ],
+            ),
+            packet(
+                1_000,
+                &[event(
+                    TYPE_SLICE_BEGIN,
+                    &[uint(EVENT_TRACK_UUID, 7), bytes(EVENT_NAME, b"outer")],
+                )],
+            ),
+            packet(
+                2_000,
+                &[event(
+                    TYPE_SLICE_BEGIN,
+                    &[
+                        uint(EVENT_TRACK_UUID, 7),
+                        uint(EVENT_NAME_IID, 1),
+                        bytes(
+                            EVENT_DEBUG_ANNOTATIONS,
+                            &message(&[bytes(ANNOTATION_NAME, b"n"), uint(ANNOTATION_UINT, 42)]),
+                        ),
+                    ],
+                )],
+            ),
+            packet(
+                3_000,
+                &[event(TYPE_SLICE_END, &[uint(EVENT_TRACK_UUID, 7)])],
+            ),
+            packet(
+                4_000,
+                &[event(TYPE_INSTANT, &[bytes(EVENT_NAME, b"instant")])],
+            ),
+            packet(
+                9_000,
+                &[event(TYPE_SLICE_END, &[uint(EVENT_TRACK_UUID, 7)])],
+            ),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_is_perfetto_trace() {
+        assert!(is_perfetto_trace(&trace()));
+        // The first packet doesn't need to be complete
+        assert!(is_perfetto_trace(&trace()[..4]));
+        assert!(!is_perfetto_trace(b"\n[{\"name\":\"a\",\"duration\":1}]"));
+        assert!(!is_perfetto_trace(b"\n{\"traceEvents\":[]}"));
+        assert!(!is_perfetto_trace(b"\n\n"));
+        assert!(!is_perfetto_trace(b"TRACEv0"));
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let store = Arc::new(StoreContainer::new());
+        let mut format = PerfettoFormat::new(store.clone());
+        let trace = trace();
+        // Incomplete packets are read with the next chunk
+        let read = format.read(&trace[..trace.len() - 1])?;
+        assert!(read < trace.len() - 1);
+        assert_eq!(format.read(&trace[read..])?, trace.len() - read);
+
+        let store = store.read();
+        assert_eq!(
+            store.span_tree(),
+            ["outer 1..9", "  interned 2..3", "instant 4..4"]
+        );
+        let outer = store.root_spans().find(|s| s.start() == 1).unwrap();
+        let inner = outer.children().next().unwrap();
+        assert_eq!(
+            inner.args().collect::<Vec<_>>(),
+            [("n", "42"), ("track", "worker")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_wire_type() {
+        let store = Arc::new(StoreContainer::new());
+        let mut format = PerfettoFormat::new(store);
+        assert!(format.read(&bytes(TRACE_PACKET, &[0x0b])).is_err());
+    }
+}
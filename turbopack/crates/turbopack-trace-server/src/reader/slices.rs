@@ -0,0 +1,217 @@
+use std::{
+    cmp::max,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    iter::once,
+};
+
+use crate::{span::SpanIndex, store::Store};
+
+/// A slice on a track, e. g. a trace event on a thread.
+pub struct Slice {
+    pub start: u64,
+    pub category: String,
+    pub name: String,
+    pub args: Vec<(String, String)>,
+}
+
+struct Node {
+    start: u64,
+    end: u64,
+    index: SpanIndex,
+    /// Sorted by `start`.
+    children: Vec<Node>,
+}
+
+#[derive(Default)]
+struct Track {
+    open: Vec<Slice>,
+    /// Sorted by `start`.
+    roots: Vec<Node>,
+}
+
+/// Builds spans from slices that are only nested by time on their track
+/// (Chrome trace events, Perfetto track events). Completed slices become the
+/// child of the innermost slice containing them and adopt the slices they
+/// contain, so they can be added in any order. Slices are usually reported when
+/// they end, so children are added before their parents.
+pub struct SliceTracks<K> {
+    tracks: HashMap<K, Track>,
+}
+
+impl<K: Hash + Eq> SliceTracks<K> {
+    pub fn new() -> Self {
+        Self {
+            tracks: HashMap::new(),
+        }
+    }
+
+    pub fn begin(&mut self, key: K, slice: Slice) {
+        self.tracks.entry(key).or_default().open.push(slice);
+    }
+
+    pub fn end(
+        &mut self,
+        store: &mut Store,
+        key: &K,
+        end: u64,
+        args: Vec<(String, String)>,
+        outdated_spans: &mut HashSet<SpanIndex>,
+    ) {
+        let Some(track) = self.tracks.get_mut(key) else {
+            return;
+        };
+        let Some(mut slice) = track.open.pop() else {
+            return;
+        };
+        slice.args.extend(args);
+        insert(&mut track.roots, store, slice, end, outdated_spans);
+    }
+
+    pub fn complete(
+        &mut self,
+        store: &mut Store,
+        key: K,
+        slice: Slice,
+        end: u64,
+        outdated_spans: &mut HashSet<SpanIndex>,
+    ) {
+        let track = self.tracks.entry(key).or_default();
+        insert(&mut track.roots, store, slice, end, outdated_spans);
+    }
+}
+
+fn insert(
+    mut nodes: &mut Vec<Node>,
+    store: &mut Store,
+    slice: Slice,
+    end: u64,
+    outdated_spans: &mut HashSet<SpanIndex>,
+) {
+    let Slice {
+        start,
+        category,
+        name,
+        args,
+    } = slice;
+    let end = max(start, end);
+
+    // Siblings don't overlap, so only the last one starting before the slice
+    // can contain it. A slice with the same range is added later, so it's the
+    // parent.
+    let mut parent = None;
+    loop {
+        let Some(i) = nodes.partition_point(|n| n.start <= start).checked_sub(1) else {
+            break;
+        };
+        let node = &nodes[i];
+        if node.end < end || (node.start == start && node.end == end) {
+            break;
+        }
+        parent = Some(node.index);
+        nodes = &mut nodes[i].children;
+    }
+
+    let index = store.add_span(parent, start, category, name, args, outdated_spans);
+
+    let from = nodes.partition_point(|n| n.start < start);
+    let to = nodes.partition_point(|n| n.start <= end);
+    let (children, rest): (Vec<_>, Vec<_>) = nodes.drain(from..to).partition(|n| n.end <= end);
+    for child in children.iter() {
+        store.set_parent(child.index, index, outdated_spans);
+    }
+    store.set_total_time(index, start, end - start, outdated_spans);
+    store.complete_span(index);
+
+    let node = Node {
+        start,
+        end,
+        index,
+        children,
+    };
+    nodes.splice(from..from, once(node).chain(rest));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(name: &str, start: u64) -> Slice {
+        Slice {
+            start,
+            category: String::new(),
+            name: name.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    fn complete(
+        tracks: &mut SliceTracks<u32>,
+        store: &mut Store,
+        name: &str,
+        start: u64,
+        end: u64,
+    ) {
+        tracks.complete(store, 0, slice(name, start), end, &mut HashSet::new());
+    }
+
+    #[test]
+    fn test_children_before_parent() {
+        let mut store = Store::new();
+        let mut tracks = SliceTracks::new();
+        complete(&mut tracks, &mut store, "a", 10, 20);
+        complete(&mut tracks, &mut store, "b", 30, 40);
+        complete(&mut tracks, &mut store, "parent", 0, 100);
+        complete(&mut tracks, &mut store, "after", 100, 110);
+        assert_eq!(
+            store.span_tree(),
+            [
+                "parent 0..100",
+                "  a 10..20",
+                "  b 30..40",
+                "after 100..110"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parent_before_children() {
+        let mut store = Store::new();
+        let mut tracks = SliceTracks::new();
+        complete(&mut tracks, &mut store, "parent", 0, 100);
+        complete(&mut tracks, &mut store, "child", 10, 50);
+        complete(&mut tracks, &mut store, "grandchild", 20, 30);
+        complete(&mut tracks, &mut store, "same", 10, 50);
+        assert_eq!(
+            store.span_tree(),
+            [
+                "parent 0..100",
+                "  same 10..50",
+                "    child 10..50",
+                "      grandchild 20..30"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_begin_end() {
+        let mut store = Store::new();
+        let mut tracks = SliceTracks::new();
+        let outdated = &mut HashSet::new();
+        tracks.begin(1, slice("outer", 0));
+        tracks.begin(1, slice("inner", 5));
+        tracks.begin(2, slice("other track", 5));
+        tracks.end(&mut store, &1, 10, Vec::new(), outdated);
+        tracks.end(&mut store, &1, 20, vec![("k".into(), "v".into())], outdated);
+        tracks.end(&mut store, &2, 15, Vec::new(), outdated);
+        // Ends without a matching begin are ignored
+        tracks.end(&mut store, &1, 30, Vec::new(), outdated);
+        tracks.end(&mut store, &3, 30, Vec::new(), outdated);
+        assert_eq!(
+            store.span_tree(),
+            ["outer 0..20", "  inner 5..10", "other track 5..15"]
+        );
+        let outer = store.root_spans().find(|s| s.start() == 0).unwrap();
+        assert_eq!(outer.args().collect::<Vec<_>>(), [("k", "v")]);
+    }
+}
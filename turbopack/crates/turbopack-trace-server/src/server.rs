@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 use tungstenite::{accept, Message};
 
 use crate::{
+    compare::{compare_stores, ComparisonRow},
     store::SpanId,
     store_container::StoreContainer,
     u64_string,
@@ -43,6 +44,14 @@ pub enum ServerToClientMessage {
         args: Vec<(String, String)>,
         path: Vec<String>,
     },
+    ComparisonResult {
+        rows: Vec<ComparisonRow>,
+    },
+    /// A comparison was requested, but the server was started without a
+    /// baseline trace.
+    ComparisonError {
+        message: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -69,6 +78,10 @@ pub enum ClientToServerMessage {
     },
     Ack,
     CheckForMoreData,
+    /// Compares the trace with the baseline trace by span name.
+    Compare {
+        query: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -110,12 +123,13 @@ pub struct ViewRect {
 
 struct ConnectionState {
     store: Arc<StoreContainer>,
+    baseline: Option<Arc<StoreContainer>>,
     viewer: Viewer,
     view_rect: ViewRect,
     last_update_generation: usize,
 }
 
-pub fn serve(store: Arc<StoreContainer>, port: u16) {
+pub fn serve(store: Arc<StoreContainer>, baseline: Option<Arc<StoreContainer>>, port: u16) {
     let server = TcpListener::bind(SocketAddr::V4(SocketAddrV4::new(
         std::net::Ipv4Addr::new(127, 0, 0, 1),
         port,
@@ -123,10 +137,11 @@ pub fn serve(store: Arc<StoreContainer>, port: u16) {
     .unwrap();
     for stream in server.incoming() {
         let store = store.clone();
+        let baseline = baseline.clone();
 
         spawn(move || {
             let websocket = accept(stream.unwrap()).unwrap();
-            if let Err(err) = handle_connection(websocket, store) {
+            if let Err(err) = handle_connection(websocket, store, baseline) {
                 eprintln!("Error: {:?}", err);
             }
         });
@@ -136,9 +151,11 @@ pub fn serve(store: Arc<StoreContainer>, port: u16) {
 fn handle_connection(
     mut websocket: tungstenite::WebSocket<TcpStream>,
     store: Arc<StoreContainer>,
+    baseline: Option<Arc<StoreContainer>>,
 ) -> Result<()> {
     let state = Arc::new(Mutex::new(ConnectionState {
         store,
+        baseline,
         viewer: Viewer::new(),
         view_rect: ViewRect {
             x: 0,
@@ -337,6 +354,23 @@ fn handle_connection(
 
                         continue;
                     }
+                    ClientToServerMessage::Compare { query } => {
+                        let message = if let Some(baseline) = &state.baseline {
+                            let baseline = baseline.read();
+                            let store = state.store.read();
+                            ServerToClientMessage::ComparisonResult {
+                                rows: compare_stores(&baseline, &store, &query),
+                            }
+                        } else {
+                            ServerToClientMessage::ComparisonError {
+                                message: "No baseline trace loaded, start the server with \
+                                          --baseline"
+                                    .to_string(),
+                            }
+                        };
+                        let message = serde_json::to_string(&message).unwrap();
+                        websocket.send(Message::Text(message))?;
+                    }
                     ClientToServerMessage::Ack => {
                         ready_for_update = true;
                         if update_skipped {
@@ -20,6 +20,10 @@ pub struct Store {
     pub(crate) spans: Vec<Span>,
     pub(crate) self_time_tree: SelfTimeTree<SpanIndex>,
     max_self_time_lookup_time: AtomicU64,
+    /// Memory that is allocated and not yet deallocated, in the order the
+    /// allocations were read from the trace.
+    allocated_memory: u64,
+    peak_allocated_memory: u64,
 }
 
 fn new_root_span() -> Span {
@@ -54,6 +58,8 @@ impl Store {
             spans: vec![new_root_span()],
             self_time_tree: SelfTimeTree::new(),
             max_self_time_lookup_time: AtomicU64::new(0),
+            allocated_memory: 0,
+            peak_allocated_memory: 0,
         }
     }
 
@@ -62,6 +68,8 @@ impl Store {
         self.spans[0] = new_root_span();
         self.self_time_tree = SelfTimeTree::new();
         *self.max_self_time_lookup_time.get_mut() = 0;
+        self.allocated_memory = 0;
+        self.peak_allocated_memory = 0;
     }
 
     pub fn has_time_info(&self) -> bool {
@@ -285,6 +293,8 @@ impl Store {
         outdated_spans.insert(span_index);
         span.self_allocations += allocation;
         span.self_allocation_count += count;
+        self.allocated_memory += allocation;
+        self.peak_allocated_memory = max(self.peak_allocated_memory, self.allocated_memory);
     }
 
     pub fn add_deallocation(
@@ -298,6 +308,12 @@ impl Store {
         outdated_spans.insert(span_index);
         span.self_deallocations += deallocation;
         span.self_deallocation_count += count;
+        self.allocated_memory = self.allocated_memory.saturating_sub(deallocation);
+    }
+
+    /// The largest amount of memory that was allocated at the same time.
+    pub fn peak_allocations(&self) -> u64 {
+        self.peak_allocated_memory
     }
 
     pub fn complete_span(&mut self, span_index: SpanIndex) {
@@ -373,3 +389,33 @@ impl Store {
         })
     }
 }
+
+#[cfg(test)]
+impl Store {
+    /// Describes the spans as `name start..end` lines, indented by their depth
+    /// and ordered by start, to compare them in tests.
+    pub fn span_tree(&self) -> Vec<String> {
+        fn add(span: SpanRef<'_>, depth: usize, lines: &mut Vec<String>) {
+            lines.push(format!(
+                "{}{} {}..{}",
+                "  ".repeat(depth),
+                span.span.name,
+                span.start(),
+                span.end()
+            ));
+            let mut children = span.children().collect::<Vec<_>>();
+            children.sort_by_key(|child| child.start());
+            for child in children {
+                add(child, depth + 1, lines);
+            }
+        }
+
+        let mut roots = self.root_spans().collect::<Vec<_>>();
+        roots.sort_by_key(|span| span.start());
+        let mut lines = Vec::new();
+        for span in roots {
+            add(span, 0, &mut lines);
+        }
+        lines
+    }
+}
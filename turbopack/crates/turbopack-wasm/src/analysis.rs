@@ -4,7 +4,7 @@ use anyhow::Result;
 use turbo_tasks::Vc;
 use turbo_tasks_fs::FileContent;
 use turbopack_core::asset::Asset;
-use wasmparser::{Chunk, Parser, Payload};
+use wasmparser::{Chunk, Encoding, Parser, Payload};
 
 use crate::source::WebAssemblySource;
 
@@ -14,11 +14,27 @@ use crate::source::WebAssemblySource;
 pub(crate) struct WebAssemblyAnalysis {
     pub imports: BTreeMap<String, Vec<String>>,
     pub exports: Vec<String>,
+    /// Whether the file is a component of the component model instead of a
+    /// core module.
+    pub is_component: bool,
+}
+
+impl WebAssemblyAnalysis {
+    /// Whether the items imported from a module are the JS glue code generated
+    /// by `wasm-bindgen` (`*_bg.js`).
+    pub fn is_wasm_bindgen_glue(items: &[String]) -> bool {
+        items.iter().any(|item| Self::is_wasm_bindgen_import(item))
+    }
+
+    /// Whether an import is a function of the `wasm-bindgen` glue code.
+    pub fn is_wasm_bindgen_import(item: &str) -> bool {
+        item.starts_with("__wbindgen_") || item.starts_with("__wbg_")
+    }
 }
 
 /// Analyse a WebAssembly file.
 ///
-/// Extracts imports and exports.
+/// Extracts imports and exports, or detects a component.
 #[turbo_tasks::function]
 pub(crate) async fn analyze(source: Vc<WebAssemblySource>) -> Result<Vc<WebAssemblyAnalysis>> {
     let content = source.content().file_content().await?;
@@ -43,6 +59,15 @@ pub(crate) async fn analyze(source: Vc<WebAssemblySource>) -> Result<Vc<WebAssem
         };
 
         match payload {
+            Payload::Version {
+                encoding: Encoding::Component,
+                ..
+            } => {
+                // Components contain nested core modules, whose imports and exports
+                // aren't the ones of the file
+                analysis.is_component = true;
+                break;
+            }
             Payload::ImportSection(s) => {
                 for import in s {
                     let import = import?;
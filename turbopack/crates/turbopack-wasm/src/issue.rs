@@ -0,0 +1,46 @@
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::issue::{Issue, IssueSeverity, IssueStage, OptionStyledString, StyledString};
+
+/// Emitted when a WebAssembly component is imported. Only core modules can be
+/// instantiated by the runtime.
+#[turbo_tasks::value(shared)]
+pub(crate) struct WebAssemblyComponentIssue {
+    pub file_path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for WebAssemblyComponentIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("WebAssembly components are not supported".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Unsupported.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "The file is a WebAssembly component (component model) instead of a core module. \
+                 Components can't be instantiated directly, transpile it into a core module and \
+                 JavaScript bindings first, e.g. with `jco transpile`."
+                    .into(),
+            )
+            .cell(),
+        ))
+    }
+}
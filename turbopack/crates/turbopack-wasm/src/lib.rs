@@ -10,6 +10,7 @@
 #![feature(arbitrary_self_types_pointers)]
 
 pub(crate) mod analysis;
+pub(crate) mod issue;
 pub(crate) mod loader;
 pub mod module_asset;
 pub(crate) mod output_asset;
@@ -7,7 +7,10 @@ use turbo_tasks_fs::File;
 use turbopack_core::{asset::AssetContent, source::Source, virtual_source::VirtualSource};
 use turbopack_ecmascript::utils::StringifyJs;
 
-use crate::{analysis::analyze, source::WebAssemblySource};
+use crate::{
+    analysis::{analyze, WebAssemblyAnalysis},
+    source::WebAssemblySource,
+};
 
 /// Create a javascript loader to instantiate the WebAssembly module with the
 /// necessary imports and exports to be processed by [turbopack_ecmascript].
@@ -20,18 +23,44 @@ pub(crate) async fn instantiating_loader_source(
     let mut code = String::new();
 
     let mut imports_obj = "{".to_string();
+    let mut glue_modules = Vec::new();
     for (path, items) in &analysis.imports {
-        writeln!(
-            code,
-            "import {{ {} }} from {};",
-            items.join(", "),
-            StringifyJs(path)
-        )?;
-
         writeln!(imports_obj, "\n    {}: {{", StringifyJs(path))?;
-        for item in items {
-            writeln!(imports_obj, "        {}: {},", StringifyJs(item), item)?;
+
+        if WebAssemblyAnalysis::is_wasm_bindgen_glue(items) {
+            // The `wasm-bindgen` glue code imports the WebAssembly module too. Its
+            // functions are looked up when they are called, as the glue code might not
+            // be evaluated yet because of the cycle.
+            let glue = format!("__turbopack_wasm_glue_{}__", glue_modules.len());
+            writeln!(code, "import * as {} from {};", glue, StringifyJs(path))?;
+            for item in items {
+                if WebAssemblyAnalysis::is_wasm_bindgen_import(item) {
+                    writeln!(
+                        imports_obj,
+                        "        {item}: (...args) => {glue}[{item}](...args),",
+                        item = StringifyJs(item),
+                    )?;
+                } else {
+                    writeln!(
+                        imports_obj,
+                        "        {item}: {glue}[{item}],",
+                        item = StringifyJs(item),
+                    )?;
+                }
+            }
+            glue_modules.push(glue);
+        } else {
+            writeln!(
+                code,
+                "import {{ {} }} from {};",
+                items.join(", "),
+                StringifyJs(path)
+            )?;
+            for item in items {
+                writeln!(imports_obj, "        {}: {},", StringifyJs(item), item)?;
+            }
         }
+
         writeln!(imports_obj, "    }},")?;
     }
     writeln!(imports_obj, "}}")?;
@@ -43,11 +72,36 @@ pub(crate) async fn instantiating_loader_source(
     writedoc!(
         code,
         r#"
-            const {{ {exports} }} = await __turbopack_wasm__(wasmPath, {imports});
+            const __turbopack_wasm_imports__ = {imports};
+            // Small WebAssembly modules are inlined into the chunk as bytes
+            const __turbopack_wasm_exports__ = typeof wasmPath === "string"
+                ? await __turbopack_wasm__(wasmPath, __turbopack_wasm_imports__)
+                : (await WebAssembly.instantiate(wasmPath, __turbopack_wasm_imports__)).instance.exports;
+        "#,
+        imports = imports_obj,
+    )?;
+
+    // Newer versions of `wasm-bindgen` expect the exports to be passed to the glue
+    // code, which allows to import the WebAssembly module directly.
+    for glue in &glue_modules {
+        writedoc!(
+            code,
+            r#"
+                if (typeof {glue}.__wbg_set_wasm === "function") {{
+                    {glue}.__wbg_set_wasm(__turbopack_wasm_exports__);
+                }}
+            "#,
+        )?;
+    }
+
+    writedoc!(
+        code,
+        r#"
+
+            const {{ {exports} }} = __turbopack_wasm_exports__;
 
             export {{ {exports} }};
         "#,
-        imports = imports_obj,
         exports = analysis.exports.join(", "),
     )?;
 
@@ -67,7 +121,9 @@ pub(crate) fn compiling_loader_source(source: Vc<WebAssemblySource>) -> Vc<Box<d
         r#"
             import wasmPath from "WASM_PATH";
 
-            const mod = await __turbopack_wasm_module__(wasmPath);
+            const mod = typeof wasmPath === "string"
+                ? await __turbopack_wasm_module__(wasmPath)
+                : await WebAssembly.compile(wasmPath);
 
             export default mod;
         "#
@@ -79,3 +135,27 @@ pub(crate) fn compiling_loader_source(source: Vc<WebAssemblySource>) -> Vc<Box<d
         AssetContent::file(File::from(code).into()),
     ))
 }
+
+/// Create a javascript loader that throws when a WebAssembly component is
+/// imported, which can't be instantiated.
+#[turbo_tasks::function]
+pub(crate) async fn component_loader_source(
+    source: Vc<WebAssemblySource>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let message = format!(
+        "{} is a WebAssembly component, which can't be instantiated",
+        source.ident().path().await?.path
+    );
+    let code: RcStr = formatdoc! {
+        r#"
+            throw new Error({message});
+        "#,
+        message = StringifyJs(&message),
+    }
+    .into();
+
+    Ok(Vc::upcast(VirtualSource::new(
+        source.ident().path().append("_.loader.mjs".into()),
+        AssetContent::file(File::from(code).into()),
+    )))
+}
@@ -7,6 +7,7 @@ use turbopack_core::{
     chunk::{AsyncModuleInfo, ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
     context::AssetContext,
     ident::AssetIdent,
+    issue::IssueExt,
     module::{Module, OptionModule},
     reference::ModuleReferences,
     reference_type::ReferenceType,
@@ -22,7 +23,9 @@ use turbopack_ecmascript::{
 };
 
 use crate::{
-    loader::{compiling_loader_source, instantiating_loader_source},
+    analysis::analyze,
+    issue::WebAssemblyComponentIssue,
+    loader::{compiling_loader_source, component_loader_source, instantiating_loader_source},
     output_asset::WebAssemblyAsset,
     raw::RawWebAssemblyModuleAsset,
     source::WebAssemblySource,
@@ -65,7 +68,14 @@ impl WebAssemblyModuleAsset {
         let this = self.await?;
         let query = &*this.source.ident().query().await?;
 
-        let loader_source = if query == "?module" {
+        let loader_source = if analyze(this.source).await?.is_component {
+            WebAssemblyComponentIssue {
+                file_path: this.source.ident().path(),
+            }
+            .cell()
+            .emit();
+            component_loader_source(this.source)
+        } else if query == "?module" {
             compiling_loader_source(this.source)
         } else {
             instantiating_loader_source(this.source)
@@ -219,8 +229,12 @@ impl ChunkItem for ModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn is_self_async(self: Vc<Self>) -> Vc<bool> {
-        Vc::cell(true)
+    fn is_self_async(&self) -> Vc<bool> {
+        // Instantiating and compiling happen in a top level await of the loader
+        self.module
+            .loader()
+            .as_chunk_item(Vc::upcast(self.chunking_context))
+            .is_self_async()
     }
 }
 
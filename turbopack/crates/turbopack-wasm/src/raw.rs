@@ -1,5 +1,7 @@
 use anyhow::{bail, Result};
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
 use turbo_tasks::{RcStr, ValueToString, Vc};
+use turbo_tasks_fs::FileContent;
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
@@ -25,7 +27,9 @@ fn modifier() -> Vc<RcStr> {
     Vc::cell("wasm raw".into())
 }
 
-/// Exports the relative path to the WebAssembly file without loading it.
+/// Exports the relative path to the WebAssembly file without loading it, or
+/// the bytes of the WebAssembly file when it's small enough to be inlined (see
+/// [ChunkingContext::inline_wasm_limit]).
 #[turbo_tasks::value]
 #[derive(Clone)]
 pub struct RawWebAssemblyModuleAsset {
@@ -104,6 +108,23 @@ struct RawModuleChunkItem {
     wasm_asset: Vc<WebAssemblyAsset>,
 }
 
+#[turbo_tasks::value_impl]
+impl RawModuleChunkItem {
+    /// Whether the WebAssembly file is embedded into the chunk instead of
+    /// being emitted.
+    #[turbo_tasks::function]
+    async fn is_inlined(&self) -> Result<Vc<bool>> {
+        let Some(limit) = *self.chunking_context.inline_wasm_limit().await? else {
+            return Ok(Vc::cell(false));
+        };
+        let content = self.module.content().file_content().await?;
+        let FileContent::Content(file) = &*content else {
+            return Ok(Vc::cell(false));
+        };
+        Ok(Vc::cell((file.content().len() as u64) < limit))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl ChunkItem for RawModuleChunkItem {
     #[turbo_tasks::function]
@@ -112,10 +133,14 @@ impl ChunkItem for RawModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+    async fn references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
+        if *self.is_inlined().await? {
+            return Ok(ModuleReferences::empty());
+        }
+        let this = self.await?;
         Ok(Vc::cell(vec![Vc::upcast(SingleOutputAssetReference::new(
-            Vc::upcast(self.wasm_asset),
-            Vc::cell(format!("wasm(url) {}", self.wasm_asset.ident().to_string().await?).into()),
+            Vc::upcast(this.wasm_asset),
+            Vc::cell(format!("wasm(url) {}", this.wasm_asset.ident().to_string().await?).into()),
         ))]))
     }
 
@@ -145,9 +170,30 @@ impl EcmascriptChunkItem for RawModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
-        let path = self.wasm_asset.ident().path().await?;
-        let output_root = self.chunking_context.output_root().await?;
+    async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let this = self.await?;
+
+        if *self.is_inlined().await? {
+            let content = this.module.content().file_content().await?;
+            let FileContent::Content(file) = &*content else {
+                bail!("Inlined WASM asset has no content");
+            };
+            let bytes = file.content().to_bytes()?;
+
+            return Ok(EcmascriptChunkItemContent {
+                inner_code: format!(
+                    "__turbopack_export_value__(Uint8Array.from(atob(\"{}\"), (c) => \
+                     c.charCodeAt(0)));",
+                    Base64Display::new(&bytes, &STANDARD)
+                )
+                .into(),
+                ..Default::default()
+            }
+            .into());
+        }
+
+        let path = this.wasm_asset.ident().path().await?;
+        let output_root = this.chunking_context.output_root().await?;
 
         let Some(path) = output_root.get_path_to(&path) else {
             bail!("WASM asset ident is not relative to output root");
@@ -0,0 +1,39 @@
+use turbo_tasks::{RcStr, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    source::Source,
+};
+
+/// A [Source] that is processed by a module rule with an issuer condition. The
+/// modifier distinguishes it from the same source processed for other
+/// issuers.
+#[turbo_tasks::value]
+pub(crate) struct IssuerRuleSource {
+    source: Vc<Box<dyn Source>>,
+    modifier: Vc<RcStr>,
+}
+
+#[turbo_tasks::value_impl]
+impl IssuerRuleSource {
+    #[turbo_tasks::function]
+    pub fn new(source: Vc<Box<dyn Source>>, modifier: Vc<RcStr>) -> Vc<Self> {
+        IssuerRuleSource { source, modifier }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for IssuerRuleSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().with_modifier(self.modifier)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for IssuerRuleSource {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
@@ -9,10 +9,10 @@
 
 pub mod evaluate_context;
 mod graph;
+mod issuer_rule_source;
 pub mod module_options;
 pub mod rebase;
 pub mod transition;
-pub(crate) mod unsupported_sass;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -28,6 +28,7 @@ use ecmascript::{
     EcmascriptModuleAsset, EcmascriptModuleAssetType, TreeShakingMode,
 };
 use graph::{aggregate, AggregatedGraph, AggregatedGraphNodeContent};
+use issuer_rule_source::IssuerRuleSource;
 use module_options::{ModuleOptions, ModuleOptionsContext, ModuleRuleEffect, ModuleType};
 use tracing::Instrument;
 use turbo_tasks::{Completion, RcStr, Value, ValueToString, Vc};
@@ -61,7 +62,7 @@ use turbopack_ecmascript::{
 use turbopack_json::JsonModuleAsset;
 pub use turbopack_resolve::{resolve::resolve_options, resolve_options_context};
 use turbopack_resolve::{resolve_options_context::ResolveOptionsContext, typescript::type_resolve};
-use turbopack_static::StaticModuleAsset;
+use turbopack_static::{data_uri::DataUriModuleAsset, StaticModuleAsset};
 use turbopack_wasm::{module_asset::WebAssemblyModuleAsset, source::WebAssemblySource};
 
 use self::{
@@ -275,6 +276,10 @@ async fn apply_module_type(
             source,
             Vc::upcast(module_asset_context),
         )),
+        ModuleType::DataUri => Vc::upcast(DataUriModuleAsset::new(
+            source,
+            Vc::upcast(module_asset_context),
+        )),
         ModuleType::WebAssembly { source_ty } => Vc::upcast(WebAssemblyModuleAsset::new(
             WebAssemblySource::new(source, *source_ty),
             Vc::upcast(module_asset_context),
@@ -411,21 +416,78 @@ impl ModuleAssetContext {
         self: Vc<Self>,
         source: Vc<Box<dyn Source>>,
         reference_type: Value<ReferenceType>,
+        issuer: Option<Vc<FileSystemPath>>,
     ) -> Result<Vc<ProcessResult>> {
         let this = self.await?;
+        let issuer_ref = match issuer {
+            Some(issuer) => Some(issuer.await?),
+            None => None,
+        };
         Ok(
             if let Some(transition) = this
                 .transitions
                 .await?
-                .get_by_rules(source, &reference_type)
+                .get_by_rules(source, &reference_type, issuer_ref.as_deref())
                 .await?
             {
                 transition.process(source, self, reference_type)
             } else {
-                self.process_default(source, reference_type)
+                self.process_default(source, reference_type, issuer)
             },
         )
     }
+
+    /// Whether module or transition rules depend on the issuer. Processing
+    /// with an issuer happens once per reference instead of once per module,
+    /// so it's only passed along when it's needed.
+    #[turbo_tasks::function]
+    async fn has_issuer_conditions(self: Vc<Self>) -> Result<Vc<bool>> {
+        let this = self.await?;
+        Ok(Vc::cell(
+            this.transitions.await?.has_issuer_conditions()
+                || *this.module_options_context.has_issuer_conditions().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn process_resolve_result_with_issuer(
+        self: Vc<Self>,
+        result: Vc<ResolveResult>,
+        reference_type: Value<ReferenceType>,
+        issuer: Option<Vc<FileSystemPath>>,
+    ) -> Result<Vc<ModuleResolveResult>> {
+        let this = self.await?;
+        let transition = this.transition;
+
+        let result = result
+            .await?
+            .map_module(|source| {
+                let reference_type = reference_type.clone();
+                async move {
+                    let process_result = if let Some(transition) = transition {
+                        transition.process(source, self, reference_type)
+                    } else {
+                        self.process_with_transition_rules(source, reference_type, issuer)
+                    };
+                    Ok(match *process_result.await? {
+                        ProcessResult::Module(m) => ModuleResolveResultItem::Module(Vc::upcast(m)),
+                        ProcessResult::Ignore => ModuleResolveResultItem::Ignore,
+                    })
+                }
+            })
+            .await?;
+
+        let result = replace_externals(
+            result,
+            this.module_options_context
+                .await?
+                .ecmascript
+                .import_externals,
+        )
+        .await?;
+
+        Ok(result.cell())
+    }
 }
 
 impl ModuleAssetContext {
@@ -433,8 +495,9 @@ impl ModuleAssetContext {
         self: Vc<Self>,
         source: Vc<Box<dyn Source>>,
         reference_type: Value<ReferenceType>,
+        issuer: Option<Vc<FileSystemPath>>,
     ) -> Vc<ProcessResult> {
-        process_default(self, source, reference_type, Vec::new())
+        process_default(self, source, reference_type, issuer, Vec::new())
     }
 }
 
@@ -443,6 +506,7 @@ async fn process_default(
     module_asset_context: Vc<ModuleAssetContext>,
     source: Vc<Box<dyn Source>>,
     reference_type: Value<ReferenceType>,
+    issuer: Option<Vc<FileSystemPath>>,
     processed_rules: Vec<usize>,
 ) -> Result<Vc<ProcessResult>> {
     let span = tracing::info_span!(
@@ -454,6 +518,7 @@ async fn process_default(
         module_asset_context,
         source,
         reference_type,
+        issuer,
         processed_rules,
     )
     .instrument(span)
@@ -464,10 +529,15 @@ async fn process_default_internal(
     module_asset_context: Vc<ModuleAssetContext>,
     source: Vc<Box<dyn Source>>,
     reference_type: Value<ReferenceType>,
+    issuer: Option<Vc<FileSystemPath>>,
     processed_rules: Vec<usize>,
 ) -> Result<Vc<ProcessResult>> {
     let ident = source.ident().resolve().await?;
     let path_ref = ident.path().await?;
+    let issuer_ref = match issuer {
+        Some(issuer) => Some(issuer.await?),
+        None => None,
+    };
     let options = ModuleOptions::new(
         ident.path().parent(),
         module_asset_context.module_options_context(),
@@ -479,6 +549,9 @@ async fn process_default_internal(
         ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportPart(part)) => {
             Some(*part)
         }
+        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(
+            ImportWithType::Other { part, .. },
+        )) => *part,
         _ => None,
     };
     let inner_assets = match &reference_type {
@@ -490,12 +563,11 @@ async fn process_default_internal(
 
     let mut current_source = source;
     let mut current_module_type = match &reference_type {
-        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(ty)) => {
+        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(
+            ImportWithType::Json,
+        )) => {
             has_type_attribute = true;
-
-            match ty {
-                ImportWithType::Json => Some(ModuleType::Json),
-            }
+            Some(ModuleType::Json)
         }
         _ => None,
     };
@@ -507,7 +579,18 @@ async fn process_default_internal(
         if processed_rules.contains(&i) {
             continue;
         }
-        if rule.matches(source, &path_ref, &reference_type).await? {
+        if rule
+            .matches(source, &path_ref, &reference_type, issuer_ref.as_deref())
+            .await?
+        {
+            if issuer.is_some() && rule.has_issuer_condition() {
+                // The module depends on the issuer now, so it must not have the same ident
+                // (and module id) as the module created for other issuers.
+                current_source = Vc::upcast(IssuerRuleSource::new(
+                    current_source,
+                    Vc::cell(format!("issuer rule {i}").into()),
+                ));
+            }
             for effect in rule.effects() {
                 match effect {
                     ModuleRuleEffect::SourceTransforms(transforms) => {
@@ -518,7 +601,11 @@ async fn process_default_internal(
                                 .await?
                                 .transitions
                                 .await?
-                                .get_by_rules(current_source, &reference_type)
+                                .get_by_rules(
+                                    current_source,
+                                    &reference_type,
+                                    issuer_ref.as_deref(),
+                                )
                                 .await?
                             {
                                 return Ok(transition.process(
@@ -533,6 +620,7 @@ async fn process_default_internal(
                                     module_asset_context,
                                     current_source,
                                     Value::new(reference_type),
+                                    issuer,
                                     processed_rules,
                                 ));
                             }
@@ -676,7 +764,16 @@ impl AssetContext for ModuleAssetContext {
             request,
             resolve_options,
         );
-        let mut result = self.process_resolve_result(result.resolve().await?, reference_type);
+        let issuer = if *self.has_issuer_conditions().await? {
+            Some(origin_path)
+        } else {
+            None
+        };
+        let mut result = self.process_resolve_result_with_issuer(
+            result.resolve().await?,
+            reference_type,
+            issuer,
+        );
 
         if *self.is_types_resolving_enabled().await? {
             let types_result = type_resolve(
@@ -691,42 +788,12 @@ impl AssetContext for ModuleAssetContext {
     }
 
     #[turbo_tasks::function]
-    async fn process_resolve_result(
+    fn process_resolve_result(
         self: Vc<Self>,
         result: Vc<ResolveResult>,
         reference_type: Value<ReferenceType>,
-    ) -> Result<Vc<ModuleResolveResult>> {
-        let this = self.await?;
-        let transition = this.transition;
-
-        let result = result
-            .await?
-            .map_module(|source| {
-                let reference_type = reference_type.clone();
-                async move {
-                    let process_result = if let Some(transition) = transition {
-                        transition.process(source, self, reference_type)
-                    } else {
-                        self.process_with_transition_rules(source, reference_type)
-                    };
-                    Ok(match *process_result.await? {
-                        ProcessResult::Module(m) => ModuleResolveResultItem::Module(Vc::upcast(m)),
-                        ProcessResult::Ignore => ModuleResolveResultItem::Ignore,
-                    })
-                }
-            })
-            .await?;
-
-        let result = replace_externals(
-            result,
-            this.module_options_context
-                .await?
-                .ecmascript
-                .import_externals,
-        )
-        .await?;
-
-        Ok(result.cell())
+    ) -> Vc<ModuleResolveResult> {
+        self.process_resolve_result_with_issuer(result, reference_type, None)
     }
 
     #[turbo_tasks::function]
@@ -739,7 +806,7 @@ impl AssetContext for ModuleAssetContext {
         if let Some(transition) = this.transition {
             Ok(transition.process(asset, self, reference_type))
         } else {
-            Ok(self.process_with_transition_rules(asset, reference_type))
+            Ok(self.process_with_transition_rules(asset, reference_type, None))
         }
     }
 
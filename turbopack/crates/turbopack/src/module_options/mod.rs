@@ -17,9 +17,14 @@ use turbopack_core::{
     resolve::options::{ImportMap, ImportMapping},
 };
 use turbopack_css::CssModuleAssetType;
-use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptOptions, SpecifiedModuleType};
+use turbopack_ecmascript::{
+    text::TextContentSourceTransform, EcmascriptInputTransform, EcmascriptOptions,
+    SpecifiedModuleType,
+};
 use turbopack_mdx::MdxTransform;
-use turbopack_node::transforms::{postcss::PostCssTransform, webpack::WebpackLoaders};
+use turbopack_node::transforms::{
+    postcss::PostCssTransform, sass::SassTransform, webpack::WebpackLoaders,
+};
 use turbopack_wasm::source::WebAssemblySourceType;
 
 use crate::{
@@ -52,6 +57,17 @@ async fn package_import_map_from_context(
     import_map.cell()
 }
 
+/// Resource queries with built-in module types, which webpack loaders don't
+/// apply to unless they have a `resource_query` condition.
+fn built_in_resource_queries() -> RuleCondition {
+    RuleCondition::any(
+        ["?raw", "?url", "?inline"]
+            .into_iter()
+            .map(|query| RuleCondition::ResourceQueryEquals(query.into()))
+            .collect(),
+    )
+}
+
 #[turbo_tasks::value(cell = "new", eq = "manual")]
 pub struct ModuleOptions {
     pub rules: Vec<ModuleRule>,
@@ -87,6 +103,7 @@ impl ModuleOptions {
                     ..
                 },
             ref enable_postcss_transform,
+            ref enable_sass,
             ref enable_webpack_loaders,
             preset_env_versions,
             ref module_rules,
@@ -217,6 +234,14 @@ impl ModuleOptions {
         );
 
         let mut rules = vec![
+            // `?raw` exports the content as a string. The transformed source is a new
+            // ecmascript module, which is processed by the rules below.
+            ModuleRule::new(
+                RuleCondition::ResourceQueryEquals("?raw".into()),
+                vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                    Vc::upcast(TextContentSourceTransform::new()),
+                ]))],
+            ),
             ModuleRule::new_all(
                 RuleCondition::ResourcePathEndsWith(".json".to_string()),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Json)],
@@ -549,18 +574,38 @@ impl ModuleOptions {
                 package_import_map_from_context("loader-runner".into(), path)
             };
             for (glob, rule) in webpack_loaders_options.rules.await?.iter() {
+                let mut conditions = vec![
+                    if !glob.contains('/') {
+                        RuleCondition::ResourceBasePathGlob(Glob::new(glob.clone()).await?)
+                    } else {
+                        RuleCondition::ResourcePathGlob {
+                            base: execution_context.project_path().await?,
+                            glob: Glob::new(glob.clone()).await?,
+                        }
+                    },
+                    RuleCondition::not(RuleCondition::ResourceIsVirtualSource),
+                ];
+                let LoaderRuleConditions {
+                    resource_query,
+                    import_with_type,
+                    issuer,
+                } = &rule.conditions;
+                if let Some(resource_query) = resource_query {
+                    conditions.push(RuleCondition::ResourceQueryEquals(resource_query.clone()));
+                } else {
+                    conditions.push(RuleCondition::not(built_in_resource_queries()));
+                }
+                if let Some(import_with_type) = import_with_type {
+                    conditions.push(RuleCondition::ImportWithType(import_with_type.clone()));
+                }
+                if let Some(issuer) = issuer {
+                    conditions.push(RuleCondition::IssuerPathGlob {
+                        base: execution_context.project_path().await?,
+                        glob: Glob::new(issuer.clone()).await?,
+                    });
+                }
                 rules.push(ModuleRule::new(
-                    RuleCondition::All(vec![
-                        if !glob.contains('/') {
-                            RuleCondition::ResourceBasePathGlob(Glob::new(glob.clone()).await?)
-                        } else {
-                            RuleCondition::ResourcePathGlob {
-                                base: execution_context.project_path().await?,
-                                glob: Glob::new(glob.clone()).await?,
-                            }
-                        },
-                        RuleCondition::not(RuleCondition::ResourceIsVirtualSource),
-                    ]),
+                    RuleCondition::All(conditions),
                     vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
                         Vc::upcast(WebpackLoaders::new(
                             node_evaluate_asset_context(
@@ -574,14 +619,65 @@ impl ModuleOptions {
                             rule.loaders,
                             rule.rename_as.clone(),
                             resolve_options_context,
+                            webpack_loaders_options.loader_cache,
                         )),
                     ]))],
                 ));
             }
         }
 
+        // Comes after the webpack loaders, so a configured `sass-loader` takes
+        // precedence.
+        if let Some(options) = enable_sass {
+            let options = options.await?;
+            let execution_context =
+                execution_context.context("execution_context is required for sass")?;
+
+            let import_map = if let Some(sass_package) = options.sass_package {
+                package_import_map_from_import_mapping("sass".into(), sass_package)
+            } else {
+                package_import_map_from_context("sass".into(), path)
+            };
+
+            rules.push(ModuleRule::new(
+                RuleCondition::all(vec![
+                    RuleCondition::any(vec![
+                        RuleCondition::ResourcePathEndsWith(".scss".to_string()),
+                        RuleCondition::ResourcePathEndsWith(".sass".to_string()),
+                    ]),
+                    RuleCondition::not(built_in_resource_queries()),
+                ]),
+                vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                    Vc::upcast(SassTransform::new(
+                        node_evaluate_asset_context(
+                            execution_context,
+                            Some(import_map),
+                            None,
+                            "sass".into(),
+                            true,
+                        ),
+                        execution_context,
+                        resolve_options_context,
+                        options.sass_options,
+                    )),
+                ]))],
+            ));
+        }
+
         rules.extend(module_rules.iter().cloned());
 
+        // These come last, so they override the module type of all other rules.
+        rules.extend([
+            ModuleRule::new(
+                RuleCondition::ResourceQueryEquals("?url".into()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
+            ),
+            ModuleRule::new(
+                RuleCondition::ResourceQueryEquals("?inline".into()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::DataUri)],
+            ),
+        ]);
+
         Ok(ModuleOptions::cell(ModuleOptions { rules }))
     }
 }
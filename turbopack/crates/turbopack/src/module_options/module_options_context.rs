@@ -1,4 +1,4 @@
-use indexmap::IndexMap;
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, RcStr, ValueDefault, Vc};
 use turbopack_core::{
@@ -8,7 +8,10 @@ use turbopack_ecmascript::{references::esm::UrlRewriteBehavior, TreeShakingMode}
 pub use turbopack_mdx::MdxTransformOptions;
 use turbopack_node::{
     execution_context::ExecutionContext,
-    transforms::{postcss::PostCssTransformOptions, webpack::WebpackLoaderItems},
+    transforms::{
+        loader_cache::LoaderCacheOptions, postcss::PostCssTransformOptions,
+        sass::SassTransformOptions, webpack::WebpackLoaderItems,
+    },
 };
 
 use super::ModuleRule;
@@ -17,11 +20,26 @@ use super::ModuleRule;
 pub struct LoaderRuleItem {
     pub loaders: Vc<WebpackLoaderItems>,
     pub rename_as: Option<RcStr>,
+    pub conditions: LoaderRuleConditions,
 }
 
+/// Conditions of a [LoaderRuleItem] in addition to the glob it's registered
+/// for. All of them need to match.
+#[derive(Clone, PartialEq, Eq, Debug, Default, TraceRawVcs, Serialize, Deserialize)]
+pub struct LoaderRuleConditions {
+    /// The query string of the resource, including the `?` prefix.
+    pub resource_query: Option<RcStr>,
+    /// The `type` import attribute of the ESM import.
+    pub import_with_type: Option<RcStr>,
+    /// A glob for the path of the importing module, relative to the project.
+    pub issuer: Option<RcStr>,
+}
+
+/// Loader rules by glob. A glob can have multiple rules with different
+/// conditions, so this is a list instead of a map.
 #[derive(Default)]
 #[turbo_tasks::value(transparent)]
-pub struct WebpackRules(IndexMap<RcStr, LoaderRuleItem>);
+pub struct WebpackRules(Vec<(RcStr, LoaderRuleItem)>);
 
 #[derive(Default)]
 #[turbo_tasks::value(transparent)]
@@ -32,6 +50,9 @@ pub struct OptionWebpackRules(Option<Vc<WebpackRules>>);
 pub struct WebpackLoadersOptions {
     pub rules: Vc<WebpackRules>,
     pub loader_runner_package: Option<Vc<ImportMapping>>,
+    /// Persists the results of the loaders, so they are reused across
+    /// restarts. Only valid for deterministic loaders.
+    pub loader_cache: Option<Vc<LoaderCacheOptions>>,
 }
 
 #[derive(Default)]
@@ -117,6 +138,8 @@ pub struct ModuleOptionsContext {
     pub css: CssOptionsContext,
 
     pub enable_postcss_transform: Option<Vc<PostCssTransformOptions>>,
+    /// Compiles `.scss` and `.sass` files to CSS.
+    pub enable_sass: Option<Vc<SassTransformOptions>>,
     pub enable_webpack_loaders: Option<Vc<WebpackLoadersOptions>>,
     // [Note]: currently mdx, and mdx_rs have different configuration entrypoint from next.config.js,
     // however we might want to unify them in the future.
@@ -183,3 +206,37 @@ impl ValueDefault for ModuleOptionsContext {
         Self::cell(Default::default())
     }
 }
+
+#[turbo_tasks::value_impl]
+impl ModuleOptionsContext {
+    /// Whether any module rule depends on the module that contains the
+    /// reference, including the rules of nested contexts.
+    #[turbo_tasks::function]
+    pub async fn has_issuer_conditions(self: Vc<Self>) -> Result<Vc<bool>> {
+        let this = self.await?;
+        if this
+            .module_rules
+            .iter()
+            .any(|rule| rule.has_issuer_condition())
+        {
+            return Ok(Vc::cell(true));
+        }
+        if let Some(webpack_loaders) = this.enable_webpack_loaders {
+            if webpack_loaders
+                .await?
+                .rules
+                .await?
+                .iter()
+                .any(|(_, rule)| rule.conditions.issuer.is_some())
+            {
+                return Ok(Vc::cell(true));
+            }
+        }
+        for (_, context) in this.rules.iter() {
+            if *context.has_issuer_conditions().await? {
+                return Ok(Vc::cell(true));
+            }
+        }
+        Ok(Vc::cell(false))
+    }
+}
@@ -55,9 +55,17 @@ impl ModuleRule {
         source: Vc<Box<dyn Source>>,
         path: &FileSystemPath,
         reference_type: &ReferenceType,
+        issuer: Option<&FileSystemPath>,
     ) -> Result<bool> {
         Ok(self.match_mode.matches(reference_type)
-            && self.condition.matches(source, path, reference_type).await?)
+            && self
+                .condition
+                .matches(source, path, reference_type, issuer)
+                .await?)
+    }
+
+    pub fn has_issuer_condition(&self) -> bool {
+        self.condition.has_issuer_condition()
     }
 }
 
@@ -106,6 +114,9 @@ pub enum ModuleType {
         use_swc_css: bool,
     },
     Static,
+    /// Like [ModuleType::Static], but exports the content as a `data:` URI
+    /// instead of emitting a file.
+    DataUri,
     WebAssembly {
         source_ty: WebAssemblySourceType,
     },
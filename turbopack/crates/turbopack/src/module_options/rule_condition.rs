@@ -1,9 +1,11 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use turbo_tasks::{primitives::Regex, trace::TraceRawVcs, ReadRef, Vc};
+use turbo_tasks::{primitives::Regex, trace::TraceRawVcs, RcStr, ReadRef, Vc};
 use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 use turbopack_core::{
-    reference_type::ReferenceType, source::Source, virtual_source::VirtualSource,
+    reference_type::{EcmaScriptModulesReferenceSubType, ImportWithType, ReferenceType},
+    source::Source,
+    virtual_source::VirtualSource,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
@@ -31,6 +33,22 @@ pub enum RuleCondition {
         glob: ReadRef<Glob>,
     },
     ResourceBasePathGlob(#[turbo_tasks(trace_ignore)] ReadRef<Glob>),
+    /// Matches the query string of the resource, including the `?` prefix,
+    /// e. g. `?raw`. Like webpack's `resourceQuery`.
+    ResourceQueryEquals(RcStr),
+    ResourceQueryRegex(#[turbo_tasks(trace_ignore)] Regex),
+    /// Matches ESM imports with a `type` import attribute, e. g. `json` for
+    /// `import data from "./data" with { type: "json" }`.
+    ImportWithType(RcStr),
+    /// Like [RuleCondition::ResourcePathGlob], but matches the path of the
+    /// module that contains the reference. Like webpack's `issuer`, it never
+    /// matches when the issuer is unknown, e. g. for entries.
+    IssuerPathGlob {
+        base: ReadRef<FileSystemPath>,
+        #[turbo_tasks(trace_ignore)]
+        glob: ReadRef<Glob>,
+    },
+    IssuerPathRegex(#[turbo_tasks(trace_ignore)] Regex),
 }
 
 impl RuleCondition {
@@ -46,6 +64,27 @@ impl RuleCondition {
     pub fn not(condition: RuleCondition) -> RuleCondition {
         RuleCondition::Not(Box::new(condition))
     }
+
+    /// Whether the condition depends on the issuer. Only these conditions need
+    /// the issuer to be passed to [RuleCondition::matches].
+    pub fn has_issuer_condition(&self) -> bool {
+        match self {
+            RuleCondition::All(conditions) | RuleCondition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.has_issuer_condition()),
+            RuleCondition::Not(condition) => condition.has_issuer_condition(),
+            RuleCondition::IssuerPathGlob { .. } | RuleCondition::IssuerPathRegex(_) => true,
+            _ => false,
+        }
+    }
+}
+
+fn matches_path_glob(base: &FileSystemPath, glob: &Glob, path: &FileSystemPath) -> bool {
+    if let Some(path) = base.get_relative_path_to(path) {
+        glob.execute(&path)
+    } else {
+        glob.execute(&path.path)
+    }
 }
 
 impl RuleCondition {
@@ -54,11 +93,12 @@ impl RuleCondition {
         source: Vc<Box<dyn Source>>,
         path: &FileSystemPath,
         reference_type: &ReferenceType,
+        issuer: Option<&FileSystemPath>,
     ) -> Result<bool> {
         Ok(match self {
             RuleCondition::All(conditions) => {
                 for condition in conditions {
-                    if !Box::pin(condition.matches(source, path, reference_type)).await? {
+                    if !Box::pin(condition.matches(source, path, reference_type, issuer)).await? {
                         return Ok(false);
                     }
                 }
@@ -66,14 +106,14 @@ impl RuleCondition {
             }
             RuleCondition::Any(conditions) => {
                 for condition in conditions {
-                    if Box::pin(condition.matches(source, path, reference_type)).await? {
+                    if Box::pin(condition.matches(source, path, reference_type, issuer)).await? {
                         return Ok(true);
                     }
                 }
                 false
             }
             RuleCondition::Not(condition) => {
-                !Box::pin(condition.matches(source, path, reference_type)).await?
+                !Box::pin(condition.matches(source, path, reference_type, issuer)).await?
             }
             RuleCondition::ResourcePathEquals(other) => path == &**other,
             RuleCondition::ResourcePathEndsWith(end) => path.path.ends_with(end),
@@ -100,13 +140,8 @@ impl RuleCondition {
                     .await?
                     .is_some()
             }
-            RuleCondition::ResourcePathGlob { glob, base } => {
-                if let Some(path) = base.get_relative_path_to(path) {
-                    glob.execute(&path)
-                } else {
-                    glob.execute(&path.path)
-                }
-            }
+            RuleCondition::ResourcePathGlob { glob, base } => matches_path_glob(base, glob, path),
+            RuleCondition::ResourcePathRegex(regex) => regex.is_match(&path.path),
             RuleCondition::ResourceBasePathGlob(glob) => {
                 let basename = path
                     .path
@@ -114,7 +149,27 @@ impl RuleCondition {
                     .map_or(path.path.as_str(), |(_, b)| b);
                 glob.execute(basename)
             }
-            _ => todo!("not implemented yet"),
+            RuleCondition::ResourceQueryEquals(query) => {
+                source.ident().query().await?.as_str() == query.as_str()
+            }
+            RuleCondition::ResourceQueryRegex(regex) => {
+                regex.is_match(source.ident().query().await?.as_str())
+            }
+            RuleCondition::ImportWithType(ty) => match reference_type {
+                ReferenceType::EcmaScriptModules(
+                    EcmaScriptModulesReferenceSubType::ImportWithType(import_with_type),
+                ) => match import_with_type {
+                    ImportWithType::Json => ty.as_str() == "json",
+                    ImportWithType::Other { ty: other, .. } => ty == other,
+                },
+                _ => false,
+            },
+            RuleCondition::IssuerPathGlob { base, glob } => {
+                issuer.is_some_and(|issuer| matches_path_glob(base, glob, issuer))
+            }
+            RuleCondition::IssuerPathRegex(regex) => {
+                issuer.is_some_and(|issuer| regex.is_match(&issuer.path))
+            }
         })
     }
 }
@@ -51,8 +51,16 @@ impl TransitionRule {
         source: Vc<Box<dyn Source>>,
         path: &FileSystemPath,
         reference_type: &ReferenceType,
+        issuer: Option<&FileSystemPath>,
     ) -> Result<bool> {
         Ok(self.match_mode.matches(reference_type)
-            && self.condition.matches(source, path, reference_type).await?)
+            && self
+                .condition
+                .matches(source, path, reference_type, issuer)
+                .await?)
+    }
+
+    pub fn has_issuer_condition(&self) -> bool {
+        self.condition.has_issuer_condition()
     }
 }
@@ -7,6 +7,7 @@ use anyhow::Result;
 pub use context_transition::ContextTransition;
 pub use full_context_transition::FullContextTransition;
 use turbo_tasks::{RcStr, Value, ValueDefault, Vc};
+use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     compile_time_info::CompileTimeInfo, context::ProcessResult, module::Module,
     reference_type::ReferenceType, source::Source,
@@ -91,7 +92,7 @@ pub trait Transition {
     ) -> Result<Vc<ProcessResult>> {
         let asset = self.process_source(asset);
         let module_asset_context = self.process_context(module_asset_context);
-        let m = module_asset_context.process_default(asset, reference_type);
+        let m = module_asset_context.process_default(asset, reference_type, None);
         Ok(match *m.await? {
             ProcessResult::Module(m) => {
                 ProcessResult::Module(self.process_module(m, module_asset_context))
@@ -123,17 +124,24 @@ impl TransitionOptions {
         self.named_transitions.get(&name).copied()
     }
 
+    pub fn has_issuer_conditions(&self) -> bool {
+        self.transition_rules
+            .iter()
+            .any(|rule| rule.has_issuer_condition())
+    }
+
     pub async fn get_by_rules(
         &self,
         source: Vc<Box<dyn Source>>,
         reference_type: &ReferenceType,
+        issuer: Option<&FileSystemPath>,
     ) -> Result<Option<Vc<Box<dyn Transition>>>> {
         if self.transition_rules.is_empty() {
             return Ok(None);
         }
         let path = &*source.ident().path().await?;
         for rule in &self.transition_rules {
-            if rule.matches(source, path, reference_type).await? {
+            if rule.matches(source, path, reference_type, issuer).await? {
                 return Ok(Some(rule.transition()));
             }
         }
@@ -0,0 +1,136 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{primitives::Regex, Vc};
+use turbo_tasks_fs::{glob::Glob, FileContent, FileSystem, FileSystemPath, VirtualFileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack::module_options::RuleCondition;
+use turbopack_core::{
+    asset::AssetContent,
+    ident::AssetIdent,
+    reference_type::{EcmaScriptModulesReferenceSubType, ImportWithType, ReferenceType},
+    source::Source,
+    virtual_source::VirtualSource,
+};
+
+static REGISTRATION: Registration = register!(turbopack::register);
+
+fn source(path: Vc<FileSystemPath>, query: &str) -> Vc<Box<dyn Source>> {
+    Vc::upcast(VirtualSource::new_with_ident(
+        AssetIdent::from_path(path).with_query(Vc::cell(query.into())),
+        AssetContent::file(FileContent::NotFound.cell()),
+    ))
+}
+
+fn regex(regex: &str) -> Regex {
+    Regex(regex::Regex::new(regex).unwrap())
+}
+
+async fn matches(
+    condition: &RuleCondition,
+    source: Vc<Box<dyn Source>>,
+    reference_type: &ReferenceType,
+    issuer: Option<Vc<FileSystemPath>>,
+) -> Result<bool> {
+    let path = source.ident().path().await?;
+    let issuer = match issuer {
+        Some(issuer) => Some(issuer.await?),
+        None => None,
+    };
+    condition
+        .matches(source, &path, reference_type, issuer.as_deref())
+        .await
+}
+
+#[tokio::test]
+async fn resource_query() {
+    run(&REGISTRATION, || async {
+        let root = VirtualFileSystem::new().root();
+        let raw = source(root.join("icon.svg".into()), "?raw");
+        let plain = source(root.join("icon.svg".into()), "");
+        let ty = ReferenceType::Undefined;
+
+        let equals = RuleCondition::ResourceQueryEquals("?raw".into());
+        assert!(matches(&equals, raw, &ty, None).await?);
+        assert!(!matches(&equals, plain, &ty, None).await?);
+
+        let regex = RuleCondition::ResourceQueryRegex(regex(r"^\?(raw|url)$"));
+        assert!(matches(&regex, raw, &ty, None).await?);
+        assert!(!matches(&regex, plain, &ty, None).await?);
+
+        let path_regex = RuleCondition::ResourcePathRegex(regex(r"\.svg$"));
+        assert!(matches(&path_regex, plain, &ty, None).await?);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn import_with_type() {
+    run(&REGISTRATION, || async {
+        let root = VirtualFileSystem::new().root();
+        let file = source(root.join("data.txt".into()), "");
+        let with_type = |ty: ImportWithType| {
+            ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(ty))
+        };
+
+        let text = RuleCondition::ImportWithType("text".into());
+        let text_import = with_type(ImportWithType::Other {
+            ty: "text".into(),
+            part: None,
+        });
+        assert!(matches(&text, file, &text_import, None).await?);
+        assert!(!matches(&text, file, &with_type(ImportWithType::Json), None).await?);
+        assert!(
+            !matches(
+                &text,
+                file,
+                &ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import),
+                None
+            )
+            .await?
+        );
+
+        let json = RuleCondition::ImportWithType("json".into());
+        assert!(matches(&json, file, &with_type(ImportWithType::Json), None).await?);
+        assert!(!matches(&json, file, &text_import, None).await?);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn issuer() {
+    run(&REGISTRATION, || async {
+        let root = VirtualFileSystem::new().root();
+        let file = source(root.join("src/icon.svg".into()), "");
+        let component = root.join("src/components/button.tsx".into());
+        let style = root.join("src/styles/main.css".into());
+        let ty = ReferenceType::Undefined;
+
+        let glob = RuleCondition::IssuerPathGlob {
+            base: root.await?,
+            glob: Glob::new("src/components/**".into()).await?,
+        };
+        assert!(glob.has_issuer_condition());
+        assert!(matches(&glob, file, &ty, Some(component)).await?);
+        assert!(!matches(&glob, file, &ty, Some(style)).await?);
+        // Like webpack's `issuer`, entries without an issuer never match
+        assert!(!matches(&glob, file, &ty, None).await?);
+
+        let regex = RuleCondition::IssuerPathRegex(regex(r"\.css$"));
+        assert!(matches(&regex, file, &ty, Some(style)).await?);
+        assert!(!matches(&regex, file, &ty, Some(component)).await?);
+
+        let not = RuleCondition::not(regex);
+        assert!(not.has_issuer_condition());
+        assert!(matches(&not, file, &ty, Some(component)).await?);
+        assert!(!RuleCondition::ResourceQueryEquals("?raw".into()).has_issuer_condition());
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}